@@ -0,0 +1,101 @@
+//! Timings of the interpreter on code which stresses its stack and memory.
+//!
+//! Run with `cargo bench --bench interpreter`. Each case is run `RUNS` times
+//! and the fastest run is reported, which is the least disturbed by whatever
+//! else the machine is doing.
+
+use std::time::{Duration, Instant};
+
+use execution_specs_rs::ethereum::{
+    base_types::{Bytes, Uint, U256},
+    frontier::{
+        fork_types::{Account, Address},
+        state::{set_account, State},
+        utils::message::prepare_message,
+        vm::{interpreter::process_message_call, Environment},
+    },
+};
+
+const CALLER: Address = [0xaa; 20];
+const TARGET: Address = [0xcc; 20];
+const CALLEE: Address = [0xdd; 20];
+
+const RUNS: u32 = 50;
+
+/// MSTORE and MLOAD at offset 0, 10000 times.
+const MEMORY_LOOP: &[u8] = &[
+    0x61, 0x27, 0x10, 0x5b, 0x80, 0x60, 0x00, 0x52, 0x60, 0x00, 0x51, 0x50, 0x60, 0x01, 0x90, 0x03,
+    0x80, 0x60, 0x03, 0x57, 0x00,
+];
+
+/// MSTORE at an offset growing by a word, 10000 times.
+const MEMORY_GROWTH: &[u8] = &[
+    0x61, 0x27, 0x10, 0x5b, 0x80, 0x80, 0x61, 0x27, 0x10, 0x03, 0x60, 0x20, 0x02, 0x52, 0x60, 0x01,
+    0x90, 0x03, 0x80, 0x60, 0x03, 0x57, 0x00,
+];
+
+/// PUSH1 1 POP STOP
+const CALLEE_CODE: &[u8] = &[0x60, 0x01, 0x50, 0x00];
+
+/// CALL(gas=1000, to=CALLEE) POP, 1000 times.
+fn calls_loop() -> Vec<u8> {
+    let mut code = vec![0x61, 0x03, 0xe8, 0x5b, 0x60, 0x00, 0x80, 0x80, 0x80, 0x80, 0x73];
+    code.extend_from_slice(&CALLEE);
+    code.extend_from_slice(&[
+        0x61, 0x03, 0xe8, 0xf1, 0x50, 0x60, 0x01, 0x90, 0x03, 0x80, 0x60, 0x03, 0x57, 0x00,
+    ]);
+    code
+}
+
+fn contract(state: &mut State, address: Address, code: &[u8]) {
+    let account = Account {
+        nonce: Uint::from(0u8),
+        balance: U256::from(0u8),
+        code: Bytes::from(code),
+    };
+    set_account(state, address, Some(account));
+}
+
+/// The fastest of `RUNS` calls to `TARGET` running `code`.
+fn run(code: &[u8]) -> Duration {
+    let mut state = State::default();
+    contract(&mut state, TARGET, code);
+    contract(&mut state, CALLEE, CALLEE_CODE);
+    let mut env = Environment {
+        caller: CALLER,
+        block_hashes: Vec::new(),
+        origin: CALLER,
+        coinbase: [0; 20],
+        number: Uint::from(1u8),
+        gas_limit: Uint::from(100_000_000u32),
+        gas_price: U256::from(0u8),
+        time: U256::from(0u8),
+        difficulty: Uint::from(0u8),
+        state,
+        inspector: (),
+    };
+
+    let mut fastest = Duration::MAX;
+    for _ in 0..RUNS {
+        let gas = Uint::from(100_000_000u32);
+        let message =
+            prepare_message(CALLER, Some(TARGET), U256::from(0u8), Bytes::default(), gas, &env, None)
+                .unwrap();
+        let start = Instant::now();
+        let output = process_message_call(message, &mut env).unwrap();
+        fastest = fastest.min(start.elapsed());
+        assert!(output.error.is_none());
+    }
+    fastest
+}
+
+fn main() {
+    let cases: [(&str, Vec<u8>); 3] = [
+        ("memory loop", MEMORY_LOOP.to_vec()),
+        ("memory growth", MEMORY_GROWTH.to_vec()),
+        ("calls", calls_loop()),
+    ];
+    for (name, code) in cases {
+        println!("{name:>14}: {:>10.3?}", run(&code));
+    }
+}
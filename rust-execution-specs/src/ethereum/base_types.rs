@@ -10,18 +10,22 @@
 // use ::__future__::{annotations};
 // use ::dataclasses::{replace};
 // use ::typing::{Any, Callable, ClassVar, Optional, Tuple, Type, TypeVar};
-// U8_MAX_VALUE = (2).pow(8) - 1;
-// U32_MAX_VALUE = (2).pow(32) - 1;
-// U32_CEIL_VALUE = (2).pow(32);
-// U64_MAX_VALUE = (2).pow(64) - 1;
-// U255_MAX_VALUE = (2).pow(255) - 1;
-// U255_CEIL_VALUE = (2).pow(255);
-// U256_MAX_VALUE = (2).pow(256) - 1;
-// U256_CEIL_VALUE = (2).pow(256);
-
-use num_bigint::BigUint;
+pub const U8_MAX_VALUE: u8 = u8::MAX;
+pub const U32_MAX_VALUE: U32 = U32::MAX;
+pub const U32_CEIL_VALUE: u64 = 1 << 32;
+pub const U64_MAX_VALUE: U64 = U64::MAX;
+pub const U255_MAX_VALUE: U256 = U256::from_limbs([u64::MAX, u64::MAX, u64::MAX, u64::MAX >> 1]);
+pub const U255_CEIL_VALUE: U256 = U256::from_limbs([0, 0, 0, 1 << 63]);
+pub const U256_MAX_VALUE: U256 = U256::MAX;
+
+/// `2 ** 256`, which is too large for a `U256`.
+#[allow(non_snake_case)]
+pub fn U256_CEIL_VALUE() -> Uint {
+    Uint::from(1u8) << 256
+}
 
-// use super::exceptions::EthereumException;
+pub use super::fixed_uint::{U32, U64};
+pub use super::u256::U256;
 
 /// Array of bytes.
 pub type Bytes = Box<[u8]>;
@@ -29,29 +33,13 @@ pub type Bytes = Box<[u8]>;
 // pub type SignedInt = num_bigint::BigInt;
 
 
-// /// 
-// ///     Unsigned positive integer.
-// ///     
-pub type Uint = num_bigint::BigUint;
-
 ///
-///     Unsigned positive integer, which can represent `0` to `2 ** 256 - 1`,
-///     inclusive.
+///     Unsigned positive integer.
 ///
+///     It has no upper bound, and `BigUint` already refuses to go below
+///     zero, so it needs no newtype of its own.
 ///
-pub type U256 = BigUint;
-
-/// 
-///     Unsigned positive integer, which can represent `0` to `2 ** 32 - 1`,
-///     inclusive.
-///     
-pub type U32 = u32;
-
-/// 
-///     Unsigned positive integer, which can represent `0` to `2 ** 64 - 1`,
-///     inclusive.
-///     
-pub type U64 = u64;
+pub type Uint = num_bigint::BigUint;
 
 
 
@@ -0,0 +1,192 @@
+//!
+//! # Elliptic Curves
+//!
+//! ## Introduction
+//!
+//! Recovery of the public key which signed a message, on the `secp256k1`
+//! curve `y ** 2 = x ** 3 + 7` over the integers modulo `SECP256K1P`.
+//!
+//! Points are kept in Jacobian coordinates `(X, Y, Z)`, standing for the
+//! affine point `(X / Z ** 2, Y / Z ** 3)`, so that only the final
+//! conversion back to affine coordinates needs a modular inverse.
+//!
+
+use hex_literal::hex;
+
+use crate::ethereum::{
+    base_types::{Bytes, Uint, U256},
+    exceptions::EthereumException,
+    frontier::fork_types::Hash32,
+};
+
+/// Order of the field the curve is defined over.
+#[allow(non_snake_case)]
+pub fn SECP256K1P() -> Uint {
+    Uint::from_bytes_be(&hex!("fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f"))
+}
+
+/// Order of the group generated by the base point.
+#[allow(non_snake_case)]
+pub fn SECP256K1N() -> Uint {
+    Uint::from_bytes_be(&hex!("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141"))
+}
+
+/// The base point, in affine coordinates.
+#[allow(non_snake_case)]
+fn SECP256K1G() -> (Uint, Uint) {
+    (
+        Uint::from_bytes_be(&hex!("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798")),
+        Uint::from_bytes_be(&hex!("483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8")),
+    )
+}
+
+/// A point of the curve in Jacobian coordinates, `z == 0` being the point at
+/// infinity.
+#[derive(Clone)]
+struct Point {
+    x: Uint,
+    y: Uint,
+    z: Uint,
+}
+
+impl Point {
+    fn infinity() -> Point {
+        Point { x: Uint::from(1u8), y: Uint::from(1u8), z: Uint::from(0u8) }
+    }
+
+    fn affine(x: Uint, y: Uint) -> Point {
+        Point { x, y, z: Uint::from(1u8) }
+    }
+
+    fn is_infinity(&self) -> bool {
+        self.z == Uint::from(0u8)
+    }
+
+    fn double(&self, p: &Uint) -> Point {
+        if self.is_infinity() || self.y == Uint::from(0u8) {
+            return Point::infinity();
+        }
+        let y_squared = &self.y * &self.y % p;
+        let s = Uint::from(4u8) * &self.x * &y_squared % p;
+        let m = Uint::from(3u8) * &self.x * &self.x % p;
+        let x = (&m * &m + Uint::from(2u8) * (p - &s)) % p;
+        let y = (&m * ((&s + p - &x) % p) + Uint::from(8u8) * (p - &y_squared * &y_squared % p)) % p;
+        let z = Uint::from(2u8) * &self.y * &self.z % p;
+        Point { x, y, z }
+    }
+
+    fn add(&self, other: &Point, p: &Uint) -> Point {
+        if self.is_infinity() {
+            return other.clone();
+        }
+        if other.is_infinity() {
+            return self.clone();
+        }
+        let z1_squared = &self.z * &self.z % p;
+        let z2_squared = &other.z * &other.z % p;
+        let u1 = &self.x * &z2_squared % p;
+        let u2 = &other.x * &z1_squared % p;
+        let s1 = &self.y * &z2_squared % p * &other.z % p;
+        let s2 = &other.y * &z1_squared % p * &self.z % p;
+        if u1 == u2 {
+            if s1 == s2 {
+                return self.double(p);
+            }
+            return Point::infinity();
+        }
+        let h = (&u2 + p - &u1) % p;
+        let r = (&s2 + p - &s1) % p;
+        let h_squared = &h * &h % p;
+        let h_cubed = &h_squared * &h % p;
+        let u1_h_squared = &u1 * &h_squared % p;
+        let x = (&r * &r + Uint::from(2u8) * (p - &u1_h_squared) + (p - &h_cubed)) % p;
+        let y = (&r * ((&u1_h_squared + p - &x) % p) + (p - &s1 * &h_cubed % p)) % p;
+        let z = h * &self.z % p * &other.z % p;
+        Point { x, y, z }
+    }
+
+    fn multiply(&self, scalar: &Uint, p: &Uint) -> Point {
+        let mut result = Point::infinity();
+        for bit in (0..scalar.bits()).rev() {
+            result = result.double(p);
+            if scalar.bit(bit) {
+                result = result.add(self, p);
+            }
+        }
+        result
+    }
+
+    fn to_affine(&self, p: &Uint) -> (Uint, Uint) {
+        let z_inverse = self.z.modpow(&(p - Uint::from(2u8)), p);
+        let z_inverse_squared = &z_inverse * &z_inverse % p;
+        let x = &self.x * &z_inverse_squared % p;
+        let y = &self.y * &z_inverse_squared % p * &z_inverse % p;
+        (x, y)
+    }
+}
+
+fn to_be_bytes32(value: &Uint) -> [u8; 32] {
+    let bytes = value.to_bytes_be();
+    let mut word = [0; 32];
+    word[32 - bytes.len()..].copy_from_slice(&bytes);
+    word
+}
+
+///
+///     Recovers the public key from a given signature.
+///
+///     Parameters
+///     ----------
+///     r :
+///         The `x` coordinate of the point picked when signing.
+///     s :
+///         The signature proper.
+///     v :
+///         The parity of the `y` coordinate of that point, `0` or `1`.
+///     msg_hash :
+///         Hash of the message being recovered.
+///
+///     Returns
+///     -------
+///     public_key : `ethereum.base_types.Bytes`
+///         Recovered public key, as the 64 bytes of its `x` and `y`
+///         coordinates.
+///
+pub fn secp256k1_recover(r: U256, s: U256, v: U256, msg_hash: Hash32) -> Result<Bytes, EthereumException> {
+    let p = SECP256K1P();
+    let n = SECP256K1N();
+    let (r, s) = (Uint::from(r), Uint::from(s));
+    let zero = Uint::from(0u8);
+    if r == zero || r >= n || s == zero || s >= n || v > U256::ONE {
+        return Err(EthereumException::InvalidBlock);
+    }
+
+    // The point whose `x` coordinate is `r`, with the parity of `y` given by
+    // `v`.
+    let alpha = (r.modpow(&Uint::from(3u8), &p) + Uint::from(7u8)) % &p;
+    let beta = alpha.modpow(&((&p + Uint::from(1u8)) / Uint::from(4u8)), &p);
+    if &beta * &beta % &p != alpha {
+        return Err(EthereumException::InvalidBlock);
+    }
+    let y = if beta.bit(0) != v.is_zero() { beta } else { &p - beta };
+    let point_r = Point::affine(r.clone(), y);
+
+    // Q = r^-1 * (s * R - e * G)
+    let e = Uint::from_bytes_be(&msg_hash) % &n;
+    let r_inverse = r.modpow(&(&n - Uint::from(2u8)), &n);
+    let u1 = (&n - e) % &n * &r_inverse % &n;
+    let u2 = s * &r_inverse % &n;
+    let (gx, gy) = SECP256K1G();
+    let public_key = Point::affine(gx, gy)
+        .multiply(&u1, &p)
+        .add(&point_r.multiply(&u2, &p), &p);
+    if public_key.is_infinity() {
+        return Err(EthereumException::InvalidBlock);
+    }
+
+    let (x, y) = public_key.to_affine(&p);
+    let mut public_key = Vec::with_capacity(64);
+    public_key.extend(to_be_bytes32(&x));
+    public_key.extend(to_be_bytes32(&y));
+    Ok(public_key.into())
+}
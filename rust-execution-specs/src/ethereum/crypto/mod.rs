@@ -0,0 +1,9 @@
+//!
+//! # Cryptographic Functions
+//!
+//! ## Introduction
+//!
+//! Cryptographic primitives used in Ethereum.
+//!
+
+pub mod elliptic_curve;
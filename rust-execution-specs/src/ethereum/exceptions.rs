@@ -34,9 +34,35 @@ pub enum EthereumException {
     // Sundry pythonesque errors.
     ValueError,
 
+    OverflowError,
+
     FileNotFound(String),
 
     JsonDecodeError(String),
 
     BadHexString(String),
+
+    ///
+    ///     Indicates that a Merkle proof does not match the root it claims to
+    ///     prove against.
+    ///
+    InvalidProof(String),
+
+    ///
+    ///     Indicates that a state backend can not provide the requested part
+    ///     of the state.
+    ///
+    StateUnavailable(String),
+
+    ///
+    ///     Indicates that a state snapshot disagrees with the tries it is a
+    ///     snapshot of.
+    ///
+    InvalidSnapshot(String),
+
+    ///
+    ///     Indicates that the committed state was asked for while a state
+    ///     transaction is ongoing.
+    ///
+    TransactionOngoing,
 }
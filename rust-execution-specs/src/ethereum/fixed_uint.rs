@@ -0,0 +1,358 @@
+//!
+//! # Fixed Width Unsigned Integers
+//!
+//! ## Introduction
+//!
+//! `U32` and `U64`, the unsigned integers of the specification which are
+//! small enough for a machine word.
+//!
+//! As with `U256`, the arithmetic operators panic on overflow, as
+//! `FixedUInt` raises in the Python specification, and constructing one from
+//! a value out of its range fails with `EthereumException::OverflowError`.
+//! The `wrapping_*` methods wrap around instead, and the `checked_*` methods
+//! return `None`.
+//!
+
+use std::fmt;
+use std::ops::{
+    Add, AddAssign, BitAnd, BitOr, BitXor, Div, Mul, MulAssign, Not, Rem, Sub, SubAssign,
+};
+
+use num_traits::ToPrimitive;
+
+use super::base_types::{Uint, U256};
+use super::exceptions::EthereumException;
+
+macro_rules! fixed_uint {
+    ($(#[$doc:meta])* $name:ident($inner:ty), $bytes:literal, $to_be_bytes_n:ident, $to_inner:ident) => {
+        $(#[$doc])*
+        #[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name($inner);
+
+        impl $name {
+            pub const ZERO: $name = $name(0);
+            pub const ONE: $name = $name(1);
+            pub const MAX: $name = $name(<$inner>::MAX);
+
+            ///
+            ///     Converts a sequence of bytes into an unsigned integer,
+            ///     interpreting them as big endian.
+            ///
+            ///     Panics when there are more bytes than fit.
+            ///
+            pub fn from_be_bytes(bytes: &[u8]) -> $name {
+                $name::try_from_be_bytes(bytes).expect(concat!("too many bytes in a ", stringify!($name)))
+            }
+
+            ///
+            ///     Converts a sequence of bytes into an unsigned integer,
+            ///     interpreting them as big endian, if they fit.
+            ///
+            pub fn try_from_be_bytes(bytes: &[u8]) -> Option<$name> {
+                if bytes.len() > $bytes {
+                    return None;
+                }
+                let mut word = [0; $bytes];
+                word[$bytes - bytes.len()..].copy_from_slice(bytes);
+                Some($name(<$inner>::from_be_bytes(word)))
+            }
+
+            ///
+            ///     Converts this unsigned integer into its big endian
+            ///     representation, with exactly as many bytes as its width.
+            ///
+            pub fn $to_be_bytes_n(&self) -> [u8; $bytes] {
+                self.0.to_be_bytes()
+            }
+
+            ///
+            ///     Converts this unsigned integer into its big endian
+            ///     representation, omitting leading zero bytes.
+            ///
+            pub fn to_be_bytes(&self) -> Vec<u8> {
+                let word = self.0.to_be_bytes();
+                let leading_zeros = (self.0.leading_zeros() / 8) as usize;
+                word[leading_zeros..].to_vec()
+            }
+
+            pub fn is_zero(&self) -> bool {
+                self.0 == 0
+            }
+
+            pub fn checked_add(self, rhs: $name) -> Option<$name> {
+                self.0.checked_add(rhs.0).map($name)
+            }
+
+            pub fn checked_sub(self, rhs: $name) -> Option<$name> {
+                self.0.checked_sub(rhs.0).map($name)
+            }
+
+            pub fn checked_mul(self, rhs: $name) -> Option<$name> {
+                self.0.checked_mul(rhs.0).map($name)
+            }
+
+            pub fn checked_div(self, rhs: $name) -> Option<$name> {
+                self.0.checked_div(rhs.0).map($name)
+            }
+
+            pub fn checked_rem(self, rhs: $name) -> Option<$name> {
+                self.0.checked_rem(rhs.0).map($name)
+            }
+
+            pub fn wrapping_add(self, rhs: $name) -> $name {
+                $name(self.0.wrapping_add(rhs.0))
+            }
+
+            pub fn wrapping_sub(self, rhs: $name) -> $name {
+                $name(self.0.wrapping_sub(rhs.0))
+            }
+
+            pub fn wrapping_mul(self, rhs: $name) -> $name {
+                $name(self.0.wrapping_mul(rhs.0))
+            }
+
+            /// `self` raised to `exponent`, wrapping around on overflow.
+            pub fn wrapping_pow(self, exponent: u32) -> $name {
+                $name(self.0.wrapping_pow(exponent))
+            }
+        }
+
+        impl From<$name> for $inner {
+            fn from(value: $name) -> $inner {
+                value.0
+            }
+        }
+
+        impl From<$name> for Uint {
+            fn from(value: $name) -> Uint {
+                Uint::from(value.0)
+            }
+        }
+
+        impl From<$name> for U256 {
+            fn from(value: $name) -> U256 {
+                U256::from(value.0)
+            }
+        }
+
+        impl TryFrom<Uint> for $name {
+            type Error = EthereumException;
+
+            fn try_from(value: Uint) -> Result<$name, EthereumException> {
+                $name::try_from(&value)
+            }
+        }
+
+        impl TryFrom<&Uint> for $name {
+            type Error = EthereumException;
+
+            fn try_from(value: &Uint) -> Result<$name, EthereumException> {
+                value.$to_inner().map($name).ok_or(EthereumException::OverflowError)
+            }
+        }
+
+        impl TryFrom<U256> for $name {
+            type Error = EthereumException;
+
+            fn try_from(value: U256) -> Result<$name, EthereumException> {
+                value.$to_inner().map($name).ok_or(EthereumException::OverflowError)
+            }
+        }
+
+        impl TryFrom<usize> for $name {
+            type Error = EthereumException;
+
+            fn try_from(value: usize) -> Result<$name, EthereumException> {
+                <$inner>::try_from(value).map($name).map_err(|_| EthereumException::OverflowError)
+            }
+        }
+
+        impl Add for $name {
+            type Output = $name;
+
+            fn add(self, rhs: $name) -> $name {
+                self.checked_add(rhs).expect(concat!(stringify!($name), " addition overflowed"))
+            }
+        }
+
+        impl Sub for $name {
+            type Output = $name;
+
+            fn sub(self, rhs: $name) -> $name {
+                self.checked_sub(rhs).expect(concat!(stringify!($name), " subtraction overflowed"))
+            }
+        }
+
+        impl Mul for $name {
+            type Output = $name;
+
+            fn mul(self, rhs: $name) -> $name {
+                self.checked_mul(rhs).expect(concat!(stringify!($name), " multiplication overflowed"))
+            }
+        }
+
+        impl Div for $name {
+            type Output = $name;
+
+            fn div(self, rhs: $name) -> $name {
+                self.checked_div(rhs).expect(concat!(stringify!($name), " division by zero"))
+            }
+        }
+
+        impl Rem for $name {
+            type Output = $name;
+
+            fn rem(self, rhs: $name) -> $name {
+                self.checked_rem(rhs).expect(concat!(stringify!($name), " division by zero"))
+            }
+        }
+
+        impl AddAssign for $name {
+            fn add_assign(&mut self, rhs: $name) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl SubAssign for $name {
+            fn sub_assign(&mut self, rhs: $name) {
+                *self = *self - rhs;
+            }
+        }
+
+        impl MulAssign for $name {
+            fn mul_assign(&mut self, rhs: $name) {
+                *self = *self * rhs;
+            }
+        }
+
+        impl BitAnd for $name {
+            type Output = $name;
+
+            fn bitand(self, rhs: $name) -> $name {
+                $name(self.0 & rhs.0)
+            }
+        }
+
+        impl BitOr for $name {
+            type Output = $name;
+
+            fn bitor(self, rhs: $name) -> $name {
+                $name(self.0 | rhs.0)
+            }
+        }
+
+        impl BitXor for $name {
+            type Output = $name;
+
+            fn bitxor(self, rhs: $name) -> $name {
+                $name(self.0 ^ rhs.0)
+            }
+        }
+
+        impl Not for $name {
+            type Output = $name;
+
+            fn not(self) -> $name {
+                $name(!self.0)
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                fmt::Display::fmt(&self.0, f)
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                fmt::Display::fmt(&self.0, f)
+            }
+        }
+
+        impl fmt::LowerHex for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                fmt::LowerHex::fmt(&self.0, f)
+            }
+        }
+    };
+}
+
+fixed_uint!(
+    ///
+    ///     Unsigned positive integer, which can represent `0` to `2 ** 32 - 1`,
+    ///     inclusive.
+    ///
+    U32(u32), 4, to_be_bytes4, to_u32
+);
+
+fixed_uint!(
+    ///
+    ///     Unsigned positive integer, which can represent `0` to `2 ** 64 - 1`,
+    ///     inclusive.
+    ///
+    U64(u64), 8, to_be_bytes8, to_u64
+);
+
+impl From<u8> for U32 {
+    fn from(value: u8) -> U32 {
+        U32(value.into())
+    }
+}
+
+impl From<u16> for U32 {
+    fn from(value: u16) -> U32 {
+        U32(value.into())
+    }
+}
+
+impl From<u32> for U32 {
+    fn from(value: u32) -> U32 {
+        U32(value)
+    }
+}
+
+impl TryFrom<u64> for U32 {
+    type Error = EthereumException;
+
+    fn try_from(value: u64) -> Result<U32, EthereumException> {
+        u32::try_from(value).map(U32).map_err(|_| EthereumException::OverflowError)
+    }
+}
+
+impl From<u8> for U64 {
+    fn from(value: u8) -> U64 {
+        U64(value.into())
+    }
+}
+
+impl From<u16> for U64 {
+    fn from(value: u16) -> U64 {
+        U64(value.into())
+    }
+}
+
+impl From<u32> for U64 {
+    fn from(value: u32) -> U64 {
+        U64(value.into())
+    }
+}
+
+impl From<u64> for U64 {
+    fn from(value: u64) -> U64 {
+        U64(value)
+    }
+}
+
+impl From<U32> for U64 {
+    fn from(value: U32) -> U64 {
+        U64(value.0.into())
+    }
+}
+
+impl TryFrom<U64> for U32 {
+    type Error = EthereumException;
+
+    fn try_from(value: U64) -> Result<U32, EthereumException> {
+        U32::try_from(value.0)
+    }
+}
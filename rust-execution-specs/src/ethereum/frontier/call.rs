@@ -0,0 +1,82 @@
+//!
+//! # Read-only Calls
+//!
+//! ## Introduction
+//!
+//! The behaviour of `eth_call`: a message executed against the state of a
+//! block, with everything it changes rolled back afterwards, for querying
+//! contracts without building a block.
+//!
+//! No intrinsic gas is charged, and the gas of the message is not paid for,
+//! so the message is usually built with `prepare_message`, without a
+//! transaction around it.
+//!
+
+use crate::ethereum::{
+    base_types::{Bytes, Uint},
+    exceptions::EthereumException,
+};
+
+use super::{
+    fork_types::Log,
+    state::{begin_transaction, rollback_transaction, StateBackend},
+    vm::{
+        exceptions::EvmError, inspector::Inspector, interpreter::process_message_call, Environment,
+        Message,
+    },
+};
+
+/// Outcome of a read-only call.
+pub struct CallOutput {
+    /// The data returned by the call.
+    pub output: Bytes,
+    /// Gas used by the call, before any refund.
+    pub gas_used: Uint,
+    /// Logs the call would have emitted.
+    pub logs: Vec<Log>,
+    /// The exceptional halt the call ended with, if any.
+    pub error: Option<EvmError>,
+}
+
+///
+/// Execute `message` in `env`, and leave the state as it was before, also
+/// when the state backend fails part way through.
+///
+/// Parameters
+/// ----------
+/// message :
+///     The message to execute.
+/// env :
+///     The block the message is executed in, and its state.
+///
+/// Returns
+/// -------
+/// output : `CallOutput`
+///     The result of the call.
+///
+pub fn call<S: StateBackend, I: Inspector>(
+    message: Message,
+    env: &mut Environment<S, I>,
+) -> Result<CallOutput, EthereumException> {
+    let gas = message.gas.clone();
+
+    begin_transaction(&mut env.state);
+    let result = process_message_call(message, env);
+    rollback_transaction(&mut env.state);
+
+    match result {
+        Ok(output) => Ok(CallOutput {
+            output: output.output,
+            gas_used: gas - output.gas_left,
+            logs: output.logs,
+            error: output.error,
+        }),
+        Err(EvmError::State(error)) => Err(error),
+        Err(error) => Ok(CallOutput {
+            output: Bytes::default(),
+            gas_used: gas,
+            logs: Vec::new(),
+            error: Some(error),
+        }),
+    }
+}
@@ -28,12 +28,15 @@
 // use super::utils::message::{prepare_message};
 // use super::vm::interpreter::{process_message_call};
 
-use std::{todo, ops::Add};
+use std::{collections::HashSet, todo};
 
-use crate::ethereum::{base_types::{U64, Uint, U256}, exceptions::EthereumException, rlp, frontier::state::state_root};
-use super::{fork_types::{Block, Hash32, keccak256, Header, Bloom, Root, Transaction, Address}, state::State};
+use crate::ethereum::{base_types::{Bytes, U64, Uint, U256}, crypto::elliptic_curve::{secp256k1_recover, SECP256K1N}, exceptions::EthereumException, rlp, utils::ensure::ensure};
+use super::{bloom::logs_bloom, fork_types::{Block, Hash32, keccak256, Header, Bloom, Log, Receipt, Root, Transaction, Address, TX_BASE_COST, TX_DATA_COST_PER_NON_ZERO, TX_DATA_COST_PER_ZERO}, state::{State, StateBackend, create_ether, destroy_account, get_account, increment_nonce, set_account_balance, state_root}, trie::{Trie, dummy_root, root, trie_set}, utils::message::prepare_message, vm::{Environment, exceptions::EvmError, interpreter::process_message_call}};
 
-// static  BLOCK_REWARD: U256 = U256::from(10u64).pow(18u64).mul(U256::from(5u64));
+#[allow(non_snake_case)]
+pub fn BLOCK_REWARD() -> U256 {
+    U256::from(5_000_000_000_000_000_000u128)
+}
 pub const GAS_LIMIT_ADJUSTMENT_FACTOR: u64 = 1024;
 pub const GAS_LIMIT_MINIMUM:u64 = 5000;
 pub const MINIMUM_DIFFICULTY:u64 = 131072;
@@ -41,10 +44,13 @@ pub const MAX_OMMER_DEPTH: u8 = 6;
 
 ///
 ///     History and current state of the block chain.
+///
+///     The state can be any `StateBackend`, and defaults to the in-memory
+///     `State`.
 ///     
-pub struct BlockChain {
+pub struct BlockChain<S = State> {
     pub blocks: Vec<Block>,
-    pub state: State,
+    pub state: S,
     pub chain_id: U64,
 }
 
@@ -69,7 +75,7 @@ pub struct BlockChain {
 ///     new : `BlockChain`
 ///         Upgraded block chain object for this hard fork.
 ///
-pub fn apply_fork(old: BlockChain) -> Result<BlockChain, EthereumException> {
+pub fn apply_fork<S: StateBackend>(old: BlockChain<S>) -> Result<BlockChain<S>, EthereumException> {
     return Ok(old);
 }
 
@@ -92,21 +98,20 @@ pub fn apply_fork(old: BlockChain) -> Result<BlockChain, EthereumException> {
 ///     recent_block_hashes : `List[Hash32]`
 ///         Hashes of the recent 256 blocks in order of increasing block number.
 ///
-pub fn get_last_256_block_hashes(chain: &BlockChain) -> Result<Vec<Hash32>, EthereumException> {
+pub fn get_last_256_block_hashes<S: StateBackend>(chain: &BlockChain<S>) -> Result<Vec<Hash32>, EthereumException> {
     // get last 255 blocks
-    let recent_blocks = chain.blocks.iter().rev().take(255).collect::<Vec<&Block>>();
-    if recent_blocks.len() == 0 {
+    let recent_blocks = &chain.blocks[chain.blocks.len().saturating_sub(255)..];
+    if recent_blocks.is_empty() {
         return Ok(Vec::default());
     }
     
     let mut recent_block_hashes = Vec::new();
-    for block in &recent_blocks {
+    for block in recent_blocks {
         let prev_block_hash = block.header.parent_hash;
         recent_block_hashes.push(prev_block_hash);
     }
     
-    let last_header = &recent_blocks.last().unwrap().header;
-    let most_recent_block_hash = keccak256(Box::leak(rlp::encode(&last_header)));
+    let most_recent_block_hash = rlp::rlp_hash(&recent_blocks.last().unwrap().header);
     recent_block_hashes.push(most_recent_block_hash);
     return Ok(recent_block_hashes);
 }
@@ -132,24 +137,27 @@ pub fn get_last_256_block_hashes(chain: &BlockChain) -> Result<Vec<Hash32>, Ethe
 ///     block :
 ///         Block to apply to `chain`.
 ///
-pub fn state_transition(chain: &mut BlockChain, block: Block) -> Result<(), EthereumException> {
+pub fn state_transition<S: StateBackend>(chain: &mut BlockChain<S>, block: Block) -> Result<(), EthereumException> {
     // should be there one block
     let parent_header = chain.blocks.last().unwrap().header.clone();
 
     validate_header(&block.header, parent_header)?;
     validate_ommers(&block.ommers, block.header.clone(), chain)?;
-    let (gas_used, transactions_root, receipt_root, block_logs_bloom, state) = apply_body(&chain.state, get_last_256_block_hashes(chain)?, &block.header.coinbase, &block.header.number, &block.header.gas_limit, &block.header.timestamp, &block.header.difficulty, &block.transactions, &block.ommers)?;
+    let block_hashes = get_last_256_block_hashes(chain)?;
+    let (gas_used, transactions_root, receipt_root, block_logs_bloom) = apply_body(&mut chain.state, block_hashes, &block.header.coinbase, &block.header.number, &block.header.gas_limit, &block.header.timestamp, &block.header.difficulty, &block.transactions, &block.ommers)?;
 
-    assert!(gas_used == block.header.gas_used, "InvalidBlock");
-    assert!(transactions_root == block.header.transactions_root, "InvalidBlock");
-    assert!(state_root(&state) == block.header.state_root, "InvalidBlock");
-    assert!(receipt_root == block.header.receipt_root, "InvalidBlock");
-    assert!(block_logs_bloom == block.header.bloom, "InvalidBlock");
+    ensure(gas_used == block.header.gas_used, EthereumException::InvalidBlock)?;
+    ensure(transactions_root == block.header.transactions_root, EthereumException::InvalidBlock)?;
+    ensure(state_root(&chain.state)? == block.header.state_root, EthereumException::InvalidBlock)?;
+    ensure(receipt_root == block.header.receipt_root, EthereumException::InvalidBlock)?;
+    ensure(block_logs_bloom == block.header.bloom, EthereumException::InvalidBlock)?;
     
     chain.blocks.push(block);
     if chain.blocks.len() > 255 {
-        // remove first 255 blocks
-        chain.blocks.drain(0..255);
+        // Real clients have to store more blocks to deal with reorgs, but the
+        // protocol only requires the last 255
+        let excess = chain.blocks.len() - 255;
+        chain.blocks.drain(..excess);
     }
 
     Ok(())
@@ -173,15 +181,14 @@ pub fn state_transition(chain: &mut BlockChain, block: Block) -> Result<(), Ethe
 ///         Parent Header of the header to check for correctness
 ///
 pub fn validate_header(header: &Header, parent_header: Header) -> Result<(), EthereumException> {
-    assert!(header.timestamp > parent_header.timestamp, "InvalidBlock");
-    let parent_header_number = parent_header.number.clone();
-    assert!(header.number == parent_header_number.add(1u64), "InvalidBlock");
-    assert!(check_gas_limit(&header.gas_limit, &parent_header.gas_limit)?, "InvalidBlock");
-    assert!(header.extra_data.len() <= 32, "InvalidBlock");
+    ensure(header.timestamp > parent_header.timestamp, EthereumException::InvalidBlock)?;
+    ensure(header.number == &parent_header.number + 1u8, EthereumException::InvalidBlock)?;
+    ensure(check_gas_limit(&header.gas_limit, &parent_header.gas_limit)?, EthereumException::InvalidBlock)?;
+    ensure(header.extra_data.len() <= 32, EthereumException::InvalidBlock)?;
     let block_difficulty = calculate_block_difficulty(&header.number, &header.timestamp, &parent_header.timestamp, &parent_header.difficulty)?;
-    assert!(header.difficulty == block_difficulty, "InvalidBlock");
-    let block_parent_hash = keccak256(Box::leak(rlp::encode(&parent_header)));
-    assert!(header.parent_hash == block_parent_hash, "InvalidBlock");
+    ensure(header.difficulty == block_difficulty, EthereumException::InvalidBlock)?;
+    let block_parent_hash = rlp::rlp_hash(&parent_header);
+    ensure(header.parent_hash == block_parent_hash, EthereumException::InvalidBlock)?;
     // validate_proof_of_work(header)?;
 
     Ok(())
@@ -238,56 +245,61 @@ pub fn generate_header_hash_for_pow(_header: &Header) -> Result<Hash32, Ethereum
 //     todo!()
 // }
 
-// ///
-// ///     Check if the transaction is includable in the block.
-// ///
-// ///     Parameters
-// ///     ----------
-// ///     tx :
-// ///         The transaction.
-// ///     gas_available :
-// ///         The gas remaining in the block.
-// ///
-// ///     Returns
-// ///     -------
-// ///     sender_address :
-// ///         The sender of the transaction.
-// ///
-// ///     Raises
-// ///     ------
-// ///     InvalidBlock :
-// ///         If the transaction is not includable.
-// ///
-// pub fn check_transaction(tx: Transaction, gas_available: Uint) -> Result<Address, Error> {
-//     ensure(tx.gas <= gas_available, InvalidBlock)?;
-//     sender_address = recover_sender(tx)?;
-//     return Ok(sender_address);
-// }
+///
+///     Check if the transaction is includable in the block.
+///
+///     Parameters
+///     ----------
+///     tx :
+///         The transaction.
+///     gas_available :
+///         The gas remaining in the block.
+///
+///     Returns
+///     -------
+///     sender_address :
+///         The sender of the transaction.
+///
+///     Raises
+///     ------
+///     InvalidBlock :
+///         If the transaction is not includable.
+///
+pub fn check_transaction(tx: &Transaction, gas_available: &Uint) -> Result<Address, EthereumException> {
+    ensure(Uint::from(tx.gas) <= *gas_available, EthereumException::InvalidBlock)?;
+    let sender_address = recover_sender(tx)?;
+    Ok(sender_address)
+}
 
-// ///
-// ///     Make the receipt for a transaction that was executed.
-// ///
-// ///     Parameters
-// ///     ----------
-// ///     tx :
-// ///         The executed transaction.
-// ///     post_state :
-// ///         The state root immediately after this transaction.
-// ///     cumulative_gas_used :
-// ///         The total gas used so far in the block after the transaction was
-// ///         executed.
-// ///     logs :
-// ///         The logs produced by the transaction.
-// ///
-// ///     Returns
-// ///     -------
-// ///     receipt :
-// ///         The receipt for the transaction.
-// ///
-// pub fn make_receipt(tx: Transaction, post_state: Bytes32, cumulative_gas_used: Uint, logs: Tuple[Log][...]) -> Result<Receipt, Error> {
-//     receipt = Receipt(post_state = post_state, cumulative_gas_used = cumulative_gas_used, bloom = logs_bloom(logs)?, logs = logs)?;
-//     return Ok(receipt);
-// }
+///
+///     Make the receipt for a transaction that was executed.
+///
+///     Parameters
+///     ----------
+///     tx :
+///         The executed transaction.
+///     post_state :
+///         The state root immediately after this transaction.
+///     cumulative_gas_used :
+///         The total gas used so far in the block after the transaction was
+///         executed.
+///     logs :
+///         The logs produced by the transaction.
+///
+///     Returns
+///     -------
+///     receipt :
+///         The receipt for the transaction.
+///
+#[allow(unused_variables)]
+pub fn make_receipt(tx: &Transaction, post_state: Root, cumulative_gas_used: Uint, logs: Vec<Log>) -> Receipt {
+    Receipt {
+        post_state,
+        cumulative_gas_used,
+        bloom: logs_bloom(&logs),
+        logs,
+    }
+}
 
 ///
 ///     Executes a block.
@@ -302,7 +314,7 @@ pub fn generate_header_hash_for_pow(_header: &Header) -> Result<Hash32, Ethereum
 ///     Parameters
 ///     ----------
 ///     state :
-///         Current account state.
+///         Current account state, modified in place.
 ///     block_hashes :
 ///         List of hashes of the previous 256 blocks in the order of
 ///         increasing block number.
@@ -333,30 +345,61 @@ pub fn generate_header_hash_for_pow(_header: &Header) -> Result<Hash32, Ethereum
 ///     block_logs_bloom : `Bloom`
 ///         Logs bloom of all the logs included in all the transactions of the
 ///         block.
-///     state : `ethereum.fork_types.State`
-///         State after all transactions have been executed.
 ///
-#[allow(unused_variables)]
-pub fn apply_body(state: &State, block_hashes: Vec<Hash32>, coinbase: &Address, block_number: &Uint, block_gas_limit: &Uint, block_time: &U256, block_difficulty: &Uint, transactions: &Vec<Transaction>, ommers: &Vec<Header>) -> Result<(Uint, Root, Root, Bloom, State), EthereumException> {
-    // gas_available = block_gas_limit;
-    // // TypedAssignment unsupported
-    // // TypedAssignment unsupported
-    // // TypedAssignment unsupported
-    // for (i, tx) in enumerate(transactions)? {
-    //     trie_set(transactions_trie, rlp.encode(Uint(i)?)?, tx)?;
-    //     sender_address = check_transaction(tx, gas_available)?;
-    //     env = vm.Environment(caller = sender_address, origin = sender_address, block_hashes = block_hashes, coinbase = coinbase, number = block_number, gas_limit = block_gas_limit, gas_price = tx.gas_price, time = block_time, difficulty = block_difficulty, state = state)?;
-    //     (gas_used, logs) = process_transaction(env, tx)?;
-    //     gas_available -= gas_used;
-    //     receipt = make_receipt(tx, state_root(state)?, block_gas_limit - gas_available, logs)?;
-    //     trie_set(receipts_trie, rlp.encode(Uint(i)?)?, receipt)?;
-    //     block_logs += logs;
-    // }
-    // pay_rewards(state, block_number, coinbase, ommers)?;
-    // block_gas_used = block_gas_limit - gas_available;
-    // block_logs_bloom = logs_bloom(block_logs)?;
-    // return Ok((block_gas_used, root(transactions_trie)?, root(receipts_trie)?, block_logs_bloom, state));
-    todo!()
+pub fn apply_body<S: StateBackend>(state: &mut S, block_hashes: Vec<Hash32>, coinbase: &Address, block_number: &Uint, block_gas_limit: &Uint, block_time: &U256, block_difficulty: &Uint, transactions: &Vec<Transaction>, ommers: &Vec<Header>) -> Result<(Uint, Root, Root, Bloom), EthereumException> {
+    let (block_gas_used, transactions_root, receipt_root, block_logs_bloom, _) = apply_body_with_receipts(state, block_hashes, coinbase, block_number, block_gas_limit, block_time, block_difficulty, transactions, ommers)?;
+    Ok((block_gas_used, transactions_root, receipt_root, block_logs_bloom))
+}
+
+///
+///     Executes a block as `apply_body` does, also returning the receipts of
+///     its transactions, which the receipts trie only holds encoded.
+///
+///     Parameters
+///     ----------
+///     The parameters of `apply_body`.
+///
+///     Returns
+///     -------
+///     The values returned by `apply_body`, followed by
+///     receipts : `List[Receipt]`
+///         The receipt of every transaction, in order.
+///
+#[allow(clippy::too_many_arguments)]
+pub fn apply_body_with_receipts<S: StateBackend>(state: &mut S, block_hashes: Vec<Hash32>, coinbase: &Address, block_number: &Uint, block_gas_limit: &Uint, block_time: &U256, block_difficulty: &Uint, transactions: &Vec<Transaction>, ommers: &Vec<Header>) -> Result<(Uint, Root, Root, Bloom, Vec<Receipt>), EthereumException> {
+    let mut gas_available = block_gas_limit.clone();
+    let mut transactions_trie = Trie::<Bytes, Bytes>::new(false, Bytes::default());
+    let mut receipts_trie = Trie::<Bytes, Bytes>::new(false, Bytes::default());
+    let mut block_logs = Vec::new();
+    let mut receipts = Vec::new();
+
+    for (i, tx) in transactions.iter().enumerate() {
+        trie_set(&mut transactions_trie, rlp::encode(&Uint::from(i)), rlp::encode(tx));
+        let sender_address = check_transaction(tx, &gas_available)?;
+        let mut env = Environment {
+            caller: sender_address,
+            block_hashes: block_hashes.clone(),
+            origin: sender_address,
+            coinbase: *coinbase,
+            number: block_number.clone(),
+            gas_limit: block_gas_limit.clone(),
+            gas_price: tx.gas_price,
+            time: *block_time,
+            difficulty: block_difficulty.clone(),
+            state: &mut *state,
+            inspector: (),
+        };
+        let (gas_used, logs) = process_transaction(&mut env, tx)?;
+        gas_available -= gas_used;
+        block_logs.extend(logs.iter().cloned());
+        let receipt = make_receipt(tx, state_root(state)?, block_gas_limit - &gas_available, logs);
+        trie_set(&mut receipts_trie, rlp::encode(&Uint::from(i)), rlp::encode(&receipt));
+        receipts.push(receipt);
+    }
+    pay_rewards(state, block_number, coinbase, ommers)?;
+    let block_gas_used = block_gas_limit - gas_available;
+    let block_logs_bloom = logs_bloom(&block_logs);
+    Ok((block_gas_used, root(&transactions_trie, dummy_root), root(&receipts_trie, dummy_root), block_logs_bloom, receipts))
 }
 
 ///
@@ -380,275 +423,320 @@ pub fn apply_body(state: &State, block_hashes: Vec<Hash32>, coinbase: &Address,
 ///     chain :
 ///         History and current state.
 ///
-#[allow(unused_variables)]
-pub fn validate_ommers(ommers: &Vec<Header>, block_header: Header, chain: &BlockChain) -> Result<(), EthereumException> {
-    // block_hash = rlp.rlp_hash(block_header)?;
-    // ensure(rlp.rlp_hash(ommers)? == block_header.ommers_hash, InvalidBlock)?;
-    // if len(ommers)? == 0 {
-    //     return;
-    // }
-    // for ommer in ommers {
-    //     ensure(1 <= ommer.number < block_header.number, InvalidBlock)?;
-    //     ommer_parent_header = chain.blocks[-(block_header.number - ommer.number) - 1].header;
-    //     validate_header(ommer, ommer_parent_header)?;
-    // }
-    // ensure(len(ommers)? <= 2, InvalidBlock)?;
-    // ommers_hashes = /* ListComp unsupported */;
-    // ensure(len(ommers_hashes)? == len(set(ommers_hashes)?)?, InvalidBlock)?;
-    // recent_canonical_blocks = chain.blocks[-(MAX_OMMER_DEPTH + 1)..];
-    // recent_canonical_block_hashes = /* SetComp unsupported */;
-    // // TypedAssignment unsupported
-    // for block in recent_canonical_blocks {
-    //     recent_ommers_hashes = recent_ommers_hashes.union(/* SetComp unsupported */)?;
-    // }
-    // for (ommer_index, ommer) in enumerate(ommers)? {
-    //     ommer_hash = ommers_hashes[ommer_index];
-    //     ensure(ommer_hash != block_hash, InvalidBlock)?;
-    //     ensure(!(ommer_hash).contains(recent_canonical_block_hashes), InvalidBlock)?;
-    //     ensure(!(ommer_hash).contains(recent_ommers_hashes), InvalidBlock)?;
-    //     ommer_age = block_header.number - ommer.number;
-    //     ensure(1 <= ommer_age <= MAX_OMMER_DEPTH, InvalidBlock)?;
-    //     ensure((ommer.parent_hash).contains(recent_canonical_block_hashes), InvalidBlock)?;
-    //     ensure(ommer.parent_hash != block_header.parent_hash, InvalidBlock)?;
-    // }
-    todo!()
+pub fn validate_ommers<S: StateBackend>(ommers: &Vec<Header>, block_header: Header, chain: &BlockChain<S>) -> Result<(), EthereumException> {
+    let block_hash = rlp::rlp_hash(&block_header);
+    ensure(rlp::rlp_hash(ommers) == block_header.ommers_hash, EthereumException::InvalidBlock)?;
+    if ommers.is_empty() {
+        // Nothing to validate
+        return Ok(());
+    }
+
+    // Check that each ommer satisfies the constraints of a header
+    for ommer in ommers {
+        ensure(Uint::from(1u8) <= ommer.number && ommer.number < block_header.number, EthereumException::InvalidBlock)?;
+        let ommer_age = usize::try_from(&block_header.number - &ommer.number).map_err(|_| EthereumException::InvalidBlock)?;
+        let ommer_parent_header = chain.blocks.len().checked_sub(ommer_age + 1)
+            .map(|index| chain.blocks[index].header.clone())
+            .ok_or(EthereumException::InvalidBlock)?;
+        validate_header(ommer, ommer_parent_header)?;
+    }
+
+    // Check that there can be only at most 2 ommers for a block.
+    ensure(ommers.len() <= 2, EthereumException::InvalidBlock)?;
+
+    let ommers_hashes: Vec<Hash32> = ommers.iter().map(rlp::rlp_hash).collect();
+    // Check that there are no duplicates in the ommers of current block
+    ensure(ommers_hashes.len() == ommers_hashes.iter().collect::<HashSet<_>>().len(), EthereumException::InvalidBlock)?;
+
+    let recent_canonical_blocks = &chain.blocks[chain.blocks.len().saturating_sub(MAX_OMMER_DEPTH as usize + 1)..];
+    let recent_canonical_block_hashes: HashSet<Hash32> = recent_canonical_blocks.iter()
+        .map(|block| rlp::rlp_hash(&block.header))
+        .collect();
+    let recent_ommers_hashes: HashSet<Hash32> = recent_canonical_blocks.iter()
+        .flat_map(|block| block.ommers.iter().map(rlp::rlp_hash))
+        .collect();
+
+    for (ommer_index, ommer) in ommers.iter().enumerate() {
+        // The current block shouldn't count as its own ommer
+        let ommer_hash = ommers_hashes[ommer_index];
+        ensure(ommer_hash != block_hash, EthereumException::InvalidBlock)?;
+
+        // Ommer shouldn't be one of the recent canonical blocks
+        ensure(!recent_canonical_block_hashes.contains(&ommer_hash), EthereumException::InvalidBlock)?;
+
+        // Ommer shouldn't be one of the uncles mentioned in the recent
+        // canonical blocks
+        ensure(!recent_ommers_hashes.contains(&ommer_hash), EthereumException::InvalidBlock)?;
+
+        // Ommer age with respect to the current block. For example, an age of
+        // 1 indicates that the ommer is a sibling of previous block.
+        let ommer_age = &block_header.number - &ommer.number;
+        ensure(Uint::from(1u8) <= ommer_age && ommer_age <= Uint::from(MAX_OMMER_DEPTH), EthereumException::InvalidBlock)?;
+
+        ensure(recent_canonical_block_hashes.contains(&ommer.parent_hash), EthereumException::InvalidBlock)?;
+        ensure(ommer.parent_hash != block_header.parent_hash, EthereumException::InvalidBlock)?;
+    }
+
+    Ok(())
 }
 
-// ///
-// ///     Pay rewards to the block miner as well as the ommers miners.
-// ///
-// ///     The miner of the canonical block is rewarded with the predetermined
-// ///     block reward, ``BLOCK_REWARD``, plus a variable award based off of the
-// ///     number of ommer blocks that were mined around the same time, and included
-// ///     in the canonical block's header. An ommer block is a block that wasn't
-// ///     added to the canonical blockchain because it wasn't validated as fast as
-// ///     the accepted block but was mined at the same time. Although not all blocks
-// ///     that are mined are added to the canonical chain, miners are still paid a
-// ///     reward for their efforts. This reward is called an ommer reward and is
-// ///     calculated based on the number associated with the ommer block that they
-// ///     mined.
-// ///
-// ///     Parameters
-// ///     ----------
-// ///     state :
-// ///         Current account state.
-// ///     block_number :
-// ///         Position of the block within the chain.
-// ///     coinbase :
-// ///         Address of account which receives block reward and transaction fees.
-// ///     ommers :
-// ///         List of ommers mentioned in the current block.
-// ///
-// pub fn pay_rewards(state: State, block_number: Uint, coinbase: Address, ommers: Tuple[Header][...]) -> Result<(), Error> {
-//     miner_reward = BLOCK_REWARD + len(ommers)? * (BLOCK_REWARD).floordiv(32);
-//     create_ether(state, coinbase, miner_reward)?;
-//     for ommer in ommers {
-//         ommer_age = U256(block_number - ommer.number)?;
-//         ommer_miner_reward = (8 - ommer_age * BLOCK_REWARD).floordiv(8);
-//         create_ether(state, ommer.coinbase, ommer_miner_reward)?;
-//     }
-// }
+///
+///     Pay rewards to the block miner as well as the ommers miners.
+///
+///     The miner of the canonical block is rewarded with the predetermined
+///     block reward, ``BLOCK_REWARD``, plus a variable award based off of the
+///     number of ommer blocks that were mined around the same time, and included
+///     in the canonical block's header. An ommer block is a block that wasn't
+///     added to the canonical blockchain because it wasn't validated as fast as
+///     the accepted block but was mined at the same time. Although not all blocks
+///     that are mined are added to the canonical chain, miners are still paid a
+///     reward for their efforts. This reward is called an ommer reward and is
+///     calculated based on the number associated with the ommer block that they
+///     mined.
+///
+///     Parameters
+///     ----------
+///     state :
+///         Current account state.
+///     block_number :
+///         Position of the block within the chain.
+///     coinbase :
+///         Address of account which receives block reward and transaction fees.
+///     ommers :
+///         List of ommers mentioned in the current block.
+///
+pub fn pay_rewards<S: StateBackend>(state: &mut S, block_number: &Uint, coinbase: &Address, ommers: &[Header]) -> Result<(), EthereumException> {
+    let miner_reward = BLOCK_REWARD() + U256::from(ommers.len()) * (BLOCK_REWARD() / U256::from(32u8));
+    create_ether(state, *coinbase, miner_reward)?;
 
-// ///
-// ///     Execute a transaction against the provided environment.
-// ///
-// ///     This function processes the actions needed to execute a transaction.
-// ///     It decrements the sender's account after calculating the gas fee and
-// ///     refunds them the proper amount after execution. Calling contracts,
-// ///     deploying code, and incrementing nonces are all examples of actions that
-// ///     happen within this function or from a call made within this function.
-// ///
-// ///     Accounts that are marked for deletion are processed and destroyed after
-// ///     execution.
-// ///
-// ///     Parameters
-// ///     ----------
-// ///     env :
-// ///         Environment for the Ethereum Virtual Machine.
-// ///     tx :
-// ///         Transaction to execute.
-// ///
-// ///     Returns
-// ///     -------
-// ///     gas_left : `ethereum.base_types.U256`
-// ///         Remaining gas after execution.
-// ///     logs : `Tuple[ethereum.fork_types.Log, ...]`
-// ///         Logs generated during execution.
-// ///
-// pub fn process_transaction(env: vm.Environment, tx: Transaction) -> Result<Tuple[U256][Tuple[Log][...]], Error> {
-//     ensure(validate_transaction(tx)?, InvalidBlock)?;
-//     sender = env.origin;
-//     sender_account = get_account(env.state, sender)?;
-//     gas_fee = tx.gas * tx.gas_price;
-//     ensure(sender_account.nonce == tx.nonce, InvalidBlock)?;
-//     ensure(sender_account.balance >= gas_fee + tx.value, InvalidBlock)?;
-//     ensure(sender_account.code == bytearray()?, InvalidBlock)?;
-//     gas = tx.gas - calculate_intrinsic_cost(tx)?;
-//     increment_nonce(env.state, sender)?;
-//     sender_balance_after_gas_fee = sender_account.balance - gas_fee;
-//     set_account_balance(env.state, sender, sender_balance_after_gas_fee)?;
-//     message = prepare_message(sender, tx.to, tx.value, tx.data, gas, env)?;
-//     output = process_message_call(message, env)?;
-//     gas_used = tx.gas - output.gas_left;
-//     gas_refund = min((gas_used).floordiv(2), output.refund_counter)?;
-//     gas_refund_amount = output.gas_left + gas_refund * tx.gas_price;
-//     transaction_fee = tx.gas - output.gas_left - gas_refund * tx.gas_price;
-//     total_gas_used = gas_used - gas_refund;
-//     sender_balance_after_refund = get_account(env.state, sender)?.balance + gas_refund_amount;
-//     set_account_balance(env.state, sender, sender_balance_after_refund)?;
-//     coinbase_balance_after_mining_fee = get_account(env.state, env.coinbase)?.balance + transaction_fee;
-//     set_account_balance(env.state, env.coinbase, coinbase_balance_after_mining_fee)?;
-//     for address in output.accounts_to_delete {
-//         destroy_account(env.state, address)?;
-//     }
-//     return Ok((total_gas_used, output.logs));
-// }
+    for ommer in ommers {
+        // Ommer age with respect to the current block.
+        let ommer_age = U256::try_from(block_number - &ommer.number)?;
+        let ommer_miner_reward = (U256::from(8u8) - ommer_age) * BLOCK_REWARD() / U256::from(8u8);
+        create_ether(state, ommer.coinbase, ommer_miner_reward)?;
+    }
 
-// ///
-// ///     Verifies a transaction.
-// ///
-// ///     The gas in a transaction gets used to pay for the intrinsic cost of
-// ///     operations, therefore if there is insufficient gas then it would not
-// ///     be possible to execute a transaction and it will be declared invalid.
-// ///
-// ///     Additionally, the nonce of a transaction must not equal or exceed the
-// ///     limit defined in `EIP-2681 <https://eips.ethereum.org/EIPS/eip-2681>`_.
-// ///     In practice, defining the limit as ``2**64-1`` has no impact because
-// ///     sending ``2**64-1`` transactions is improbable. It's not strictly
-// ///     impossible though, ``2**64-1`` transactions is the entire capacity of the
-// ///     Ethereum blockchain at 2022 gas limits for a little over 22 years.
-// ///
-// ///     Parameters
-// ///     ----------
-// ///     tx :
-// ///         Transaction to validate.
-// ///
-// ///     Returns
-// ///     -------
-// ///     verified : `bool`
-// ///         True if the transaction can be executed, or False otherwise.
-// ///
-// pub fn validate_transaction(tx: Transaction) -> Result<bool, Error> {
-//     return Ok(calculate_intrinsic_cost(tx)? <= tx.gas && tx.nonce < (2).pow(64) - 1);
-// }
+    Ok(())
+}
 
-// ///
-// ///     Calculates the gas that is charged before execution is started.
-// ///
-// ///     The intrinsic cost of the transaction is charged before execution has
-// ///     begun. Functions/operations in the EVM cost money to execute so this
-// ///     intrinsic cost is for the operations that need to be paid for as part of
-// ///     the transaction. Data transfer, for example, is part of this intrinsic
-// ///     cost. It costs ether to send data over the wire and that ether is
-// ///     accounted for in the intrinsic cost calculated in this function. This
-// ///     intrinsic cost must be calculated and paid for before execution in order
-// ///     for all operations to be implemented.
-// ///
-// ///     Parameters
-// ///     ----------
-// ///     tx :
-// ///         Transaction to compute the intrinsic cost of.
-// ///
-// ///     Returns
-// ///     -------
-// ///     verified : `ethereum.base_types.Uint`
-// ///         The intrinsic cost of the transaction.
-// ///
-// pub fn calculate_intrinsic_cost(tx: Transaction) -> Result<Uint, Error> {
-//     data_cost = 0;
-//     for byte in tx.data {
-//         if byte == 0 {
-//             data_cost += TX_DATA_COST_PER_ZERO;
-//         } else {
-//             data_cost += TX_DATA_COST_PER_NON_ZERO;
-//         }
-//     }
-//     return Ok(Uint(TX_BASE_COST + data_cost)?);
-// }
+///
+///     Execute a transaction against the provided environment.
+///
+///     This function processes the actions needed to execute a transaction.
+///     It decrements the sender's account after calculating the gas fee and
+///     refunds them the proper amount after execution. Calling contracts,
+///     deploying code, and incrementing nonces are all examples of actions that
+///     happen within this function or from a call made within this function.
+///
+///     Accounts that are marked for deletion are processed and destroyed after
+///     execution.
+///
+///     Parameters
+///     ----------
+///     env :
+///         Environment for the Ethereum Virtual Machine.
+///     tx :
+///         Transaction to execute.
+///
+///     Returns
+///     -------
+///     gas_left : `ethereum.base_types.U256`
+///         Remaining gas after execution.
+///     logs : `Tuple[ethereum.fork_types.Log, ...]`
+///         Logs generated during execution.
+///
+pub fn process_transaction<S: StateBackend>(env: &mut Environment<S>, tx: &Transaction) -> Result<(Uint, Vec<Log>), EthereumException> {
+    ensure(validate_transaction(tx), EthereumException::InvalidBlock)?;
 
-// ///
-// ///     Extracts the sender address from a transaction.
-// ///
-// ///     The v, r, and s values are the three parts that make up the signature
-// ///     of a transaction. In order to recover the sender of a transaction the two
-// ///     components needed are the signature (``v``, ``r``, and ``s``) and the
-// ///     signing hash of the transaction. The sender's public key can be obtained
-// ///     with these two values and therefore the sender address can be retrieved.
-// ///
-// ///     Parameters
-// ///     ----------
-// ///     tx :
-// ///         Transaction of interest.
-// ///
-// ///     Returns
-// ///     -------
-// ///     sender : `ethereum.fork_types.Address`
-// ///         The address of the account that signed the transaction.
-// ///
-// pub fn recover_sender(tx: Transaction) -> Result<Address, Error> {
-//     (v, r, s) = tx.v = tx.r = tx.s;
-//     ensure(v == 27 || v == 28, InvalidBlock)?;
-//     ensure(0 < r && r < SECP256K1N, InvalidBlock)?;
-//     ensure(0 < s && s < SECP256K1N, InvalidBlock)?;
-//     public_key = secp256k1_recover(r, s, v - 27, signing_hash(tx)?)?;
-//     return Ok(Address(keccak256(public_key)?[12..32])?);
-// }
+    let sender = env.origin;
+    let sender_account = get_account(&env.state, &sender)?;
+    let gas_fee = Uint::from(tx.gas) * Uint::from(tx.gas_price);
+    ensure(sender_account.nonce == Uint::from(tx.nonce), EthereumException::InvalidBlock)?;
+    ensure(Uint::from(sender_account.balance) >= &gas_fee + Uint::from(tx.value), EthereumException::InvalidBlock)?;
+    ensure(sender_account.code.is_empty(), EthereumException::InvalidBlock)?;
 
-// ///
-// ///     Compute the hash of a transaction used in the signature.
-// ///
-// ///     The values that are used to compute the signing hash set the rules for a
-// ///     transaction. For example, signing over the gas sets a limit for the
-// ///     amount of money that is allowed to be pulled out of the sender's account.
-// ///
-// ///     Parameters
-// ///     ----------
-// ///     tx :
-// ///         Transaction of interest.
-// ///
-// ///     Returns
-// ///     -------
-// ///     hash : `ethereum.crypto.hash.Hash32`
-// ///         Hash of the transaction.
-// ///
-// pub fn signing_hash(tx: Transaction) -> Result<Hash32, Error> {
-//     return Ok(keccak256(rlp.encode((tx.nonce, tx.gas_price, tx.gas, tx.to, tx.value, tx.data))?)?);
-// }
+    let gas = Uint::from(tx.gas) - calculate_intrinsic_cost(tx);
+    increment_nonce(&mut env.state, sender)?;
+    let sender_balance_after_gas_fee = sender_account.balance - U256::try_from(gas_fee)?;
+    set_account_balance(&mut env.state, sender, sender_balance_after_gas_fee)?;
 
-// ///
-// ///     Computes the hash of a block header.
-// ///
-// ///     The header hash of a block is the canonical hash that is used to refer
-// ///     to a specific block and completely distinguishes a block from another.
-// ///
-// ///     ``keccak256`` is a function that produces a 256 bit hash of any input.
-// ///     It also takes in any number of bytes as an input and produces a single
-// ///     hash for them. A hash is a completely unique output for a single input.
-// ///     So an input corresponds to one unique hash that can be used to identify
-// ///     the input exactly.
-// ///
-// ///     Prior to using the ``keccak256`` hash function, the header must be
-// ///     encoded using the Recursive-Length Prefix. See :ref:`rlp`.
-// ///     RLP encoding the header converts it into a space-efficient format that
-// ///     allows for easy transfer of data between nodes. The purpose of RLP is to
-// ///     encode arbitrarily nested arrays of binary data, and RLP is the primary
-// ///     encoding method used to serialize objects in Ethereum's execution layer.
-// ///     The only purpose of RLP is to encode structure; encoding specific data
-// ///     types (e.g. strings, floats) is left up to higher-order protocols.
-// ///
-// ///     Parameters
-// ///     ----------
-// ///     header :
-// ///         Header of interest.
-// ///
-// ///     Returns
-// ///     -------
-// ///     hash : `ethereum.crypto.hash.Hash32`
-// ///         Hash of the header.
-// ///
-// pub fn compute_header_hash(header: Header) -> Result<Hash32, Error> {
-//     return Ok(keccak256(rlp.encode(header)?)?);
-// }
+    let message = prepare_message(sender, tx.to, tx.value, tx.data.clone(), gas, env, None)?;
+
+    let output = process_message_call(message, env).map_err(|error| match error {
+        EvmError::State(error) => error,
+        _ => EthereumException::InvalidBlock,
+    })?;
+
+    let gas_used = Uint::from(tx.gas) - &output.gas_left;
+    let gas_refund = (&gas_used / 2u8).min(Uint::from(output.refund_counter));
+    let gas_refund_amount = (&output.gas_left + &gas_refund) * Uint::from(tx.gas_price);
+    let transaction_fee = (Uint::from(tx.gas) - &output.gas_left - &gas_refund) * Uint::from(tx.gas_price);
+    let total_gas_used = gas_used - gas_refund;
+
+    // refund gas
+    let sender_balance_after_refund = Uint::from(get_account(&env.state, &sender)?.balance) + gas_refund_amount;
+    set_account_balance(&mut env.state, sender, U256::try_from(sender_balance_after_refund)?)?;
+
+    // transfer miner fees
+    let coinbase = env.coinbase;
+    let coinbase_balance_after_mining_fee = Uint::from(get_account(&env.state, &coinbase)?.balance) + transaction_fee;
+    set_account_balance(&mut env.state, coinbase, U256::try_from(coinbase_balance_after_mining_fee)?)?;
+
+    for address in &output.accounts_to_delete {
+        destroy_account(&mut env.state, address);
+    }
+
+    Ok((total_gas_used, output.logs))
+}
+
+///
+///     Verifies a transaction.
+///
+///     The gas in a transaction gets used to pay for the intrinsic cost of
+///     operations, therefore if there is insufficient gas then it would not
+///     be possible to execute a transaction and it will be declared invalid.
+///
+///     Additionally, the nonce of a transaction must not equal or exceed the
+///     limit defined in `EIP-2681 <https://eips.ethereum.org/EIPS/eip-2681>`_.
+///     In practice, defining the limit as ``2**64-1`` has no impact because
+///     sending ``2**64-1`` transactions is improbable. It's not strictly
+///     impossible though, ``2**64-1`` transactions is the entire capacity of the
+///     Ethereum blockchain at 2022 gas limits for a little over 22 years.
+///
+///     Parameters
+///     ----------
+///     tx :
+///         Transaction to validate.
+///
+///     Returns
+///     -------
+///     verified : `bool`
+///         True if the transaction can be executed, or False otherwise.
+///
+pub fn validate_transaction(tx: &Transaction) -> bool {
+    calculate_intrinsic_cost(tx) <= Uint::from(tx.gas) && tx.nonce < U256::from(u64::MAX)
+}
+
+///
+///     Calculates the gas that is charged before execution is started.
+///
+///     The intrinsic cost of the transaction is charged before execution has
+///     begun. Functions/operations in the EVM cost money to execute so this
+///     intrinsic cost is for the operations that need to be paid for as part of
+///     the transaction. Data transfer, for example, is part of this intrinsic
+///     cost. It costs ether to send data over the wire and that ether is
+///     accounted for in the intrinsic cost calculated in this function. This
+///     intrinsic cost must be calculated and paid for before execution in order
+///     for all operations to be implemented.
+///
+///     Parameters
+///     ----------
+///     tx :
+///         Transaction to compute the intrinsic cost of.
+///
+///     Returns
+///     -------
+///     verified : `ethereum.base_types.Uint`
+///         The intrinsic cost of the transaction.
+///
+pub fn calculate_intrinsic_cost(tx: &Transaction) -> Uint {
+    let mut data_cost = 0;
+    for byte in tx.data.iter() {
+        if *byte == 0 {
+            data_cost += TX_DATA_COST_PER_ZERO;
+        } else {
+            data_cost += TX_DATA_COST_PER_NON_ZERO;
+        }
+    }
+    Uint::from(TX_BASE_COST + data_cost)
+}
+
+///
+///     Extracts the sender address from a transaction.
+///
+///     The v, r, and s values are the three parts that make up the signature
+///     of a transaction. In order to recover the sender of a transaction the two
+///     components needed are the signature (``v``, ``r``, and ``s``) and the
+///     signing hash of the transaction. The sender's public key can be obtained
+///     with these two values and therefore the sender address can be retrieved.
+///
+///     Parameters
+///     ----------
+///     tx :
+///         Transaction of interest.
+///
+///     Returns
+///     -------
+///     sender : `ethereum.fork_types.Address`
+///         The address of the account that signed the transaction.
+///
+pub fn recover_sender(tx: &Transaction) -> Result<Address, EthereumException> {
+    let (v, r, s) = (tx.v, tx.r, tx.s);
+    ensure(v == U256::from(27u8) || v == U256::from(28u8), EthereumException::InvalidBlock)?;
+    ensure(!r.is_zero() && Uint::from(r) < SECP256K1N(), EthereumException::InvalidBlock)?;
+    ensure(!s.is_zero() && Uint::from(s) < SECP256K1N(), EthereumException::InvalidBlock)?;
+
+    let public_key = secp256k1_recover(r, s, v - U256::from(27u8), signing_hash(tx))?;
+    let mut sender = Address::default();
+    sender.copy_from_slice(&keccak256(&public_key)[12..32]);
+    Ok(sender)
+}
+
+///
+///     Compute the hash of a transaction used in the signature.
+///
+///     The values that are used to compute the signing hash set the rules for a
+///     transaction. For example, signing over the gas sets a limit for the
+///     amount of money that is allowed to be pulled out of the sender's account.
+///
+///     Parameters
+///     ----------
+///     tx :
+///         Transaction of interest.
+///
+///     Returns
+///     -------
+///     hash : `ethereum.crypto.hash.Hash32`
+///         Hash of the transaction.
+///
+pub fn signing_hash(tx: &Transaction) -> Hash32 {
+    keccak256(&rlp::encode(&(&tx.nonce, &tx.gas_price, &tx.gas, &tx.to, &tx.value, &tx.data)))
+}
+
+///
+///     Computes the hash of a block header.
+///
+///     The header hash of a block is the canonical hash that is used to refer
+///     to a specific block and completely distinguishes a block from another.
+///
+///     ``keccak256`` is a function that produces a 256 bit hash of any input.
+///     It also takes in any number of bytes as an input and produces a single
+///     hash for them. A hash is a completely unique output for a single input.
+///     So an input corresponds to one unique hash that can be used to identify
+///     the input exactly.
+///
+///     Prior to using the ``keccak256`` hash function, the header must be
+///     encoded using the Recursive-Length Prefix. See :ref:`rlp`.
+///     RLP encoding the header converts it into a space-efficient format that
+///     allows for easy transfer of data between nodes. The purpose of RLP is to
+///     encode arbitrarily nested arrays of binary data, and RLP is the primary
+///     encoding method used to serialize objects in Ethereum's execution layer.
+///     The only purpose of RLP is to encode structure; encoding specific data
+///     types (e.g. strings, floats) is left up to higher-order protocols.
+///
+///     Parameters
+///     ----------
+///     header :
+///         Header of interest.
+///
+///     Returns
+///     -------
+///     hash : `ethereum.crypto.hash.Hash32`
+///         Hash of the header.
+///
+pub fn compute_header_hash(header: &Header) -> Hash32 {
+    keccak256(&rlp::encode(header))
+}
 
 ///
 ///     Validates the gas limit for a block.
@@ -677,20 +765,19 @@ pub fn validate_ommers(ommers: &Vec<Header>, block_header: Header, chain: &Block
 ///     check : `bool`
 ///         True if gas limit constraints are satisfied, False otherwise.
 ///
-#[allow(unused_variables)]
 pub fn check_gas_limit(gas_limit: &Uint, parent_gas_limit: &Uint) -> Result<bool, EthereumException> {
-    // max_adjustment_delta = (parent_gas_limit).floordiv(GAS_LIMIT_ADJUSTMENT_FACTOR);
-    // if gas_limit >= parent_gas_limit + max_adjustment_delta {
-    //     return Ok(false);
-    // }
-    // if gas_limit <= parent_gas_limit - max_adjustment_delta {
-    //     return Ok(false);
-    // }
-    // if gas_limit < GAS_LIMIT_MINIMUM {
-    //     return Ok(false);
-    // }
-    // return Ok(true);
-    todo!()
+    let max_adjustment_delta = parent_gas_limit / Uint::from(GAS_LIMIT_ADJUSTMENT_FACTOR);
+    if *gas_limit >= parent_gas_limit + &max_adjustment_delta {
+        return Ok(false);
+    }
+    if *gas_limit <= parent_gas_limit - &max_adjustment_delta {
+        return Ok(false);
+    }
+    if *gas_limit < Uint::from(GAS_LIMIT_MINIMUM) {
+        return Ok(false);
+    }
+
+    Ok(true)
 }
 
 ///
@@ -722,18 +809,27 @@ pub fn check_gas_limit(gas_limit: &Uint, parent_gas_limit: &Uint) -> Result<bool
 ///     difficulty : `ethereum.base_types.Uint`
 ///         Computed difficulty for a block.
 ///
-#[allow(unused_variables)]
 pub fn calculate_block_difficulty(block_number: &Uint, block_timestamp: &U256, parent_timestamp: &U256, parent_difficulty: &Uint) -> Result<Uint, EthereumException> {
-    // max_adjustment_delta = (parent_difficulty).floordiv(Uint(2048)?);
-    // if block_timestamp < parent_timestamp + 13 {
-    //     difficulty = parent_difficulty + max_adjustment_delta;
-    // } else {
-    //     difficulty = parent_difficulty - max_adjustment_delta;
-    // }
-    // num_bomb_periods = (int(block_number)?).floordiv(100000) - 2;
-    // if num_bomb_periods >= 0 {
-    //     difficulty += (2).pow(num_bomb_periods);
-    // }
-    // return Ok(max(difficulty, MINIMUM_DIFFICULTY)?);
-    todo!()
+    let max_adjustment_delta = parent_difficulty / Uint::from(2048u16);
+    let mut difficulty = if *block_timestamp < *parent_timestamp + U256::from(13u8) {
+        parent_difficulty + max_adjustment_delta
+    } else {
+        parent_difficulty - max_adjustment_delta
+    };
+
+    // Historical Note: The difficulty bomb was not present in Ethereum at the
+    // start of Frontier, but was added shortly after launch. However since the
+    // bomb has no effect prior to block 200000 we pretend it existed from
+    // genesis.
+    // See https://github.com/ethereum/go-ethereum/pull/1588
+    let num_bomb_periods = block_number / Uint::from(100_000u32);
+    if num_bomb_periods >= Uint::from(2u8) {
+        let exponent = usize::try_from(num_bomb_periods - 2u8).map_err(|_| EthereumException::OverflowError)?;
+        difficulty += Uint::from(1u8) << exponent;
+    }
+
+    // Some clients raise the difficulty to `MINIMUM_DIFFICULTY` prior to adding
+    // the bomb. This bug does not matter because the difficulty is always much
+    // greater than `MINIMUM_DIFFICULTY` on Mainnet.
+    Ok(difficulty.max(Uint::from(MINIMUM_DIFFICULTY)))
 }
@@ -6,7 +6,7 @@
 /// Types re-used throughout the specification, which are specific to Ethereum.
 /// 
 
-use crate::ethereum::{base_types::{Uint, U256, Bytes, Bytes20, Bytes256, Bytes32, Bytes8}, rlp::{self, RLP}};
+use crate::ethereum::{base_types::{Uint, U256, Bytes, Bytes20, Bytes256, Bytes32, Bytes8}, exceptions::EthereumException, rlp::{self, Simple, RLP}};
 
 pub type Hash32 = [u8; 32];
 pub type Hash64 = [u8; 64];
@@ -42,6 +42,7 @@ pub fn keccak512(data: &[u8]) -> Hash64 {
 /// 
 ///     Atomic operation performed on the block chain.
 ///     
+#[derive(Clone, Debug, PartialEq)]
 pub struct Transaction {
     pub nonce: U256,
     pub gas_price: U256,
@@ -54,6 +55,65 @@ pub struct Transaction {
     pub s: U256,
 }
 
+impl Transaction {
+    /// Decode a transaction from its decoded RLP.
+    pub fn decode(decoded: &Simple) -> Result<Self, EthereumException> {
+        let [nonce, gas_price, gas, to, value, data, v, r, s] = decoded.as_list()? else {
+            return Err(EthereumException::RLPDecodingError);
+        };
+        let to = match to.as_bytes()?.len() {
+            0 => None,
+            _ => Some(decode_fixed(to)?),
+        };
+        Ok(Self {
+            nonce: decode_u256(nonce)?,
+            gas_price: decode_u256(gas_price)?,
+            gas: decode_u256(gas)?,
+            to,
+            value: decode_u256(value)?,
+            data: data.as_bytes()?.clone(),
+            v: decode_u256(v)?,
+            r: decode_u256(r)?,
+            s: decode_u256(s)?,
+        })
+    }
+}
+
+impl RLP for Transaction {
+    fn encode(&self) -> Bytes {
+        rlp::encode(&(
+            &self.nonce,
+            &self.gas_price,
+            &self.gas,
+            &self.to,
+            &self.value,
+            &self.data,
+            &self.v,
+            &self.r,
+            &self.s,
+        ))
+    }
+}
+
+/// Decode an unsigned integer, which must not have leading zeros.
+fn decode_uint(decoded: &Simple) -> Result<Uint, EthereumException> {
+    let bytes = decoded.as_bytes()?;
+    if bytes.first() == Some(&0) {
+        return Err(EthereumException::RLPDecodingError);
+    }
+    Ok(Uint::from_bytes_be(bytes))
+}
+
+/// Decode an unsigned integer of at most 256 bits.
+fn decode_u256(decoded: &Simple) -> Result<U256, EthereumException> {
+    U256::try_from(decode_uint(decoded)?).map_err(|_| EthereumException::RLPDecodingError)
+}
+
+/// Decode a byte array of exactly `N` bytes.
+fn decode_fixed<const N: usize>(decoded: &Simple) -> Result<[u8; N], EthereumException> {
+    <[u8; N]>::try_from(decoded.as_bytes()?.as_ref()).map_err(|_| EthereumException::RLPDecodingError)
+}
+
 
 /// 
 ///     State associated with an address.
@@ -88,7 +148,7 @@ pub fn encode_account(raw_account_data: Account, storage_root: Root) -> Bytes {
 /// 
 ///     Header portion of a block on the chain.
 ///     
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Header {
     pub parent_hash: Hash32,
     pub ommers_hash: Hash32,
@@ -109,28 +169,53 @@ pub struct Header {
 
 
 impl Header {
+    /// Decode a header from its decoded RLP.
+    pub fn decode(decoded: &Simple) -> Result<Self, EthereumException> {
+        let [parent_hash, ommers_hash, coinbase, state_root, transactions_root, receipt_root, bloom, difficulty, number, gas_limit, gas_used, timestamp, extra_data, mix_digest, nonce] = decoded.as_list()? else {
+            return Err(EthereumException::RLPDecodingError);
+        };
+        Ok(Self {
+            parent_hash: decode_fixed(parent_hash)?,
+            ommers_hash: decode_fixed(ommers_hash)?,
+            coinbase: decode_fixed(coinbase)?,
+            state_root: decode_fixed(state_root)?,
+            transactions_root: decode_fixed(transactions_root)?,
+            receipt_root: decode_fixed(receipt_root)?,
+            bloom: decode_fixed(bloom)?,
+            difficulty: decode_uint(difficulty)?,
+            number: decode_uint(number)?,
+            gas_limit: decode_uint(gas_limit)?,
+            gas_used: decode_uint(gas_used)?,
+            timestamp: decode_u256(timestamp)?,
+            extra_data: extra_data.as_bytes()?.clone(),
+            mix_digest: decode_fixed(mix_digest)?,
+            nonce: decode_fixed(nonce)?,
+        })
+    }
 }
 
 impl RLP for Header {
     fn encode(&self) -> Bytes {
-        // rlp::encode(&(
-        //     &self.parent_hash,
-        //     &self.ommers_hash,
-        //     &self.coinbase,
-        //     &self.state_root,
-        //     &self.transactions_root,
-        //     &self.receipt_root,
-        //     &self.bloom,
-        //     &self.difficulty.clone(),
-        //     &self.number.clone(),
-        //     &self.gas_limit.clone(),
-        //     &self.gas_used.clone(),
-        //     &self.timestamp.clone(),
-        //     &self.extra_data.clone(),
-        //     &self.mix_digest,
-        //     &self.nonce,
-        // ))
-        todo!()
+        // There are more fields than the tuples `RLP` is implemented for.
+        let joined_encodings = [
+            self.parent_hash.encode(),
+            self.ommers_hash.encode(),
+            self.coinbase.encode(),
+            self.state_root.encode(),
+            self.transactions_root.encode(),
+            self.receipt_root.encode(),
+            self.bloom.encode(),
+            self.difficulty.encode(),
+            self.number.encode(),
+            self.gas_limit.encode(),
+            self.gas_used.encode(),
+            self.timestamp.encode(),
+            self.extra_data.encode(),
+            self.mix_digest.encode(),
+            self.nonce.encode(),
+        ]
+        .concat();
+        rlp::encode_sequence(&joined_encodings)
     }
 }
 
@@ -138,6 +223,7 @@ impl RLP for Header {
 /// 
 ///     A complete block.
 ///     
+#[derive(Clone, Debug, PartialEq)]
 pub struct Block {
     pub header: Header,
     pub transactions: Vec<Transaction>,
@@ -146,13 +232,30 @@ pub struct Block {
 
 
 impl Block {
+    /// Decode a block from its decoded RLP.
+    pub fn decode(decoded: &Simple) -> Result<Self, EthereumException> {
+        let [header, transactions, ommers] = decoded.as_list()? else {
+            return Err(EthereumException::RLPDecodingError);
+        };
+        Ok(Self {
+            header: Header::decode(header)?,
+            transactions: transactions.as_list()?.iter().map(Transaction::decode).collect::<Result<_, _>>()?,
+            ommers: ommers.as_list()?.iter().map(Header::decode).collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl RLP for Block {
+    fn encode(&self) -> Bytes {
+        rlp::encode(&(&self.header, &self.transactions, &self.ommers))
+    }
 }
 
 
 /// 
 ///     Data record produced during the execution of a transaction.
 ///     
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Log {
     pub address: Address,
     pub topics: Vec<Hash32>,
@@ -160,13 +263,17 @@ pub struct Log {
 }
 
 
-impl Log {
+impl RLP for Log {
+    fn encode(&self) -> Bytes {
+        rlp::encode(&(&self.address, &self.topics, &self.data))
+    }
 }
 
 
 /// 
 ///     Result of a transaction.
 ///     
+#[derive(Clone, Debug, PartialEq)]
 pub struct Receipt {
     pub post_state: Root,
     pub cumulative_gas_used: Uint,
@@ -175,5 +282,8 @@ pub struct Receipt {
 }
 
 
-impl Receipt {
+impl RLP for Receipt {
+    fn encode(&self) -> Bytes {
+        rlp::encode(&(&self.post_state, &self.cumulative_gas_used, &self.bloom, &self.logs))
+    }
 }
@@ -0,0 +1,166 @@
+//!
+//! # Gas Estimation
+//!
+//! ## Introduction
+//!
+//! The behaviour of `eth_estimateGas`: the smallest gas limit with which a
+//! transaction executes without an exceptional halt.
+//!
+//! The transaction is executed for every gas limit tried, in a transaction
+//! of the state which is rolled back afterwards, in a binary search between
+//! its intrinsic cost and the most gas it can be given, which is the gas
+//! limit of the block, or less when the sender cannot pay for that much.
+//!
+
+use crate::ethereum::{
+    base_types::{Uint, U256},
+    exceptions::EthereumException,
+};
+
+use super::{
+    call::call,
+    fork::calculate_intrinsic_cost,
+    fork_types::{Address, Transaction},
+    state::{
+        begin_transaction, get_account, increment_nonce, rollback_transaction, set_account_balance,
+        StateBackend,
+    },
+    utils::message::prepare_message,
+    vm::{exceptions::EvmError, Environment},
+};
+
+/// Why no gas limit lets a transaction succeed.
+#[derive(Debug)]
+pub enum EstimateGasError {
+    /// The intrinsic cost of the transaction, which is more than the gas
+    /// limit of the block.
+    IntrinsicGasTooHigh(Uint),
+    /// The sender cannot pay for the value and the intrinsic cost of the
+    /// transaction.
+    InsufficientFunds,
+    /// The transaction halts exceptionally even with the most gas it can be
+    /// given.
+    Failed(EvmError),
+    /// The state could not be read.
+    State(EthereumException),
+}
+
+impl From<EthereumException> for EstimateGasError {
+    fn from(error: EthereumException) -> Self {
+        EstimateGasError::State(error)
+    }
+}
+
+///
+/// Execute `tx` from the caller of `env` with a gas limit of `gas`, and
+/// return the exceptional halt it ends with, if any. Every change to the
+/// state is rolled back, also when the state can not be read.
+///
+fn execute<S: StateBackend>(
+    env: &mut Environment<S>,
+    tx: &Transaction,
+    gas: &Uint,
+) -> Result<Option<EvmError>, EstimateGasError> {
+    begin_transaction(&mut env.state);
+    let result = pay_and_call(env, tx, gas);
+    rollback_transaction(&mut env.state);
+    result
+}
+
+fn pay_and_call<S: StateBackend>(
+    env: &mut Environment<S>,
+    tx: &Transaction,
+    gas: &Uint,
+) -> Result<Option<EvmError>, EstimateGasError> {
+    let sender = env.caller;
+    let sender_account = get_account(&env.state, &sender)?;
+    let gas_fee = U256::try_from(Uint::from(tx.gas_price) * gas)?;
+    increment_nonce(&mut env.state, sender)?;
+    set_account_balance(&mut env.state, sender, sender_account.balance - gas_fee)?;
+
+    let message = prepare_message(
+        sender,
+        tx.to,
+        tx.value,
+        tx.data.clone(),
+        gas - calculate_intrinsic_cost(tx),
+        env,
+        None,
+    )?;
+    Ok(call(message, env)?.error)
+}
+
+///
+/// Find the smallest gas limit with which `tx` succeeds.
+///
+/// The gas limit, nonce and signature of `tx` are ignored.
+///
+/// Parameters
+/// ----------
+/// env :
+///     The block the transaction is executed in, and its state, which is
+///     left as it was.
+/// sender :
+///     Address which sends the transaction.
+/// tx :
+///     Transaction to estimate the gas of.
+///
+/// Returns
+/// -------
+/// gas : `ethereum.base_types.Uint`
+///     The smallest gas limit with which the transaction succeeds.
+///
+pub fn estimate_gas<S: StateBackend, I>(
+    env: &mut Environment<S, I>,
+    sender: Address,
+    tx: &Transaction,
+) -> Result<Uint, EstimateGasError> {
+    let intrinsic_cost = calculate_intrinsic_cost(tx);
+    if intrinsic_cost > env.gas_limit {
+        return Err(EstimateGasError::IntrinsicGasTooHigh(intrinsic_cost));
+    }
+
+    // The sender has to pay for all of the gas up front.
+    let balance = get_account(&env.state, &sender)?.balance;
+    let mut high = env.gas_limit.clone();
+    if !tx.gas_price.is_zero() {
+        let affordable = balance
+            .checked_sub(tx.value)
+            .ok_or(EstimateGasError::InsufficientFunds)?
+            / tx.gas_price;
+        high = high.min(Uint::from(affordable));
+    } else if balance < tx.value {
+        return Err(EstimateGasError::InsufficientFunds);
+    }
+    if high < intrinsic_cost {
+        return Err(EstimateGasError::InsufficientFunds);
+    }
+
+    let mut env = Environment {
+        caller: sender,
+        block_hashes: env.block_hashes.clone(),
+        origin: sender,
+        coinbase: env.coinbase,
+        number: env.number.clone(),
+        gas_limit: env.gas_limit.clone(),
+        gas_price: tx.gas_price,
+        time: env.time,
+        difficulty: env.difficulty.clone(),
+        state: &mut env.state,
+        inspector: (),
+    };
+    if let Some(error) = execute(&mut env, tx, &high)? {
+        return Err(EstimateGasError::Failed(error));
+    }
+
+    // `low` always fails, and `high` always succeeds.
+    let mut low = intrinsic_cost - 1u8;
+    while &low + 1u8 < high {
+        let gas = (&low + &high) / 2u8;
+        match execute(&mut env, tx, &gas)? {
+            None => high = gas,
+            Some(_) => low = gas,
+        }
+    }
+    Ok(high)
+}
@@ -10,7 +10,18 @@ pub const MAINNET_FORK_BLOCK : u32 = 0;
 
 pub mod fork_types;
 pub mod trie;
+pub mod trie_proof;
+pub mod trie_diff;
 pub mod bloom;
+pub mod call;
 pub mod fork;
+pub mod gas_estimation;
 pub mod state;
+pub mod state_backends;
+pub mod state_dump;
+pub mod snapshot;
+pub mod state_overrides;
+pub mod prestate;
+pub mod witness;
+pub mod utils;
 pub mod vm;
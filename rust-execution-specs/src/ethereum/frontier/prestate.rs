@@ -0,0 +1,278 @@
+//!
+//! # Prestate Recorder
+//!
+//! ## Introduction
+//!
+//! Records what a transaction changed, in the shape written by geth's
+//! `prestateTracer` with `diffMode` enabled:
+//!
+//! ```json
+//! {
+//!     "pre": { "0x<address>": { "balance": "0x…", "nonce": 1, "code": "0x…", "storage": { "0x<slot>": "0x<value>" } } },
+//!     "post": { "0x<address>": { "balance": "0x…" } }
+//! }
+//! ```
+//!
+//! `pre` holds every modified account as it was before the transaction, with
+//! only the storage slots that changed. `post` holds only the fields that
+//! changed. Created accounts only appear in `post`, and destroyed accounts
+//! only in `pre`.
+//!
+
+use std::collections::{BTreeMap, HashMap};
+
+use num_traits::ToPrimitive;
+use serde_json::{json, Map, Value};
+
+use crate::ethereum::{
+    base_types::{Bytes, Bytes32, Uint, U256},
+    exceptions::EthereumException,
+    utils::hexadecimal::hex,
+};
+
+use super::{
+    fork_types::{Account, Address, Hash32, Root},
+    state::StateBackend,
+};
+
+/// An account, as it appears in `pre` or `post`. Fields that are left out of
+/// `post` because they did not change are `None`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AccountState {
+    pub balance: Option<U256>,
+    pub nonce: Option<Uint>,
+    pub code: Option<Bytes>,
+    pub storage: BTreeMap<Bytes32, U256>,
+}
+
+/// The accounts a transaction changed, before and after.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PrestateDiff {
+    pub pre: BTreeMap<Address, AccountState>,
+    pub post: BTreeMap<Address, AccountState>,
+}
+
+///
+/// A `StateBackend` that remembers the value of every account and storage
+/// slot before it is first written, so the changes can be listed with
+/// `take_diff` once a transaction is done.
+///
+/// Storage cleared by `destroy_storage` is only listed for the slots that
+/// were written through this recorder, as a backend cannot enumerate them.
+///
+pub struct PrestateRecorder<S> {
+    pub state: S,
+    accounts: HashMap<Address, Option<Account>>,
+    storage: HashMap<Address, HashMap<Bytes32, U256>>,
+    error: Option<EthereumException>,
+}
+
+impl<S: StateBackend> PrestateRecorder<S> {
+    /// Start recording changes made to `state`.
+    pub fn new(state: S) -> Self {
+        Self {
+            state,
+            accounts: HashMap::new(),
+            storage: HashMap::new(),
+            error: None,
+        }
+    }
+
+    /// Stop recording and give back the state.
+    pub fn into_inner(self) -> S {
+        self.state
+    }
+
+    fn remember_account(&mut self, address: &Address) {
+        if self.accounts.contains_key(address) {
+            return;
+        }
+        match self.state.get_account_optional(address) {
+            Ok(account) => {
+                self.accounts.insert(*address, account);
+            }
+            Err(error) => {
+                self.error.get_or_insert(error);
+            }
+        }
+    }
+
+    fn remember_storage(&mut self, address: &Address, key: &Bytes32) {
+        self.remember_account(address);
+        if self.storage.get(address).is_some_and(|slots| slots.contains_key(key)) {
+            return;
+        }
+        match self.state.get_storage(address, key) {
+            Ok(value) => {
+                self.storage.entry(*address).or_default().insert(*key, value);
+            }
+            Err(error) => {
+                self.error.get_or_insert(error);
+            }
+        }
+    }
+
+    ///
+    /// List the changes made since the recorder was created or last asked,
+    /// and start recording afresh.
+    ///
+    /// Returns
+    /// -------
+    /// diff : `PrestateDiff`
+    ///     The modified accounts before and after the changes.
+    ///
+    pub fn take_diff(&mut self) -> Result<PrestateDiff, EthereumException> {
+        let accounts = std::mem::take(&mut self.accounts);
+        let mut storage = std::mem::take(&mut self.storage);
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+
+        let mut diff = PrestateDiff::default();
+        for (address, before) in accounts {
+            let after = self.state.get_account_optional(&address)?;
+            let mut pre_storage = BTreeMap::new();
+            let mut post_storage = BTreeMap::new();
+            for (key, old) in storage.remove(&address).unwrap_or_default() {
+                let new = self.state.get_storage(&address, &key)?;
+                if old == new {
+                    continue;
+                }
+                if old != U256::default() {
+                    pre_storage.insert(key, old);
+                }
+                if new != U256::default() {
+                    post_storage.insert(key, new);
+                }
+            }
+
+            match (before, after) {
+                (None, None) => {}
+                (Some(before), None) => {
+                    diff.pre.insert(address, full_state(before, pre_storage));
+                }
+                (None, Some(after)) => {
+                    diff.post.insert(address, full_state(after, post_storage));
+                }
+                (Some(before), Some(after)) => {
+                    if before == after && pre_storage.is_empty() && post_storage.is_empty() {
+                        continue;
+                    }
+                    let post = AccountState {
+                        balance: (before.balance != after.balance).then_some(after.balance),
+                        nonce: (before.nonce != after.nonce).then(|| after.nonce.clone()),
+                        code: (before.code != after.code).then(|| after.code.clone()),
+                        storage: post_storage,
+                    };
+                    diff.pre.insert(address, full_state(before, pre_storage));
+                    diff.post.insert(address, post);
+                }
+            }
+        }
+        Ok(diff)
+    }
+}
+
+impl<S: StateBackend> StateBackend for PrestateRecorder<S> {
+    fn get_account_optional(&self, address: &Address) -> Result<Option<Account>, EthereumException> {
+        self.state.get_account_optional(address)
+    }
+
+    fn set_account(&mut self, address: Address, account: Option<Account>) {
+        self.remember_account(&address);
+        self.state.set_account(address, account);
+    }
+
+    fn destroy_storage(&mut self, address: &Address) {
+        self.remember_account(address);
+        self.state.destroy_storage(address);
+    }
+
+    fn get_storage(&self, address: &Address, key: &Bytes32) -> Result<U256, EthereumException> {
+        self.state.get_storage(address, key)
+    }
+
+    fn set_storage(&mut self, address: Address, key: &Bytes32, value: U256) {
+        self.remember_storage(&address, key);
+        self.state.set_storage(address, key, value);
+    }
+
+    fn get_code_hash(&self, address: &Address) -> Result<Hash32, EthereumException> {
+        self.state.get_code_hash(address)
+    }
+
+    fn begin_transaction(&mut self) {
+        self.state.begin_transaction();
+    }
+
+    fn commit_transaction(&mut self) {
+        self.state.commit_transaction();
+    }
+
+    fn rollback_transaction(&mut self) {
+        self.state.rollback_transaction();
+    }
+
+    fn storage_root(&self, address: &Address) -> Result<Root, EthereumException> {
+        self.state.storage_root(address)
+    }
+
+    fn state_root(&self) -> Result<Root, EthereumException> {
+        self.state.state_root()
+    }
+}
+
+fn full_state(account: Account, storage: BTreeMap<Bytes32, U256>) -> AccountState {
+    AccountState {
+        balance: Some(account.balance),
+        nonce: Some(account.nonce),
+        code: Some(account.code),
+        storage,
+    }
+}
+
+impl AccountState {
+    /// Convert to JSON, leaving out empty fields as geth does.
+    pub fn to_json(&self) -> Value {
+        let mut object = Map::new();
+        if let Some(balance) = &self.balance {
+            object.insert("balance".into(), Value::String(format!("{balance:#x}")));
+        }
+        if let Some(nonce) = self.nonce.as_ref().filter(|nonce| **nonce != Uint::default()) {
+            object.insert("nonce".into(), json!(nonce.to_u64()));
+        }
+        if let Some(code) = self.code.as_ref().filter(|code| !code.is_empty()) {
+            object.insert("code".into(), Value::String(hex(code)));
+        }
+        if !self.storage.is_empty() {
+            let storage = self
+                .storage
+                .iter()
+                .map(|(key, value)| (hex(key), Value::String(hex(&slot_value(value)))))
+                .collect::<Map<_, _>>();
+            object.insert("storage".into(), Value::Object(storage));
+        }
+        Value::Object(object)
+    }
+}
+
+impl PrestateDiff {
+    /// Convert to the JSON written by geth's `prestateTracer` in `diffMode`.
+    pub fn to_json(&self) -> Value {
+        let accounts = |accounts: &BTreeMap<Address, AccountState>| {
+            accounts
+                .iter()
+                .map(|(address, account)| (hex(address), account.to_json()))
+                .collect::<Map<_, _>>()
+        };
+        json!({
+            "pre": accounts(&self.pre),
+            "post": accounts(&self.post),
+        })
+    }
+}
+
+/// A storage value as a full 32 byte word.
+fn slot_value(value: &U256) -> Bytes32 {
+    value.to_be_bytes32()
+}
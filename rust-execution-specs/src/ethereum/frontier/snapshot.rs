@@ -0,0 +1,240 @@
+//!
+//! # State Snapshots
+//!
+//! ## Introduction
+//!
+//! A flat view of the state in front of the tries of a `NodeDb`, so reads
+//! are a map lookup by address, or by address and slot, instead of a walk
+//! down the tries that hashes the key.
+//!
+//! The snapshot is a stack of layers:
+//!
+//! - The disk layer holds the state at some block. It is filled from the
+//!   tries the first time an account or slot is read.
+//! - On top of it, each committed block adds a diff layer holding the
+//!   changes made by that block. Diff layers can be flattened into the disk
+//!   layer once their block is final, or discarded on a reorg.
+//!
+//! When a diff layer is committed, the layers below it are checked against
+//! the tries before the block, for every account and storage slot the block
+//! changes.
+//!
+
+use std::{cell::RefCell, collections::HashMap};
+
+use crate::ethereum::{
+    base_types::{Bytes32, U256},
+    exceptions::EthereumException,
+    utils::hexadecimal::hex,
+};
+
+use super::{
+    fork_types::{Account, Address, Root},
+    state_backends::{Changes, NodeDb, OverlayState, StateSource},
+};
+
+/// The changes made by a single block, on top of the layer below.
+#[derive(Clone, Debug)]
+pub struct DiffLayer {
+    /// The state root after the block.
+    pub root: Root,
+    pub changes: Changes,
+}
+
+/// Accounts and storage slots read from the tries at the disk layer's root.
+#[derive(Debug, Default)]
+struct FlatCache {
+    accounts: HashMap<Address, Option<Account>>,
+    storage: HashMap<Address, HashMap<Bytes32, U256>>,
+}
+
+///
+/// A layered, flat snapshot of the state held in `trie`.
+///
+pub struct Snapshot {
+    pub trie: NodeDb,
+    disk_root: Root,
+    disk: RefCell<FlatCache>,
+    layers: Vec<DiffLayer>,
+}
+
+/// State backed by a `Snapshot`.
+pub type SnapshotState = OverlayState<Snapshot>;
+
+impl Snapshot {
+    /// Create a snapshot of the current state of `trie`, without any diff
+    /// layers.
+    pub fn new(trie: NodeDb) -> Self {
+        Self {
+            disk_root: trie.root,
+            trie,
+            disk: RefCell::new(FlatCache::default()),
+            layers: Vec::new(),
+        }
+    }
+
+    /// The state root of the disk layer.
+    pub fn disk_root(&self) -> Root {
+        self.disk_root
+    }
+
+    /// The diff layers, oldest first.
+    pub fn layers(&self) -> &[DiffLayer] {
+        &self.layers
+    }
+
+    ///
+    /// Merge the oldest diff layers into the disk layer, so that at most
+    /// `keep` diff layers are left.
+    ///
+    /// Parameters
+    /// ----------
+    /// keep :
+    ///     The number of most recent diff layers to keep.
+    ///
+    pub fn flatten(&mut self, keep: usize) {
+        let count = self.layers.len().saturating_sub(keep);
+        let disk = self.disk.get_mut();
+        for layer in self.layers.drain(..count) {
+            for address in &layer.changes.destroyed {
+                disk.storage.remove(address);
+            }
+            for (address, slots) in layer.changes.storage {
+                disk.storage.entry(address).or_default().extend(slots);
+            }
+            disk.accounts.extend(layer.changes.accounts);
+            self.disk_root = layer.root;
+        }
+    }
+
+    ///
+    /// Discard the diff layers after the block with state root `root`, e.g.
+    /// on a reorg, and make that the current state.
+    ///
+    /// Parameters
+    /// ----------
+    /// root :
+    ///     State root of the disk layer, or of one of the diff layers.
+    ///
+    pub fn revert(&mut self, root: &Root) -> Result<(), EthereumException> {
+        let keep = match self.layers.iter().rposition(|layer| layer.root == *root) {
+            Some(index) => index + 1,
+            None if *root == self.disk_root => 0,
+            None => {
+                return Err(EthereumException::StateUnavailable(format!(
+                    "no snapshot layer with root {}",
+                    hex(root)
+                )))
+            }
+        };
+        self.layers.truncate(keep);
+        self.trie.root = *root;
+        Ok(())
+    }
+
+    ///
+    /// Write `changes` to the tries, and add them to the snapshot as a new
+    /// diff layer.
+    ///
+    /// The block was executed against the snapshot, so first check that the
+    /// snapshot and the tries agree on every account and storage slot it
+    /// changed, as they were before the block.
+    ///
+    /// Returns
+    /// -------
+    /// root : `Root`
+    ///     The new state root.
+    ///
+    fn commit(&mut self, changes: Changes) -> Result<Root, EthereumException> {
+        let addresses = changes.accounts.keys().chain(changes.storage.keys()).chain(&changes.destroyed);
+        for address in addresses {
+            if self.account(address)? != self.trie.account(address)? {
+                return Err(mismatch(address, None));
+            }
+        }
+        for (address, slots) in &changes.storage {
+            for key in slots.keys() {
+                if self.storage(address, key)? != self.trie.storage(address, key)? {
+                    return Err(mismatch(address, Some(key)));
+                }
+            }
+        }
+
+        let root = self.trie.apply(&changes)?;
+        self.layers.push(DiffLayer { root, changes });
+        Ok(root)
+    }
+}
+
+impl StateSource for Snapshot {
+    fn account(&self, address: &Address) -> Result<Option<Account>, EthereumException> {
+        for layer in self.layers.iter().rev() {
+            if let Some(account) = layer.changes.accounts.get(address) {
+                return Ok(account.clone());
+            }
+        }
+
+        if let Some(account) = self.disk.borrow().accounts.get(address) {
+            return Ok(account.clone());
+        }
+        let account = self.trie.account_at(&self.disk_root, address)?;
+        self.disk.borrow_mut().accounts.insert(*address, account.clone());
+        Ok(account)
+    }
+
+    fn storage(&self, address: &Address, key: &Bytes32) -> Result<U256, EthereumException> {
+        for layer in self.layers.iter().rev() {
+            if let Some(value) = layer.changes.storage.get(address).and_then(|slots| slots.get(key)) {
+                return Ok(*value);
+            }
+            if layer.changes.destroyed.contains(address) {
+                return Ok(U256::from(0u8));
+            }
+        }
+
+        let cached = self.disk.borrow().storage.get(address).and_then(|slots| slots.get(key).cloned());
+        if let Some(value) = cached {
+            return Ok(value);
+        }
+        let value = self.trie.storage_at(&self.disk_root, address, key)?;
+        self.disk
+            .borrow_mut()
+            .storage
+            .entry(*address)
+            .or_default()
+            .insert(*key, value);
+        Ok(value)
+    }
+
+    fn storage_root(&self, address: &Address, changes: &Changes) -> Result<Root, EthereumException> {
+        self.trie.storage_root(address, changes)
+    }
+
+    fn state_root(&self, changes: &Changes) -> Result<Root, EthereumException> {
+        self.trie.state_root(changes)
+    }
+}
+
+impl SnapshotState {
+    ///
+    /// Write the buffered changes to the tries, and add them to the snapshot
+    /// as a new diff layer.
+    ///
+    /// Returns
+    /// -------
+    /// root : `Root`
+    ///     The new state root.
+    ///
+    pub fn commit(&mut self) -> Result<Root, EthereumException> {
+        let changes = self.take_changes();
+        self.source.commit(changes)
+    }
+}
+
+fn mismatch(address: &Address, key: Option<&Bytes32>) -> EthereumException {
+    let what = match key {
+        Some(key) => format!("storage {} of {}", hex(key), hex(address)),
+        None => format!("account {}", hex(address)),
+    };
+    EthereumException::InvalidSnapshot(format!("snapshot and trie disagree on {what}"))
+}
@@ -10,23 +10,158 @@
 //! There is a distinction between an account that does not exist and
 //! `EMPTY_ACCOUNT`.
 //!
+//! The operations on the state are captured by the `StateBackend` trait, so
+//! that the virtual machine and the block processing in `fork` can run on
+//! other storage than the in-memory `State` defined here (see
+//! [state_backends](super::state_backends)).
+//!
 
 use super::{
-    fork_types::{Account, Address, Root, empty_account},
+    fork_types::{keccak256, Account, Address, Hash32, Root, empty_account},
     trie::{self, Trie},
+    trie_diff::{self, Change},
 };
-use crate::ethereum::{base_types::{Bytes, Uint, U256, Bytes32}, frontier::trie::dummy_root};
-use num_traits::CheckedSub;
+use crate::ethereum::{base_types::{Bytes, Uint, U256, Bytes32}, exceptions::EthereumException, frontier::trie::dummy_root};
+use std::cell::RefCell;
 use std::collections::HashMap;
 
+/// The operations the specification performs on the state.
+///
+/// Reads may fail, because implementations other than `State` can load
+/// accounts and storage lazily from somewhere else. Writes are only ever
+/// buffered, so they can not.
+pub trait StateBackend {
+    /// See [get_account_optional].
+    fn get_account_optional(&self, address: &Address) -> Result<Option<Account>, EthereumException>;
+
+    /// See [set_account].
+    fn set_account(&mut self, address: Address, account: Option<Account>);
+
+    /// See [destroy_storage].
+    fn destroy_storage(&mut self, address: &Address);
+
+    /// See [get_storage].
+    fn get_storage(&self, address: &Address, key: &Bytes32) -> Result<U256, EthereumException>;
+
+    /// See [set_storage].
+    fn set_storage(&mut self, address: Address, key: &Bytes32, value: U256);
+
+    /// See [get_code_hash]. Implementations which store the hashes of codes
+    /// should return them rather than hash the code again.
+    fn get_code_hash(&self, address: &Address) -> Result<Hash32, EthereumException> {
+        let account = self.get_account_optional(address)?;
+        Ok(keccak256(account.as_ref().map_or(&[][..], |account| &account.code)))
+    }
+
+    /// See [begin_transaction].
+    fn begin_transaction(&mut self);
+
+    /// See [commit_transaction].
+    fn commit_transaction(&mut self);
+
+    /// See [rollback_transaction].
+    fn rollback_transaction(&mut self);
+
+    /// See [storage_root].
+    fn storage_root(&self, address: &Address) -> Result<Root, EthereumException>;
+
+    /// See [state_root].
+    fn state_root(&self) -> Result<Root, EthereumException>;
+}
+
+/// Lets an `Environment` borrow a state which outlives it, such as the state
+/// of a `BlockChain` while a block is applied.
+impl<S: StateBackend + ?Sized> StateBackend for &mut S {
+    fn get_account_optional(&self, address: &Address) -> Result<Option<Account>, EthereumException> {
+        (**self).get_account_optional(address)
+    }
+
+    fn set_account(&mut self, address: Address, account: Option<Account>) {
+        (**self).set_account(address, account)
+    }
+
+    fn destroy_storage(&mut self, address: &Address) {
+        (**self).destroy_storage(address)
+    }
+
+    fn get_storage(&self, address: &Address, key: &Bytes32) -> Result<U256, EthereumException> {
+        (**self).get_storage(address, key)
+    }
+
+    fn set_storage(&mut self, address: Address, key: &Bytes32, value: U256) {
+        (**self).set_storage(address, key, value)
+    }
+
+    fn get_code_hash(&self, address: &Address) -> Result<Hash32, EthereumException> {
+        (**self).get_code_hash(address)
+    }
+
+    fn begin_transaction(&mut self) {
+        (**self).begin_transaction()
+    }
+
+    fn commit_transaction(&mut self) {
+        (**self).commit_transaction()
+    }
+
+    fn rollback_transaction(&mut self) {
+        (**self).rollback_transaction()
+    }
+
+    fn storage_root(&self, address: &Address) -> Result<Root, EthereumException> {
+        (**self).storage_root(address)
+    }
+
+    fn state_root(&self) -> Result<Root, EthereumException> {
+        (**self).state_root()
+    }
+}
+
 /// Contains all information that is preserved between transactions.
+#[derive(Clone)]
 pub struct State {
     main_trie: Trie<Address, Option<Account>>,
     storage_tries: HashMap<Address, Trie<Bytes32, U256>>,
-    snapshots: Vec<(
-        Trie<Address, Option<Account>>,
-        HashMap<Address, Trie<Bytes32, U256>>,
-    )>,
+    journal: Vec<JournalEntry>,
+    checkpoints: Vec<usize>,
+    /// Where the changes of the block begun with `begin_block` start in the
+    /// journal.
+    block_checkpoint: Option<usize>,
+    /// The hashes of the codes of accounts, computed when first asked for
+    /// and forgotten when the code of the account changes.
+    code_hashes: RefCell<HashMap<Address, Hash32>>,
+}
+
+/// Undo information for a single change made during a state transaction.
+///
+/// Previous values are those stored in the trie's data, so `None` means the
+/// key was absent rather than set to the default.
+#[derive(Clone)]
+enum JournalEntry {
+    AccountSet {
+        address: Address,
+        previous: Option<Option<Account>>,
+    },
+    StorageSet {
+        address: Address,
+        key: Bytes32,
+        previous: Option<U256>,
+        created_trie: bool,
+    },
+    StorageDestroyed {
+        address: Address,
+        previous: Option<Trie<Bytes32, U256>>,
+    },
+}
+
+/// The storage slots of an account that differ between two states.
+pub type StorageDiff = Vec<(Bytes32, Change<U256>)>;
+
+/// The accounts and storage slots that differ between two states.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StateDiff {
+    pub accounts: Vec<(Address, Change<Account>)>,
+    pub storage: Vec<(Address, StorageDiff)>,
 }
 
 impl Default for State {
@@ -34,28 +169,263 @@ impl Default for State {
         Self {
             main_trie: Trie::new(true, None),
             storage_tries: HashMap::new(),
-            snapshots: Vec::new(),
+            journal: Vec::new(),
+            checkpoints: Vec::new(),
+            block_checkpoint: None,
+            code_hashes: RefCell::default(),
+        }
+    }
+}
+
+impl State {
+    /// Record an undo entry, if a transaction or a block is ongoing.
+    fn record(&mut self, entry: JournalEntry) {
+        if !self.checkpoints.is_empty() || self.block_checkpoint.is_some() {
+            self.journal.push(entry);
+        }
+    }
+
+    /// Undo the changes recorded after the first `checkpoint` entries of the
+    /// journal.
+    fn unwind(&mut self, checkpoint: usize) {
+        while self.journal.len() > checkpoint {
+            let entry = self.journal.pop().unwrap();
+            self.revert(entry);
+        }
+    }
+
+    /// Undo a single change, and return the entry which makes it again.
+    fn revert(&mut self, entry: JournalEntry) -> JournalEntry {
+        match entry {
+            JournalEntry::AccountSet { address, previous } => {
+                self.code_hashes.get_mut().remove(&address);
+                let previous = restore(&mut self.main_trie.data, address, previous);
+                JournalEntry::AccountSet { address, previous }
+            }
+            JournalEntry::StorageSet {
+                address,
+                key,
+                previous,
+                created_trie,
+            } => {
+                if created_trie {
+                    let previous = self.storage_tries.remove(&address);
+                    return JournalEntry::StorageDestroyed { address, previous };
+                }
+                let previous = self
+                    .storage_tries
+                    .get_mut(&address)
+                    .and_then(|trie| restore(&mut trie.data, key, previous));
+                JournalEntry::StorageSet {
+                    address,
+                    key,
+                    previous,
+                    created_trie: false,
+                }
+            }
+            JournalEntry::StorageDestroyed { address, previous } => {
+                let previous = restore(&mut self.storage_tries, address, previous);
+                JournalEntry::StorageDestroyed { address, previous }
+            }
+        }
+    }
+
+    /// Start recording the changes of a block, so that they can be rolled
+    /// back with `rollback_block` even after its transactions have been
+    /// committed and the state root computed, which a transaction does not
+    /// allow.
+    pub fn begin_block(&mut self) {
+        assert!(self.checkpoints.is_empty() && self.block_checkpoint.is_none());
+        self.block_checkpoint = Some(self.journal.len());
+    }
+
+    /// Keep the changes of the block begun with `begin_block`.
+    pub fn commit_block(&mut self) {
+        self.block_checkpoint.take().expect("No ongoing block");
+        if self.checkpoints.is_empty() {
+            self.journal.clear();
+        }
+    }
+
+    /// Undo every change made since `begin_block`, including those of
+    /// transactions the block left open when it failed.
+    pub fn rollback_block(&mut self) {
+        let checkpoint = self.block_checkpoint.take().expect("No ongoing block");
+        self.checkpoints.clear();
+        self.unwind(checkpoint);
+    }
+
+    /// Keep the changes of the block begun with `begin_block`, as
+    /// `commit_block` does, after calling `before` with the state as it was
+    /// before the block. The changes are undone and made again through the
+    /// journal, rather than by copying the state.
+    pub fn commit_block_after<T>(&mut self, before: impl FnOnce(&State) -> T) -> T {
+        assert!(self.checkpoints.is_empty());
+        let checkpoint = self.block_checkpoint.take().expect("No ongoing block");
+        let mut redo = Vec::with_capacity(self.journal.len() - checkpoint);
+        while self.journal.len() > checkpoint {
+            let entry = self.journal.pop().unwrap();
+            redo.push(self.revert(entry));
+        }
+
+        let result = before(self);
+        for entry in redo.into_iter().rev() {
+            self.revert(entry);
+        }
+        self.journal.clear();
+        result
+    }
+
+    /// All accounts in the state.
+    pub(crate) fn accounts(&self) -> impl Iterator<Item = (&Address, &Account)> {
+        self.main_trie
+            .data
+            .iter()
+            .filter_map(|(address, account)| Some((address, account.as_ref()?)))
+    }
+
+    /// All non-zero storage slots of the account at `address`.
+    pub(crate) fn storage_slots(&self, address: &Address) -> impl Iterator<Item = (&Bytes32, &U256)> {
+        self.storage_tries.get(address).into_iter().flat_map(|trie| trie.data.iter())
+    }
+
+    /// The trie holding all accounts.
+    pub(crate) fn main_trie(&self) -> &Trie<Address, Option<Account>> {
+        &self.main_trie
+    }
+
+    /// The storage trie of the account at `address`, if it has any storage.
+    pub(crate) fn storage_trie(&self, address: &Address) -> Option<&Trie<Bytes32, U256>> {
+        self.storage_tries.get(address)
+    }
+
+    /// The storage root of an account, which can always be computed for an
+    /// in-memory state.
+    pub(crate) fn storage_trie_root(&self, address: &Address) -> Root {
+        assert!(self.checkpoints.is_empty());
+        self.storage_tries
+            .get(address)
+            .map(|trie| trie::root(trie, dummy_root))
+            .unwrap_or(trie::EMPTY_TRIE_ROOT())
+    }
+}
+
+impl StateBackend for State {
+    fn get_account_optional(&self, address: &Address) -> Result<Option<Account>, EthereumException> {
+        Ok(trie::trie_get(&self.main_trie, address))
+    }
+
+    fn set_account(&mut self, address: Address, account: Option<Account>) {
+        let previous = self.main_trie.data.get(&address).cloned();
+        let previous_code = previous.as_ref().and_then(Option::as_ref).map(|account| &account.code);
+        if previous_code != account.as_ref().map(|account| &account.code) {
+            self.code_hashes.get_mut().remove(&address);
+        }
+        self.record(JournalEntry::AccountSet { address, previous });
+        trie::trie_set(&mut self.main_trie, address, account);
+    }
+
+    fn destroy_storage(&mut self, address: &Address) {
+        let previous = self.storage_tries.remove(address);
+        if previous.is_some() {
+            let address = *address;
+            self.record(JournalEntry::StorageDestroyed { address, previous });
+        }
+    }
+
+    fn get_storage(&self, address: &Address, key: &Bytes32) -> Result<U256, EthereumException> {
+        let Some(trie) = self.storage_tries.get(address) else {
+            return Ok(U256::from(0u8));
+        };
+        Ok(trie::trie_get(trie, key))
+    }
+
+    fn set_storage(&mut self, address: Address, key: &Bytes32, value: U256) {
+        // assert!(trie::trie_get(&state.main_trie).is_some());
+
+        let created_trie = !self.storage_tries.contains_key(&address);
+        let trie = self
+            .storage_tries
+            .entry(address)
+            .or_insert_with(|| Trie::new(true, U256::default()));
+        let previous = trie.data.get(key).cloned();
+        trie::trie_set(trie, *key, value);
+        self.record(JournalEntry::StorageSet {
+            address,
+            key: *key,
+            previous,
+            created_trie,
+        });
+        // todo
+        // if trie._data == {}:
+        //         del state._storage_tries[address]
+    }
+
+    fn get_code_hash(&self, address: &Address) -> Result<Hash32, EthereumException> {
+        if let Some(code_hash) = self.code_hashes.borrow().get(address) {
+            return Ok(*code_hash);
         }
+        let account = self.main_trie.data.get(address).and_then(Option::as_ref);
+        let code_hash = keccak256(account.map_or(&[][..], |account| &account.code));
+        self.code_hashes.borrow_mut().insert(*address, code_hash);
+        Ok(code_hash)
+    }
+
+    fn begin_transaction(&mut self) {
+        self.checkpoints.push(self.journal.len());
+    }
+
+    fn commit_transaction(&mut self) {
+        self.checkpoints.pop().expect("No ongoing transaction");
+        if self.checkpoints.is_empty() && self.block_checkpoint.is_none() {
+            self.journal.clear();
+        }
+    }
+
+    fn rollback_transaction(&mut self) {
+        let checkpoint = self.checkpoints.pop().expect("No ongoing transaction");
+        self.unwind(checkpoint);
+    }
+
+    fn storage_root(&self, address: &Address) -> Result<Root, EthereumException> {
+        Ok(self.storage_trie_root(address))
+    }
+
+    fn state_root(&self) -> Result<Root, EthereumException> {
+        assert!(self.checkpoints.is_empty());
+
+        let get_storage_root = |address: &Address| -> Root { self.storage_trie_root(address) };
+        Ok(trie::root(&self.main_trie, get_storage_root))
+    }
+}
+
+/// Put back a journaled entry of `map`, and return the one it replaces.
+pub(crate) fn restore<K: std::hash::Hash + Eq, V>(map: &mut HashMap<K, V>, key: K, previous: Option<V>) -> Option<V> {
+    match previous {
+        Some(value) => map.insert(key, value),
+        None => map.remove(&key),
     }
 }
 
 /// Free resources held by the state. Used by optimized implementations to
 /// release file descriptors.
-pub fn close_state(_: State) {}
+pub fn close_state<S: StateBackend>(_: S) {}
 
 /// Start a state transaction.
 ///
 /// Transactions are entirely implicit and can be nested. It is not possible to
 /// calculate the state root during a transaction.
 ///
+/// Rather than copying the state, every change made during a transaction is
+/// recorded in a journal, so that the cost of a transaction is proportional
+/// to what it touches.
+///
 /// Parameters
 /// ----------
 /// state : State
 ///     The state.
-pub fn begin_transaction(state: &mut State) {
-    state
-        .snapshots
-        .push((state.main_trie.clone(), state.storage_tries.clone()));
+pub fn begin_transaction<S: StateBackend>(state: &mut S) {
+    state.begin_transaction()
 }
 
 /// Commit a state transaction.
@@ -65,8 +435,8 @@ pub fn begin_transaction(state: &mut State) {
 /// state : State
 ///     The state.
 ///
-pub fn commit_transaction(state: &mut State) {
-    state.snapshots.pop();
+pub fn commit_transaction<S: StateBackend>(state: &mut S) {
+    state.commit_transaction()
 }
 
 /// Rollback a state transaction, resetting the state to the point when the
@@ -77,10 +447,8 @@ pub fn commit_transaction(state: &mut State) {
 /// state : State
 ///     The state.
 ///
-pub fn rollback_transaction(state: &mut State) {
-    let (main_trie, storage_tries) = state.snapshots.pop().expect("No ongoing transaction");
-    state.main_trie = main_trie;
-    state.storage_tries = storage_tries;
+pub fn rollback_transaction<S: StateBackend>(state: &mut S) {
+    state.rollback_transaction()
 }
 
 /// Get the `Account` object at an address. Returns `EMPTY_ACCOUNT` if there
@@ -100,8 +468,8 @@ pub fn rollback_transaction(state: &mut State) {
 /// -------
 /// account : `Account`
 ///     Account at address.
-pub fn get_account(state: &State, address: &Address) -> Account {
-    get_account_optional(state, address).unwrap_or(empty_account())
+pub fn get_account<S: StateBackend>(state: &S, address: &Address) -> Result<Account, EthereumException> {
+    Ok(get_account_optional(state, address)?.unwrap_or(empty_account()))
 }
 
 /// Get the `Account` object at an address. Returns `None` (rather than
@@ -118,8 +486,26 @@ pub fn get_account(state: &State, address: &Address) -> Account {
 /// -------
 /// account : `Account`
 ///     Account at address.
-pub fn get_account_optional(state: &State, address: &Address) -> Option<Account> {
-    trie::trie_get(&state.main_trie, address)
+pub fn get_account_optional<S: StateBackend>(state: &S, address: &Address) -> Result<Option<Account>, EthereumException> {
+    state.get_account_optional(address)
+}
+
+/// Get the hash of the code of the account at an address, which is the hash
+/// of the empty code if there is no account at the address.
+///
+/// Parameters
+/// ----------
+/// state: `State`
+///     The state
+/// address : `Address`
+///     Address to lookup.
+///
+/// Returns
+/// -------
+/// code_hash : `Hash32`
+///     Hash of the code of the account at address.
+pub fn get_code_hash<S: StateBackend>(state: &S, address: &Address) -> Result<Hash32, EthereumException> {
+    state.get_code_hash(address)
 }
 
 /// Set the `Account` object at an address. Setting to `None` deletes
@@ -133,8 +519,8 @@ pub fn get_account_optional(state: &State, address: &Address) -> Option<Account>
 /// Address to set.
 /// account : `Account`
 /// Account to set at address.
-pub fn set_account(state: &mut State, address: Address, account: Option<Account>) {
-    trie::trie_set(&mut state.main_trie, address, account);
+pub fn set_account<S: StateBackend>(state: &mut S, address: Address, account: Option<Account>) {
+    state.set_account(address, account)
 }
 
 /// Completely remove the account at `address` and all of its storage.
@@ -150,9 +536,9 @@ pub fn set_account(state: &mut State, address: Address, account: Option<Account>
 /// address : `Address`
 ///     Address of account to destroy.
 ///
-pub fn destroy_account(state: &mut State, address: &Address) {
+pub fn destroy_account<S: StateBackend>(state: &mut S, address: &Address) {
     destroy_storage(state, address);
-    set_account(state, address.clone(), None);
+    set_account(state, *address, None);
 }
 
 /// Completely remove the storage at `address`.
@@ -164,8 +550,8 @@ pub fn destroy_account(state: &mut State, address: &Address) {
 /// address : `Address`
 ///     Address of account whose storage is to be deleted.
 ///
-pub fn destroy_storage(state: &mut State, address: &Address) {
-    state.storage_tries.remove(address);
+pub fn destroy_storage<S: StateBackend>(state: &mut S, address: &Address) {
+    state.destroy_storage(address)
 }
 
 /// Get a value at a storage key on an account. Returns `U256(0)` if the
@@ -185,11 +571,8 @@ pub fn destroy_storage(state: &mut State, address: &Address) {
 /// value : `U256`
 ///     Value at the key.
 ///
-pub fn get_storage(state: &State, address: &Address, key: &Bytes32) -> U256 {
-    let Some(trie) = state.storage_tries.get(address) else {
-        return U256::from(0u8);
-    };
-    trie::trie_get(&trie, key)
+pub fn get_storage<S: StateBackend>(state: &S, address: &Address, key: &Bytes32) -> Result<U256, EthereumException> {
+    state.get_storage(address, key)
 }
 
 /// Set a value at a storage key on an account. Setting to `U256(0)` deletes
@@ -206,17 +589,8 @@ pub fn get_storage(state: &State, address: &Address, key: &Bytes32) -> U256 {
 /// value : `U256`
 ///     Value to set at the key.
 ///
-pub fn set_storage(state: &mut State, address: Address, key: &Bytes32, value: U256) {
-    // assert!(trie::trie_get(&state.main_trie).is_some());
-
-    let trie = state
-        .storage_tries
-        .entry(address)
-        .or_insert_with(|| Trie::new(true, Uint::default()));
-    trie::trie_set(trie, key.clone(), value);
-    // todo
-    // if trie._data == {}:
-    //         del state._storage_tries[address]
+pub fn set_storage<S: StateBackend>(state: &mut S, address: Address, key: &Bytes32, value: U256) {
+    state.set_storage(address, key, value)
 }
 
 /// Calculate the storage root of an account.
@@ -233,16 +607,8 @@ pub fn set_storage(state: &mut State, address: Address, key: &Bytes32, value: U2
 /// root : `Root`
 ///     Storage root of the account.
 ///
-pub fn storage_root(state: &State, address: &Address) -> Root {
-    assert!(state.snapshots.is_empty());
-    let z = state
-        .storage_tries
-        .get(address)
-        .map(|trie| trie::root(trie, dummy_root))
-        .unwrap()
-        // .unwrap_or(trie::EMPTY_TRIE_ROOT.clone());
-    ;
-    z
+pub fn storage_root<S: StateBackend>(state: &S, address: &Address) -> Result<Root, EthereumException> {
+    state.storage_root(address)
 }
 
 /// Calculate the state root.
@@ -257,11 +623,65 @@ pub fn storage_root(state: &State, address: &Address) -> Root {
 /// root : `Root`
 ///     The state root.
 ///
-pub fn state_root(state: &State) -> Root {
-    assert!(state.snapshots.is_empty());
+pub fn state_root<S: StateBackend>(state: &S) -> Result<Root, EthereumException> {
+    state.state_root()
+}
+
+/// Compare two states, e.g. the post-states of two blocks, walking only the
+/// parts of the account and storage tries that differ.
+///
+/// Parameters
+/// ----------
+/// old:
+///     The state to compare against.
+/// new:
+///     The state to compare.
+///
+/// Returns
+/// -------
+/// diff : `StateDiff`
+///     The changed accounts, and the changed storage of each account whose
+///     storage root differs. `TransactionOngoing` if a transaction is
+///     ongoing on either state, as storage roots are only computed between
+///     transactions.
+///
+pub fn diff_state(old: &State, new: &State) -> Result<StateDiff, EthereumException> {
+    if !old.checkpoints.is_empty() || !new.checkpoints.is_empty() {
+        return Err(EthereumException::TransactionOngoing);
+    }
 
-    let get_state_root = |address: &Address| -> Root { storage_root(state, address) };
-    trie::root(&state.main_trie, get_state_root)
+    let account_changes = trie_diff::diff_tries(
+        &old.main_trie,
+        &new.main_trie,
+        |address: &Address| old.storage_trie_root(address),
+        |address: &Address| new.storage_trie_root(address),
+    )?;
+
+    let mut diff = StateDiff::default();
+    let empty = Trie::new(true, U256::default());
+    for (address, change) in account_changes {
+        let storage_changes = trie_diff::diff_tries(
+            old.storage_tries.get(&address).unwrap_or(&empty),
+            new.storage_tries.get(&address).unwrap_or(&empty),
+            dummy_root,
+            dummy_root,
+        )?;
+        if !storage_changes.is_empty() {
+            diff.storage.push((address, storage_changes));
+        }
+
+        let change = match change {
+            Change::Added(Some(account)) => Change::Added(account),
+            Change::Removed(Some(account)) => Change::Removed(account),
+            Change::Changed {
+                old: Some(old),
+                new: Some(new),
+            } if old != new => Change::Changed { old, new },
+            _ => continue,
+        };
+        diff.accounts.push((address, change));
+    }
+    Ok(diff)
 }
 
 /// Checks if an account exists in the state trie
@@ -278,8 +698,8 @@ pub fn state_root(state: &State) -> Root {
 /// account_exists : `bool`
 ///     True if account exists in the state trie, False otherwise
 ///
-pub fn account_exists(state: &State, address: &Address) -> bool {
-    get_account_optional(state, address).is_some()
+pub fn account_exists<S: StateBackend>(state: &S, address: &Address) -> Result<bool, EthereumException> {
+    Ok(get_account_optional(state, address)?.is_some())
 }
 
 /// Checks if an account has non zero nonce or non empty code
@@ -297,40 +717,42 @@ pub fn account_exists(state: &State, address: &Address) -> bool {
 ///     True if if an account has non zero nonce or non empty code,
 ///     False otherwise.
 ///
-pub fn account_has_code_or_nonce(state: &State, address: &Address) -> bool {
-    let account = get_account(state, address);
-    account.nonce != Uint::from(0u8) || *account.code != *b""
+pub fn account_has_code_or_nonce<S: StateBackend>(state: &S, address: &Address) -> Result<bool, EthereumException> {
+    let account = get_account(state, address)?;
+    Ok(account.nonce != Uint::from(0u8) || *account.code != *b"")
 }
 
 /// Modify an `Account` in the `State`.
-pub fn modify_state<F>(state: &mut State, address: Address, f: F)
+pub fn modify_state<S, F>(state: &mut S, address: Address, f: F) -> Result<(), EthereumException>
 where
+    S: StateBackend,
     F: FnOnce(&mut Account),
 {
-    let mut account = get_account(state, &address).clone();
+    let mut account = get_account(state, &address)?;
     f(&mut account);
     set_account(state, address, Some(account));
+    Ok(())
 }
 
 /// Move funds between accounts.
-pub fn move_ether(
-    state: &mut State,
+pub fn move_ether<S: StateBackend>(
+    state: &mut S,
     sender_address: Address,
     recipient_address: Address,
     amount: U256,
-) {
-    let sub_amount = amount.clone();
+) -> Result<(), EthereumException> {
+    let sub_amount = amount;
 
     let reduce_sender_balance = |sender: &mut Account| {
-        sender.balance = sender.balance.checked_sub(&sub_amount).expect("Sender does not have enough ether");
+        sender.balance = sender.balance.checked_sub(sub_amount).expect("Sender does not have enough ether");
     };
 
     let increase_recipient_balance = |recipient: &mut Account| {
         recipient.balance += amount;
     };
 
-    modify_state(state, sender_address, reduce_sender_balance);
-    modify_state(state, recipient_address, increase_recipient_balance);
+    modify_state(state, sender_address, reduce_sender_balance)?;
+    modify_state(state, recipient_address, increase_recipient_balance)
 }
 
 /// Sets the balance of an account.
@@ -346,7 +768,7 @@ pub fn move_ether(
 /// amount:
 ///     The amount that needs to set in balance.
 ///
-pub fn set_account_balance(state: &mut State, address: Address, amount: U256) {
+pub fn set_account_balance<S: StateBackend>(state: &mut S, address: Address, amount: U256) -> Result<(), EthereumException> {
     let set_balance = |account: &mut Account| {
         account.balance = amount;
     };
@@ -364,10 +786,11 @@ pub fn set_account_balance(state: &mut State, address: Address, amount: U256) {
 /// address:
 ///     The address of the account that need to initialised.
 ///
-pub fn touch_account(state: &mut State, address: Address) {
-    if !account_exists(state, &address) {
+pub fn touch_account<S: StateBackend>(state: &mut S, address: Address) -> Result<(), EthereumException> {
+    if !account_exists(state, &address)? {
         set_account(state, address, Some(empty_account()));
     }
+    Ok(())
 }
 
 /// Increments the nonce of an account.
@@ -380,11 +803,11 @@ pub fn touch_account(state: &mut State, address: Address) {
 /// address:
 ///     Address of the account whose nonce needs to be incremented.
 ///
-pub fn increment_nonce(state: &mut State, address: Address) {
+pub fn increment_nonce<S: StateBackend>(state: &mut S, address: Address) -> Result<(), EthereumException> {
     let increase_nonce = |sender: &mut Account| {
         sender.nonce += Uint::from(1u8);
     };
-    modify_state(state, address, increase_nonce);
+    modify_state(state, address, increase_nonce)
 }
 
 /// Sets Account code.
@@ -400,11 +823,11 @@ pub fn increment_nonce(state: &mut State, address: Address) {
 /// code:
 ///     The bytecode that needs to be set.
 ///
-pub fn set_code(state: &mut State, address: Address, code: Bytes) {
+pub fn set_code<S: StateBackend>(state: &mut S, address: Address, code: Bytes) -> Result<(), EthereumException> {
     let write_code = |sender: &mut Account| {
         sender.code = code;
     };
-    modify_state(state, address, write_code);
+    modify_state(state, address, write_code)
 }
 
 /// Add newly created ether to an account.
@@ -418,9 +841,9 @@ pub fn set_code(state: &mut State, address: Address, code: Bytes) {
 /// amount:
 ///     The amount of ether to be added to the account of interest.
 ///
-pub fn create_ether(state: &mut State, address: Address, amount: U256) {
+pub fn create_ether<S: StateBackend>(state: &mut S, address: Address, amount: U256) -> Result<(), EthereumException> {
     let increase_balance = |account: &mut Account| {
         account.balance += amount;
     };
-    modify_state(state, address, increase_balance);
+    modify_state(state, address, increase_balance)
 }
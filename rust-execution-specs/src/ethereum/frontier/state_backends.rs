@@ -0,0 +1,605 @@
+//!
+//! # State Backends
+//!
+//! ## Introduction
+//!
+//! Implementations of `StateBackend` other than the in-memory `State`.
+//!
+//! They are all an `OverlayState`: changes made by the specification are
+//! buffered in memory, on top of a read-only `StateSource` which is only
+//! asked for accounts and storage slots that have not been changed yet.
+//!
+//! - `NodeDbState` reads the state from a database of trie nodes, and writes
+//!   the new nodes back on `commit`. The database may hold only part of the
+//!   tries, e.g. a witness, as long as nothing outside of it is touched.
+//! - `CachedState` reads through a cache from a source that is slow to ask,
+//!   e.g. a remote node. A `State` can stand in for such a source.
+//!
+
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, HashSet},
+};
+
+use crate::ethereum::{
+    base_types::{Bytes, Bytes32, Uint, U256},
+    exceptions::EthereumException,
+    rlp::{self, Simple},
+    utils::hexadecimal::hex,
+};
+
+use super::{
+    fork_types::{encode_account, keccak256, Account, Address, Hash32, Root},
+    state::{restore, State, StateBackend},
+    trie::{bytes_to_nibble_list, nibble_list_to_bytes, Encodable, EMPTY_TRIE_ROOT},
+    trie_diff::NodeStore,
+    trie_proof::{collect_items, patricialize_partial, resolve_node, DecodedNode, Item},
+};
+
+/// A read-only view of a state.
+pub trait StateSource {
+    /// Get the account at `address`, `None` if there is none.
+    fn account(&self, address: &Address) -> Result<Option<Account>, EthereumException>;
+
+    /// Get the storage value at `key` of the account at `address`.
+    fn storage(&self, address: &Address, key: &Bytes32) -> Result<U256, EthereumException>;
+
+    /// Compute the storage root of the account at `address` once `changes`
+    /// are applied. Only sources that can see all of the state can do this.
+    fn storage_root(&self, _address: &Address, _changes: &Changes) -> Result<Root, EthereumException> {
+        Err(unavailable("storage root"))
+    }
+
+    /// Compute the state root once `changes` are applied. Only sources that
+    /// can see all of the state can do this.
+    fn state_root(&self, _changes: &Changes) -> Result<Root, EthereumException> {
+        Err(unavailable("state root"))
+    }
+}
+
+///
+/// Changes buffered by an `OverlayState`.
+///
+#[derive(Clone, Debug, Default)]
+pub struct Changes {
+    /// Accounts that have been set, `None` for deleted ones.
+    pub accounts: HashMap<Address, Option<Account>>,
+    /// Storage slots that have been set.
+    pub storage: HashMap<Address, HashMap<Bytes32, U256>>,
+    /// Accounts whose storage has been destroyed before the slots in
+    /// `storage` were set.
+    pub destroyed: HashSet<Address>,
+}
+
+/// Undo information for a single change made during a state transaction.
+enum JournalEntry {
+    AccountSet {
+        address: Address,
+        previous: Option<Option<Account>>,
+    },
+    StorageSet {
+        address: Address,
+        key: Bytes32,
+        previous: Option<U256>,
+    },
+    StorageDestroyed {
+        address: Address,
+        was_destroyed: bool,
+        previous: Option<HashMap<Bytes32, U256>>,
+    },
+}
+
+///
+/// A `StateBackend` that buffers all changes in memory, on top of a
+/// read-only `source`.
+///
+pub struct OverlayState<R> {
+    pub source: R,
+    changes: Changes,
+    journal: Vec<JournalEntry>,
+    checkpoints: Vec<usize>,
+}
+
+impl<R: StateSource> OverlayState<R> {
+    /// Create an overlay without any changes on top of `source`.
+    pub fn new(source: R) -> Self {
+        Self {
+            source,
+            changes: Changes::default(),
+            journal: Vec::new(),
+            checkpoints: Vec::new(),
+        }
+    }
+
+    /// The changes made so far.
+    pub fn changes(&self) -> &Changes {
+        &self.changes
+    }
+
+    /// Take the changes made so far, leaving none. No transaction may be
+    /// ongoing.
+    pub(crate) fn take_changes(&mut self) -> Changes {
+        assert!(self.checkpoints.is_empty());
+        std::mem::take(&mut self.changes)
+    }
+
+    fn record(&mut self, entry: JournalEntry) {
+        if !self.checkpoints.is_empty() {
+            self.journal.push(entry);
+        }
+    }
+}
+
+impl<R: StateSource> StateBackend for OverlayState<R> {
+    fn get_account_optional(&self, address: &Address) -> Result<Option<Account>, EthereumException> {
+        match self.changes.accounts.get(address) {
+            Some(account) => Ok(account.clone()),
+            None => self.source.account(address),
+        }
+    }
+
+    fn set_account(&mut self, address: Address, account: Option<Account>) {
+        let previous = self.changes.accounts.insert(address, account);
+        self.record(JournalEntry::AccountSet { address, previous });
+    }
+
+    fn destroy_storage(&mut self, address: &Address) {
+        let was_destroyed = !self.changes.destroyed.insert(*address);
+        let previous = self.changes.storage.remove(address);
+        self.record(JournalEntry::StorageDestroyed {
+            address: *address,
+            was_destroyed,
+            previous,
+        });
+    }
+
+    fn get_storage(&self, address: &Address, key: &Bytes32) -> Result<U256, EthereumException> {
+        if let Some(value) = self.changes.storage.get(address).and_then(|slots| slots.get(key)) {
+            return Ok(*value);
+        }
+        if self.changes.destroyed.contains(address) {
+            return Ok(U256::from(0u8));
+        }
+        self.source.storage(address, key)
+    }
+
+    fn set_storage(&mut self, address: Address, key: &Bytes32, value: U256) {
+        let previous = self.changes.storage.entry(address).or_default().insert(*key, value);
+        self.record(JournalEntry::StorageSet {
+            address,
+            key: *key,
+            previous,
+        });
+    }
+
+    fn begin_transaction(&mut self) {
+        self.checkpoints.push(self.journal.len());
+    }
+
+    fn commit_transaction(&mut self) {
+        self.checkpoints.pop().expect("No ongoing transaction");
+        if self.checkpoints.is_empty() {
+            self.journal.clear();
+        }
+    }
+
+    fn rollback_transaction(&mut self) {
+        let checkpoint = self.checkpoints.pop().expect("No ongoing transaction");
+        while self.journal.len() > checkpoint {
+            match self.journal.pop().unwrap() {
+                JournalEntry::AccountSet { address, previous } => {
+                    restore(&mut self.changes.accounts, address, previous);
+                }
+                JournalEntry::StorageSet {
+                    address,
+                    key,
+                    previous,
+                } => {
+                    let slots = self.changes.storage.entry(address).or_default();
+                    restore(slots, key, previous);
+                    if slots.is_empty() {
+                        self.changes.storage.remove(&address);
+                    }
+                }
+                JournalEntry::StorageDestroyed {
+                    address,
+                    was_destroyed,
+                    previous,
+                } => {
+                    if !was_destroyed {
+                        self.changes.destroyed.remove(&address);
+                    }
+                    restore(&mut self.changes.storage, address, previous);
+                }
+            }
+        }
+    }
+
+    fn storage_root(&self, address: &Address) -> Result<Root, EthereumException> {
+        assert!(self.checkpoints.is_empty());
+        self.source.storage_root(address, &self.changes)
+    }
+
+    fn state_root(&self) -> Result<Root, EthereumException> {
+        assert!(self.checkpoints.is_empty());
+        self.source.state_root(&self.changes)
+    }
+}
+
+///
+/// A database of trie nodes, keyed by hash, and of contract code, keyed by
+/// code hash, together with the state root to read from.
+///
+#[derive(Clone, Debug)]
+pub struct NodeDb {
+    pub nodes: NodeStore,
+    pub codes: HashMap<Hash32, Bytes>,
+    pub root: Root,
+}
+
+impl Default for NodeDb {
+    fn default() -> Self {
+        Self {
+            nodes: NodeStore::new(),
+            codes: HashMap::new(),
+            root: EMPTY_TRIE_ROOT(),
+        }
+    }
+}
+
+/// State backed by a `NodeDb`.
+pub type NodeDbState = OverlayState<NodeDb>;
+
+impl NodeDb {
+    /// Look up the value at `key` (a trie path) in the trie with root `root`.
+    fn get(&self, root: &Root, key: &[u8]) -> Result<Option<Bytes>, EthereumException> {
+        let mut path = &bytes_to_nibble_list(key)[..];
+        let mut node = self.resolve_root(root)?;
+        loop {
+            match node {
+                None => return Ok(None),
+                Some(DecodedNode::LeafNode { rest_of_key, value }) => {
+                    return Ok((*rest_of_key == *path).then_some(value));
+                }
+                Some(DecodedNode::ExtensionNode {
+                    key_segment,
+                    subnode,
+                }) => {
+                    if !path.starts_with(&key_segment) {
+                        return Ok(None);
+                    }
+                    path = &path[key_segment.len()..];
+                    node = resolve_node(&subnode, &self.nodes)?;
+                }
+                Some(DecodedNode::BranchNode { subnodes, value }) => {
+                    let Some((nibble, rest)) = path.split_first() else {
+                        return Ok((!value.is_empty()).then_some(value));
+                    };
+                    path = rest;
+                    node = resolve_node(&subnodes[*nibble as usize], &self.nodes)?;
+                }
+            }
+        }
+    }
+
+    ///
+    /// Compute the root of the trie with root `root` once `updates` are
+    /// applied, handing every new node to `visit`. Subtrees without updates
+    /// are not resolved, so they do not need to be in the database.
+    ///
+    fn update_root(
+        &self,
+        root: &Root,
+        updates: Vec<(Vec<u8>, Option<Bytes>)>,
+        visit: &mut dyn FnMut(&[u8], &[u8]),
+    ) -> Result<Root, EthereumException> {
+        if updates.is_empty() {
+            return Ok(*root);
+        }
+
+        let mut items = BTreeMap::new();
+        if *root != EMPTY_TRIE_ROOT() {
+            let reference = Simple::Bytes(Bytes::from(root.as_slice()));
+            collect_items(&self.nodes, &reference, &[], false, &mut items)?;
+        }
+        for (path, value) in updates {
+            // A subtree that was not resolved holds every path below it.
+            if let Some((prefix, Item::Subtree(_) | Item::Branch(_))) = items.range(..=path.clone()).next_back() {
+                if path.starts_with(prefix) {
+                    return Err(EthereumException::InvalidProof(format!(
+                        "missing trie node below {}",
+                        hex(&nibble_list_to_bytes(prefix))
+                    )));
+                }
+            }
+            match value {
+                Some(value) => items.insert(path, Item::Value(value)),
+                None => items.remove(&path),
+            };
+        }
+
+        let items = items.into_iter().map(|(path, item)| (Bytes::from(path), item)).collect();
+        match patricialize_partial(items, &[], visit)? {
+            Encodable::RLP(rlp) => Ok(keccak256(&rlp.encode())),
+            Encodable::Root(root) => Ok(root),
+            Encodable::Bytes(_) => unreachable!(),
+        }
+    }
+
+    fn resolve_root(&self, root: &Root) -> Result<Option<DecodedNode>, EthereumException> {
+        if *root == EMPTY_TRIE_ROOT() {
+            return Ok(None);
+        }
+        resolve_node(&Simple::Bytes(Bytes::from(root.as_slice())), &self.nodes)
+    }
+
+    /// The account at `address` and its storage root, in the state with root
+    /// `state_root`.
+    fn account_and_storage_root(
+        &self,
+        state_root: &Root,
+        address: &Address,
+    ) -> Result<Option<(Account, Root)>, EthereumException> {
+        let Some(encoded) = self.get(state_root, &keccak256(address))? else {
+            return Ok(None);
+        };
+        let decoded = rlp::decode(&encoded)?;
+        let [nonce, balance, storage_root, code_hash] = decoded.as_list()? else {
+            return Err(EthereumException::RLPDecodingError);
+        };
+        let code_hash = Hash32::try_from(code_hash.as_bytes()?.as_ref())
+            .map_err(|_| EthereumException::RLPDecodingError)?;
+        let code = if code_hash == keccak256(&[]) {
+            Bytes::default()
+        } else {
+            self.codes
+                .get(&code_hash)
+                .cloned()
+                .ok_or_else(|| unavailable(&format!("code of {}", hex(address))))?
+        };
+        let account = Account {
+            nonce: Uint::from_bytes_be(nonce.as_bytes()?),
+            balance: U256::try_from_be_bytes(balance.as_bytes()?)
+                .ok_or(EthereumException::RLPDecodingError)?,
+            code,
+        };
+        let storage_root = Root::try_from(storage_root.as_bytes()?.as_ref())
+            .map_err(|_| EthereumException::RLPDecodingError)?;
+        Ok(Some((account, storage_root)))
+    }
+
+    /// Compute the storage root of `address` with `changes` applied, handing
+    /// every new node to `visit`.
+    fn compute_storage_root(
+        &self,
+        address: &Address,
+        changes: &Changes,
+        visit: &mut dyn FnMut(&[u8], &[u8]),
+    ) -> Result<Root, EthereumException> {
+        let root = match self.account_and_storage_root(&self.root, address)? {
+            Some((_, root)) if !changes.destroyed.contains(address) => root,
+            _ => EMPTY_TRIE_ROOT(),
+        };
+        let updates = changes
+            .storage
+            .get(address)
+            .into_iter()
+            .flatten()
+            .map(|(key, value)| {
+                let path = bytes_to_nibble_list(&keccak256(key)).to_vec();
+                (path, (*value != U256::from(0u8)).then(|| rlp::encode(value)))
+            })
+            .collect();
+        self.update_root(&root, updates, visit)
+    }
+
+    /// Compute the state root with `changes` applied, handing every new node
+    /// of the account and storage tries to `visit`.
+    fn compute_state_root(
+        &self,
+        changes: &Changes,
+        visit: &mut dyn FnMut(&[u8], &[u8]),
+    ) -> Result<Root, EthereumException> {
+        let touched = changes
+            .accounts
+            .keys()
+            .chain(changes.storage.keys())
+            .chain(&changes.destroyed)
+            .collect::<HashSet<_>>();
+        let mut updates = vec![];
+        for address in touched {
+            let path = bytes_to_nibble_list(&keccak256(address)).to_vec();
+            let account = match changes.accounts.get(address) {
+                Some(account) => account.clone(),
+                None => self.account_and_storage_root(&self.root, address)?.map(|(account, _)| account),
+            };
+            let value = match account {
+                None => None,
+                Some(account) => {
+                    let storage_root = self.compute_storage_root(address, changes, visit)?;
+                    Some(encode_account(account, storage_root))
+                }
+            };
+            updates.push((path, value));
+        }
+        self.update_root(&self.root, updates, visit)
+    }
+}
+
+impl NodeDb {
+    /// Get the account at `address` in the state with root `state_root`.
+    pub(crate) fn account_at(
+        &self,
+        state_root: &Root,
+        address: &Address,
+    ) -> Result<Option<Account>, EthereumException> {
+        Ok(self.account_and_storage_root(state_root, address)?.map(|(account, _)| account))
+    }
+
+    /// Get the storage value at `key` of the account at `address` in the
+    /// state with root `state_root`.
+    pub(crate) fn storage_at(
+        &self,
+        state_root: &Root,
+        address: &Address,
+        key: &Bytes32,
+    ) -> Result<U256, EthereumException> {
+        let Some((_, root)) = self.account_and_storage_root(state_root, address)? else {
+            return Ok(U256::from(0u8));
+        };
+        match self.get(&root, &keccak256(key))? {
+            Some(encoded) => U256::try_from_be_bytes(rlp::decode_to_bytes(&encoded)?.as_ref())
+                .ok_or(EthereumException::RLPDecodingError),
+            None => Ok(U256::from(0u8)),
+        }
+    }
+
+    ///
+    /// Write the nodes and code of the state with `changes` applied, and make
+    /// it the current state.
+    ///
+    /// Returns
+    /// -------
+    /// root : `Root`
+    ///     The new state root.
+    ///
+    pub(crate) fn apply(&mut self, changes: &Changes) -> Result<Root, EthereumException> {
+        let mut nodes = vec![];
+        let root = self.compute_state_root(changes, &mut |path, encoded| {
+            if path.is_empty() || encoded.len() >= 32 {
+                nodes.push(Bytes::from(encoded));
+            }
+        })?;
+        for node in nodes {
+            self.nodes.insert(keccak256(&node), node);
+        }
+        for account in changes.accounts.values().flatten() {
+            self.codes.insert(keccak256(&account.code), account.code.clone());
+        }
+
+        self.root = root;
+        Ok(root)
+    }
+}
+
+impl StateSource for NodeDb {
+    fn account(&self, address: &Address) -> Result<Option<Account>, EthereumException> {
+        self.account_at(&self.root, address)
+    }
+
+    fn storage(&self, address: &Address, key: &Bytes32) -> Result<U256, EthereumException> {
+        self.storage_at(&self.root, address, key)
+    }
+
+    fn storage_root(&self, address: &Address, changes: &Changes) -> Result<Root, EthereumException> {
+        self.compute_storage_root(address, changes, &mut |_, _| {})
+    }
+
+    fn state_root(&self, changes: &Changes) -> Result<Root, EthereumException> {
+        self.compute_state_root(changes, &mut |_, _| {})
+    }
+}
+
+impl NodeDbState {
+    ///
+    /// Write the buffered changes to the node database.
+    ///
+    /// Returns
+    /// -------
+    /// root : `Root`
+    ///     The new state root, which later reads are served from.
+    ///
+    pub fn commit(&mut self) -> Result<Root, EthereumException> {
+        assert!(self.checkpoints.is_empty());
+        let root = self.source.apply(&self.changes)?;
+        self.changes = Changes::default();
+        Ok(root)
+    }
+}
+
+///
+/// A read-through cache in front of a `StateSource` that is expensive to
+/// ask, e.g. one that fetches from a remote node.
+///
+pub struct CachedSource<R> {
+    pub inner: R,
+    accounts: RefCell<HashMap<Address, Option<Account>>>,
+    storage: RefCell<HashMap<(Address, Bytes32), U256>>,
+}
+
+/// State read through a cache from another source.
+pub type CachedState<R> = OverlayState<CachedSource<R>>;
+
+impl<R: StateSource> CachedSource<R> {
+    /// Create an empty cache in front of `inner`.
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            accounts: RefCell::new(HashMap::new()),
+            storage: RefCell::new(HashMap::new()),
+        }
+    }
+}
+
+impl<R: StateSource> StateSource for CachedSource<R> {
+    fn account(&self, address: &Address) -> Result<Option<Account>, EthereumException> {
+        if let Some(account) = self.accounts.borrow().get(address) {
+            return Ok(account.clone());
+        }
+        let account = self.inner.account(address)?;
+        self.accounts.borrow_mut().insert(*address, account.clone());
+        Ok(account)
+    }
+
+    fn storage(&self, address: &Address, key: &Bytes32) -> Result<U256, EthereumException> {
+        if let Some(value) = self.storage.borrow().get(&(*address, *key)) {
+            return Ok(*value);
+        }
+        let value = self.inner.storage(address, key)?;
+        self.storage.borrow_mut().insert((*address, *key), value);
+        Ok(value)
+    }
+
+    fn storage_root(&self, address: &Address, changes: &Changes) -> Result<Root, EthereumException> {
+        self.inner.storage_root(address, changes)
+    }
+
+    fn state_root(&self, changes: &Changes) -> Result<Root, EthereumException> {
+        self.inner.state_root(changes)
+    }
+}
+
+/// A borrowed source serves the same state as the source it borrows.
+impl<R: StateSource + ?Sized> StateSource for &R {
+    fn account(&self, address: &Address) -> Result<Option<Account>, EthereumException> {
+        (**self).account(address)
+    }
+
+    fn storage(&self, address: &Address, key: &Bytes32) -> Result<U256, EthereumException> {
+        (**self).storage(address, key)
+    }
+
+    fn storage_root(&self, address: &Address, changes: &Changes) -> Result<Root, EthereumException> {
+        (**self).storage_root(address, changes)
+    }
+
+    fn state_root(&self, changes: &Changes) -> Result<Root, EthereumException> {
+        (**self).state_root(changes)
+    }
+}
+
+/// An in-memory `State` can serve as a source, standing in for a remote
+/// node in tests and tools.
+impl StateSource for State {
+    fn account(&self, address: &Address) -> Result<Option<Account>, EthereumException> {
+        self.get_account_optional(address)
+    }
+
+    fn storage(&self, address: &Address, key: &Bytes32) -> Result<U256, EthereumException> {
+        self.get_storage(address, key)
+    }
+}
+
+fn unavailable(what: &str) -> EthereumException {
+    EthereumException::StateUnavailable(what.to_owned())
+}
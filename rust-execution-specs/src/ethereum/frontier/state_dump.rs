@@ -0,0 +1,174 @@
+//!
+//! # State Dump
+//!
+//! ## Introduction
+//!
+//! Moves a `State` in and out of the JSON format written by geth's `dump`
+//! command:
+//!
+//! ```json
+//! {
+//!     "root": "0x…",
+//!     "accounts": {
+//!         "0x<address>": {
+//!             "balance": "<decimal>",
+//!             "nonce": 0,
+//!             "root": "0x<storage root>",
+//!             "codeHash": "0x…",
+//!             "code": "0x…",
+//!             "storage": { "0x<slot>": "<hex value>" },
+//!             "address": "0x<address>",
+//!             "key": "0x<keccak256(address)>"
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! `code` and `storage` are left out when empty.
+//!
+
+use num_traits::ToPrimitive;
+use serde_json::{json, Map, Value};
+
+use crate::ethereum::{
+    base_types::{Bytes, Uint, U256},
+    exceptions::EthereumException,
+    utils::hexadecimal::{
+        has_hex_prefix, hex, hex_to_bytes, hex_to_bytes20, hex_to_bytes32, hex_to_u256,
+        hex_to_uint,
+    },
+};
+
+use super::{
+    fork_types::{keccak256, Account},
+    state::{self, set_account, set_storage, State},
+};
+
+///
+/// Dump the state in the format of geth's `dump` command.
+///
+/// Parameters
+/// ----------
+/// state :
+///     The state to dump. No transaction may be ongoing.
+///
+/// Returns
+/// -------
+/// dump : `serde_json::Value`
+///     The state root, and every account with its code and storage.
+///
+pub fn dump_state(state: &State) -> Result<Value, EthereumException> {
+    let mut accounts = Map::new();
+    for (address, account) in state.accounts() {
+        let mut dumped = Map::new();
+        dumped.insert("balance".into(), Value::String(account.balance.to_string()));
+        dumped.insert(
+            "nonce".into(),
+            json!(account.nonce.to_u64().ok_or(EthereumException::ValueError)?),
+        );
+        dumped.insert("root".into(), Value::String(hex(&state::storage_root(state, address)?)));
+        dumped.insert("codeHash".into(), Value::String(hex(&keccak256(&account.code))));
+        if !account.code.is_empty() {
+            dumped.insert("code".into(), Value::String(hex(&account.code)));
+        }
+
+        let storage = state
+            .storage_slots(address)
+            .map(|(key, value)| {
+                let value = hex(&value.to_be_bytes());
+                (hex(key), Value::String(value[2..].to_owned()))
+            })
+            .collect::<Map<_, _>>();
+        if !storage.is_empty() {
+            dumped.insert("storage".into(), Value::Object(storage));
+        }
+
+        dumped.insert("address".into(), Value::String(hex(address)));
+        dumped.insert("key".into(), Value::String(hex(&keccak256(address))));
+        accounts.insert(hex(address), Value::Object(dumped));
+    }
+
+    Ok(json!({
+        "root": hex(&state::state_root(state)?),
+        "accounts": accounts,
+    }))
+}
+
+///
+/// Load a state from the format of geth's `dump` command.
+///
+/// The roots in the dump are not checked, since dumps may leave out code or
+/// storage. Compare `state_root` of the result with `dump["root"]` where that
+/// matters.
+///
+/// Parameters
+/// ----------
+/// dump :
+///     The parsed JSON dump.
+///
+/// Returns
+/// -------
+/// state : `State`
+///     The state holding the dumped accounts.
+///
+pub fn load_state(dump: &Value) -> Result<State, EthereumException> {
+    let accounts = dump["accounts"]
+        .as_object()
+        .ok_or_else(|| malformed("accounts"))?;
+
+    let mut state = State::default();
+    for (key, dumped) in accounts {
+        let address = match dumped["address"].as_str() {
+            Some(address) => hex_to_bytes20(address)?,
+            None => hex_to_bytes20(key)?,
+        };
+
+        let balance = dumped["balance"].as_str().ok_or_else(|| malformed("balance"))?;
+        let nonce = match &dumped["nonce"] {
+            Value::Number(nonce) => Uint::from(nonce.as_u64().ok_or_else(|| malformed("nonce"))?),
+            Value::String(nonce) => parse_quantity(nonce)?,
+            Value::Null => Uint::default(),
+            _ => return Err(malformed("nonce")),
+        };
+        let code = match dumped["code"].as_str() {
+            Some(code) => hex_to_bytes(code)?,
+            None => Bytes::default(),
+        };
+        let account = Account {
+            nonce,
+            balance: U256::try_from(parse_quantity(balance)?)?,
+            code,
+        };
+        set_account(&mut state, address, Some(account));
+
+        if let Some(storage) = dumped["storage"].as_object() {
+            for (slot, value) in storage {
+                let value = value.as_str().ok_or_else(|| malformed("storage"))?;
+                set_storage(&mut state, address, &hex_to_bytes32(slot)?, parse_storage_value(value)?);
+            }
+        }
+    }
+    Ok(state)
+}
+
+/// Geth writes balances in decimal, but hexadecimal is accepted as well.
+fn parse_quantity(quantity: &str) -> Result<Uint, EthereumException> {
+    if has_hex_prefix(quantity) {
+        hex_to_uint(quantity)
+    } else {
+        Uint::parse_bytes(quantity.as_bytes(), 10)
+            .ok_or_else(|| EthereumException::JsonDecodeError(format!("bad quantity {quantity}")))
+    }
+}
+
+/// Geth writes storage values as hexadecimal without a prefix.
+fn parse_storage_value(value: &str) -> Result<U256, EthereumException> {
+    if value.is_empty() || value == "0x" {
+        return Ok(U256::default());
+    }
+    hex_to_u256(value)
+}
+
+fn malformed(field: &str) -> EthereumException {
+    EthereumException::JsonDecodeError(format!("missing or malformed {field}"))
+}
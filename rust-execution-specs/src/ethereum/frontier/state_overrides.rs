@@ -0,0 +1,191 @@
+//!
+//! # State Overrides
+//!
+//! ## Introduction
+//!
+//! Temporary changes to accounts for simulating calls, in the format of
+//! geth's `stateOverrides` parameter:
+//!
+//! ```json
+//! {
+//!     "0x<address>": {
+//!         "balance": "0x…",
+//!         "nonce": "0x…",
+//!         "code": "0x…",
+//!         "state": { "0x<slot>": "0x<value>" },
+//!         "stateDiff": { "0x<slot>": "0x<value>" }
+//!     }
+//! }
+//! ```
+//!
+//! `state` replaces all of the account's storage, while `stateDiff` only
+//! replaces the given slots. All fields are optional.
+//!
+//! The overrides are applied to an `OverrideState`, which buffers them and
+//! whatever the simulation changes on top of a borrowed `State`, leaving the
+//! `State` itself untouched.
+//!
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use crate::ethereum::{
+    base_types::{Bytes, Bytes32, Uint, U256},
+    exceptions::EthereumException,
+    utils::hexadecimal::{hex_to_bytes, hex_to_bytes20, hex_to_bytes32, hex_to_u256, hex_to_uint},
+};
+
+use super::{
+    fork_types::Address,
+    state::{destroy_storage, modify_state, set_storage, State, StateBackend},
+    state_backends::OverlayState,
+};
+
+/// Changes to a single account. Fields left as `None` are not overridden.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AccountOverride {
+    pub balance: Option<U256>,
+    pub nonce: Option<Uint>,
+    pub code: Option<Bytes>,
+    /// Replaces all of the storage.
+    pub state: Option<BTreeMap<Bytes32, U256>>,
+    /// Replaces only the given slots.
+    pub state_diff: BTreeMap<Bytes32, U256>,
+}
+
+/// Changes to a set of accounts.
+pub type StateOverrides = BTreeMap<Address, AccountOverride>;
+
+/// A `State` with overrides, and the changes of a simulation, on top.
+pub type OverrideState<'a> = OverlayState<&'a State>;
+
+///
+/// Parse state overrides from the format of geth's `stateOverrides`.
+///
+/// Parameters
+/// ----------
+/// overrides :
+///     The parsed JSON object.
+///
+/// Returns
+/// -------
+/// overrides : `StateOverrides`
+///     The overrides for each account.
+///
+pub fn parse_state_overrides(overrides: &Value) -> Result<StateOverrides, EthereumException> {
+    let accounts = overrides
+        .as_object()
+        .ok_or_else(|| malformed("state overrides"))?;
+
+    let mut parsed = StateOverrides::new();
+    for (address, account) in accounts {
+        let account = account.as_object().ok_or_else(|| malformed("account override"))?;
+        let mut account_override = AccountOverride::default();
+        for (field, value) in account {
+            match field.as_str() {
+                "balance" => account_override.balance = Some(hex_to_u256(as_str(value, field)?)?),
+                "nonce" => {
+                    account_override.nonce = Some(match value {
+                        Value::Number(nonce) => {
+                            Uint::from(nonce.as_u64().ok_or_else(|| malformed("nonce"))?)
+                        }
+                        _ => hex_to_uint(as_str(value, field)?)?,
+                    })
+                }
+                "code" => account_override.code = Some(hex_to_bytes(as_str(value, field)?)?),
+                "state" => account_override.state = Some(parse_storage(value)?),
+                "stateDiff" => account_override.state_diff = parse_storage(value)?,
+                _ => return Err(malformed(field)),
+            }
+        }
+        if account_override.state.is_some() && !account_override.state_diff.is_empty() {
+            return Err(EthereumException::JsonDecodeError(format!(
+                "both state and stateDiff overridden for {address}"
+            )));
+        }
+        parsed.insert(hex_to_bytes20(address)?, account_override);
+    }
+    Ok(parsed)
+}
+
+///
+/// Apply state overrides to `state`.
+///
+/// Parameters
+/// ----------
+/// state :
+///     The state to change, usually an `OverrideState`.
+/// overrides :
+///     The changes to make.
+///
+pub fn apply_state_overrides<S: StateBackend>(
+    state: &mut S,
+    overrides: &StateOverrides,
+) -> Result<(), EthereumException> {
+    for (address, account_override) in overrides {
+        modify_state(state, *address, |account| {
+            if let Some(balance) = &account_override.balance {
+                account.balance = *balance;
+            }
+            if let Some(nonce) = &account_override.nonce {
+                account.nonce = nonce.clone();
+            }
+            if let Some(code) = &account_override.code {
+                account.code = code.clone();
+            }
+        })?;
+
+        if let Some(storage) = &account_override.state {
+            destroy_storage(state, address);
+            for (key, value) in storage {
+                set_storage(state, *address, key, *value);
+            }
+        }
+        for (key, value) in &account_override.state_diff {
+            set_storage(state, *address, key, *value);
+        }
+    }
+    Ok(())
+}
+
+///
+/// Create an overlay over `state` with `overrides` applied.
+///
+/// Parameters
+/// ----------
+/// state :
+///     The canonical state, which is left as it is.
+/// overrides :
+///     The changes to make for the simulation.
+///
+/// Returns
+/// -------
+/// state : `OverrideState`
+///     The overridden state, which the VM `Environment` can hold.
+///
+pub fn override_state<'a>(
+    state: &'a State,
+    overrides: &StateOverrides,
+) -> Result<OverrideState<'a>, EthereumException> {
+    let mut overridden = OverlayState::new(state);
+    apply_state_overrides(&mut overridden, overrides)?;
+    Ok(overridden)
+}
+
+fn parse_storage(storage: &Value) -> Result<BTreeMap<Bytes32, U256>, EthereumException> {
+    storage
+        .as_object()
+        .ok_or_else(|| malformed("storage override"))?
+        .iter()
+        .map(|(key, value)| Ok((hex_to_bytes32(key)?, hex_to_u256(as_str(value, key)?)?)))
+        .collect()
+}
+
+fn as_str<'v>(value: &'v Value, field: &str) -> Result<&'v str, EthereumException> {
+    value.as_str().ok_or_else(|| malformed(field))
+}
+
+fn malformed(field: &str) -> EthereumException {
+    EthereumException::JsonDecodeError(format!("missing or malformed {field}"))
+}
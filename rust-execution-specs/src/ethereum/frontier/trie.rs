@@ -10,9 +10,9 @@
 
 use std::collections::HashMap;
 
-use crate::ethereum::{rlp::{RLP}, base_types::{Bytes, U256, Bytes32}, exceptions::EthereumException};
+use crate::ethereum::{rlp::{self, RLP}, base_types::{Bytes, U256, Bytes32}, exceptions::EthereumException};
 
-use super::fork_types::{keccak256, Account, Address, Root};
+use super::fork_types::{encode_account, keccak256, Account, Address, Root};
 
 pub trait Key : Eq + std::hash::Hash + AsRef<[u8]> + Clone {}
 
@@ -23,36 +23,42 @@ pub fn EMPTY_TRIE_ROOT() -> Root {
 }
 
 pub trait Node: PartialEq + std::fmt::Debug + Clone {
-    fn encode<F : Fn(&Address) -> Root>(&self, f: F) -> Bytes;
+    /// Encode the node for storage in the trie. `storage_root` produces the
+    /// storage root of the account stored under the node's key, and is only
+    /// called for accounts.
+    fn encode<F : FnOnce() -> Root>(&self, storage_root: F) -> Bytes;
 }
 
 impl Node for String {
-    fn encode<F : Fn(&Address) -> Root>(&self, _f: F) -> Bytes {
+    fn encode<F : FnOnce() -> Root>(&self, _storage_root: F) -> Bytes {
         self.as_bytes().into()
     }
 }
 
 impl Node for Bytes {
-    fn encode<F : Fn(&Address) -> Root>(&self, _f: F) -> Bytes {
+    fn encode<F : FnOnce() -> Root>(&self, _storage_root: F) -> Bytes {
         self.clone()
     }
 }
 
 impl Node for () {
-    fn encode<F : Fn(&Address) -> Root>(&self, _f: F) -> Bytes {
+    fn encode<F : FnOnce() -> Root>(&self, _storage_root: F) -> Bytes {
         Bytes::default()
     }
 }
 
 impl Node for Option<Account> {
-    fn encode<F : Fn(&Address) -> Root>(&self, _f: F) -> Bytes {
-        Bytes::default()
+    fn encode<F : FnOnce() -> Root>(&self, storage_root: F) -> Bytes {
+        match self {
+            Some(account) => encode_account(account.clone(), storage_root()),
+            None => Bytes::default(),
+        }
     }
 }
 
 impl Node for U256 {
-    fn encode<F : Fn(&Address) -> Root>(&self, _f: F) -> Bytes {
-        Bytes::default()
+    fn encode<F : FnOnce() -> Root>(&self, _storage_root: F) -> Bytes {
+        rlp::encode(self)
     }
 }
 
@@ -121,6 +127,14 @@ impl RLP for Encodable {
 /// The use of Dyn is significantly suboptimal, but the code is illustrative only.
 /// 
 pub fn encode_internal_node(node: InternalNode) -> Encodable {
+    encode_internal_node_with(node, &[], &mut |_, _| {})
+}
+
+/// 
+/// Like [encode_internal_node], but also hands the full RLP encoding of the
+/// node, together with its nibble path, to `visit`.
+/// 
+pub(crate) fn encode_internal_node_with(node: InternalNode, path: &[u8], visit: &mut dyn FnMut(&[u8], &[u8])) -> Encodable {
     let unencoded : Box<dyn RLP> = match node {
         InternalNode::LeafNode{rest_of_key, value} => {
             Box::new((
@@ -146,6 +160,7 @@ pub fn encode_internal_node(node: InternalNode) -> Encodable {
     };
 
     let encoded = unencoded.encode();
+    visit(path, &encoded);
     if encoded.len() < 32 {
         Encodable::RLP(unencoded)
     } else {
@@ -157,10 +172,10 @@ pub fn encode_internal_node(node: InternalNode) -> Encodable {
 /// 
 /// Encode a Node for storage in the Merkle Trie.
 /// 
-/// Currently mostly an unimplemented stub.
+/// `storage_root` is only called if the node is an `Account`.
 /// 
-pub fn encode_node<N : Node, F : Fn(&Address) -> Root>(node: &N, f: F) -> Bytes {
-    node.encode(f)
+pub fn encode_node<N : Node, F : FnOnce() -> Root>(node: &N, storage_root: F) -> Bytes {
+    node.encode(storage_root)
 }
 
 /// 
@@ -329,6 +344,38 @@ pub fn nibble_list_to_compact(x: &[u8], is_leaf: bool) -> Bytes {
 }
 
 
+/// 
+/// Inverse of [nibble_list_to_compact]: expands a compact byte array back
+/// into its nibble-list, returning it along with the `is_leaf` flag.
+/// 
+/// ## Parameters
+/// compact :
+///     Compact byte array, as found in leaf and extension nodes.
+/// 
+/// ## Returns
+/// (nibbles, is_leaf) : `(Bytes, bool)`
+///     The nibble-list and whether it belongs to a leaf node.
+/// 
+pub fn compact_to_nibble_list(compact: &[u8]) -> Result<(Bytes, bool), EthereumException> {
+    let Some(&flags) = compact.first() else {
+        return Err(EthereumException::RLPDecodingError);
+    };
+    let flag_nibble = flags >> 4;
+    if flag_nibble > 3 {
+        return Err(EthereumException::RLPDecodingError);
+    }
+    let is_leaf = flag_nibble & 2 != 0;
+    let mut nibbles = vec![];
+    if flag_nibble & 1 != 0 {
+        nibbles.push(flags & 15);
+    } else if flags & 15 != 0 {
+        return Err(EthereumException::RLPDecodingError);
+    }
+    nibbles.extend(bytes_to_nibble_list(&compact[1..]).iter());
+    Ok((Bytes::from(nibbles), is_leaf))
+}
+
+
 /// 
 /// Converts a `Bytes` into to a sequence of nibbles (bytes with value < 16).
 /// 
@@ -352,6 +399,28 @@ pub fn bytes_to_nibble_list(bytes: &[u8]) -> Bytes {
 }
 
 
+/// 
+/// Converts a nibble-list of even length back into `Bytes`. The inverse of
+/// `bytes_to_nibble_list`.
+/// 
+/// Parameters
+/// ----------
+/// nibbles:
+///     The nibble-list to convert.
+/// 
+/// Returns
+/// -------
+/// bytes : `Bytes`
+///     The packed bytes.
+/// 
+pub fn nibble_list_to_bytes(nibbles: &[u8]) -> Bytes {
+    nibbles
+        .chunks(2)
+        .map(|pair| 16 * pair[0] + pair.get(1).copied().unwrap_or(0))
+        .collect()
+}
+
+
 /// 
 /// Prepares the trie for root calculation. Removes values that are empty,
 /// hashes the keys (if `secured == True`) and encodes all the nodes.
@@ -369,7 +438,7 @@ pub fn bytes_to_nibble_list(bytes: &[u8]) -> Bytes {
 /// out : `Mapping[ethereum.base_types.Bytes, Node]`
 ///     Object with keys mapped to nibble-byte form.
 /// 
-fn _prepare_trie<K, V, F>(trie: &Trie<K, V>, f: F) -> Result<Vec<(Bytes, Bytes)>, EthereumException>
+pub(crate) fn _prepare_trie<K, V, F>(trie: &Trie<K, V>, f: F) -> Result<Vec<(Bytes, Bytes)>, EthereumException>
 where
     K: Key, V: Node,
     F : Fn(&Address) -> Root + Clone,
@@ -377,8 +446,9 @@ where
     let mut res = vec![];
     for (preimage, value) in &trie.data {
         let preimage = preimage.as_ref();
-        let f = f.clone();
-        let encoded_value = encode_node(value, f);
+        let encoded_value = encode_node(value, || {
+            f(&Address::try_from(preimage).expect("accounts are keyed by address"))
+        });
         assert!(!encoded_value.is_empty());
         if trie.secured {
             res.push((bytes_to_nibble_list(&keccak256(preimage)), encoded_value));
@@ -411,7 +481,29 @@ where
     K: Key, V: Node,
 {
     let obj = _prepare_trie(&trie, f).unwrap();
-    match encode_internal_node(patricialize(obj, 0)) {
+    root_with(obj, &mut |_, _| {})
+}
+
+
+/// 
+/// Computes the MPT root of already prepared key-value pairs (keys in
+/// nibble-list format), handing the nibble path and RLP encoding of every node
+/// in the trie, the root included, to `visit`.
+/// 
+/// Parameters
+/// ----------
+/// obj :
+///     Underlying trie key-value pairs, with keys in nibble-list format.
+/// visit :
+///     Called once for each node as it is encoded.
+/// 
+/// Returns
+/// -------
+/// root : `.fork_types.Root`
+///     MPT root of `obj`.
+/// 
+pub fn root_with(obj: Vec<(Bytes, Bytes)>, visit: &mut dyn FnMut(&[u8], &[u8])) -> Root {
+    match encode_internal_node_with(patricialize_with(obj, 0, visit), &[], visit) {
         Encodable::RLP(rlp) => {
             let encoded = rlp.encode();
             Root::from(keccak256(&encoded))
@@ -440,7 +532,16 @@ where
 /// node : `ethereum.base_types.Bytes`
 /// Root node of `obj`.
 /// 
-pub fn patricialize(mut obj: Vec<(Bytes, Bytes)>, level: usize) -> InternalNode {
+pub fn patricialize(obj: Vec<(Bytes, Bytes)>, level: usize) -> InternalNode {
+    patricialize_with(obj, level, &mut |_, _| {})
+}
+
+/// 
+/// Like [patricialize], but hands the nibble path and RLP encoding of every
+/// subnode to `visit` as it is encoded. The returned node itself is not
+/// visited.
+/// 
+pub fn patricialize_with(mut obj: Vec<(Bytes, Bytes)>, level: usize, visit: &mut dyn FnMut(&[u8], &[u8])) -> InternalNode {
     if obj.is_empty() {
         return InternalNode::None;
     }
@@ -468,13 +569,15 @@ pub fn patricialize(mut obj: Vec<(Bytes, Bytes)>, level: usize) -> InternalNode
 
     if prefix_length > 0 {
         let prefix : Bytes = arbitrary_key[level..level + prefix_length].into();
-        let subnode = patricialize(obj, level + prefix_length);
+        let path : Bytes = arbitrary_key[..level + prefix_length].into();
+        let subnode = patricialize_with(obj, level + prefix_length, visit);
         return InternalNode::ExtensionNode {
             key_segment: prefix,
-            subnode: encode_internal_node(subnode),
+            subnode: encode_internal_node_with(subnode, &path, visit),
         };
     }
 
+    let mut path = arbitrary_key[..level].to_vec();
     const EMPTY : Vec<(Bytes, Bytes)> = Vec::new();
     let mut branches = [EMPTY; 16];
     let mut value = Bytes::default();
@@ -488,7 +591,14 @@ pub fn patricialize(mut obj: Vec<(Bytes, Bytes)>, level: usize) -> InternalNode
 
     let subnodes = branches
         .into_iter()
-        .map(|obj| encode_internal_node(patricialize(obj, level+1)))
+        .enumerate()
+        .map(|(nibble, obj)| {
+            path.push(nibble as u8);
+            let subnode = patricialize_with(obj, level+1, visit);
+            let encoded = encode_internal_node_with(subnode, &path, visit);
+            path.pop();
+            encoded
+        })
         .collect::<Vec<_>>();
 
     InternalNode::BranchNode { subnodes, value }
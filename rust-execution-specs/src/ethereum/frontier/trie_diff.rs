@@ -0,0 +1,316 @@
+//!
+//! # Trie Diffing
+//!
+//! ## Introduction
+//!
+//! Compares two Merkle Patricia Tries and reports the keys that were added,
+//! removed or changed between them.
+//!
+//! Both tries are walked side by side from their roots by [diff_roots].
+//! Subtrees whose references are identical (the same hash, or the same
+//! inlined node) are skipped without being looked at, so once the nodes of
+//! both tries are in a [NodeStore], the walk is proportional to the size of
+//! the change rather than the size of the tries.
+//!
+//! [diff_tries] compares in-memory tries, which store no nodes, so it first
+//! encodes every node of both tries into a store. Its cost is therefore
+//! proportional to the size of the tries, like computing their roots.
+//!
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::ethereum::{
+    base_types::Bytes,
+    exceptions::EthereumException,
+    rlp::Simple,
+};
+
+use super::{
+    fork_types::{keccak256, Address, Root},
+    trie::{_prepare_trie, nibble_list_to_bytes, root_with, trie_get, Key, Node, Trie},
+    trie_proof::{resolve_node, DecodedNode},
+};
+
+/// Encoded trie nodes, keyed by their hash.
+pub type NodeStore = HashMap<Root, Bytes>;
+
+///
+/// The difference between the values of a key in two tries.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change<V> {
+    /// The key is only present in the new trie.
+    Added(V),
+    /// The key is only present in the old trie.
+    Removed(V),
+    /// The key is present in both tries, with different values.
+    Changed { old: V, new: V },
+}
+
+///
+/// Adds the nodes of `trie` to `store`, so that it can later be walked by
+/// [diff_roots].
+///
+/// Only nodes which are referenced by hash are stored; smaller nodes are
+/// inlined in their parent. The root is always stored.
+///
+/// Parameters
+/// ----------
+/// trie :
+///     The trie whose nodes to store.
+/// get_storage_root :
+///     Function to get the storage root of an account. Needed to encode
+///     `Account` objects.
+/// store :
+///     The node store to add to.
+///
+/// Returns
+/// -------
+/// root : `Root`
+///     MPT root of `trie`.
+///
+pub fn store_nodes<K, V, F>(
+    trie: &Trie<K, V>,
+    get_storage_root: F,
+    store: &mut NodeStore,
+) -> Result<Root, EthereumException>
+where
+    K: Key,
+    V: Node,
+    F: Fn(&Address) -> Root + Clone,
+{
+    let obj = _prepare_trie(trie, get_storage_root)?;
+    Ok(root_with(obj, &mut |path, encoded| {
+        if path.is_empty() || encoded.len() >= 32 {
+            store.insert(keccak256(encoded), Bytes::from(encoded));
+        }
+    }))
+}
+
+///
+/// Compares the tries with roots `old_root` and `new_root`, whose nodes must
+/// be present in `store`.
+///
+/// Parameters
+/// ----------
+/// store :
+///     Nodes of both tries.
+/// old_root :
+///     Root of the trie to compare against.
+/// new_root :
+///     Root of the trie to compare.
+///
+/// Returns
+/// -------
+/// changes : `Vec<(Bytes, Change<Bytes>)>`
+///     The changed trie paths and their encoded values, sorted by path.
+///
+pub fn diff_roots(
+    store: &NodeStore,
+    old_root: &Root,
+    new_root: &Root,
+) -> Result<Vec<(Bytes, Change<Bytes>)>, EthereumException> {
+    let mut changes = vec![];
+    diff_references(
+        store,
+        &root_reference(old_root),
+        &root_reference(new_root),
+        &mut vec![],
+        &mut changes,
+    )?;
+    Ok(changes
+        .into_iter()
+        .map(|(path, change)| (nibble_list_to_bytes(&path), change))
+        .collect())
+}
+
+///
+/// Compares two tries with the same key and value types.
+///
+/// Both tries are encoded in full, as by [store_nodes], before they are
+/// walked.
+///
+/// Parameters
+/// ----------
+/// old :
+///     The trie to compare against.
+/// new :
+///     The trie to compare.
+/// old_storage_root :
+///     Function to get the storage root of an account in `old`. Needed to
+///     encode `Account` objects.
+/// new_storage_root :
+///     Function to get the storage root of an account in `new`.
+///
+/// Returns
+/// -------
+/// changes : `Vec<(K, Change<V>)>`
+///     The changed keys with their old and new values, sorted by trie path.
+///
+pub fn diff_tries<K, V, F, G>(
+    old: &Trie<K, V>,
+    new: &Trie<K, V>,
+    old_storage_root: F,
+    new_storage_root: G,
+) -> Result<Vec<(K, Change<V>)>, EthereumException>
+where
+    K: Key,
+    V: Node,
+    F: Fn(&Address) -> Root + Clone,
+    G: Fn(&Address) -> Root + Clone,
+{
+    let mut store = NodeStore::new();
+    let old_root = store_nodes(old, old_storage_root, &mut store)?;
+    let new_root = store_nodes(new, new_storage_root, &mut store)?;
+
+    let mut preimages = HashMap::new();
+    let keys = old.data.keys().map(|key| (old, key));
+    for (trie, key) in keys.chain(new.data.keys().map(|key| (new, key))) {
+        let path = if trie.secured {
+            Bytes::from(keccak256(key.as_ref()).as_slice())
+        } else {
+            Bytes::from(key.as_ref())
+        };
+        preimages.insert(path, key);
+    }
+
+    let changes = diff_roots(&store, &old_root, &new_root)?
+        .into_iter()
+        .map(|(path, change)| {
+            let key = preimages[&path];
+            let change = match change {
+                Change::Added(_) => Change::Added(trie_get(new, key)),
+                Change::Removed(_) => Change::Removed(trie_get(old, key)),
+                Change::Changed { .. } => Change::Changed {
+                    old: trie_get(old, key),
+                    new: trie_get(new, key),
+                },
+            };
+            (key.clone(), change)
+        })
+        .collect();
+    Ok(changes)
+}
+
+/// Reference to the root node of a trie, as its parent would hold it.
+fn root_reference(root: &Root) -> Simple {
+    Simple::Bytes(Bytes::from(root.as_slice()))
+}
+
+/// Recursively compares the subtrees `old` and `new`, both below `path`.
+fn diff_references(
+    store: &NodeStore,
+    old: &Simple,
+    new: &Simple,
+    path: &mut Vec<u8>,
+    changes: &mut Vec<(Vec<u8>, Change<Bytes>)>,
+) -> Result<(), EthereumException> {
+    if old == new {
+        return Ok(());
+    }
+
+    let old_node = resolve_node(old, store)?;
+    let new_node = resolve_node(new, store)?;
+    match (&old_node, &new_node) {
+        (
+            Some(DecodedNode::BranchNode {
+                subnodes: old_subnodes,
+                value: old_value,
+            }),
+            Some(DecodedNode::BranchNode {
+                subnodes: new_subnodes,
+                value: new_value,
+            }),
+        ) => {
+            diff_values(path, old_value, new_value, changes);
+            for (nibble, (old, new)) in old_subnodes.iter().zip(new_subnodes).enumerate() {
+                path.push(nibble as u8);
+                diff_references(store, old, new, path, changes)?;
+                path.pop();
+            }
+        }
+        (
+            Some(DecodedNode::ExtensionNode {
+                key_segment: old_segment,
+                subnode: old_subnode,
+            }),
+            Some(DecodedNode::ExtensionNode {
+                key_segment: new_segment,
+                subnode: new_subnode,
+            }),
+        ) if old_segment == new_segment => {
+            path.extend_from_slice(old_segment);
+            diff_references(store, old_subnode, new_subnode, path, changes)?;
+            path.truncate(path.len() - old_segment.len());
+        }
+        _ => {
+            // The shapes differ, so compare the leaves of both subtrees.
+            let mut old_leaves = BTreeMap::new();
+            let mut new_leaves = BTreeMap::new();
+            collect_leaves(store, old_node, path, &mut old_leaves)?;
+            collect_leaves(store, new_node, path, &mut new_leaves)?;
+
+            let paths = old_leaves.keys().chain(new_leaves.keys()).collect::<BTreeSet<_>>();
+            let empty = Bytes::default();
+            for path in paths {
+                let old_value = old_leaves.get(path).unwrap_or(&empty);
+                let new_value = new_leaves.get(path).unwrap_or(&empty);
+                diff_values(path, old_value, new_value, changes);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Records the change between two values stored at `path`, if any.
+fn diff_values(
+    path: &[u8],
+    old: &Bytes,
+    new: &Bytes,
+    changes: &mut Vec<(Vec<u8>, Change<Bytes>)>,
+) {
+    let change = match (old.is_empty(), new.is_empty()) {
+        (true, true) => return,
+        (true, false) => Change::Added(new.clone()),
+        (false, true) => Change::Removed(old.clone()),
+        (false, false) if old == new => return,
+        (false, false) => Change::Changed {
+            old: old.clone(),
+            new: new.clone(),
+        },
+    };
+    changes.push((path.to_vec(), change));
+}
+
+/// Gathers all values below `path` in the subtree rooted at `node`.
+fn collect_leaves(
+    store: &NodeStore,
+    node: Option<DecodedNode>,
+    path: &[u8],
+    leaves: &mut BTreeMap<Vec<u8>, Bytes>,
+) -> Result<(), EthereumException> {
+    match node {
+        None => {}
+        Some(DecodedNode::LeafNode { rest_of_key, value }) => {
+            leaves.insert([path, &rest_of_key].concat(), value);
+        }
+        Some(DecodedNode::ExtensionNode {
+            key_segment,
+            subnode,
+        }) => {
+            let path = [path, &key_segment].concat();
+            collect_leaves(store, resolve_node(&subnode, store)?, &path, leaves)?;
+        }
+        Some(DecodedNode::BranchNode { subnodes, value }) => {
+            if !value.is_empty() {
+                leaves.insert(path.to_vec(), value);
+            }
+            for (nibble, subnode) in subnodes.iter().enumerate() {
+                let path = [path, &[nibble as u8]].concat();
+                collect_leaves(store, resolve_node(subnode, store)?, &path, leaves)?;
+            }
+        }
+    }
+    Ok(())
+}
+
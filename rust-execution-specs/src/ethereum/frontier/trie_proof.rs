@@ -0,0 +1,667 @@
+//!
+//! # Trie Range Proofs
+//!
+//! ## Introduction
+//!
+//! Snap-sync style range proofs: evidence that a sorted run of key-value
+//! pairs is exactly the content of a trie between two boundary keys.
+//!
+//! A proof consists of the RLP encodings of the nodes on the lookup paths of
+//! both boundary keys. Everything to the left of the first path and to the
+//! right of the last path is taken from the proof as opaque subtrees, while
+//! everything between the two paths is rebuilt from the supplied pairs with
+//! [patricialize](super::trie::patricialize). The range is proven if the resulting root matches.
+//!
+//! Keys are trie paths, so for a secured trie they are the hashed keys.
+//!
+//! [prove_keys] proves individual keys instead, as needed to look them up
+//! and update them in a partially known trie.
+//!
+
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap, HashSet},
+};
+
+use crate::ethereum::{
+    base_types::Bytes,
+    exceptions::EthereumException,
+    rlp::{self, Simple, RLP},
+    utils::hexadecimal::hex,
+};
+
+use super::{
+    fork_types::{keccak256, Address, Root},
+    trie::{
+        _prepare_trie, bytes_to_nibble_list, common_prefix_length, compact_to_nibble_list,
+        encode_internal_node_with, nibble_list_to_bytes, root_with, Encodable, InternalNode, Key, Node, Trie,
+    },
+};
+
+///
+/// The answer to a range request: the key-value pairs of the trie between
+/// two boundary keys, and the nodes proving that nothing was left out.
+///
+#[derive(Clone, Debug, Default)]
+pub struct RangeProof {
+    pub keys: Vec<Bytes>,
+    pub values: Vec<Bytes>,
+    pub proof: Vec<Bytes>,
+}
+
+///
+/// A trie node decoded from its RLP form. Subnodes are left as references:
+/// either the empty string, a 32 byte hash, or an inlined node.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum DecodedNode {
+    LeafNode {
+        rest_of_key: Bytes,
+        value: Bytes,
+    },
+    ExtensionNode {
+        key_segment: Bytes,
+        subnode: Simple,
+    },
+    BranchNode {
+        subnodes: Vec<Simple>,
+        value: Bytes,
+    },
+}
+
+///
+/// Decodes a trie node from the output of `rlp::decode`.
+///
+/// Parameters
+/// ----------
+/// node :
+///     The decoded RLP of a leaf, extension or branch node.
+///
+/// Returns
+/// -------
+/// node : `DecodedNode`
+///     The node, with subnodes left as references.
+///
+pub fn decode_node(node: &Simple) -> Result<DecodedNode, EthereumException> {
+    let items = node.as_list()?;
+    match items.len() {
+        2 => {
+            let (key, is_leaf) = compact_to_nibble_list(items[0].as_bytes()?)?;
+            if is_leaf {
+                Ok(DecodedNode::LeafNode {
+                    rest_of_key: key,
+                    value: items[1].as_bytes()?.clone(),
+                })
+            } else {
+                Ok(DecodedNode::ExtensionNode {
+                    key_segment: key,
+                    subnode: items[1].clone(),
+                })
+            }
+        }
+        17 => Ok(DecodedNode::BranchNode {
+            subnodes: items[..16].to_vec(),
+            value: items[16].as_bytes()?.clone(),
+        }),
+        _ => Err(EthereumException::RLPDecodingError),
+    }
+}
+
+///
+/// Follows a subnode reference, looking hashed nodes up in `nodes`.
+///
+/// Returns `None` for the empty reference, and for the root node of the
+/// empty trie.
+///
+pub fn resolve_node(
+    reference: &Simple,
+    nodes: &HashMap<Root, Bytes>,
+) -> Result<Option<DecodedNode>, EthereumException> {
+    match reference {
+        Simple::Bytes(bytes) if bytes.is_empty() => Ok(None),
+        Simple::Bytes(bytes) if bytes.len() == 32 => {
+            let hash = Root::try_from(bytes.as_ref()).unwrap();
+            let encoded = nodes.get(&hash).ok_or_else(|| {
+                EthereumException::InvalidProof(format!("missing trie node {}", hex(&hash)))
+            })?;
+            match rlp::decode(encoded)? {
+                Simple::Bytes(bytes) if bytes.is_empty() => Ok(None),
+                node => Ok(Some(decode_node(&node)?)),
+            }
+        }
+        Simple::List(_) => Ok(Some(decode_node(reference)?)),
+        Simple::Bytes(_) => Err(EthereumException::RLPDecodingError),
+    }
+}
+
+///
+/// Creates a range proof for the keys of `trie` between `first_key` and
+/// `last_key`, inclusive.
+///
+/// Parameters
+/// ----------
+/// trie :
+///     The trie to prove against.
+/// first_key :
+///     Left boundary, as a trie path (hashed when `trie.secured`).
+/// last_key :
+///     Right boundary, as a trie path (hashed when `trie.secured`).
+/// get_storage_root :
+///     Function to get the storage root of an account. Needed to encode
+///     `Account` objects.
+///
+/// Returns
+/// -------
+/// range_proof : `RangeProof`
+///     The sorted pairs within the range, and the nodes on the paths of both
+///     boundary keys.
+///
+pub fn prove_range<K, V, F>(
+    trie: &Trie<K, V>,
+    first_key: &[u8],
+    last_key: &[u8],
+    get_storage_root: F,
+) -> Result<RangeProof, EthereumException>
+where
+    K: Key,
+    V: Node,
+    F: Fn(&Address) -> Root + Clone,
+{
+    let obj = _prepare_trie(trie, get_storage_root)?;
+    let first = bytes_to_nibble_list(first_key);
+    let last = bytes_to_nibble_list(last_key);
+
+    let mut range = obj
+        .iter()
+        .filter(|(key, _)| first <= *key && *key <= last)
+        .cloned()
+        .collect::<Vec<_>>();
+    range.sort();
+
+    let mut proof = vec![];
+    root_with(obj, &mut |path, encoded| {
+        let on_path = first.starts_with(path) || last.starts_with(path);
+        if on_path && (path.is_empty() || encoded.len() >= 32) {
+            proof.push(Bytes::from(encoded));
+        }
+    });
+
+    let (keys, values) = range
+        .into_iter()
+        .map(|(key, value)| (nibble_list_to_bytes(&key), value))
+        .unzip();
+
+    Ok(RangeProof {
+        keys,
+        values,
+        proof,
+    })
+}
+
+///
+/// Verifies that `keys` and `values` are exactly the content of the trie with
+/// root `root` between `first_key` and `last_key`, inclusive.
+///
+/// An empty `proof` asserts that the pairs are the whole trie.
+///
+/// Parameters
+/// ----------
+/// root :
+///     Root of the trie.
+/// first_key :
+///     Left boundary, as a trie path.
+/// last_key :
+///     Right boundary, as a trie path.
+/// keys :
+///     Strictly increasing trie paths within the boundaries.
+/// values :
+///     Encoded values belonging to `keys`.
+/// proof :
+///     Nodes on the paths of both boundary keys.
+///
+/// Returns
+/// -------
+/// has_more : `bool`
+///     Whether the trie contains keys after `last_key`.
+///
+pub fn verify_range(
+    root: &Root,
+    first_key: &[u8],
+    last_key: &[u8],
+    keys: &[Bytes],
+    values: &[Bytes],
+    proof: &[Bytes],
+) -> Result<bool, EthereumException> {
+    if keys.len() != values.len() {
+        return Err(invalid("mismatched number of keys and values"));
+    }
+    if first_key > last_key {
+        return Err(invalid("range boundaries out of order"));
+    }
+    for pair in keys.windows(2) {
+        if pair[0] >= pair[1] {
+            return Err(invalid("keys are not strictly increasing"));
+        }
+    }
+    for (key, value) in keys.iter().zip(values) {
+        if **key < *first_key || **key > *last_key {
+            return Err(invalid("key outside of the range"));
+        }
+        if value.is_empty() {
+            return Err(invalid("empty value"));
+        }
+    }
+
+    let first = bytes_to_nibble_list(first_key);
+    let last = bytes_to_nibble_list(last_key);
+    let obj = keys
+        .iter()
+        .zip(values)
+        .map(|(key, value)| (bytes_to_nibble_list(key), value.clone()))
+        .collect::<Vec<_>>();
+
+    if proof.is_empty() {
+        if root_with(obj, &mut |_, _| {}) != *root {
+            return Err(invalid("root mismatch"));
+        }
+        return Ok(false);
+    }
+
+    let nodes = proof
+        .iter()
+        .map(|node| (keccak256(node), node.clone()))
+        .collect::<HashMap<_, _>>();
+
+    let mut collector = Collector {
+        first: &first,
+        last: &last,
+        nodes: &nodes,
+        items: vec![],
+        has_more: false,
+    };
+    collector.walk(&Simple::Bytes(Bytes::from(root.as_slice())), &[])?;
+
+    let mut items = collector.items;
+    items.extend(obj.into_iter().map(|(key, value)| (key, Item::Value(value))));
+
+    let computed = match patricialize_partial(items, &[], &mut |_, _| {})? {
+        Encodable::RLP(rlp) => keccak256(&rlp.encode()),
+        Encodable::Root(root) => root,
+        Encodable::Bytes(_) => unreachable!(),
+    };
+    if computed != *root {
+        return Err(invalid("root mismatch"));
+    }
+
+    Ok(collector.has_more)
+}
+
+///
+/// Collects the nodes of `trie` needed to look up each of `keys`, and to
+/// write or delete them afterwards.
+///
+/// Besides the nodes on the lookup paths, this includes the other child of
+/// every branch node on a path that has only two children, as deleting one
+/// of them folds the branch into the remaining child.
+///
+/// Parameters
+/// ----------
+/// trie :
+///     The trie to prove against.
+/// keys :
+///     The keys to prove, hashed when `trie.secured`.
+/// get_storage_root :
+///     Function to get the storage root of an account. Needed to encode
+///     `Account` objects.
+///
+/// Returns
+/// -------
+/// proof : `Vec<Bytes>`
+///     The encoded nodes, each of them hashed in its parent.
+///
+pub fn prove_keys<K, V, F>(
+    trie: &Trie<K, V>,
+    keys: &[K],
+    get_storage_root: F,
+) -> Result<Vec<Bytes>, EthereumException>
+where
+    K: Key,
+    V: Node,
+    F: Fn(&Address) -> Root + Clone,
+{
+    let obj = _prepare_trie(trie, get_storage_root)?;
+    let paths = keys
+        .iter()
+        .map(|key| match trie.secured {
+            true => bytes_to_nibble_list(&keccak256(key.as_ref())),
+            false => bytes_to_nibble_list(key.as_ref()),
+        })
+        .collect::<Vec<_>>();
+    let on_path = |path: &[u8]| paths.iter().any(|key| key.starts_with(path));
+
+    let mut proof = vec![];
+    let mut narrow_branches = HashSet::new();
+    let mut siblings = vec![];
+    root_with(obj, &mut |path, encoded| {
+        if on_path(path) {
+            if children(encoded) == Some(2) {
+                narrow_branches.insert(path.to_vec());
+            }
+            if path.is_empty() || encoded.len() >= 32 {
+                proof.push(Bytes::from(encoded));
+            }
+        } else if encoded.len() >= 32 && path.split_last().is_some_and(|(_, parent)| on_path(parent)) {
+            siblings.push((path.to_vec(), Bytes::from(encoded)));
+        }
+    });
+
+    // Nodes are visited before their parents, so siblings can only be picked
+    // once all branches are known.
+    proof.extend(
+        siblings
+            .into_iter()
+            .filter(|(path, _)| narrow_branches.contains(&path[..path.len() - 1]))
+            .map(|(_, encoded)| encoded),
+    );
+    Ok(proof)
+}
+
+/// The number of children of an encoded branch node, `None` for other nodes.
+fn children(encoded: &[u8]) -> Option<usize> {
+    match rlp::decode(encoded).ok()? {
+        Simple::List(items) if items.len() == 17 => Some(
+            items[..16]
+                .iter()
+                .filter(|item| !matches!(item, Simple::Bytes(bytes) if bytes.is_empty()))
+                .count(),
+        ),
+        _ => None,
+    }
+}
+
+///
+/// Collects the values below `reference` into `items`, keyed by nibble path,
+/// for [patricialize_partial]. Hashed nodes missing from `nodes` are kept as
+/// opaque subtrees.
+///
+/// `in_extension` tells whether `reference` is the subnode of an extension
+/// node, which is always a branch node.
+///
+pub(crate) fn collect_items(
+    nodes: &HashMap<Root, Bytes>,
+    reference: &Simple,
+    path: &[u8],
+    in_extension: bool,
+    items: &mut BTreeMap<Vec<u8>, Item>,
+) -> Result<(), EthereumException> {
+    if let Simple::Bytes(hash) = reference {
+        if hash.len() == 32 && !nodes.contains_key(hash.as_ref()) {
+            let item = match in_extension {
+                true => Item::Branch(reference.clone()),
+                false => Item::Subtree(reference.clone()),
+            };
+            items.insert(path.to_vec(), item);
+            return Ok(());
+        }
+    }
+
+    match resolve_node(reference, nodes)? {
+        None => {}
+        Some(DecodedNode::LeafNode { rest_of_key, value }) => {
+            items.insert([path, &rest_of_key].concat(), Item::Value(value));
+        }
+        Some(DecodedNode::ExtensionNode {
+            key_segment,
+            subnode,
+        }) => {
+            collect_items(nodes, &subnode, &[path, &key_segment].concat(), true, items)?;
+        }
+        Some(DecodedNode::BranchNode { subnodes, value }) => {
+            if !value.is_empty() {
+                items.insert(path.to_vec(), Item::Value(value));
+            }
+            for (nibble, subnode) in subnodes.iter().enumerate() {
+                collect_items(nodes, subnode, &[path, &[nibble as u8]].concat(), false, items)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Something below a nibble path, as input to [patricialize_partial].
+pub(crate) enum Item {
+    /// A value stored at exactly that path.
+    Value(Bytes),
+    /// An opaque subtree taken from the proof.
+    Subtree(Simple),
+    /// An opaque subtree known to start with a branch node, which may be
+    /// moved below a new extension node.
+    Branch(Simple),
+}
+
+/// Gathers everything outside of the proven range from the proof nodes.
+struct Collector<'a> {
+    first: &'a [u8],
+    last: &'a [u8],
+    nodes: &'a HashMap<Root, Bytes>,
+    items: Vec<(Bytes, Item)>,
+    has_more: bool,
+}
+
+/// Where the keys below a nibble path lie relative to the range.
+#[derive(PartialEq)]
+enum Position {
+    Left,
+    Inside,
+    Right,
+    Boundary,
+}
+
+impl Collector<'_> {
+    /// Adds the subtree `reference` found at `path` to the collected items,
+    /// unless it lies entirely within the range, descending into it if it
+    /// straddles a boundary.
+    fn walk(&mut self, reference: &Simple, path: &[u8]) -> Result<(), EthereumException> {
+        if matches!(reference, Simple::Bytes(bytes) if bytes.is_empty()) {
+            return Ok(());
+        }
+        match self.position(path) {
+            Position::Boundary => {}
+            Position::Inside => return Ok(()),
+            position => {
+                self.subtree(path, reference, position);
+                return Ok(());
+            }
+        }
+
+        match resolve_node(reference, self.nodes)? {
+            None => Ok(()),
+            Some(DecodedNode::LeafNode { rest_of_key, value }) => {
+                self.value([path, &rest_of_key].concat(), value);
+                Ok(())
+            }
+            Some(DecodedNode::ExtensionNode {
+                key_segment,
+                subnode,
+            }) => {
+                // An extension that leads out of the range is kept whole, at
+                // its own position.
+                let key = [path, &key_segment].concat();
+                match self.position(&key) {
+                    Position::Boundary => self.walk(&subnode, &key),
+                    Position::Inside => Ok(()),
+                    position => {
+                        self.subtree(path, reference, position);
+                        Ok(())
+                    }
+                }
+            }
+            Some(DecodedNode::BranchNode { subnodes, value }) => {
+                let mut key = path.to_vec();
+                for (nibble, subnode) in subnodes.iter().enumerate() {
+                    key.push(nibble as u8);
+                    self.walk(subnode, &key)?;
+                    key.pop();
+                }
+                if !value.is_empty() {
+                    self.value(path.into(), value);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn position(&self, path: &[u8]) -> Position {
+        let first = compare_prefix(path, self.first);
+        let last = compare_prefix(path, self.last);
+        if first == Ordering::Less {
+            Position::Left
+        } else if last == Ordering::Greater {
+            Position::Right
+        } else if first == Ordering::Greater && last == Ordering::Less {
+            Position::Inside
+        } else {
+            Position::Boundary
+        }
+    }
+
+    /// Keeps an out of range subtree.
+    fn subtree(&mut self, path: &[u8], reference: &Simple, position: Position) {
+        self.has_more |= position == Position::Right;
+        self.items.push((path.into(), Item::Subtree(reference.clone())));
+    }
+
+    /// Keeps a value found in the proof if its key is out of range.
+    fn value(&mut self, key: Vec<u8>, value: Bytes) {
+        if *key < *self.first || *key > *self.last {
+            self.has_more |= *key > *self.last;
+            self.items.push((key.into(), Item::Value(value)));
+        }
+    }
+}
+
+///
+/// Compares the keys below the nibble path `prefix` with `bound`: `Less` if
+/// they all sort before it, `Greater` if they all sort after it, and `Equal`
+/// if `prefix` leads towards `bound`.
+///
+fn compare_prefix(prefix: &[u8], bound: &[u8]) -> Ordering {
+    let n = prefix.len().min(bound.len());
+    match prefix[..n].cmp(&bound[..n]) {
+        Ordering::Equal if prefix.len() > bound.len() => Ordering::Greater,
+        ordering => ordering,
+    }
+}
+
+///
+/// Structural composition function for a partially known trie.
+///
+/// Works like [patricialize_with](super::trie::patricialize_with), except
+/// that items may also be whole subtrees taken from a proof, which are
+/// embedded as-is at their position, and that the node at `path` is encoded
+/// (and visited) as well.
+///
+pub(crate) fn patricialize_partial(
+    mut obj: Vec<(Bytes, Item)>,
+    path: &[u8],
+    visit: &mut dyn FnMut(&[u8], &[u8]),
+) -> Result<Encodable, EthereumException> {
+    let level = path.len();
+    if obj.is_empty() {
+        return Ok(encode_internal_node_with(InternalNode::None, path, visit));
+    }
+
+    if obj.len() == 1 {
+        let (key, item) = obj.pop().unwrap();
+        return match item {
+            Item::Value(value) => Ok(encode_internal_node_with(
+                InternalNode::LeafNode {
+                    rest_of_key: Box::from(&key[level..]),
+                    value,
+                },
+                path,
+                visit,
+            )),
+            Item::Subtree(reference) | Item::Branch(reference) if key.len() == level => {
+                Ok(subtree(reference))
+            }
+            Item::Branch(reference) => Ok(encode_internal_node_with(
+                InternalNode::ExtensionNode {
+                    key_segment: key[level..].into(),
+                    subnode: subtree(reference),
+                },
+                path,
+                visit,
+            )),
+            Item::Subtree(_) => Err(invalid("dangling subtree")),
+        };
+    }
+
+    let arbitrary_key = &obj[0].0;
+    let substring = &arbitrary_key[level..];
+    let mut prefix_length = substring.len();
+    for (key, _item) in obj.iter().skip(1) {
+        prefix_length = usize::min(
+            prefix_length,
+            common_prefix_length(substring, &key[level..]),
+        );
+        if prefix_length == 0 {
+            break;
+        }
+    }
+
+    if prefix_length > 0 {
+        let prefix: Bytes = arbitrary_key[level..level + prefix_length].into();
+        let subpath = arbitrary_key[..level + prefix_length].to_vec();
+        let subnode = patricialize_partial(obj, &subpath, visit)?;
+        return Ok(encode_internal_node_with(
+            InternalNode::ExtensionNode {
+                key_segment: prefix,
+                subnode,
+            },
+            path,
+            visit,
+        ));
+    }
+
+    let mut branches: [Vec<(Bytes, Item)>; 16] = Default::default();
+    let mut value = Bytes::default();
+    for (key, item) in obj {
+        if key.len() == level {
+            match item {
+                Item::Value(v) => value = v,
+                _ => return Err(invalid("overlapping subtree")),
+            }
+        } else {
+            branches[key[level] as usize].push((key, item));
+        }
+    }
+
+    let mut subpath = path.to_vec();
+    let mut subnodes = Vec::with_capacity(16);
+    for (nibble, obj) in branches.into_iter().enumerate() {
+        subpath.push(nibble as u8);
+        subnodes.push(patricialize_partial(obj, &subpath, visit)?);
+        subpath.pop();
+    }
+
+    Ok(encode_internal_node_with(
+        InternalNode::BranchNode { subnodes, value },
+        path,
+        visit,
+    ))
+}
+
+/// Embeds a subnode reference taken from a proof.
+fn subtree(reference: Simple) -> Encodable {
+    match reference {
+        Simple::Bytes(hash) if hash.len() == 32 => {
+            Encodable::Root(Root::try_from(hash.as_ref()).unwrap())
+        }
+        inlined => Encodable::RLP(Box::new(inlined)),
+    }
+}
+
+fn invalid(reason: &str) -> EthereumException {
+    EthereumException::InvalidProof(reason.to_owned())
+}
@@ -0,0 +1,55 @@
+//!
+//! # Hardfork Utility Functions For Addresses
+//!
+//! ## Introduction
+//!
+//! Address specific functions used in this frontier version of
+//! specification.
+//!
+
+use crate::ethereum::{
+    base_types::{Uint, U256},
+    rlp,
+};
+
+use super::super::fork_types::{keccak256, Address};
+
+///
+///     Convert a Uint or U256 value to a valid address (20 bytes).
+///
+///     Parameters
+///     ----------
+///     data :
+///         The string to be converted to bytes.
+///
+///     Returns
+///     -------
+///     address : `Address`
+///         The obtained address.
+///
+pub fn to_address(data: &U256) -> Address {
+    data.to_be_bytes32()[12..].try_into().unwrap()
+}
+
+///
+///     Computes address of the new account that needs to be created.
+///
+///     Parameters
+///     ----------
+///     address :
+///         The address of the account that wants to create the new account.
+///     nonce :
+///         The transaction count of the account that wants to create the new
+///         account.
+///
+///     Returns
+///     -------
+///     address: `ethereum.frontier.fork_types.Address`
+///         The computed address of the new account.
+///
+pub fn compute_contract_address(address: &Address, nonce: &Uint) -> Address {
+    let computed_address = keccak256(&rlp::encode(&(*address, nonce.clone())));
+    let mut canonical_address = [0; 20];
+    canonical_address.copy_from_slice(&computed_address[12..]);
+    canonical_address
+}
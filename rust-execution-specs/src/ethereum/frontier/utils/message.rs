@@ -0,0 +1,92 @@
+//!
+//! # Hardfork Utility Functions For The Message Data-structure
+//!
+//! ## Introduction
+//!
+//! Message specific functions used in this frontier version of
+//! specification.
+//!
+
+use crate::ethereum::{
+    base_types::{Bytes, Uint, U256},
+    exceptions::EthereumException,
+};
+
+use super::super::{
+    fork_types::Address,
+    state::{get_account, get_code_hash, StateBackend},
+    vm::{Environment, Message},
+};
+use super::address::compute_contract_address;
+
+///
+///     Execute a transaction against the provided environment.
+///
+///     Parameters
+///     ----------
+///     caller :
+///         Address which initiated the transaction
+///     target :
+///         Address whose code will be executed, or `None` to create a
+///         contract.
+///     value :
+///         Value to be transferred.
+///     data :
+///         Array of bytes provided to the code in `target`, or the code of
+///         the contract to create.
+///     gas :
+///         Gas provided for the code in `target`.
+///     env :
+///         Environment for the Ethereum Virtual Machine.
+///     code_address :
+///         This is optional. It is used only at the time of contract
+///         creation and sets the address of the code to be executed,
+///         defaulting to `target`.
+///
+///     Returns
+///     -------
+///     message: `ethereum.frontier.vm.Message`
+///         Items containing contract creation or message call specific data.
+///
+pub fn prepare_message<S: StateBackend, I>(
+    caller: Address,
+    target: Option<Address>,
+    value: U256,
+    data: Bytes,
+    gas: Uint,
+    env: &Environment<S, I>,
+    code_address: Option<Address>,
+) -> Result<Message, EthereumException> {
+    Ok(match target {
+        None => {
+            // The nonce of the caller has already been incremented.
+            let nonce = get_account(&env.state, &caller)?.nonce - 1u8;
+            Message {
+                caller,
+                target: None,
+                current_target: compute_contract_address(&caller, &nonce),
+                gas,
+                value,
+                data: Bytes::default(),
+                code_address,
+                code: data,
+                code_hash: None,
+                depth: Uint::from(0u8),
+                is_callcode: false,
+            }
+        }
+        Some(target) => Message {
+            caller,
+            target: Some(target),
+            current_target: target,
+            gas,
+            value,
+            data,
+            code_address: code_address.or(Some(target)),
+            code: get_account(&env.state, &target)?.code,
+            code_hash: Some(get_code_hash(&env.state, &target)?),
+            depth: Uint::from(0u8),
+            is_callcode: false,
+        },
+    })
+}
@@ -0,0 +1,10 @@
+//!
+//! # Frontier Utility Functions
+//!
+//! ## Introduction
+//!
+//! Utility functions used in this frontier version of specification.
+//!
+
+pub mod address;
+pub mod message;
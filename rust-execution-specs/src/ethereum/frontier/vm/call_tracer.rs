@@ -0,0 +1,147 @@
+//! Ethereum Virtual Machine (EVM) Call Tracing
+//! ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+//!
+//! .. contents:: Table of Contents
+//!     :backlinks: none
+//!     :local:
+//!
+//! Introduction
+//! ------------
+//!
+//! An `Inspector` building the tree of frames of each message call, in the
+//! JSON shape of the geth `callTracer`:
+//!
+//! ```json
+//! {
+//!   "type": "CALL", "from": "0xaa..", "to": "0xcc..", "value": "0x0",
+//!   "gas": "0x2710", "gasUsed": "0x2f5", "input": "0x", "output": "0x",
+//!   "calls": [{"type": "CREATE", "error": "out of gas", ...}]
+//! }
+//! ```
+//!
+//! `output`, `error` and `calls` are left out when empty, and errors are
+//! named as geth names them.
+
+use serde_json::{json, Value};
+
+use super::{
+    exceptions::EvmError,
+    inspector::{Frame, Inspector},
+    Message,
+};
+use crate::ethereum::base_types::U256;
+use crate::ethereum::frontier::fork_types::Address;
+use crate::ethereum::utils::hexadecimal::hex;
+
+///
+/// Builds a geth `callTracer` tree of each message call.
+///
+#[derive(Default)]
+pub struct CallTracer {
+    frames: Vec<Value>,
+    traces: Vec<Value>,
+}
+
+impl CallTracer {
+    /// Create a tracer with no traces.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The call trees of the message calls traced so far, oldest first.
+    pub fn traces(&self) -> &[Value] {
+        &self.traces
+    }
+
+    /// Take the call trees of the message calls traced so far.
+    pub fn take_traces(&mut self) -> Vec<Value> {
+        std::mem::take(&mut self.traces)
+    }
+
+    fn enter(&mut self, kind: &str, to: &Address, message: &Message) {
+        self.frames.push(json!({
+            "type": kind,
+            "from": hex(&message.caller),
+            "to": hex(to),
+            "value": format!("{:#x}", message.value),
+            "gas": format!("{:#x}", message.gas),
+            "gasUsed": "0x0",
+            "input": hex(if message.target.is_some() { &message.data } else { &message.code }),
+        }));
+    }
+
+    fn exit(&mut self, frame: &Frame, error: Option<&EvmError>) {
+        let Some(mut call) = self.frames.pop() else {
+            return;
+        };
+        call["gasUsed"] = json!(format!("{:#x}", &frame.message.gas - frame.gas_left));
+        if !frame.output.is_empty() {
+            call["output"] = json!(hex(frame.output));
+        }
+        if let Some(error) = error {
+            call["error"] = json!(error_message(error));
+        }
+        self.push_call(call);
+    }
+
+    fn push_call(&mut self, call: Value) {
+        match self.frames.last_mut() {
+            Some(parent) => match parent.get_mut("calls").and_then(Value::as_array_mut) {
+                Some(calls) => calls.push(call),
+                None => parent["calls"] = json!([call]),
+            },
+            None => self.traces.push(call),
+        }
+    }
+}
+
+/// The message geth reports for `error`.
+fn error_message(error: &EvmError) -> String {
+    match error {
+        // The only exceptional halt that is not one of the errors below.
+        EvmError::Halt => "contract address collision".to_string(),
+        EvmError::StackUnderflow => "stack underflow".to_string(),
+        EvmError::StackOverflow => "stack limit reached".to_string(),
+        EvmError::OutOfGas => "out of gas".to_string(),
+        EvmError::InvalidOpcode => "invalid opcode".to_string(),
+        EvmError::InvalidJumpDest => "invalid jump destination".to_string(),
+        EvmError::StackDepthLimit => "max call depth exceeded".to_string(),
+        EvmError::State(error) => format!("{error:?}"),
+    }
+}
+
+impl Inspector for CallTracer {
+    fn call(&mut self, frame: &Frame) {
+        let message = frame.message;
+        match message.code_address {
+            Some(code_address) if message.is_callcode => {
+                self.enter("CALLCODE", &code_address, message)
+            }
+            _ => self.enter("CALL", &message.current_target, message),
+        }
+    }
+
+    fn call_end(&mut self, frame: &Frame, error: Option<&EvmError>) {
+        self.exit(frame, error);
+    }
+
+    fn create(&mut self, frame: &Frame) {
+        self.enter("CREATE", &frame.message.current_target, frame.message);
+    }
+
+    fn create_end(&mut self, frame: &Frame, error: Option<&EvmError>) {
+        self.exit(frame, error);
+    }
+
+    fn selfdestruct(&mut self, frame: &Frame, beneficiary: &Address, value: &U256) {
+        self.push_call(json!({
+            "type": "SELFDESTRUCT",
+            "from": hex(&frame.message.current_target),
+            "to": hex(beneficiary),
+            "value": format!("{value:#x}"),
+            "gas": "0x0",
+            "gasUsed": "0x0",
+            "input": "0x",
+        }));
+    }
+}
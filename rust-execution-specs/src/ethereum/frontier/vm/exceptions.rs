@@ -10,6 +10,8 @@
 //!
 //! Exceptions which cause the EVM to halt exceptionally.
 
+use crate::ethereum::exceptions::EthereumException;
+
 pub type Result<T, E = EvmError> = std::result::Result<T, E>;
 
 #[derive(Debug)]
@@ -36,4 +38,14 @@ pub enum EvmError {
     InvalidJumpDest,
     /// Raised when the message depth is greater than `1024`
     StackDepthLimit,
+    /// Raised when the state backend fails to provide an account or a
+    /// storage slot. Unlike the errors above this is not an exceptional halt
+    /// of the EVM: execution can not continue at all.
+    State(EthereumException),
+}
+
+impl From<EthereumException> for EvmError {
+    fn from(error: EthereumException) -> Self {
+        EvmError::State(error)
+    }
 }
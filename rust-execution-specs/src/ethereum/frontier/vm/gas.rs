@@ -11,8 +11,10 @@
 //! EVM gas constants and calculators.
 
 use super::{
-    super::state::{self, State},
+    super::state::{self, StateBackend},
     exceptions::{EvmError, Result},
+    inspector::{inspect, Inspector},
+    memory::Memory,
     Evm,
 };
 use crate::ethereum::base_types::U256;
@@ -103,7 +105,8 @@ pub struct MessageCallGas {
 /// amount :
 ///     The amount of gas the current operation requires.
 ///
-pub fn charge_gas(evm: &mut Evm, amount: Uint) -> Result<()> {
+pub fn charge_gas<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>, amount: Uint) -> Result<()> {
+    inspect(evm, |inspector, frame| inspector.charge_gas(frame, &amount));
     evm.gas_left = evm
         .gas_left
         .checked_sub(&amount)
@@ -145,34 +148,8 @@ pub fn calculate_memory_gas_cost(size_in_bytes: Uint) -> Uint {
 /// Returns
 /// -------
 /// extend_memory: `ExtendMemory`
-pub fn calculate_gas_extend_memory(memory: &[u8], extensions: Vec<(U256, U256)>) -> ExtendMemory {
-    let mut size_to_extend = Uint::from(0u8);
-    let mut to_be_paid = Uint::from(0u8);
-    let mut current_size = Uint::from(memory.len());
-
-    for (start_position, size) in extensions {
-        if size == Uint::from(0u8) {
-            continue;
-        }
-
-        let before_size = ceil32(current_size.clone());
-        let after_size = ceil32(start_position + size);
-        if after_size <= before_size {
-            continue;
-        }
-
-        size_to_extend += &after_size - &before_size;
-        let already_paid = calculate_memory_gas_cost(before_size);
-        let total_cost = calculate_memory_gas_cost(after_size.clone());
-        to_be_paid += total_cost - already_paid;
-
-        current_size = after_size;
-    }
-
-    ExtendMemory {
-        cost: to_be_paid,
-        expand_by: size_to_extend,
-    }
+pub fn calculate_gas_extend_memory(memory: &Memory, extensions: Vec<(U256, U256)>) -> ExtendMemory {
+    memory.extend_cost(&extensions)
 }
 
 /// Calculates the gas amount for executing Opcodes `CALL` and `CALLCODE`.
@@ -191,13 +168,13 @@ pub fn calculate_gas_extend_memory(memory: &[u8], extensions: Vec<(U256, U256)>)
 /// Returns
 /// -------
 /// message_call_gas: `MessageCallGas`
-pub fn calculate_message_call_gas(
-    state: &State,
+pub fn calculate_message_call_gas<S: StateBackend>(
+    state: &S,
     gas: Uint,
     to: &Address,
     value: U256,
-) -> MessageCallGas {
-    let create_gas_cost = if state::account_exists(state, to) {
+) -> Result<MessageCallGas> {
+    let create_gas_cost = if state::account_exists(state, to)? {
         Uint::from(0u8)
     } else {
         GAS_NEW_ACCOUNT()
@@ -208,11 +185,11 @@ pub fn calculate_message_call_gas(
         GAS_CALL_VALUE()
     };
     let cost = GAS_CALL() + &gas + create_gas_cost + transfer_gas_cost;
-    let stipend = if value == Uint::from(0u8) {
+    let stipend = if value == U256::from(0u8) {
         gas
     } else {
         GAS_CALL_STIPEND() + gas
     };
 
-    MessageCallGas { cost, stipend }
+    Ok(MessageCallGas { cost, stipend })
 }
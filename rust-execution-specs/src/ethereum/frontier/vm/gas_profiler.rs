@@ -0,0 +1,254 @@
+//! Ethereum Virtual Machine (EVM) Gas Profiling
+//! ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+//!
+//! .. contents:: Table of Contents
+//!     :backlinks: none
+//!     :local:
+//!
+//! Introduction
+//! ------------
+//!
+//! An `Inspector` aggregating where gas goes, by opcode, by contract and by
+//! program counter.
+//!
+//! The gas of an opcode is the gas it took from its frame, less the gas used
+//! by the child frame it ran, if any. This is the gas it charged through
+//! `charge_gas`, without the gas forwarded to a `CALL`, and including all the
+//! gas left when the frame halted exceptionally. The part of it spent on
+//! expanding memory is also kept apart. Gas that no opcode accounts for is
+//! reported under `PRECOMPILE` and `CODE_DEPOSIT`.
+//!
+//! The profile can be printed as a sorted report, or as folded stacks for
+//! `flamegraph.pl` and `inferno`, with one frame per contract:
+//!
+//! ```text
+//! 0xcccccccccccccccccccccccccccccccccccccccc;CALL 40
+//! 0xcccccccccccccccccccccccccccccccccccccccc;0x00000000000000000000000000000000000000bb;SSTORE 20000
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use super::{
+    exceptions::EvmError,
+    gas::calculate_memory_gas_cost,
+    inspector::{Frame, Inspector},
+    instructions::Ops,
+};
+use crate::ethereum::base_types::Uint;
+use crate::ethereum::frontier::fork_types::Address;
+use crate::ethereum::utils::hexadecimal::hex;
+
+/// Gas used by all executions of an opcode.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OpcodeGas {
+    pub count: u64,
+    pub gas: Uint,
+    pub memory_gas: Uint,
+}
+
+struct Step {
+    pc: usize,
+    op: u8,
+    gas_left: Uint,
+    memory_size: usize,
+    children_gas: Uint,
+}
+
+struct FrameProfile {
+    address: Address,
+    stack: String,
+    is_create: bool,
+    accounted: Uint,
+    step: Option<Step>,
+}
+
+///
+/// Aggregates the gas used by each message call it inspects.
+///
+#[derive(Default)]
+pub struct GasProfiler {
+    frames: Vec<FrameProfile>,
+    by_opcode: BTreeMap<String, OpcodeGas>,
+    by_contract: BTreeMap<Address, Uint>,
+    by_pc: BTreeMap<(Address, usize), Uint>,
+    folded: BTreeMap<String, Uint>,
+}
+
+impl GasProfiler {
+    /// Create a profiler with an empty profile.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gas used by opcode, most expensive first.
+    pub fn by_opcode(&self) -> Vec<(&str, &OpcodeGas)> {
+        let mut rows = self
+            .by_opcode
+            .iter()
+            .map(|(name, gas)| (name.as_str(), gas))
+            .collect::<Vec<_>>();
+        rows.sort_by(|a, b| b.1.gas.cmp(&a.1.gas));
+        rows
+    }
+
+    /// Gas used by the code of each contract, most expensive first.
+    pub fn by_contract(&self) -> Vec<(&Address, &Uint)> {
+        let mut rows = self.by_contract.iter().collect::<Vec<_>>();
+        rows.sort_by(|a, b| b.1.cmp(a.1));
+        rows
+    }
+
+    /// Gas used by each program counter of each contract, most expensive
+    /// first.
+    pub fn by_pc(&self) -> Vec<(&Address, usize, &Uint)> {
+        let mut rows = self
+            .by_pc
+            .iter()
+            .map(|((address, pc), gas)| (address, *pc, gas))
+            .collect::<Vec<_>>();
+        rows.sort_by(|a, b| b.2.cmp(a.2));
+        rows
+    }
+
+    /// A report of the profile, most expensive first in each section.
+    pub fn report(&self) -> String {
+        let mut report = String::new();
+
+        let _ = writeln!(report, "{:<14} {:>8} {:>12} {:>12}", "opcode", "count", "gas", "memory gas");
+        for (name, gas) in self.by_opcode() {
+            let _ = writeln!(
+                report,
+                "{:<14} {:>8} {:>12} {:>12}",
+                name, gas.count, gas.gas, gas.memory_gas
+            );
+        }
+
+        let _ = writeln!(report, "\n{:<42} {:>12}", "contract", "gas");
+        for (address, gas) in self.by_contract() {
+            let _ = writeln!(report, "{:<42} {:>12}", hex(address), gas);
+        }
+
+        let _ = writeln!(report, "\n{:<42} {:>8} {:>12}", "contract", "pc", "gas");
+        for (address, pc, gas) in self.by_pc() {
+            let _ = writeln!(report, "{:<42} {:>8} {:>12}", hex(address), pc, gas);
+        }
+        report
+    }
+
+    /// The profile as folded stacks, one line per stack.
+    pub fn folded_stacks(&self) -> String {
+        self.folded
+            .iter()
+            .filter(|(_, gas)| **gas != Uint::from(0u8))
+            .map(|(stack, gas)| format!("{stack} {gas}\n"))
+            .collect()
+    }
+
+    fn record(&mut self, name: String, pc: Option<usize>, gas: Uint, memory_gas: Uint) {
+        let Some(frame) = self.frames.last_mut() else {
+            return;
+        };
+        frame.accounted += &gas;
+
+        let opcode = self.by_opcode.entry(name.clone()).or_default();
+        opcode.count += 1;
+        opcode.gas += &gas;
+        opcode.memory_gas += memory_gas;
+        *self.by_contract.entry(frame.address).or_default() += &gas;
+        if let Some(pc) = pc {
+            *self.by_pc.entry((frame.address, pc)).or_default() += &gas;
+        }
+        *self.folded.entry(format!("{};{}", frame.stack, name)).or_default() += gas;
+    }
+
+    fn enter(&mut self, frame: &Frame) {
+        let message = frame.message;
+        let address = message.code_address.unwrap_or(message.current_target);
+        let stack = match self.frames.last() {
+            Some(parent) => format!("{};{}", parent.stack, hex(&address)),
+            None => hex(&address),
+        };
+        self.frames.push(FrameProfile {
+            address,
+            stack,
+            is_create: message.target.is_none(),
+            accounted: Uint::from(0u8),
+            step: None,
+        });
+    }
+
+    fn exit(&mut self, frame: &Frame) {
+        let Some(profile) = self.frames.last() else {
+            return;
+        };
+        let used = &frame.message.gas - frame.gas_left;
+        if used > profile.accounted {
+            let name = if profile.is_create { "CODE_DEPOSIT" } else { "PRECOMPILE" };
+            let unaccounted = &used - &profile.accounted;
+            self.record(name.to_string(), None, unaccounted, Uint::from(0u8));
+        }
+
+        self.frames.pop();
+        if let Some(parent) = self.frames.last_mut() {
+            parent.accounted += &used;
+            if let Some(step) = parent.step.as_mut() {
+                step.children_gas += used;
+            }
+        }
+    }
+}
+
+fn op_name(op: u8) -> String {
+    Ops::from_byte(op).map_or_else(|| "INVALID".to_string(), |op| op.name())
+}
+
+impl Inspector for GasProfiler {
+    fn step(&mut self, frame: &Frame, op: u8) {
+        if let Some(profile) = self.frames.last_mut() {
+            profile.step = Some(Step {
+                pc: frame.pc,
+                op,
+                gas_left: frame.gas_left.clone(),
+                memory_size: frame.memory.len(),
+                children_gas: Uint::from(0u8),
+            });
+        }
+    }
+
+    fn step_end(&mut self, frame: &Frame) {
+        let Some(step) = self.frames.last_mut().and_then(|profile| profile.step.take()) else {
+            return;
+        };
+        let gas = step.gas_left - frame.gas_left - step.children_gas;
+        let memory_gas = calculate_memory_gas_cost(Uint::from(frame.memory.len()))
+            - calculate_memory_gas_cost(Uint::from(step.memory_size));
+        self.record(op_name(step.op), Some(step.pc), gas, memory_gas);
+    }
+
+    fn call(&mut self, frame: &Frame) {
+        self.enter(frame);
+    }
+
+    fn call_end(&mut self, frame: &Frame, _error: Option<&EvmError>) {
+        self.exit(frame);
+    }
+
+    fn create(&mut self, frame: &Frame) {
+        self.enter(frame);
+    }
+
+    fn create_end(&mut self, frame: &Frame, _error: Option<&EvmError>) {
+        self.exit(frame);
+    }
+
+    fn error(&mut self, _frame: &Frame, _error: &EvmError) {
+        // The frame loses all of its gas, which the failing opcode is
+        // charged with.
+        let Some(step) = self.frames.last_mut().and_then(|profile| profile.step.take()) else {
+            return;
+        };
+        let gas = step.gas_left - step.children_gas;
+        self.record(op_name(step.op), Some(step.pc), gas, Uint::from(0u8));
+    }
+}
@@ -0,0 +1,153 @@
+//! Ethereum Virtual Machine (EVM) Inspector
+//! ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+//!
+//! .. contents:: Table of Contents
+//!     :backlinks: none
+//!     :local:
+//!
+//! Introduction
+//! ------------
+//!
+//! The `Inspector` trait, whose hooks the interpreter calls as it executes.
+//!
+//! The inspector is carried by the `Environment`, and is shared by every
+//! frame of a message call. Every hook has an empty default, and `()` is the
+//! inspector of an `Environment` that does not set one, so that an absent
+//! inspector is compiled away entirely.
+
+use super::{exceptions::EvmError, Evm, Message};
+use crate::ethereum::base_types::{Uint, U256};
+use crate::ethereum::frontier::{
+    fork_types::{Address, Log},
+    state::StateBackend,
+};
+
+/// A read-only view of the frame being executed.
+pub struct Frame<'e> {
+    pub pc: usize,
+    pub code: &'e [u8],
+    pub stack: &'e [U256],
+    pub memory: &'e [u8],
+    pub gas_left: &'e Uint,
+    pub refund_counter: &'e U256,
+    pub message: &'e Message,
+    pub output: &'e [u8],
+    pub state: &'e dyn StateBackend,
+}
+
+/// Hooks into the execution of the EVM.
+///
+/// Frames are entered through `call` or `create`, and always exited through
+/// the matching `call_end` or `create_end`, including when they halt
+/// exceptionally, and when execution fails with `EvmError::State`.
+#[allow(unused_variables)]
+pub trait Inspector {
+    /// The opcode `op` at `frame.pc` is about to be executed. `op` need not
+    /// be a valid opcode.
+    fn step(&mut self, frame: &Frame, op: u8) {}
+
+    /// The opcode last passed to `step` has been executed.
+    fn step_end(&mut self, frame: &Frame) {}
+
+    /// `amount` gas is about to be charged.
+    fn charge_gas(&mut self, frame: &Frame, amount: &Uint) {}
+
+    /// A message call frame has been entered.
+    fn call(&mut self, frame: &Frame) {}
+
+    /// A message call frame has been exited.
+    fn call_end(&mut self, frame: &Frame, error: Option<&EvmError>) {}
+
+    /// A contract creation frame has been entered.
+    fn create(&mut self, frame: &Frame) {}
+
+    /// A contract creation frame has been exited, after its code has been
+    /// deposited.
+    fn create_end(&mut self, frame: &Frame, error: Option<&EvmError>) {}
+
+    /// `log` is about to be appended to the logs of the frame.
+    fn log(&mut self, frame: &Frame, log: &Log) {}
+
+    /// The frame self-destructed, sending `value` to `beneficiary`.
+    fn selfdestruct(&mut self, frame: &Frame, beneficiary: &Address, value: &U256) {}
+
+    /// The frame halted exceptionally with `error`.
+    fn error(&mut self, frame: &Frame, error: &EvmError) {}
+
+    /// The message call passed to `process_message_call` is over, having
+    /// used `gas_used`. Unlike the frame hooks, this is also called when no
+    /// frame was entered, as on an address collision, and when execution
+    /// failed with `EvmError::State`.
+    fn message_call_end(&mut self, gas_used: &Uint, output: &[u8], error: Option<&EvmError>) {}
+}
+
+impl Inspector for () {}
+
+impl<T: Inspector + ?Sized> Inspector for &mut T {
+    fn step(&mut self, frame: &Frame, op: u8) {
+        (**self).step(frame, op)
+    }
+
+    fn step_end(&mut self, frame: &Frame) {
+        (**self).step_end(frame)
+    }
+
+    fn charge_gas(&mut self, frame: &Frame, amount: &Uint) {
+        (**self).charge_gas(frame, amount)
+    }
+
+    fn call(&mut self, frame: &Frame) {
+        (**self).call(frame)
+    }
+
+    fn call_end(&mut self, frame: &Frame, error: Option<&EvmError>) {
+        (**self).call_end(frame, error)
+    }
+
+    fn create(&mut self, frame: &Frame) {
+        (**self).create(frame)
+    }
+
+    fn create_end(&mut self, frame: &Frame, error: Option<&EvmError>) {
+        (**self).create_end(frame, error)
+    }
+
+    fn log(&mut self, frame: &Frame, log: &Log) {
+        (**self).log(frame, log)
+    }
+
+    fn selfdestruct(&mut self, frame: &Frame, beneficiary: &Address, value: &U256) {
+        (**self).selfdestruct(frame, beneficiary, value)
+    }
+
+    fn error(&mut self, frame: &Frame, error: &EvmError) {
+        (**self).error(frame, error)
+    }
+
+    fn message_call_end(&mut self, gas_used: &Uint, output: &[u8], error: Option<&EvmError>) {
+        (**self).message_call_end(gas_used, output, error)
+    }
+}
+
+/// Call `hook` with the inspector of the `Environment` and a view of `evm`.
+///
+/// Parameters
+/// ----------
+/// evm :
+///     The current EVM frame.
+/// hook :
+///     The call of the inspector hook.
+pub fn inspect<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>, hook: impl FnOnce(&mut I, &Frame)) {
+    let frame = Frame {
+        pc: evm.pc,
+        code: &evm.code,
+        stack: &evm.stack,
+        memory: &evm.memory,
+        gas_left: &evm.gas_left,
+        refund_counter: &evm.refund_counter,
+        message: &evm.message,
+        output: &evm.output,
+        state: &evm.env.state,
+    };
+    hook(&mut evm.env.inspector, &frame);
+}
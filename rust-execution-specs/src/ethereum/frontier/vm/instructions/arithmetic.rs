@@ -2,10 +2,9 @@ use super::super::{
     exceptions::{Result},
     gas, stack, Evm,
 };
-use crate::ethereum::base_types::U256;
-use crate::ethereum::utils::numeric::get_sign;
-use num_bigint::BigInt;
-use num_traits::Signed;
+use crate::ethereum::frontier::vm::inspector::Inspector;
+use crate::ethereum::frontier::state::StateBackend;
+use crate::ethereum::base_types::{Uint, U256};
 
 /// Adds the top two elements of the stack together, and pushes the result back
 /// on the stack.
@@ -14,7 +13,7 @@ use num_traits::Signed;
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn add(evm: &mut Evm) -> Result<()> {
+pub fn add<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let x = stack::pop(&mut evm.stack)?;
     let y = stack::pop(&mut evm.stack)?;
@@ -23,8 +22,7 @@ pub fn add(evm: &mut Evm) -> Result<()> {
     gas::charge_gas(evm, gas::GAS_VERY_LOW())?;
 
     // OPERATION
-    // fixme: wrapping_add
-    let result = x + y;
+    let result = x.wrapping_add(y);
 
     stack::push(&mut evm.stack, result)?;
 
@@ -40,7 +38,7 @@ pub fn add(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn sub(evm: &mut Evm) -> Result<()> {
+pub fn sub<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let x = stack::pop(&mut evm.stack)?;
     let y = stack::pop(&mut evm.stack)?;
@@ -49,8 +47,7 @@ pub fn sub(evm: &mut Evm) -> Result<()> {
     gas::charge_gas(evm, gas::GAS_VERY_LOW())?;
 
     // OPERATION
-    // fixme: wrapping_sub
-    let result = x - y;
+    let result = x.wrapping_sub(y);
 
     stack::push(&mut evm.stack, result)?;
 
@@ -66,7 +63,7 @@ pub fn sub(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn mul(evm: &mut Evm) -> Result<()> {
+pub fn mul<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let x = stack::pop(&mut evm.stack)?;
     let y = stack::pop(&mut evm.stack)?;
@@ -75,8 +72,7 @@ pub fn mul(evm: &mut Evm) -> Result<()> {
     gas::charge_gas(evm, gas::GAS_LOW())?;
 
     // OPERATION
-    // fixme: wrapping_mul
-    let result = x * y;
+    let result = x.wrapping_mul(y);
 
     stack::push(&mut evm.stack, result)?;
 
@@ -92,7 +88,7 @@ pub fn mul(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn div(evm: &mut Evm) -> Result<()> {
+pub fn div<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let dividend = stack::pop(&mut evm.stack)?;
     let divisor = stack::pop(&mut evm.stack)?;
@@ -121,26 +117,18 @@ pub fn div(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn sdiv(evm: &mut Evm) -> Result<()> {
+pub fn sdiv<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
-    // fixme: will not do sign conversion
-    let dividend = BigInt::from(stack::pop(&mut evm.stack)?);
-    let divisor = BigInt::from(stack::pop(&mut evm.stack)?);
+    let dividend = stack::pop(&mut evm.stack)?;
+    let divisor = stack::pop(&mut evm.stack)?;
 
     // GAS
     gas::charge_gas(evm, gas::GAS_LOW())?;
 
     // OPERATION
-    let quotient = if divisor == BigInt::from(0u8) {
-        BigInt::from(0u8)
-    } else if dividend == -BigInt::from(2u8).pow(255) && divisor == BigInt::from(-1) {
-        -BigInt::from(2u8).pow(255)
-    } else {
-        let sign = get_sign(&dividend * &divisor);
-        sign * (dividend.abs() / divisor.abs())
-    };
+    let quotient = dividend.signed_div(divisor);
 
-    stack::push(&mut evm.stack, U256::new(quotient.to_u32_digits().1))?;
+    stack::push(&mut evm.stack, quotient)?;
 
     // PROGRAM COUNTER
     evm.pc += 1;
@@ -154,7 +142,7 @@ pub fn sdiv(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn r#mod(evm: &mut Evm) -> Result<()> {
+pub fn r#mod<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let x = stack::pop(&mut evm.stack)?;
     let y = stack::pop(&mut evm.stack)?;
@@ -183,23 +171,18 @@ pub fn r#mod(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn smod(evm: &mut Evm) -> Result<()> {
+pub fn smod<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
-    // fixme: does not do sign conversion
-    let x = BigInt::from(stack::pop(&mut evm.stack)?);
-    let y = BigInt::from(stack::pop(&mut evm.stack)?);
+    let x = stack::pop(&mut evm.stack)?;
+    let y = stack::pop(&mut evm.stack)?;
 
     // GAS
     gas::charge_gas(evm, gas::GAS_LOW())?;
 
     // OPERATION
-    let remainder = if y == BigInt::from(0u8) {
-        BigInt::from(0u8)
-    } else {
-        get_sign(x.clone()) * (x.abs() % y.abs())
-    };
+    let remainder = x.signed_rem(y);
 
-    stack::push(&mut evm.stack, U256::new(remainder.to_u32_digits().1))?;
+    stack::push(&mut evm.stack, remainder)?;
 
     // PROGRAM COUNTER
     evm.pc += 1;
@@ -213,7 +196,7 @@ pub fn smod(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn addmod(evm: &mut Evm) -> Result<()> {
+pub fn addmod<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let x = stack::pop(&mut evm.stack)?;
     let y = stack::pop(&mut evm.stack)?;
@@ -223,11 +206,7 @@ pub fn addmod(evm: &mut Evm) -> Result<()> {
     gas::charge_gas(evm, gas::GAS_MID())?;
 
     // OPERATION
-    let result = if z == U256::from(0u8) {
-        U256::from(0u8)
-    } else {
-        (x + y) % z
-    };
+    let result = x.add_mod(y, z);
 
     stack::push(&mut evm.stack, result)?;
 
@@ -243,7 +222,7 @@ pub fn addmod(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn mulmod(evm: &mut Evm) -> Result<()> {
+pub fn mulmod<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let x = stack::pop(&mut evm.stack)?;
     let y = stack::pop(&mut evm.stack)?;
@@ -253,11 +232,7 @@ pub fn mulmod(evm: &mut Evm) -> Result<()> {
     gas::charge_gas(evm, gas::GAS_MID())?;
 
     // OPERATION
-    let result = if z == U256::from(0u8) {
-        U256::from(0u8)
-    } else {
-        (x * y) % z
-    };
+    let result = x.mul_mod(y, z);
 
     stack::push(&mut evm.stack, result)?;
 
@@ -273,7 +248,7 @@ pub fn mulmod(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn exp(evm: &mut Evm) -> Result<()> {
+pub fn exp<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let base = stack::pop(&mut evm.stack)?;
     let exponent = stack::pop(&mut evm.stack)?;
@@ -285,12 +260,11 @@ pub fn exp(evm: &mut Evm) -> Result<()> {
     let exponent_bytes = (exponent_bits + 7) / 8;
     gas::charge_gas(
         evm,
-        gas::GAS_EXPONENTIATION() + gas::GAS_EXPONENTIATION_PER_BYTE() * U256::from(exponent_bytes),
+        gas::GAS_EXPONENTIATION() + gas::GAS_EXPONENTIATION_PER_BYTE() * Uint::from(exponent_bytes),
     )?;
 
     // OPERATION
-    // fixme: ceil
-    let result = base.pow(u32::try_from(exponent).unwrap());
+    let result = base.wrapping_pow(exponent);
 
     stack::push(&mut evm.stack, result)?;
 
@@ -306,7 +280,7 @@ pub fn exp(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn signextend(evm: &mut Evm) -> Result<()> {
+pub fn signextend<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let byte_num = stack::pop(&mut evm.stack)?;
     let value = stack::pop(&mut evm.stack)?;
@@ -319,20 +293,19 @@ pub fn signextend(evm: &mut Evm) -> Result<()> {
         // Can't extend any further
         value
     } else {
-        // U256(0).to_be_bytes() gives b'' instead b'\x00'. (fixme: how does to_bytes_be handle 0)
-        let value_bytes = value.to_bytes_be();
+        let value_bytes = value.to_be_bytes32();
         // Now among the obtained value bytes, consider only
         // N `least significant bytes`, where N is `byte_num + 1`.
-        let value_bytes = &value_bytes[(31 - usize::try_from(byte_num.clone()).unwrap())..];
+        let value_bytes = &value_bytes[(31 - usize::try_from(byte_num).unwrap())..];
         let sign_bit = value_bytes[0] >> 7;
 
         if sign_bit == 0 {
-            U256::from_bytes_be(value_bytes)
+            U256::from_be_bytes(value_bytes)
         } else {
             let num_bytes_prepend = U256::from(32u8) - (byte_num + U256::from(1u8));
             let mut bytes = [0xff].repeat(usize::try_from(num_bytes_prepend).unwrap());
             bytes.extend(value_bytes);
-            U256::from_bytes_be(&bytes)
+            U256::from_be_bytes(&bytes)
         }
     };
 
@@ -5,6 +5,8 @@
 //! Implementations of the EVM bitwise instructions.
 
 use super::super::{exceptions::Result, gas, stack, Evm};
+use crate::ethereum::frontier::vm::inspector::Inspector;
+use crate::ethereum::frontier::state::StateBackend;
 use crate::ethereum::base_types::U256;
 
 /// Bitwise AND operation of the top 2 elements of the stack. Pushes the
@@ -14,7 +16,7 @@ use crate::ethereum::base_types::U256;
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn bitwise_and(evm: &mut Evm) -> Result<()> {
+pub fn bitwise_and<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let x = stack::pop(&mut evm.stack)?;
     let y = stack::pop(&mut evm.stack)?;
@@ -37,7 +39,7 @@ pub fn bitwise_and(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn bitwise_or(evm: &mut Evm) -> Result<()> {
+pub fn bitwise_or<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let x = stack::pop(&mut evm.stack)?;
     let y = stack::pop(&mut evm.stack)?;
@@ -60,7 +62,7 @@ pub fn bitwise_or(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn bitwise_xor(evm: &mut Evm) -> Result<()> {
+pub fn bitwise_xor<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let x = stack::pop(&mut evm.stack)?;
     let y = stack::pop(&mut evm.stack)?;
@@ -83,7 +85,7 @@ pub fn bitwise_xor(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn bitwise_not(evm: &mut Evm) -> Result<()> {
+pub fn bitwise_not<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let x = stack::pop(&mut evm.stack)?;
 
@@ -91,8 +93,7 @@ pub fn bitwise_not(evm: &mut Evm) -> Result<()> {
     gas::charge_gas(evm, gas::GAS_VERY_LOW())?;
 
     // OPERATION
-    let all_set = U256::from_bytes_be(&[0xff].repeat(x.bits() as usize * 8 + 1));
-    let inverse = x ^ all_set;
+    let inverse = !x;
     stack::push(&mut evm.stack, inverse)?;
 
     // PROGRAM COUNTER
@@ -108,10 +109,10 @@ pub fn bitwise_not(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn get_byte(evm: &mut Evm) -> Result<()> {
+pub fn get_byte<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let byte_index = stack::pop(&mut evm.stack)?;
-    let mut word = stack::pop(&mut evm.stack)?;
+    let word = stack::pop(&mut evm.stack)?;
 
     // GAS
     gas::charge_gas(evm, gas::GAS_VERY_LOW())?;
@@ -121,9 +122,8 @@ pub fn get_byte(evm: &mut Evm) -> Result<()> {
         U256::from(0u8)
     } else {
         let extra_bytes_to_right = U256::from(31u8) - byte_index;
-        word >>= usize::try_from(extra_bytes_to_right * U256::from(8u8)).unwrap();
-        word &= U256::from(255u8);
-        word
+        let word = word >> usize::try_from(extra_bytes_to_right * U256::from(8u8)).unwrap();
+        word & U256::from(255u8)
     };
     stack::push(&mut evm.stack, result)?;
 
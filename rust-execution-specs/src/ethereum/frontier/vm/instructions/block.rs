@@ -11,7 +11,9 @@
 //! Implementations of the EVM block instructions.
 
 use super::super::{exceptions::Result, gas, stack, Evm};
-use crate::ethereum::base_types::U256;
+use crate::ethereum::frontier::vm::inspector::Inspector;
+use crate::ethereum::frontier::state::StateBackend;
+use crate::ethereum::base_types::{Uint, U256};
 
 /// Push the hash of one of the 256 most recent complete blocks onto the
 /// stack. The block number to hash is present at the top of the stack.
@@ -20,23 +22,24 @@ use crate::ethereum::base_types::U256;
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn block_hash(evm: &mut Evm) -> Result<()> {
+pub fn block_hash<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
-    let block_number = stack::pop(&mut evm.stack)?;
+    let block_number = Uint::from(stack::pop(&mut evm.stack)?);
 
     // GAS
     gas::charge_gas(evm, gas::GAS_BLOCK_HASH())?;
 
     // OPERATION
     let hash =
-        if evm.env.number <= block_number || evm.env.number > &block_number + U256::from(256u16) {
+        if evm.env.number <= block_number || evm.env.number > &block_number + Uint::from(256u16) {
             U256::from(0u8)
         } else {
-            // fixme: not sure about this
             // hash = evm.env.block_hashes[-(evm.env.number - block_number)]
-            let hash = evm.env.block_hashes
-                [!usize::try_from(evm.env.number.clone() - block_number).unwrap()];
-            U256::from_bytes_be(&hash)
+            let depth = usize::try_from(evm.env.number.clone() - block_number).unwrap();
+            match evm.env.block_hashes.len().checked_sub(depth) {
+                Some(index) => U256::from_be_bytes(&evm.env.block_hashes[index]),
+                None => U256::from(0u8),
+            }
         };
     stack::push(&mut evm.stack, hash)?;
 
@@ -55,7 +58,7 @@ pub fn block_hash(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn coinbase(evm: &mut Evm) -> Result<()> {
+pub fn coinbase<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let _ = {};
 
@@ -63,7 +66,7 @@ pub fn coinbase(evm: &mut Evm) -> Result<()> {
     gas::charge_gas(evm, gas::GAS_BASE())?;
 
     // OPERATION
-    stack::push(&mut evm.stack, U256::from_bytes_be(&evm.env.coinbase))?;
+    stack::push(&mut evm.stack, U256::from_be_bytes(&evm.env.coinbase))?;
 
     // PROGRAM COUNTER
     evm.pc += 1;
@@ -80,7 +83,7 @@ pub fn coinbase(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn timestamp(evm: &mut Evm) -> Result<()> {
+pub fn timestamp<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let _ = {};
 
@@ -88,7 +91,7 @@ pub fn timestamp(evm: &mut Evm) -> Result<()> {
     gas::charge_gas(evm, gas::GAS_BASE())?;
 
     // OPERATION
-    stack::push(&mut evm.stack, evm.env.time.clone())?;
+    stack::push(&mut evm.stack, evm.env.time)?;
 
     // PROGRAM COUNTER
     evm.pc += 1;
@@ -104,7 +107,7 @@ pub fn timestamp(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn number(evm: &mut Evm) -> Result<()> {
+pub fn number<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let _ = {};
 
@@ -112,7 +115,7 @@ pub fn number(evm: &mut Evm) -> Result<()> {
     gas::charge_gas(evm, gas::GAS_BASE())?;
 
     // OPERATION
-    stack::push(&mut evm.stack, evm.env.number.clone())?;
+    stack::push(&mut evm.stack, U256::try_from(&evm.env.number)?)?;
 
     // PROGRAM COUNTER
     evm.pc += 1;
@@ -128,7 +131,7 @@ pub fn number(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn difficulty(evm: &mut Evm) -> Result<()> {
+pub fn difficulty<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let _ = {};
 
@@ -136,7 +139,7 @@ pub fn difficulty(evm: &mut Evm) -> Result<()> {
     gas::charge_gas(evm, gas::GAS_BASE())?;
 
     // OPERATION
-    stack::push(&mut evm.stack, evm.env.difficulty.clone())?;
+    stack::push(&mut evm.stack, U256::try_from(&evm.env.difficulty)?)?;
 
     // PROGRAM COUNTER
     evm.pc += 1;
@@ -152,7 +155,7 @@ pub fn difficulty(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn gas_limit(evm: &mut Evm) -> Result<()> {
+pub fn gas_limit<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let _ = {};
 
@@ -160,7 +163,7 @@ pub fn gas_limit(evm: &mut Evm) -> Result<()> {
     gas::charge_gas(evm, gas::GAS_BASE())?;
 
     // OPERATION
-    stack::push(&mut evm.stack, evm.env.gas_limit.clone())?;
+    stack::push(&mut evm.stack, U256::try_from(&evm.env.gas_limit)?)?;
 
     // PROGRAM COUNTER
     evm.pc += 1;
@@ -11,8 +11,9 @@
 //! Implementations of the EVM Comparison instructions.
 
 use super::super::{exceptions::Result, gas, stack, Evm};
+use crate::ethereum::frontier::vm::inspector::Inspector;
+use crate::ethereum::frontier::state::StateBackend;
 use crate::ethereum::base_types::U256;
-use num_bigint::BigInt;
 
 /// Checks if the top element is less than the next top element. Pushes the
 /// result back on the stack.
@@ -21,7 +22,7 @@ use num_bigint::BigInt;
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn less_than(evm: &mut Evm) -> Result<()> {
+pub fn less_than<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let left = stack::pop(&mut evm.stack)?;
     let right = stack::pop(&mut evm.stack)?;
@@ -44,17 +45,16 @@ pub fn less_than(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn signed_less_than(evm: &mut Evm) -> Result<()> {
+pub fn signed_less_than<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
-    // fixme: sign conversion is incorrect
-    let left = U256::from(stack::pop(&mut evm.stack)?);
-    let right = U256::from(stack::pop(&mut evm.stack)?);
+    let left = stack::pop(&mut evm.stack)?;
+    let right = stack::pop(&mut evm.stack)?;
 
     // GAS
     gas::charge_gas(evm, gas::GAS_VERY_LOW())?;
 
     // OPERATION
-    let result = U256::from((left < right) as u8);
+    let result = U256::from(left.signed_cmp(&right).is_lt() as u8);
     stack::push(&mut evm.stack, result)?;
 
     // PROGRAM COUNTER
@@ -69,7 +69,7 @@ pub fn signed_less_than(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn greater_than(evm: &mut Evm) -> Result<()> {
+pub fn greater_than<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let left = stack::pop(&mut evm.stack)?;
     let right = stack::pop(&mut evm.stack)?;
@@ -92,17 +92,16 @@ pub fn greater_than(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn signed_greater_than(evm: &mut Evm) -> Result<()> {
+pub fn signed_greater_than<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
-    // fixme: sign conversion is incorrect
-    let left = BigInt::from(stack::pop(&mut evm.stack)?);
-    let right = BigInt::from(stack::pop(&mut evm.stack)?);
+    let left = stack::pop(&mut evm.stack)?;
+    let right = stack::pop(&mut evm.stack)?;
 
     // GAS
     gas::charge_gas(evm, gas::GAS_VERY_LOW())?;
 
     // OPERATION
-    let result = U256::from((left > right) as u8);
+    let result = U256::from(left.signed_cmp(&right).is_gt() as u8);
     stack::push(&mut evm.stack, result)?;
 
     // PROGRAM COUNTER
@@ -117,7 +116,7 @@ pub fn signed_greater_than(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn equal(evm: &mut Evm) -> Result<()> {
+pub fn equal<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let left = stack::pop(&mut evm.stack)?;
     let right = stack::pop(&mut evm.stack)?;
@@ -141,7 +140,7 @@ pub fn equal(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn is_zero(evm: &mut Evm) -> Result<()> {
+pub fn is_zero<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let x = stack::pop(&mut evm.stack)?;
 
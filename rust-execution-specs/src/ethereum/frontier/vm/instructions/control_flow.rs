@@ -11,6 +11,8 @@
 //! Implementations of the EVM control flow instructions.
 
 use super::super::{exceptions::Result, gas, stack, Evm};
+use crate::ethereum::frontier::vm::inspector::Inspector;
+use crate::ethereum::frontier::state::StateBackend;
 use crate::ethereum::base_types::U256;
 use crate::ethereum::frontier::vm::exceptions::EvmError;
 
@@ -20,7 +22,7 @@ use crate::ethereum::frontier::vm::exceptions::EvmError;
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn stop(evm: &mut Evm) -> Result<()> {
+pub fn stop<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let _ = {};
 
@@ -42,7 +44,7 @@ pub fn stop(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn jump(evm: &mut Evm) -> Result<()> {
+pub fn jump<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let jump_dest = stack::pop(&mut evm.stack)?;
 
@@ -67,7 +69,7 @@ pub fn jump(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn jumpi(evm: &mut Evm) -> Result<()> {
+pub fn jumpi<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let jump_dest = stack::pop(&mut evm.stack)?;
     let conditional_value = stack::pop(&mut evm.stack)?;
@@ -77,7 +79,7 @@ pub fn jumpi(evm: &mut Evm) -> Result<()> {
 
     // OPERATION
     let destination = if conditional_value == U256::from(0u8) {
-        evm.pc + U256::from(1u8)
+        U256::from(evm.pc + 1)
     } else if !evm.valid_jump_destinations.contains(&jump_dest) {
         return Err(EvmError::InvalidJumpDest);
     } else {
@@ -96,7 +98,7 @@ pub fn jumpi(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn pc(evm: &mut Evm) -> Result<()> {
+pub fn pc<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let _ = {};
 
@@ -118,7 +120,7 @@ pub fn pc(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn gas_left(evm: &mut Evm) -> Result<()> {
+pub fn gas_left<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let _ = {};
 
@@ -126,7 +128,7 @@ pub fn gas_left(evm: &mut Evm) -> Result<()> {
     gas::charge_gas(evm, gas::GAS_BASE())?;
 
     // OPERATION
-    stack::push(&mut evm.stack, evm.gas_left.clone())?;
+    stack::push(&mut evm.stack, U256::try_from(&evm.gas_left)?)?;
 
     // PROGRAM COUNTER
     evm.pc += 1;
@@ -141,7 +143,7 @@ pub fn gas_left(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn jumpdest(evm: &mut Evm) -> Result<()> {
+pub fn jumpdest<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let _ = {};
 
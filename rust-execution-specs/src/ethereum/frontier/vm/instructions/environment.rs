@@ -11,8 +11,10 @@
 //! Implementations of the EVM environment related instructions.
 
 use super::super::{exceptions::Result, gas, stack, Evm};
-use crate::ethereum::base_types::U256;
-use crate::ethereum::frontier::state::get_account;
+use crate::ethereum::frontier::vm::inspector::Inspector;
+use crate::ethereum::base_types::{Uint, U256};
+use crate::ethereum::frontier::state::{get_account, StateBackend};
+use crate::ethereum::frontier::utils::address::to_address;
 use crate::ethereum::frontier::vm::memory::{buffer_read, memory_write};
 use crate::ethereum::utils::numeric::ceil32;
 
@@ -22,7 +24,7 @@ use crate::ethereum::utils::numeric::ceil32;
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn address(evm: &mut Evm) -> Result<()> {
+pub fn address<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let _ = {};
 
@@ -32,7 +34,7 @@ pub fn address(evm: &mut Evm) -> Result<()> {
     // OPERATION
     stack::push(
         &mut evm.stack,
-        U256::from_bytes_be(&evm.message.current_target),
+        U256::from_be_bytes(&evm.message.current_target),
     )?;
 
     // PROGRAM COUNTER
@@ -46,18 +48,15 @@ pub fn address(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn balance(evm: &mut Evm) -> Result<()> {
+pub fn balance<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
-    let address = stack::pop(&mut evm.stack)?
-        .to_bytes_be()
-        .try_into()
-        .unwrap();
+    let address = to_address(&stack::pop(&mut evm.stack)?);
 
     // GAS
     gas::charge_gas(evm, gas::GAS_BALANCE())?;
 
     // OPERATION
-    let balance = get_account(&evm.env.state, &address).balance;
+    let balance = get_account(&evm.env.state, &address)?.balance;
     stack::push(&mut evm.stack, balance)?;
 
     // PROGRAM COUNTER
@@ -72,7 +71,7 @@ pub fn balance(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn origin(evm: &mut Evm) -> Result<()> {
+pub fn origin<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let _ = {};
 
@@ -80,7 +79,7 @@ pub fn origin(evm: &mut Evm) -> Result<()> {
     gas::charge_gas(evm, gas::GAS_BASE())?;
 
     // OPERATION
-    stack::push(&mut evm.stack, U256::from_bytes_be(&evm.env.origin))?;
+    stack::push(&mut evm.stack, U256::from_be_bytes(&evm.env.origin))?;
 
     // PROGRAM COUNTER
     evm.pc += 1;
@@ -93,7 +92,7 @@ pub fn origin(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn caller(evm: &mut Evm) -> Result<()> {
+pub fn caller<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let _ = {};
 
@@ -101,7 +100,7 @@ pub fn caller(evm: &mut Evm) -> Result<()> {
     gas::charge_gas(evm, gas::GAS_BASE())?;
 
     // OPERATION
-    stack::push(&mut evm.stack, U256::from_bytes_be(&evm.message.caller))?;
+    stack::push(&mut evm.stack, U256::from_be_bytes(&evm.message.caller))?;
 
     // PROGRAM COUNTER
     evm.pc += 1;
@@ -114,7 +113,7 @@ pub fn caller(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn callvalue(evm: &mut Evm) -> Result<()> {
+pub fn callvalue<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let _ = {};
 
@@ -122,7 +121,7 @@ pub fn callvalue(evm: &mut Evm) -> Result<()> {
     gas::charge_gas(evm, gas::GAS_BASE())?;
 
     // OPERATION
-    stack::push(&mut evm.stack, evm.message.value.clone())?;
+    stack::push(&mut evm.stack, evm.message.value)?;
 
     // PROGRAM COUNTER
     evm.pc += 1;
@@ -136,7 +135,7 @@ pub fn callvalue(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn calldataload(evm: &mut Evm) -> Result<()> {
+pub fn calldataload<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let start_index = stack::pop(&mut evm.stack)?;
 
@@ -145,7 +144,7 @@ pub fn calldataload(evm: &mut Evm) -> Result<()> {
 
     // OPERATION
     let value = buffer_read(evm.message.data.clone(), start_index, U256::from(32u8));
-    stack::push(&mut evm.stack, U256::from_bytes_be(&value))?;
+    stack::push(&mut evm.stack, U256::from_be_bytes(&value))?;
 
     // PROGRAM COUNTER
     evm.pc += 1;
@@ -158,7 +157,7 @@ pub fn calldataload(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn calldatasize(evm: &mut Evm) -> Result<()> {
+pub fn calldatasize<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let _ = {};
 
@@ -182,18 +181,18 @@ pub fn calldatasize(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn calldatacopy(evm: &mut Evm) -> Result<()> {
+pub fn calldatacopy<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let memory_start_index = stack::pop(&mut evm.stack)?;
     let data_start_index = stack::pop(&mut evm.stack)?;
     let size = stack::pop(&mut evm.stack)?;
 
     // GAS
-    let words = ceil32(size.clone()) / U256::from(32u8);
+    let words = ceil32(Uint::from(size)) / Uint::from(32u8);
     let copy_gas_cost = gas::GAS_COPY() * words;
     let extend_memory = gas::calculate_gas_extend_memory(
         &evm.memory,
-        [(memory_start_index.clone(), size.clone())].to_vec(),
+        [(memory_start_index, size)].to_vec(),
     );
     gas::charge_gas(
         evm,
@@ -201,8 +200,7 @@ pub fn calldatacopy(evm: &mut Evm) -> Result<()> {
     )?;
 
     // OPERATION
-    evm.memory
-        .extend([0].repeat(usize::try_from(extend_memory.expand_by).unwrap()));
+    evm.memory.expand(&extend_memory.expand_by);
     let value = buffer_read(evm.message.data.clone(), data_start_index, size);
     memory_write(&mut evm.memory, memory_start_index, value);
 
@@ -217,7 +215,7 @@ pub fn calldatacopy(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn codesize(evm: &mut Evm) -> Result<()> {
+pub fn codesize<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let _ = {};
 
@@ -241,18 +239,18 @@ pub fn codesize(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn codecopy(evm: &mut Evm) -> Result<()> {
+pub fn codecopy<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let memory_start_index = stack::pop(&mut evm.stack)?;
     let code_start_index = stack::pop(&mut evm.stack)?;
     let size = stack::pop(&mut evm.stack)?;
 
     // GAS
-    let words = ceil32(size.clone()) / U256::from(32u8);
+    let words = ceil32(Uint::from(size)) / Uint::from(32u8);
     let copy_gas_cost = gas::GAS_COPY() * words;
     let extend_memory = gas::calculate_gas_extend_memory(
         &evm.memory,
-        [(memory_start_index.clone(), size.clone())].to_vec(),
+        [(memory_start_index, size)].to_vec(),
     );
     gas::charge_gas(
         evm,
@@ -260,8 +258,7 @@ pub fn codecopy(evm: &mut Evm) -> Result<()> {
     )?;
 
     // OPERATION
-    evm.memory
-        .extend([0].repeat(usize::try_from(extend_memory.expand_by).unwrap()));
+    evm.memory.expand(&extend_memory.expand_by);
     let value = buffer_read(evm.code.clone(), code_start_index, size);
     memory_write(&mut evm.memory, memory_start_index, value);
 
@@ -276,7 +273,7 @@ pub fn codecopy(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn gasprice(evm: &mut Evm) -> Result<()> {
+pub fn gasprice<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let _ = {};
 
@@ -284,7 +281,7 @@ pub fn gasprice(evm: &mut Evm) -> Result<()> {
     gas::charge_gas(evm, gas::GAS_BASE())?;
 
     // OPERATION
-    stack::push(&mut evm.stack, evm.env.gas_price.clone())?;
+    stack::push(&mut evm.stack, evm.env.gas_price)?;
 
     // PROGRAM COUNTER
     evm.pc += 1;
@@ -297,18 +294,15 @@ pub fn gasprice(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn extcodesize(evm: &mut Evm) -> Result<()> {
+pub fn extcodesize<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
-    let address = stack::pop(&mut evm.stack)?
-        .to_bytes_be()
-        .try_into()
-        .unwrap();
+    let address = to_address(&stack::pop(&mut evm.stack)?);
 
     // GAS
     gas::charge_gas(evm, gas::GAS_EXTERNAL())?;
 
     // OPERATION
-    let codesize = U256::from_bytes_be(&get_account(&evm.env.state, &address).code);
+    let codesize = U256::from(get_account(&evm.env.state, &address)?.code.len());
     stack::push(&mut evm.stack, codesize)?;
 
     // PROGRAM COUNTER
@@ -322,22 +316,19 @@ pub fn extcodesize(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn extcodecopy(evm: &mut Evm) -> Result<()> {
+pub fn extcodecopy<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
-    let address = stack::pop(&mut evm.stack)?
-        .to_bytes_be()
-        .try_into()
-        .unwrap();
+    let address = to_address(&stack::pop(&mut evm.stack)?);
     let memory_start_index = stack::pop(&mut evm.stack)?;
     let code_start_index = stack::pop(&mut evm.stack)?;
     let size = stack::pop(&mut evm.stack)?;
 
     // GAS
-    let words = ceil32(size.clone()) / U256::from(32u8);
+    let words = ceil32(Uint::from(size)) / Uint::from(32u8);
     let copy_gas_cost = gas::GAS_COPY() * words;
     let extend_memory = gas::calculate_gas_extend_memory(
         &evm.memory,
-        [(memory_start_index.clone(), size.clone())].to_vec(),
+        [(memory_start_index, size)].to_vec(),
     );
     gas::charge_gas(
         evm,
@@ -345,9 +336,8 @@ pub fn extcodecopy(evm: &mut Evm) -> Result<()> {
     )?;
 
     // OPERATION
-    evm.memory
-        .extend([0].repeat(usize::try_from(extend_memory.expand_by).unwrap()));
-    let code = get_account(&evm.env.state, &address).code;
+    evm.memory.expand(&extend_memory.expand_by);
+    let code = get_account(&evm.env.state, &address)?.code;
     let value = buffer_read(code, code_start_index, size);
     memory_write(&mut evm.memory, memory_start_index, value);
 
@@ -11,7 +11,9 @@
 //! Implementations of the EVM keccak instructions.
 
 use super::super::{exceptions::Result, gas, stack, Evm};
-use crate::ethereum::base_types::U256;
+use crate::ethereum::frontier::vm::inspector::Inspector;
+use crate::ethereum::frontier::state::StateBackend;
+use crate::ethereum::base_types::{Uint, U256};
 use crate::ethereum::frontier::fork_types::keccak256;
 use crate::ethereum::frontier::vm::memory::memory_read_bytes;
 use crate::ethereum::utils::numeric::ceil32;
@@ -25,17 +27,17 @@ use crate::ethereum::utils::numeric::ceil32;
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn keccak(evm: &mut Evm) -> Result<()> {
+pub fn keccak<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let memory_start_index = stack::pop(&mut evm.stack)?;
     let size = stack::pop(&mut evm.stack)?;
 
     // GAS
-    let words = ceil32(size.clone()) / U256::from(32u8);
+    let words = ceil32(Uint::from(size)) / Uint::from(32u8);
     let word_gas_cost = gas::GAS_KECCAK256_WORD() * words;
     let extend_memory = gas::calculate_gas_extend_memory(
         &evm.memory,
-        [(memory_start_index.clone(), size.clone())].to_vec(),
+        [(memory_start_index, size)].to_vec(),
     );
     gas::charge_gas(
         evm,
@@ -43,11 +45,10 @@ pub fn keccak(evm: &mut Evm) -> Result<()> {
     )?;
 
     // OPERATION
-    evm.memory
-        .extend([0].repeat(usize::try_from(extend_memory.expand_by).unwrap()));
+    evm.memory.expand(&extend_memory.expand_by);
     let data = memory_read_bytes(&evm.memory, memory_start_index, size);
     let hash = keccak256(data);
-    stack::push(&mut evm.stack, U256::from_bytes_be(&hash))?;
+    stack::push(&mut evm.stack, U256::from_be_bytes(&hash))?;
 
     // PROGRAM COUNTER
     evm.pc += 1;
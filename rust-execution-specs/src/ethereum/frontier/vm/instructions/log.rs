@@ -10,7 +10,14 @@
 //!
 //! Implementations of the EVM logging instructions.
 
-use super::super::{exceptions::Result, gas, stack, Evm};
+use super::super::{
+    exceptions::Result,
+    gas,
+    inspector::{inspect, Inspector},
+    stack, Evm,
+};
+use crate::ethereum::base_types::Uint;
+use crate::ethereum::frontier::state::StateBackend;
 use crate::ethereum::frontier::fork_types::Log;
 use crate::ethereum::frontier::vm::memory::memory_read_bytes;
 
@@ -25,33 +32,32 @@ use crate::ethereum::frontier::vm::memory::memory_read_bytes;
 ///     The current EVM frame.
 /// num_topics :
 ///     The number of topics to be included in the log entry.
-pub fn log_n(evm: &mut Evm, num_topics: usize) -> Result<()> {
+pub fn log_n<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>, num_topics: usize) -> Result<()> {
     // STACK
     let memory_start_index = stack::pop(&mut evm.stack)?;
     let size = stack::pop(&mut evm.stack)?;
 
     let mut topics = Vec::new();
     for _ in 0..num_topics {
-        let topic = stack::pop(&mut evm.stack)?.to_bytes_be();
-        topics.push(topic.try_into().unwrap());
+        let topic = stack::pop(&mut evm.stack)?;
+        topics.push(topic.to_be_bytes32());
     }
 
     // GAS
     let extend_memory = gas::calculate_gas_extend_memory(
         &evm.memory,
-        [(memory_start_index.clone(), size.clone())].to_vec(),
+        [(memory_start_index, size)].to_vec(),
     );
     gas::charge_gas(
         evm,
         gas::GAS_LOG()
-            + gas::GAS_LOG_DATA() * &size
+            + gas::GAS_LOG_DATA() * Uint::from(size)
             + gas::GAS_LOG_TOPIC() * num_topics
             + extend_memory.cost,
     )?;
 
     // OPERATION
-    evm.memory
-        .extend([0].repeat(usize::try_from(extend_memory.expand_by).unwrap()));
+    evm.memory.expand(&extend_memory.expand_by);
     let log_entry = Log {
         address: evm.message.current_target,
         topics,
@@ -59,6 +65,7 @@ pub fn log_n(evm: &mut Evm, num_topics: usize) -> Result<()> {
             .to_vec()
             .into_boxed_slice(),
     };
+    inspect(evm, |inspector, frame| inspector.log(frame, &log_entry));
     evm.logs.push(log_entry);
 
     // PROGRAM COUNTER
@@ -66,14 +73,22 @@ pub fn log_n(evm: &mut Evm, num_topics: usize) -> Result<()> {
     Ok(())
 }
 
-pub fn log0(evm: &mut Evm) -> Result<()> {
+pub fn log0<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     log_n(evm, 0)
 }
 
-pub fn log1(evm: &mut Evm) -> Result<()> {
+pub fn log1<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     log_n(evm, 1)
 }
 
-pub fn log4(evm: &mut Evm) -> Result<()> {
+pub fn log2<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
+    log_n(evm, 2)
+}
+
+pub fn log3<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
+    log_n(evm, 3)
+}
+
+pub fn log4<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     log_n(evm, 4)
 }
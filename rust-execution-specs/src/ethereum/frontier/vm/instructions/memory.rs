@@ -11,6 +11,8 @@
 //! Implementations of the EVM Memory instructions.
 
 use super::super::{exceptions::Result, gas, stack, Evm};
+use crate::ethereum::frontier::vm::inspector::Inspector;
+use crate::ethereum::frontier::state::StateBackend;
 use crate::ethereum::base_types::U256;
 use crate::ethereum::frontier::vm::memory::{memory_read_bytes, memory_write};
 
@@ -22,18 +24,18 @@ use crate::ethereum::frontier::vm::memory::{memory_read_bytes, memory_write};
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn mstore(evm: &mut Evm) -> Result<()> {
+pub fn mstore<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let start_position = stack::pop(&mut evm.stack)?;
-    let value = stack::pop(&mut evm.stack)?.to_bytes_be();
+    let value = stack::pop(&mut evm.stack)?.to_be_bytes32();
     
     // GAS
-    let extend_memory = gas::calculate_gas_extend_memory(&evm.memory, [(start_position.clone(), U256::from(value.len()))].to_vec());
+    let extend_memory = gas::calculate_gas_extend_memory(&evm.memory, [(start_position, U256::from(value.len()))].to_vec());
     gas::charge_gas(evm, gas::GAS_VERY_LOW() + extend_memory.cost)?;
     
     // OPERATION
-    evm.memory.extend([0].repeat(usize::try_from(extend_memory.expand_by).unwrap()));
-    memory_write(&mut evm.memory, start_position, value.into_boxed_slice());
+    evm.memory.expand(&extend_memory.expand_by);
+    memory_write(&mut evm.memory, start_position, Box::new(value));
     
     // PROGRAM COUNTER
     evm.pc += 1;
@@ -49,18 +51,18 @@ pub fn mstore(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn mstore8(evm: &mut Evm) -> Result<()> {
+pub fn mstore8<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let start_position = stack::pop(&mut evm.stack)?;
     let value = stack::pop(&mut evm.stack)?;
     
     // GAS
-    let extend_memory = gas::calculate_gas_extend_memory(&evm.memory, [(start_position.clone(), U256::from(1u8))].to_vec());
+    let extend_memory = gas::calculate_gas_extend_memory(&evm.memory, [(start_position, U256::from(1u8))].to_vec());
     gas::charge_gas(evm, gas::GAS_VERY_LOW() + extend_memory.cost)?;
     
     // OPERATION
-    evm.memory.extend([0].repeat(usize::try_from(extend_memory.expand_by).unwrap()));
-    let normalized_bytes_value = (value & U256::from(u8::MAX)).to_bytes_be().into_boxed_slice();
+    evm.memory.expand(&extend_memory.expand_by);
+    let normalized_bytes_value = Box::new([u8::try_from(value & U256::from(u8::MAX)).unwrap()]);
     memory_write(&mut evm.memory, start_position, normalized_bytes_value);
     
     // PROGRAM COUNTER
@@ -74,17 +76,17 @@ pub fn mstore8(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn mload(evm: &mut Evm) -> Result<()> {
+pub fn mload<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let start_position = stack::pop(&mut evm.stack)?;
     
     // GAS
-    let extend_memory = gas::calculate_gas_extend_memory(&evm.memory, [(start_position.clone(), U256::from(32u8))].to_vec());
+    let extend_memory = gas::calculate_gas_extend_memory(&evm.memory, [(start_position, U256::from(32u8))].to_vec());
     gas::charge_gas(evm, gas::GAS_VERY_LOW() + extend_memory.cost)?;
     
     // OPERATION
-    evm.memory.extend([0].repeat(usize::try_from(extend_memory.expand_by).unwrap()));
-    let value = U256::from_bytes_be(memory_read_bytes(&evm.memory, start_position, U256::from(32u8)));
+    evm.memory.expand(&extend_memory.expand_by);
+    let value = U256::from_be_bytes(memory_read_bytes(&evm.memory, start_position, U256::from(32u8)));
     stack::push(&mut evm.stack, value)?;
     
     // PROGRAM COUNTER
@@ -99,7 +101,7 @@ pub fn mload(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn msize(evm: &mut Evm) -> Result<()> {
+pub fn msize<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     let _ = {};
     
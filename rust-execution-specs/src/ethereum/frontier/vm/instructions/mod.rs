@@ -10,7 +10,9 @@
 //! Machine readable representations of EVM instructions, and a mapping to their
 //! implementations.
 
-// use super::Evm;
+use super::{exceptions::Result, Evm};
+use crate::ethereum::frontier::vm::inspector::Inspector;
+use crate::ethereum::frontier::state::StateBackend;
 
 pub mod arithmetic;
 pub mod bitwise;
@@ -23,9 +25,11 @@ pub mod log;
 pub mod memory;
 pub mod stack;
 pub mod storage;
+pub mod system;
 
 /// Enum for EVM Opcodes
 #[repr(usize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Ops {
     // Arithmetic Ops
     ADD = 0x01,
@@ -187,4 +191,313 @@ pub enum Ops {
     SELFDESTRUCT = 0xFF,
 }
 
-// pub const OP_IMPLEMENTATIONS: [fn(&mut Evm); 256] = todo!();
+impl Ops {
+    /// The opcode with the byte value `byte`, if there is one.
+    pub fn from_byte(byte: u8) -> Option<Ops> {
+        Some(match byte {
+            0x01 => Ops::ADD,
+            0x02 => Ops::MUL,
+            0x03 => Ops::SUB,
+            0x04 => Ops::DIV,
+            0x05 => Ops::SDIV,
+            0x06 => Ops::MOD,
+            0x07 => Ops::SMOD,
+            0x08 => Ops::ADDMOD,
+            0x09 => Ops::MULMOD,
+            0x0A => Ops::EXP,
+            0x0B => Ops::SIGNEXTEND,
+            0x10 => Ops::LT,
+            0x11 => Ops::GT,
+            0x12 => Ops::SLT,
+            0x13 => Ops::SGT,
+            0x14 => Ops::EQ,
+            0x15 => Ops::ISZERO,
+            0x16 => Ops::AND,
+            0x17 => Ops::OR,
+            0x18 => Ops::XOR,
+            0x19 => Ops::NOT,
+            0x1A => Ops::BYTE,
+            0x20 => Ops::KECCAK,
+            0x30 => Ops::ADDRESS,
+            0x31 => Ops::BALANCE,
+            0x32 => Ops::ORIGIN,
+            0x33 => Ops::CALLER,
+            0x34 => Ops::CALLVALUE,
+            0x35 => Ops::CALLDATALOAD,
+            0x36 => Ops::CALLDATASIZE,
+            0x37 => Ops::CALLDATACOPY,
+            0x38 => Ops::CODESIZE,
+            0x39 => Ops::CODECOPY,
+            0x3A => Ops::GASPRICE,
+            0x3B => Ops::EXTCODESIZE,
+            0x3C => Ops::EXTCODECOPY,
+            0x40 => Ops::BLOCKHASH,
+            0x41 => Ops::COINBASE,
+            0x42 => Ops::TIMESTAMP,
+            0x43 => Ops::NUMBER,
+            0x44 => Ops::DIFFICULTY,
+            0x45 => Ops::GASLIMIT,
+            0x00 => Ops::STOP,
+            0x56 => Ops::JUMP,
+            0x57 => Ops::JUMPI,
+            0x58 => Ops::PC,
+            0x5A => Ops::GAS,
+            0x5B => Ops::JUMPDEST,
+            0x54 => Ops::SLOAD,
+            0x55 => Ops::SSTORE,
+            0x50 => Ops::POP,
+            0x60 => Ops::PUSH1,
+            0x61 => Ops::PUSH2,
+            0x62 => Ops::PUSH3,
+            0x63 => Ops::PUSH4,
+            0x64 => Ops::PUSH5,
+            0x65 => Ops::PUSH6,
+            0x66 => Ops::PUSH7,
+            0x67 => Ops::PUSH8,
+            0x68 => Ops::PUSH9,
+            0x69 => Ops::PUSH10,
+            0x6A => Ops::PUSH11,
+            0x6B => Ops::PUSH12,
+            0x6C => Ops::PUSH13,
+            0x6D => Ops::PUSH14,
+            0x6E => Ops::PUSH15,
+            0x6F => Ops::PUSH16,
+            0x70 => Ops::PUSH17,
+            0x71 => Ops::PUSH18,
+            0x72 => Ops::PUSH19,
+            0x73 => Ops::PUSH20,
+            0x74 => Ops::PUSH21,
+            0x75 => Ops::PUSH22,
+            0x76 => Ops::PUSH23,
+            0x77 => Ops::PUSH24,
+            0x78 => Ops::PUSH25,
+            0x79 => Ops::PUSH26,
+            0x7A => Ops::PUSH27,
+            0x7B => Ops::PUSH28,
+            0x7C => Ops::PUSH29,
+            0x7D => Ops::PUSH30,
+            0x7E => Ops::PUSH31,
+            0x7F => Ops::PUSH32,
+            0x80 => Ops::DUP1,
+            0x81 => Ops::DUP2,
+            0x82 => Ops::DUP3,
+            0x83 => Ops::DUP4,
+            0x84 => Ops::DUP5,
+            0x85 => Ops::DUP6,
+            0x86 => Ops::DUP7,
+            0x87 => Ops::DUP8,
+            0x88 => Ops::DUP9,
+            0x89 => Ops::DUP10,
+            0x8A => Ops::DUP11,
+            0x8B => Ops::DUP12,
+            0x8C => Ops::DUP13,
+            0x8D => Ops::DUP14,
+            0x8E => Ops::DUP15,
+            0x8F => Ops::DUP16,
+            0x90 => Ops::SWAP1,
+            0x91 => Ops::SWAP2,
+            0x92 => Ops::SWAP3,
+            0x93 => Ops::SWAP4,
+            0x94 => Ops::SWAP5,
+            0x95 => Ops::SWAP6,
+            0x96 => Ops::SWAP7,
+            0x97 => Ops::SWAP8,
+            0x98 => Ops::SWAP9,
+            0x99 => Ops::SWAP10,
+            0x9A => Ops::SWAP11,
+            0x9B => Ops::SWAP12,
+            0x9C => Ops::SWAP13,
+            0x9D => Ops::SWAP14,
+            0x9E => Ops::SWAP15,
+            0x9F => Ops::SWAP16,
+            0x51 => Ops::MLOAD,
+            0x52 => Ops::MSTORE,
+            0x53 => Ops::MSTORE8,
+            0x59 => Ops::MSIZE,
+            0xA0 => Ops::LOG0,
+            0xA1 => Ops::LOG1,
+            0xA2 => Ops::LOG2,
+            0xA3 => Ops::LOG3,
+            0xA4 => Ops::LOG4,
+            0xF0 => Ops::CREATE,
+            0xF3 => Ops::RETURN,
+            0xF1 => Ops::CALL,
+            0xF2 => Ops::CALLCODE,
+            0xFF => Ops::SELFDESTRUCT,
+            _ => return None,
+        })
+    }
+
+    /// Number of immediate bytes following the opcode in code, which is
+    /// only non-zero for `PUSHn`.
+    pub fn immediate_size(&self) -> usize {
+        match *self as u8 {
+            byte @ 0x60..=0x7f => usize::from(byte - 0x5f),
+            _ => 0,
+        }
+    }
+
+    /// Whether execution never continues with the next instruction, because
+    /// the opcode jumps or halts the frame.
+    pub fn is_terminating(&self) -> bool {
+        matches!(self, Ops::STOP | Ops::JUMP | Ops::RETURN | Ops::SELFDESTRUCT)
+    }
+
+    /// Whether the opcode may transfer control to a jump destination.
+    pub fn is_jump(&self) -> bool {
+        matches!(self, Ops::JUMP | Ops::JUMPI)
+    }
+
+    /// The mnemonic of the opcode, as used in EIP-3155 traces.
+    pub fn name(&self) -> String {
+        match self {
+            Ops::KECCAK => "KECCAK256".to_string(),
+            op => format!("{op:?}"),
+        }
+    }
+
+    /// The opcode with the mnemonic `name`, ignoring case.
+    pub fn from_name(name: &str) -> Option<Ops> {
+        (0..=u8::MAX)
+            .filter_map(Ops::from_byte)
+            .find(|op| op.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// An instruction, or a precompiled contract, executed on an EVM frame.
+pub type Implementation<S, I> = fn(&mut Evm<S, I>) -> Result<()>;
+
+/// The implementation of `op`.
+pub fn op_implementation<S: StateBackend, I: Inspector>(op: Ops) -> Implementation<S, I> {
+    match op {
+        Ops::ADD => arithmetic::add,
+        Ops::MUL => arithmetic::mul,
+        Ops::SUB => arithmetic::sub,
+        Ops::DIV => arithmetic::div,
+        Ops::SDIV => arithmetic::sdiv,
+        Ops::MOD => arithmetic::r#mod,
+        Ops::SMOD => arithmetic::smod,
+        Ops::ADDMOD => arithmetic::addmod,
+        Ops::MULMOD => arithmetic::mulmod,
+        Ops::EXP => arithmetic::exp,
+        Ops::SIGNEXTEND => arithmetic::signextend,
+        Ops::LT => comparison::less_than,
+        Ops::GT => comparison::greater_than,
+        Ops::SLT => comparison::signed_less_than,
+        Ops::SGT => comparison::signed_greater_than,
+        Ops::EQ => comparison::equal,
+        Ops::ISZERO => comparison::is_zero,
+        Ops::AND => bitwise::bitwise_and,
+        Ops::OR => bitwise::bitwise_or,
+        Ops::XOR => bitwise::bitwise_xor,
+        Ops::NOT => bitwise::bitwise_not,
+        Ops::BYTE => bitwise::get_byte,
+        Ops::KECCAK => keccak::keccak,
+        Ops::ADDRESS => environment::address,
+        Ops::BALANCE => environment::balance,
+        Ops::ORIGIN => environment::origin,
+        Ops::CALLER => environment::caller,
+        Ops::CALLVALUE => environment::callvalue,
+        Ops::CALLDATALOAD => environment::calldataload,
+        Ops::CALLDATASIZE => environment::calldatasize,
+        Ops::CALLDATACOPY => environment::calldatacopy,
+        Ops::CODESIZE => environment::codesize,
+        Ops::CODECOPY => environment::codecopy,
+        Ops::GASPRICE => environment::gasprice,
+        Ops::EXTCODESIZE => environment::extcodesize,
+        Ops::EXTCODECOPY => environment::extcodecopy,
+        Ops::BLOCKHASH => block::block_hash,
+        Ops::COINBASE => block::coinbase,
+        Ops::TIMESTAMP => block::timestamp,
+        Ops::NUMBER => block::number,
+        Ops::DIFFICULTY => block::difficulty,
+        Ops::GASLIMIT => block::gas_limit,
+        Ops::STOP => control_flow::stop,
+        Ops::JUMP => control_flow::jump,
+        Ops::JUMPI => control_flow::jumpi,
+        Ops::PC => control_flow::pc,
+        Ops::GAS => control_flow::gas_left,
+        Ops::JUMPDEST => control_flow::jumpdest,
+        Ops::SLOAD => storage::sload,
+        Ops::SSTORE => storage::sstore,
+        Ops::POP => stack::pop,
+        Ops::PUSH1 => stack::push1,
+        Ops::PUSH2 => stack::push2,
+        Ops::PUSH3 => stack::push3,
+        Ops::PUSH4 => stack::push4,
+        Ops::PUSH5 => stack::push5,
+        Ops::PUSH6 => stack::push6,
+        Ops::PUSH7 => stack::push7,
+        Ops::PUSH8 => stack::push8,
+        Ops::PUSH9 => stack::push9,
+        Ops::PUSH10 => stack::push10,
+        Ops::PUSH11 => stack::push11,
+        Ops::PUSH12 => stack::push12,
+        Ops::PUSH13 => stack::push13,
+        Ops::PUSH14 => stack::push14,
+        Ops::PUSH15 => stack::push15,
+        Ops::PUSH16 => stack::push16,
+        Ops::PUSH17 => stack::push17,
+        Ops::PUSH18 => stack::push18,
+        Ops::PUSH19 => stack::push19,
+        Ops::PUSH20 => stack::push20,
+        Ops::PUSH21 => stack::push21,
+        Ops::PUSH22 => stack::push22,
+        Ops::PUSH23 => stack::push23,
+        Ops::PUSH24 => stack::push24,
+        Ops::PUSH25 => stack::push25,
+        Ops::PUSH26 => stack::push26,
+        Ops::PUSH27 => stack::push27,
+        Ops::PUSH28 => stack::push28,
+        Ops::PUSH29 => stack::push29,
+        Ops::PUSH30 => stack::push30,
+        Ops::PUSH31 => stack::push31,
+        Ops::PUSH32 => stack::push32,
+        Ops::DUP1 => stack::dup1,
+        Ops::DUP2 => stack::dup2,
+        Ops::DUP3 => stack::dup3,
+        Ops::DUP4 => stack::dup4,
+        Ops::DUP5 => stack::dup5,
+        Ops::DUP6 => stack::dup6,
+        Ops::DUP7 => stack::dup7,
+        Ops::DUP8 => stack::dup8,
+        Ops::DUP9 => stack::dup9,
+        Ops::DUP10 => stack::dup10,
+        Ops::DUP11 => stack::dup11,
+        Ops::DUP12 => stack::dup12,
+        Ops::DUP13 => stack::dup13,
+        Ops::DUP14 => stack::dup14,
+        Ops::DUP15 => stack::dup15,
+        Ops::DUP16 => stack::dup16,
+        Ops::SWAP1 => stack::swap1,
+        Ops::SWAP2 => stack::swap2,
+        Ops::SWAP3 => stack::swap3,
+        Ops::SWAP4 => stack::swap4,
+        Ops::SWAP5 => stack::swap5,
+        Ops::SWAP6 => stack::swap6,
+        Ops::SWAP7 => stack::swap7,
+        Ops::SWAP8 => stack::swap8,
+        Ops::SWAP9 => stack::swap9,
+        Ops::SWAP10 => stack::swap10,
+        Ops::SWAP11 => stack::swap11,
+        Ops::SWAP12 => stack::swap12,
+        Ops::SWAP13 => stack::swap13,
+        Ops::SWAP14 => stack::swap14,
+        Ops::SWAP15 => stack::swap15,
+        Ops::SWAP16 => stack::swap16,
+        Ops::MLOAD => memory::mload,
+        Ops::MSTORE => memory::mstore,
+        Ops::MSTORE8 => memory::mstore8,
+        Ops::MSIZE => memory::msize,
+        Ops::LOG0 => log::log0,
+        Ops::LOG1 => log::log1,
+        Ops::LOG2 => log::log2,
+        Ops::LOG3 => log::log3,
+        Ops::LOG4 => log::log4,
+        Ops::CREATE => system::create,
+        Ops::RETURN => system::return_,
+        Ops::CALL => system::call,
+        Ops::CALLCODE => system::callcode,
+        Ops::SELFDESTRUCT => system::selfdestruct,
+    }
+}
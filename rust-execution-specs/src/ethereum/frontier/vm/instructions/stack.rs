@@ -9,11 +9,10 @@
 //! ------------
 //!
 //! Implementations of the EVM stack related instructions.
-#![allow(dead_code)]
-
 use super::super::{exceptions::Result, gas, stack, Evm};
+use crate::ethereum::frontier::vm::inspector::Inspector;
+use crate::ethereum::frontier::state::StateBackend;
 use crate::ethereum::base_types::U256;
-use crate::ethereum::frontier::vm::exceptions::EvmError;
 use crate::ethereum::frontier::vm::memory::buffer_read;
 
 /// Remove item from stack.
@@ -22,7 +21,7 @@ use crate::ethereum::frontier::vm::memory::buffer_read;
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn pop(evm: &mut Evm) -> Result<()> {
+pub fn pop<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
     stack::pop(&mut evm.stack)?;
 
@@ -47,7 +46,7 @@ pub fn pop(evm: &mut Evm) -> Result<()> {
 /// num_bytes :
 ///     The number of immediate bytes to be read from the code and pushed to
 ///     the stack.
-pub fn push_n(evm: &mut Evm, num_bytes: usize) -> Result<()> {
+pub fn push_n<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>, num_bytes: usize) -> Result<()> {
     // STACK
     let _ = {};
 
@@ -55,7 +54,7 @@ pub fn push_n(evm: &mut Evm, num_bytes: usize) -> Result<()> {
     gas::charge_gas(evm, gas::GAS_VERY_LOW())?;
 
     // OPERATION
-    let data_to_push = U256::from_bytes_be(&buffer_read(
+    let data_to_push = U256::from_be_bytes(&buffer_read(
         evm.code.clone(),
         U256::from(evm.pc + 1),
         U256::from(num_bytes),
@@ -77,7 +76,7 @@ pub fn push_n(evm: &mut Evm, num_bytes: usize) -> Result<()> {
 /// item_number :
 ///     The stack item number (0-indexed from top of stack) to be duplicated
 ///     to the top of stack.
-pub fn dup_n(evm: &mut Evm, item_number: usize) -> Result<()> {
+pub fn dup_n<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>, item_number: usize) -> Result<()> {
     // STACK
     let _ = {};
 
@@ -85,11 +84,7 @@ pub fn dup_n(evm: &mut Evm, item_number: usize) -> Result<()> {
     gas::charge_gas(evm, gas::GAS_VERY_LOW())?;
 
     // OPERATION
-    if !(item_number < evm.stack.len()) {
-        return Err(EvmError::StackOverflow);
-    }
-    let data_to_duplicate = evm.stack[evm.stack.len() - 1 - item_number].clone();
-    stack::push(&mut evm.stack, data_to_duplicate)?;
+    evm.stack.dup(item_number)?;
 
     // PROGRAM COUNTER
     evm.pc += 1;
@@ -110,7 +105,7 @@ pub fn dup_n(evm: &mut Evm, item_number: usize) -> Result<()> {
 /// item_number :
 ///     The stack item number (0-indexed from top of stack) to be swapped
 ///     with the top of stack element.
-pub fn swap_n(evm: &mut Evm, item_number: usize) -> Result<()> {
+pub fn swap_n<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>, item_number: usize) -> Result<()> {
     // STACK
     let _ = {};
 
@@ -118,14 +113,7 @@ pub fn swap_n(evm: &mut Evm, item_number: usize) -> Result<()> {
     gas::charge_gas(evm, gas::GAS_VERY_LOW())?;
 
     // OPERATION
-    if !(item_number < evm.stack.len()) {
-        return Err(EvmError::StackOverflow);
-    }
-    let other_idx = evm.stack.len() - 1 - item_number;
-    let last = evm.stack.last().unwrap().clone();
-    let other = evm.stack[other_idx].clone();
-    *evm.stack.last_mut().unwrap() = other;
-    evm.stack[other_idx] = last;
+    evm.stack.swap(item_number)?;
 
     // PROGRAM COUNTER
     evm.pc += 1;
@@ -134,7 +122,7 @@ pub fn swap_n(evm: &mut Evm, item_number: usize) -> Result<()> {
 
 macro_rules! make_fn {
     ($evm:ident $($fn:ident $call:expr;)*) => {$(
-        fn $fn($evm: &mut Evm) -> Result<()> {
+        pub fn $fn<S: StateBackend, I: Inspector>($evm: &mut Evm<S, I>) -> Result<()> {
             $call
         }
     )*};
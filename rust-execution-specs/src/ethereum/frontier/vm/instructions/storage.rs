@@ -11,8 +11,9 @@
 //! Implementations of the EVM storage related instructions.
 
 use super::super::{exceptions::Result, gas, stack, Evm};
+use crate::ethereum::frontier::vm::inspector::Inspector;
 use crate::ethereum::base_types::U256;
-use crate::ethereum::frontier::state::{get_storage, set_storage};
+use crate::ethereum::frontier::state::{get_storage, set_storage, StateBackend};
 
 /// Loads to the stack, the value corresponding to a certain key from the
 /// storage of the current account.
@@ -21,18 +22,15 @@ use crate::ethereum::frontier::state::{get_storage, set_storage};
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn sload(evm: &mut Evm) -> Result<()> {
+pub fn sload<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
-    let key = stack::pop(&mut evm.stack)?
-        .to_bytes_be()
-        .try_into()
-        .unwrap();
+    let key = stack::pop(&mut evm.stack)?.to_be_bytes32();
 
     // GAS
     gas::charge_gas(evm, gas::GAS_SLOAD())?;
 
     // OPERATION
-    let value = get_storage(&evm.env.state, &evm.message.current_target, &key);
+    let value = get_storage(&evm.env.state, &evm.message.current_target, &key)?;
     stack::push(&mut evm.stack, value)?;
 
     // PROGRAM COUNTER
@@ -46,16 +44,13 @@ pub fn sload(evm: &mut Evm) -> Result<()> {
 /// ----------
 /// evm :
 ///     The current EVM frame.
-pub fn sstore(evm: &mut Evm) -> Result<()> {
+pub fn sstore<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
     // STACK
-    let key = stack::pop(&mut evm.stack)?
-        .to_bytes_be()
-        .try_into()
-        .unwrap();
+    let key = stack::pop(&mut evm.stack)?.to_be_bytes32();
     let new_value = stack::pop(&mut evm.stack)?;
 
     // GAS
-    let current_value = get_storage(&evm.env.state, &evm.message.current_target, &key);
+    let current_value = get_storage(&evm.env.state, &evm.message.current_target, &key)?;
     let gas_cost = if new_value != U256::from(0u8) && current_value == U256::from(0u8) {
         gas::GAS_STORAGE_SET()
     } else {
@@ -65,7 +60,7 @@ pub fn sstore(evm: &mut Evm) -> Result<()> {
 
     // OPERATION
     if new_value == U256::from(0u8) && current_value != U256::from(0u8) {
-        evm.refund_counter += gas::GAS_STORAGE_CLEAR_REFUND();
+        evm.refund_counter += U256::try_from(gas::GAS_STORAGE_CLEAR_REFUND())?;
     }
     set_storage(
         &mut evm.env.state,
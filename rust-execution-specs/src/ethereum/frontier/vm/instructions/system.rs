@@ -1,196 +1,339 @@
-// /// 
-// /// Ethereum Virtual Machine (EVM) System Instructions
-// /// ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
-// /// 
-// /// .. contents:: Table of Contents
-// ///     :backlinks: none
-// ///     :local:
-// /// 
-// /// Introduction
-// /// ------------
-// /// 
-// /// Implementations of the EVM system related instructions.
-// /// 
-// use ::ethereum::base_types::{U256, Bytes0, Uint};
-// use super::super::super::fork_types::{Address};
-// use super::super::super::state::{account_has_code_or_nonce, get_account, increment_nonce, set_account_balance};
-// use super::super::super::utils::address::{compute_contract_address, to_address};
-// use super::super::::{Evm, Message, incorporate_child_on_error, incorporate_child_on_success};
-// use super::super::gas::{GAS_CREATE, GAS_ZERO, calculate_gas_extend_memory, calculate_message_call_gas, charge_gas};
-// use super::super::memory::{memory_read_bytes, memory_write};
-// use super::super::stack::{pop, push};
-// /// 
-// ///     Creates a new account with associated code.
-// /// 
-// ///     Parameters
-// ///     ----------
-// ///     evm :
-// ///         The current EVM frame.
-// ///     
-// pub fn create(evm: Evm) -> Result<(), Error> {
-//     use super::super::super::vm::interpreter::{STACK_DEPTH_LIMIT, process_create_message};
-//     endowment = pop(evm.stack)?;
-//     memory_start_position = pop(evm.stack)?;
-//     memory_size = pop(evm.stack)?;
-//     extend_memory = calculate_gas_extend_memory(evm.memory, [(memory_start_position, memory_size)])?;
-//     charge_gas(evm, GAS_CREATE + extend_memory.cost)?;
-//     create_message_gas = evm.gas_left;
-//     evm.gas_left = U256(0)?;
-//     evm.memory += [0] * extend_memory.expand_by;
-//     sender_address = evm.message.current_target;
-//     sender = get_account(evm.env.state, sender_address)?;
-//     contract_address = compute_contract_address(evm.message.current_target, get_account(evm.env.state, evm.message.current_target)?.nonce)?;
-//     if sender.balance < endowment || sender.nonce == Uint((2).pow(64) - 1)? || evm.message.depth + 1 > STACK_DEPTH_LIMIT {
-//         push(evm.stack, U256(0)?)?;
-//         evm.gas_left += create_message_gas;
-//     } else if account_has_code_or_nonce(evm.env.state, contract_address)? {
-//         increment_nonce(evm.env.state, evm.message.current_target)?;
-//         push(evm.stack, U256(0)?)?;
-//     } else {
-//         call_data = memory_read_bytes(evm.memory, memory_start_position, memory_size)?;
-//         increment_nonce(evm.env.state, evm.message.current_target)?;
-//         child_message = Message(caller = evm.message.current_target, target = Bytes0()?, gas = create_message_gas, value = endowment, data = [], code = call_data, current_target = contract_address, depth = evm.message.depth + 1, code_address = ())?;
-//         child_evm = process_create_message(child_message, evm.env)?;
-//         if child_evm.has_erred {
-//             incorporate_child_on_error(evm, child_evm)?;
-//             push(evm.stack, U256(0)?)?;
-//         } else {
-//             incorporate_child_on_success(evm, child_evm)?;
-//             push(evm.stack, U256.from_be_bytes(child_evm.message.current_target)?)?;
-//         }
-//     }
-//     evm.pc += 1;
-// }
-
-
-// /// 
-// ///     Halts execution returning output data.
-// /// 
-// ///     Parameters
-// ///     ----------
-// ///     evm :
-// ///         The current EVM frame.
-// ///     
-// pub fn return_(evm: Evm) -> Result<(), Error> {
-//     memory_start_position = pop(evm.stack)?;
-//     memory_size = pop(evm.stack)?;
-//     extend_memory = calculate_gas_extend_memory(evm.memory, [(memory_start_position, memory_size)])?;
-//     charge_gas(evm, GAS_ZERO + extend_memory.cost)?;
-//     evm.memory += [0] * extend_memory.expand_by;
-//     evm.output = memory_read_bytes(evm.memory, memory_start_position, memory_size)?;
-//     evm.running = false;
-//     // pass;
-// }
-
-
-// /// 
-// ///     Perform the core logic of the `CALL*` family of opcodes.
-// ///     
-// pub fn generic_call(evm: Evm, gas: Uint, value: U256, caller: Address, to: Address, code_address: Address, memory_input_start_position: U256, memory_input_size: U256, memory_output_start_position: U256, memory_output_size: U256) -> Result<(), Error> {
-//     use super::super::super::vm::interpreter::{STACK_DEPTH_LIMIT, process_message};
-//     if evm.message.depth + 1 > STACK_DEPTH_LIMIT {
-//         evm.gas_left += gas;
-//         push(evm.stack, U256(0)?)?;
-//         return;
-//     }
-//     call_data = memory_read_bytes(evm.memory, memory_input_start_position, memory_input_size)?;
-//     code = get_account(evm.env.state, code_address)?.code;
-//     child_message = Message(caller = caller, target = to, gas = U256(gas)?, value = value, data = call_data, code = code, current_target = to, depth = evm.message.depth + 1, code_address = code_address)?;
-//     child_evm = process_message(child_message, evm.env)?;
-//     if child_evm.has_erred {
-//         incorporate_child_on_error(evm, child_evm)?;
-//         push(evm.stack, U256(0)?)?;
-//     } else {
-//         incorporate_child_on_success(evm, child_evm)?;
-//         push(evm.stack, U256(1)?)?;
-//     }
-//     actual_output_size = min(memory_output_size, U256(len(child_evm.output)?)?)?;
-//     memory_write(evm.memory, memory_output_start_position, child_evm.output[..actual_output_size])?;
-// }
-
-
-// /// 
-// ///     Message-call into an account.
-// /// 
-// ///     Parameters
-// ///     ----------
-// ///     evm :
-// ///         The current EVM frame.
-// ///     
-// pub fn call(evm: Evm) -> Result<(), Error> {
-//     gas = Uint(pop(evm.stack)?)?;
-//     to = to_address(pop(evm.stack)?)?;
-//     value = pop(evm.stack)?;
-//     memory_input_start_position = pop(evm.stack)?;
-//     memory_input_size = pop(evm.stack)?;
-//     memory_output_start_position = pop(evm.stack)?;
-//     memory_output_size = pop(evm.stack)?;
-//     extend_memory = calculate_gas_extend_memory(evm.memory, [(memory_input_start_position, memory_input_size), (memory_output_start_position, memory_output_size)])?;
-//     message_call_gas = calculate_message_call_gas(evm.env.state, gas, to, value)?;
-//     charge_gas(evm, message_call_gas.cost + extend_memory.cost)?;
-//     evm.memory += [0] * extend_memory.expand_by;
-//     sender_balance = get_account(evm.env.state, evm.message.current_target)?.balance;
-//     if sender_balance < value {
-//         push(evm.stack, U256(0)?)?;
-//         evm.gas_left += message_call_gas.stipend;
-//     } else {
-//         generic_call(evm, message_call_gas.stipend, value, evm.message.current_target, to, to, memory_input_start_position, memory_input_size, memory_output_start_position, memory_output_size)?;
-//     }
-//     evm.pc += 1;
-// }
-
-
-// /// 
-// ///     Message-call into this account with alternative account’s code.
-// /// 
-// ///     Parameters
-// ///     ----------
-// ///     evm :
-// ///         The current EVM frame.
-// ///     
-// pub fn callcode(evm: Evm) -> Result<(), Error> {
-//     gas = Uint(pop(evm.stack)?)?;
-//     code_address = to_address(pop(evm.stack)?)?;
-//     value = pop(evm.stack)?;
-//     memory_input_start_position = pop(evm.stack)?;
-//     memory_input_size = pop(evm.stack)?;
-//     memory_output_start_position = pop(evm.stack)?;
-//     memory_output_size = pop(evm.stack)?;
-//     to = evm.message.current_target;
-//     extend_memory = calculate_gas_extend_memory(evm.memory, [(memory_input_start_position, memory_input_size), (memory_output_start_position, memory_output_size)])?;
-//     message_call_gas = calculate_message_call_gas(evm.env.state, gas, to, value)?;
-//     charge_gas(evm, message_call_gas.cost + extend_memory.cost)?;
-//     evm.memory += [0] * extend_memory.expand_by;
-//     sender_balance = get_account(evm.env.state, evm.message.current_target)?.balance;
-//     if sender_balance < value {
-//         push(evm.stack, U256(0)?)?;
-//         evm.gas_left += message_call_gas.stipend;
-//     } else {
-//         generic_call(evm, message_call_gas.stipend, value, evm.message.current_target, to, code_address, memory_input_start_position, memory_input_size, memory_output_start_position, memory_output_size)?;
-//     }
-//     evm.pc += 1;
-// }
-
-
-// /// 
-// ///     Halt execution and register account for later deletion.
-// /// 
-// ///     Parameters
-// ///     ----------
-// ///     evm :
-// ///         The current EVM frame.
-// ///     
-// pub fn selfdestruct(evm: Evm) -> Result<(), Error> {
-//     beneficiary = to_address(pop(evm.stack)?)?;
-//     // pass;
-//     originator = evm.message.current_target;
-//     beneficiary_balance = get_account(evm.env.state, beneficiary)?.balance;
-//     originator_balance = get_account(evm.env.state, originator)?.balance;
-//     set_account_balance(evm.env.state, beneficiary, beneficiary_balance + originator_balance)?;
-//     set_account_balance(evm.env.state, originator, U256(0)?)?;
-//     evm.accounts_to_delete.add(originator)?;
-//     evm.running = false;
-//     // pass;
-// }
+//! Ethereum Virtual Machine (EVM) System Instructions
+//! ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+//!
+//! .. contents:: Table of Contents
+//!     :backlinks: none
+//!     :local:
+//!
+//! Introduction
+//! ------------
+//!
+//! Implementations of the EVM system related instructions.
 
+use super::super::{
+    exceptions::Result,
+    gas, incorporate_child_on_error, incorporate_child_on_success,
+    inspector::{inspect, Inspector},
+    interpreter::{process_create_message, process_message, STACK_DEPTH_LIMIT},
+    stack, ChildEvm, Evm, Message,
+};
+use crate::ethereum::base_types::{Bytes, Uint, U256};
+use crate::ethereum::frontier::fork_types::Address;
+use crate::ethereum::frontier::state::{
+    account_has_code_or_nonce, get_account, get_code_hash, increment_nonce, set_account_balance,
+    StateBackend,
+};
+use crate::ethereum::frontier::utils::address::{compute_contract_address, to_address};
+use crate::ethereum::frontier::vm::memory::{memory_read_bytes, memory_write};
 
+/// Creates a new account with associated code.
+///
+/// Parameters
+/// ----------
+/// evm :
+///     The current EVM frame.
+pub fn create<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
+    // STACK
+    let endowment = stack::pop(&mut evm.stack)?;
+    let memory_start_position = stack::pop(&mut evm.stack)?;
+    let memory_size = stack::pop(&mut evm.stack)?;
+
+    // GAS
+    let extend_memory = gas::calculate_gas_extend_memory(
+        &evm.memory,
+        [(memory_start_position, memory_size)].to_vec(),
+    );
+    gas::charge_gas(evm, gas::GAS_CREATE() + extend_memory.cost)?;
+
+    let create_message_gas = std::mem::take(&mut evm.gas_left);
+
+    // OPERATION
+    evm.memory.expand(&extend_memory.expand_by);
+    let sender_address = evm.message.current_target;
+    let sender = get_account(&evm.env.state, &sender_address)?;
+
+    let contract_address = compute_contract_address(&sender_address, &sender.nonce);
+
+    if sender.balance < endowment
+        || sender.nonce == Uint::from(u64::MAX)
+        || &evm.message.depth + 1u8 > STACK_DEPTH_LIMIT()
+    {
+        stack::push(&mut evm.stack, U256::from(0u8))?;
+        evm.gas_left += create_message_gas;
+    } else if account_has_code_or_nonce(&evm.env.state, &contract_address)? {
+        increment_nonce(&mut evm.env.state, sender_address)?;
+        stack::push(&mut evm.stack, U256::from(0u8))?;
+    } else {
+        let call_data = memory_read_bytes(&evm.memory, memory_start_position, memory_size)
+            .to_vec()
+            .into_boxed_slice();
+
+        increment_nonce(&mut evm.env.state, sender_address)?;
+
+        let child_message = Message {
+            caller: sender_address,
+            target: None,
+            current_target: contract_address,
+            gas: create_message_gas,
+            value: endowment,
+            data: Bytes::default(),
+            code_address: None,
+            code: call_data,
+            code_hash: None,
+            depth: &evm.message.depth + 1u8,
+            is_callcode: false,
+        };
+        let child_evm = ChildEvm::from(process_create_message(child_message, evm.env)?);
+
+        if child_evm.error.is_some() {
+            incorporate_child_on_error(evm, &child_evm);
+            stack::push(&mut evm.stack, U256::from(0u8))?;
+        } else {
+            incorporate_child_on_success(evm, &child_evm);
+            stack::push(
+                &mut evm.stack,
+                U256::from_be_bytes(&child_evm.message.current_target),
+            )?;
+        }
+    }
+
+    // PROGRAM COUNTER
+    evm.pc += 1;
+    Ok(())
+}
+
+/// Halts execution returning output data.
+///
+/// Parameters
+/// ----------
+/// evm :
+///     The current EVM frame.
+pub fn return_<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
+    // STACK
+    let memory_start_position = stack::pop(&mut evm.stack)?;
+    let memory_size = stack::pop(&mut evm.stack)?;
+
+    // GAS
+    let extend_memory = gas::calculate_gas_extend_memory(
+        &evm.memory,
+        [(memory_start_position, memory_size)].to_vec(),
+    );
+    gas::charge_gas(evm, gas::GAS_ZERO() + extend_memory.cost)?;
+
+    // OPERATION
+    evm.memory.expand(&extend_memory.expand_by);
+    evm.output = memory_read_bytes(&evm.memory, memory_start_position, memory_size)
+        .to_vec()
+        .into_boxed_slice();
+    evm.running = false;
+    Ok(())
+}
+
+/// Perform the core logic of the `CALL*` family of opcodes.
+#[allow(clippy::too_many_arguments)]
+pub fn generic_call<S: StateBackend, I: Inspector>(
+    evm: &mut Evm<S, I>,
+    gas: Uint,
+    value: U256,
+    caller: Address,
+    to: Address,
+    code_address: Address,
+    memory_input_start_position: U256,
+    memory_input_size: U256,
+    memory_output_start_position: U256,
+    memory_output_size: U256,
+    is_callcode: bool,
+) -> Result<()> {
+    if &evm.message.depth + 1u8 > STACK_DEPTH_LIMIT() {
+        evm.gas_left += gas;
+        stack::push(&mut evm.stack, U256::from(0u8))?;
+        return Ok(());
+    }
+
+    let call_data = memory_read_bytes(&evm.memory, memory_input_start_position, memory_input_size)
+        .to_vec()
+        .into_boxed_slice();
+    let code = get_account(&evm.env.state, &code_address)?.code;
+    let code_hash = get_code_hash(&evm.env.state, &code_address)?;
+    let child_message = Message {
+        caller,
+        target: Some(to),
+        current_target: to,
+        gas,
+        value,
+        data: call_data,
+        code_address: Some(code_address),
+        code,
+        code_hash: Some(code_hash),
+        depth: &evm.message.depth + 1u8,
+        is_callcode,
+    };
+    let child_evm = ChildEvm::from(process_message(child_message, evm.env)?);
+
+    if child_evm.error.is_some() {
+        incorporate_child_on_error(evm, &child_evm);
+        stack::push(&mut evm.stack, U256::from(0u8))?;
+    } else {
+        incorporate_child_on_success(evm, &child_evm);
+        stack::push(&mut evm.stack, U256::from(1u8))?;
+    }
+
+    let actual_output_size = usize::try_from(memory_output_size)
+        .unwrap_or(usize::MAX)
+        .min(child_evm.output.len());
+    memory_write(
+        &mut evm.memory,
+        memory_output_start_position,
+        child_evm.output[..actual_output_size].into(),
+    );
+    Ok(())
+}
+
+/// Message-call into an account.
+///
+/// Parameters
+/// ----------
+/// evm :
+///     The current EVM frame.
+pub fn call<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
+    // STACK
+    let gas = Uint::from(stack::pop(&mut evm.stack)?);
+    let to = to_address(&stack::pop(&mut evm.stack)?);
+    let value = stack::pop(&mut evm.stack)?;
+    let memory_input_start_position = stack::pop(&mut evm.stack)?;
+    let memory_input_size = stack::pop(&mut evm.stack)?;
+    let memory_output_start_position = stack::pop(&mut evm.stack)?;
+    let memory_output_size = stack::pop(&mut evm.stack)?;
+
+    // GAS
+    let extend_memory = gas::calculate_gas_extend_memory(
+        &evm.memory,
+        [
+            (memory_input_start_position, memory_input_size),
+            (memory_output_start_position, memory_output_size),
+        ]
+        .to_vec(),
+    );
+    let message_call_gas = gas::calculate_message_call_gas(&evm.env.state, gas, &to, value)?;
+    gas::charge_gas(evm, message_call_gas.cost + extend_memory.cost)?;
+
+    // OPERATION
+    evm.memory.expand(&extend_memory.expand_by);
+    let sender_balance = get_account(&evm.env.state, &evm.message.current_target)?.balance;
+    if sender_balance < value {
+        stack::push(&mut evm.stack, U256::from(0u8))?;
+        evm.gas_left += message_call_gas.stipend;
+    } else {
+        generic_call(
+            evm,
+            message_call_gas.stipend,
+            value,
+            evm.message.current_target,
+            to,
+            to,
+            memory_input_start_position,
+            memory_input_size,
+            memory_output_start_position,
+            memory_output_size,
+            false,
+        )?;
+    }
+
+    // PROGRAM COUNTER
+    evm.pc += 1;
+    Ok(())
+}
+
+/// Message-call into this account with alternative account’s code.
+///
+/// Parameters
+/// ----------
+/// evm :
+///     The current EVM frame.
+pub fn callcode<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
+    // STACK
+    let gas = Uint::from(stack::pop(&mut evm.stack)?);
+    let code_address = to_address(&stack::pop(&mut evm.stack)?);
+    let value = stack::pop(&mut evm.stack)?;
+    let memory_input_start_position = stack::pop(&mut evm.stack)?;
+    let memory_input_size = stack::pop(&mut evm.stack)?;
+    let memory_output_start_position = stack::pop(&mut evm.stack)?;
+    let memory_output_size = stack::pop(&mut evm.stack)?;
+
+    // GAS
+    let to = evm.message.current_target;
+
+    let extend_memory = gas::calculate_gas_extend_memory(
+        &evm.memory,
+        [
+            (memory_input_start_position, memory_input_size),
+            (memory_output_start_position, memory_output_size),
+        ]
+        .to_vec(),
+    );
+    let message_call_gas = gas::calculate_message_call_gas(&evm.env.state, gas, &to, value)?;
+    gas::charge_gas(evm, message_call_gas.cost + extend_memory.cost)?;
+
+    // OPERATION
+    evm.memory.expand(&extend_memory.expand_by);
+    let sender_balance = get_account(&evm.env.state, &evm.message.current_target)?.balance;
+    if sender_balance < value {
+        stack::push(&mut evm.stack, U256::from(0u8))?;
+        evm.gas_left += message_call_gas.stipend;
+    } else {
+        generic_call(
+            evm,
+            message_call_gas.stipend,
+            value,
+            evm.message.current_target,
+            to,
+            code_address,
+            memory_input_start_position,
+            memory_input_size,
+            memory_output_start_position,
+            memory_output_size,
+            true,
+        )?;
+    }
+
+    // PROGRAM COUNTER
+    evm.pc += 1;
+    Ok(())
+}
+
+/// Halt execution and register account for later deletion.
+///
+/// Parameters
+/// ----------
+/// evm :
+///     The current EVM frame.
+pub fn selfdestruct<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
+    // STACK
+    let beneficiary = to_address(&stack::pop(&mut evm.stack)?);
+
+    // OPERATION
+    let originator = evm.message.current_target;
+    let beneficiary_balance = get_account(&evm.env.state, &beneficiary)?.balance;
+    let originator_balance = get_account(&evm.env.state, &originator)?.balance;
+
+    // First Transfer to beneficiary
+    set_account_balance(
+        &mut evm.env.state,
+        beneficiary,
+        beneficiary_balance + originator_balance,
+    )?;
+    // Next, Zero the balance of the address being deleted (must come after
+    // sending to beneficiary in case the contract named itself as the
+    // beneficiary).
+    set_account_balance(&mut evm.env.state, originator, U256::from(0u8))?;
+    inspect(evm, |inspector, frame| {
+        inspector.selfdestruct(frame, &beneficiary, &originator_balance)
+    });
+
+    // register account for deletion
+    evm.accounts_to_delete.insert(originator);
+
+    // HALT the execution
+    evm.running = false;
+    Ok(())
+}
@@ -1,188 +1,353 @@
-// /// 
-// /// Ethereum Virtual Machine (EVM) Interpreter
-// /// ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
-// /// 
-// /// .. contents:: Table of Contents
-// ///     :backlinks: none
-// ///     :local:
-// /// 
-// /// Introduction
-// /// ------------
-// /// 
-// /// A straightforward interpreter that executes EVM code.
-// /// 
-// use ::dataclasses::{dataclass};
-// use ::typing::{Set, Tuple};
-// use ::ethereum::{evm_trace};
-// use ::ethereum::base_types::{U256, Bytes0, Uint};
-// use super::super::fork_types::{Address, Log};
-// use super::super::state::{account_has_code_or_nonce, begin_transaction, commit_transaction, move_ether, rollback_transaction, set_code, touch_account};
-// use super::super::vm::{Message};
-// use super::super::vm::gas::{GAS_CODE_DEPOSIT, REFUND_SELF_DESTRUCT, charge_gas};
-// use super::super::vm::precompiled_contracts::mapping::{PRE_COMPILED_CONTRACTS};
-// use super::::{Environment, Evm};
-// use super::exceptions::{ExceptionalHalt, InvalidOpcode, StackDepthLimitError};
-// use super::instructions::{Ops, op_implementation};
-// use super::runtime::{get_valid_jump_destinations};
-// STACK_DEPTH_LIMIT = U256(1024)?;
-// /// 
-// ///     Output of a particular message call
-// /// 
-// ///     Contains the following:
-// /// 
-// ///           1. `gas_left`: remaining gas after execution.
-// ///           2. `refund_counter`: gas to refund after execution.
-// ///           3. `logs`: list of `Log` generated during execution.
-// ///           4. `accounts_to_delete`: Contracts which have self-destructed.
-// ///           5. `has_erred`: True if execution has caused an error.
-// ///     
-// struct MessageCallOutput {
-//     gas_left: U256,
-//     refund_counter: U256,
-//     logs: Tuple[Log][...],
-//     accounts_to_delete: Set[Address],
-//     has_erred: bool,
-// }
-
-
-// impl MessageCallOutput {
-// }
-
-
-// /// 
-// ///     If `message.current` is empty then it creates a smart contract
-// ///     else it executes a call from the `message.caller` to the `message.target`.
-// /// 
-// ///     Parameters
-// ///     ----------
-// ///     message :
-// ///         Transaction specific items.
-// /// 
-// ///     env :
-// ///         External items required for EVM execution.
-// /// 
-// ///     Returns
-// ///     -------
-// ///     output : `MessageCallOutput`
-// ///         Output of the message call
-// ///     
-// pub fn process_message_call(message: Message, env: Environment) -> Result<MessageCallOutput, Error> {
-//     if message.target == Bytes0([])? {
-//         is_collision = account_has_code_or_nonce(env.state, message.current_target)?;
-//         if is_collision {
-//             return Ok(MessageCallOutput(U256(0)?, U256(0)?, tuple()?, set()?, true)?);
-//         } else {
-//             evm = process_create_message(message, env)?;
-//         }
-//     } else {
-//         evm = process_message(message, env)?;
-//     }
-//     if evm.has_erred {
-//         // TypedAssignment unsupported
-//         accounts_to_delete = set()?;
-//         refund_counter = U256(0)?;
-//     } else {
-//         logs = evm.logs;
-//         accounts_to_delete = evm.accounts_to_delete;
-//         refund_counter = evm.refund_counter + REFUND_SELF_DESTRUCT * len(evm.accounts_to_delete)?;
-//     }
-//     return Ok(MessageCallOutput(gas_left = evm.gas_left, refund_counter = refund_counter, logs = logs, accounts_to_delete = accounts_to_delete, has_erred = evm.has_erred)?);
-// }
-
-
-// /// 
-// ///     Executes a call to create a smart contract.
-// /// 
-// ///     Parameters
-// ///     ----------
-// ///     message :
-// ///         Transaction specific items.
-// ///     env :
-// ///         External items required for EVM execution.
-// /// 
-// ///     Returns
-// ///     -------
-// ///     evm: :py:class:`~ethereum.frontier.vm.Evm`
-// ///         Items containing execution specific objects.
-// ///     
-// pub fn process_create_message(message: Message, env: Environment) -> Result<Evm, Error> {
-//     evm = process_message(message, env)?;
-//     if !(evm.has_erred) {
-//         contract_code = evm.output;
-//         contract_code_gas = len(contract_code)? * GAS_CODE_DEPOSIT;
-//         // Try 
-//             charge_gas(evm, contract_code_gas)?;
-//     }
-//     return Ok(evm);
-// }
-
-
-// /// 
-// ///     Executes a call to create a smart contract.
-// /// 
-// ///     Parameters
-// ///     ----------
-// ///     message :
-// ///         Transaction specific items.
-// ///     env :
-// ///         External items required for EVM execution.
-// /// 
-// ///     Returns
-// ///     -------
-// ///     evm: :py:class:`~ethereum.frontier.vm.Evm`
-// ///         Items containing execution specific objects
-// ///     
-// pub fn process_message(message: Message, env: Environment) -> Result<Evm, Error> {
-//     if message.depth > STACK_DEPTH_LIMIT {
-//         return Err(Error::StackDepthLimitError("Stack depth limit reached")?);
-//     }
-//     begin_transaction(env.state)?;
-//     touch_account(env.state, message.current_target)?;
-//     if message.value != 0 {
-//         move_ether(env.state, message.caller, message.current_target, message.value)?;
-//     }
-//     evm = execute_code(message, env)?;
-//     if evm.has_erred {
-//         rollback_transaction(env.state)?;
-//     } else {
-//         commit_transaction(env.state)?;
-//     }
-//     return Ok(evm);
-// }
-
-
-// /// 
-// ///     Executes bytecode present in the `message`.
-// /// 
-// ///     Parameters
-// ///     ----------
-// ///     message :
-// ///         Transaction specific items.
-// ///     env :
-// ///         External items required for EVM execution.
-// /// 
-// ///     Returns
-// ///     -------
-// ///     evm: `ethereum.vm.EVM`
-// ///         Items containing execution specific objects
-// ///     
-// pub fn execute_code(message: Message, env: Environment) -> Result<Evm, Error> {
-//     code = message.code;
-//     valid_jump_destinations = get_valid_jump_destinations(code)?;
-//     evm = Evm(pc = Uint(0)?, stack = [], memory = bytearray()?, code = code, gas_left = message.gas, env = env, valid_jump_destinations = valid_jump_destinations, logs = (), refund_counter = U256(0)?, running = true, message = message, output = [], accounts_to_delete = set()?, has_erred = false)?;
-//     // Try 
-//         if (evm.message.code_address).contains(PRE_COMPILED_CONTRACTS) {
-//             evm_trace(evm, evm.message.code_address)?;
-//             PRE_COMPILED_CONTRACTS[evm.message.code_address](evm)?;
-//             return Ok(evm);
-//         }
-//         while evm.running && evm.pc < len(evm.code)? {
-//             // Try 
-//                 op = Ops(evm.code[evm.pc])?;
-//             evm_trace(evm, op)?;
-//             op_implementation[op](evm)?;
-//         }
-//     return Ok(evm);
-// }
+//! Ethereum Virtual Machine (EVM) Interpreter
+//! ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+//!
+//! .. contents:: Table of Contents
+//!     :backlinks: none
+//!     :local:
+//!
+//! Introduction
+//! ------------
+//!
+//! A straightforward interpreter that executes EVM code.
 
+use std::collections::HashSet;
 
+use super::{
+    exceptions::{EvmError, Result},
+    gas::{charge_gas, GAS_CODE_DEPOSIT, REFUND_SELF_DESTRUCT},
+    inspector::{inspect, Inspector},
+    instructions::{op_implementation, Ops},
+    memory::Memory,
+    precompiled_contracts::mapping::pre_compiled_contract,
+    runtime::cached_jump_destinations,
+    stack::Stack,
+    Environment, Evm, Message,
+};
+use crate::ethereum::base_types::{Bytes, Uint, U256};
+use crate::ethereum::frontier::{
+    fork_types::{Address, Log},
+    state::{
+        account_has_code_or_nonce, begin_transaction, commit_transaction, move_ether,
+        rollback_transaction, set_code, touch_account, StateBackend,
+    },
+};
+
+#[allow(non_snake_case)]
+pub fn STACK_DEPTH_LIMIT() -> Uint {
+    Uint::from(1024u16)
+}
+
+/// Output of a particular message call
+///
+/// Contains the following:
+///
+/// 1. `gas_left`: remaining gas after execution.
+/// 2. `refund_counter`: gas to refund after execution.
+/// 3. `logs`: list of `Log` generated during execution.
+/// 4. `accounts_to_delete`: Contracts which need to be deleted.
+/// 5. `output`: the data returned by the call.
+/// 6. `error`: the exceptional halt the call ended with, if any.
+pub struct MessageCallOutput {
+    pub gas_left: Uint,
+    pub refund_counter: U256,
+    pub logs: Vec<Log>,
+    pub accounts_to_delete: HashSet<Address>,
+    pub output: Bytes,
+    pub error: Option<EvmError>,
+}
+
+/// If `message.current` is empty then it creates a smart contract
+/// else it executes a call from the `message.caller` to the `message.target`.
+///
+/// Parameters
+/// ----------
+/// message :
+///     Transaction specific items.
+///
+/// env :
+///     External items required for EVM execution.
+///
+/// Returns
+/// -------
+/// output : `MessageCallOutput`
+///     Output of the message call
+pub fn process_message_call<S: StateBackend, I: Inspector>(
+    message: Message,
+    env: &mut Environment<S, I>,
+) -> Result<MessageCallOutput> {
+    let gas = message.gas.clone();
+    let result = message_call(message, env);
+    match &result {
+        Ok(output) => env.inspector.message_call_end(&(gas - &output.gas_left), &output.output, output.error.as_ref()),
+        Err(error) => env.inspector.message_call_end(&gas, &[], Some(error)),
+    }
+    result
+}
+
+fn message_call<S: StateBackend, I: Inspector>(
+    message: Message,
+    env: &mut Environment<S, I>,
+) -> Result<MessageCallOutput> {
+    let output = if message.target.is_none() {
+        let is_collision = account_has_code_or_nonce(&env.state, &message.current_target)?;
+        if is_collision {
+            MessageCallOutput {
+                gas_left: Uint::from(0u8),
+                refund_counter: U256::from(0u8),
+                logs: Vec::new(),
+                accounts_to_delete: HashSet::new(),
+                output: Bytes::default(),
+                error: Some(EvmError::Halt),
+            }
+        } else {
+            finish_message_call(process_create_message(message, env)?)
+        }
+    } else {
+        finish_message_call(process_message(message, env)?)
+    };
+    Ok(output)
+}
+
+fn finish_message_call<S, I>(evm: Evm<S, I>) -> MessageCallOutput {
+    if evm.error.is_some() {
+        MessageCallOutput {
+            gas_left: evm.gas_left,
+            refund_counter: U256::from(0u8),
+            logs: Vec::new(),
+            accounts_to_delete: HashSet::new(),
+            output: evm.output,
+            error: evm.error,
+        }
+    } else {
+        let self_destruct_refund = REFUND_SELF_DESTRUCT() * evm.accounts_to_delete.len();
+        let refund_counter = evm.refund_counter + U256::try_from(self_destruct_refund).unwrap();
+        MessageCallOutput {
+            gas_left: evm.gas_left,
+            refund_counter,
+            logs: evm.logs,
+            accounts_to_delete: evm.accounts_to_delete,
+            output: evm.output,
+            error: None,
+        }
+    }
+}
+
+/// Executes a call to create a smart contract.
+///
+/// Parameters
+/// ----------
+/// message :
+///     Transaction specific items.
+/// env :
+///     External items required for EVM execution.
+///
+/// Returns
+/// -------
+/// evm: `ethereum.frontier.vm.Evm`
+///     Items containing execution specific objects.
+pub fn process_create_message<S: StateBackend, I: Inspector>(
+    message: Message,
+    env: &mut Environment<S, I>,
+) -> Result<Evm<'_, S, I>> {
+    if message.depth > STACK_DEPTH_LIMIT() {
+        return Err(EvmError::StackDepthLimit);
+    }
+    let mut evm = new_evm(message, env);
+
+    // take snapshot of state before processing the message
+    begin_transaction(&mut evm.env.state);
+
+    if let Err(error) = process_frame(&mut evm) {
+        rollback_transaction(&mut evm.env.state);
+        return Err(error);
+    }
+    if evm.error.is_none() {
+        let contract_code = evm.output.clone();
+        let contract_code_gas = Uint::from(contract_code.len()) * GAS_CODE_DEPOSIT();
+        let deposited = match charge_gas(&mut evm, contract_code_gas) {
+            Ok(()) => set_code(&mut evm.env.state, evm.message.current_target, contract_code).map_err(EvmError::from),
+            Err(EvmError::State(error)) => Err(EvmError::State(error)),
+            Err(_) => {
+                evm.output = Bytes::default();
+                Ok(())
+            }
+        };
+        if let Err(error) = deposited {
+            rollback_transaction(&mut evm.env.state);
+            end_frame(&mut evm, Some(&error));
+            return Err(error);
+        }
+        commit_transaction(&mut evm.env.state);
+    } else {
+        rollback_transaction(&mut evm.env.state);
+    }
+    let error = evm.error.take();
+    end_frame(&mut evm, error.as_ref());
+    evm.error = error;
+    Ok(evm)
+}
+
+/// Executes a call to create a smart contract.
+///
+/// Parameters
+/// ----------
+/// message :
+///     Transaction specific items.
+/// env :
+///     External items required for EVM execution.
+///
+/// Returns
+/// -------
+/// evm: `ethereum.frontier.vm.Evm`
+///     Items containing execution specific objects
+pub fn process_message<S: StateBackend, I: Inspector>(
+    message: Message,
+    env: &mut Environment<S, I>,
+) -> Result<Evm<'_, S, I>> {
+    if message.depth > STACK_DEPTH_LIMIT() {
+        return Err(EvmError::StackDepthLimit);
+    }
+    let mut evm = new_evm(message, env);
+    process_frame(&mut evm)?;
+    if evm.message.target.is_some() {
+        let error = evm.error.take();
+        end_frame(&mut evm, error.as_ref());
+        evm.error = error;
+    }
+    Ok(evm)
+}
+
+/// Run the message of `evm` in a transaction of its own, committed if the
+/// message succeeds and rolled back otherwise. The transaction is rolled
+/// back as well when the state backend fails, since that error ends the
+/// execution without any of the frames above getting to finish.
+fn process_frame<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
+    // take snapshot of state before processing the message
+    begin_transaction(&mut evm.env.state);
+
+    if let Err(error) = transfer_value(evm).and_then(|()| execute_frame(evm)) {
+        rollback_transaction(&mut evm.env.state);
+        return Err(error);
+    }
+    if evm.error.is_some() {
+        // revert state to the last saved checkpoint
+        // since the message call resulted in an error
+        rollback_transaction(&mut evm.env.state);
+    } else {
+        commit_transaction(&mut evm.env.state);
+    }
+    Ok(())
+}
+
+fn transfer_value<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
+    let message = &evm.message;
+    touch_account(&mut evm.env.state, message.current_target)?;
+
+    if message.value != U256::from(0u8) {
+        move_ether(
+            &mut evm.env.state,
+            message.caller,
+            message.current_target,
+            message.value,
+        )?;
+    }
+    Ok(())
+}
+
+/// Executes bytecode present in the `message`.
+///
+/// Parameters
+/// ----------
+/// message :
+///     Transaction specific items.
+/// env :
+///     External items required for EVM execution.
+///
+/// Returns
+/// -------
+/// evm: `ethereum.vm.EVM`
+///     Items containing execution specific objects
+pub fn execute_code<S: StateBackend, I: Inspector>(
+    message: Message,
+    env: &mut Environment<S, I>,
+) -> Result<Evm<'_, S, I>> {
+    let mut evm = new_evm(message, env);
+    execute_frame(&mut evm)?;
+    Ok(evm)
+}
+
+fn new_evm<S: StateBackend, I: Inspector>(message: Message, env: &mut Environment<S, I>) -> Evm<'_, S, I> {
+    let code = message.code.clone();
+    let valid_jump_destinations = cached_jump_destinations(&code, message.code_hash.as_ref());
+
+    Evm {
+        pc: 0,
+        stack: Stack::new(),
+        memory: Memory::new(),
+        code,
+        gas_left: message.gas.clone(),
+        env,
+        valid_jump_destinations,
+        logs: Vec::new(),
+        refund_counter: U256::from(0u8),
+        running: true,
+        message,
+        output: Bytes::default(),
+        accounts_to_delete: HashSet::new(),
+        error: None,
+    }
+}
+
+fn execute_frame<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
+    if evm.message.target.is_some() {
+        inspect(evm, |inspector, frame| inspector.call(frame));
+    } else {
+        inspect(evm, |inspector, frame| inspector.create(frame));
+    }
+
+    match run(evm) {
+        Ok(()) => {}
+        Err(EvmError::State(error)) => {
+            let error = EvmError::State(error);
+            end_frame(evm, Some(&error));
+            return Err(error);
+        }
+        Err(error) => {
+            inspect(evm, |inspector, frame| inspector.error(frame, &error));
+            evm.gas_left = Uint::from(0u8);
+            evm.error = Some(error);
+        }
+    }
+    Ok(())
+}
+
+/// Exit the frame of `evm` through the hook matching the one it was entered
+/// through.
+fn end_frame<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>, error: Option<&EvmError>) {
+    if evm.message.target.is_some() {
+        inspect(evm, |inspector, frame| inspector.call_end(frame, error));
+    } else {
+        inspect(evm, |inspector, frame| inspector.create_end(frame, error));
+    }
+}
+
+fn run<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
+    if let Some(contract) = evm.message.code_address.as_ref().and_then(pre_compiled_contract) {
+        return contract(evm);
+    }
+
+    while evm.running && evm.pc < evm.code.len() {
+        let op = evm.code[evm.pc];
+        inspect(evm, |inspector, frame| inspector.step(frame, op));
+        let op = Ops::from_byte(op).ok_or(EvmError::InvalidOpcode)?;
+        op_implementation(op)(evm)?;
+        inspect(evm, |inspector, frame| inspector.step_end(frame));
+    }
+
+    // Running off the end of the code is an implicit `STOP`.
+    if evm.running && !evm.code.is_empty() {
+        inspect(evm, |inspector, frame| inspector.step(frame, Ops::STOP as u8));
+        inspect(evm, |inspector, frame| inspector.step_end(frame));
+    }
+    Ok(())
+}
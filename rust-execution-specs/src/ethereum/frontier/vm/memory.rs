@@ -10,11 +10,103 @@
 //!
 //! EVM memory operations.
 
+use std::ops::Deref;
+
+use super::gas::{calculate_memory_gas_cost, ExtendMemory};
 use crate::ethereum::{
-    base_types::{Bytes, U256},
-    utils::byte::right_pad_zero_bytes,
+    base_types::{Bytes, Uint, U256},
+    utils::{byte::right_pad_zero_bytes, numeric::ceil32},
 };
 
+/// Memory is allocated in pages of this many bytes, so that most expansions
+/// do not reallocate.
+pub const PAGE_SIZE: usize = 4096;
+
+/// The memory of an EVM frame. It is only ever expanded by whole words, after
+/// the gas for the expansion has been charged.
+///
+/// It dereferences to its contents.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Memory {
+    bytes: Vec<u8>,
+}
+
+impl Memory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The gas to charge, and the number of bytes to grow the memory by, for
+    /// the accesses in `extensions`, each a start position and a size.
+    ///
+    /// Memory is always a whole number of words, so accesses which end
+    /// within it cost nothing and are checked without arithmetic on `Uint`.
+    pub fn extend_cost(&self, extensions: &[(U256, U256)]) -> ExtendMemory {
+        let within_memory = extensions.iter().all(|(start_position, size)| {
+            size.is_zero()
+                || start_position
+                    .checked_add(*size)
+                    .is_some_and(|end| end <= U256::from(self.bytes.len()))
+        });
+        if within_memory {
+            return ExtendMemory {
+                cost: Uint::from(0u8),
+                expand_by: Uint::from(0u8),
+            };
+        }
+
+        let mut size_to_extend = Uint::from(0u8);
+        let mut to_be_paid = Uint::from(0u8);
+        let mut current_size = Uint::from(self.bytes.len());
+
+        for (start_position, size) in extensions {
+            if size.is_zero() {
+                continue;
+            }
+
+            let before_size = ceil32(current_size.clone());
+            let after_size = ceil32(Uint::from(*start_position) + Uint::from(*size));
+            if after_size <= before_size {
+                continue;
+            }
+
+            size_to_extend += &after_size - &before_size;
+            let already_paid = calculate_memory_gas_cost(before_size);
+            let total_cost = calculate_memory_gas_cost(after_size.clone());
+            to_be_paid += total_cost - already_paid;
+
+            current_size = after_size;
+        }
+
+        ExtendMemory {
+            cost: to_be_paid,
+            expand_by: size_to_extend,
+        }
+    }
+
+    /// Grow the memory by `expand_by` zero bytes, as computed by
+    /// `extend_cost`.
+    pub fn expand(&mut self, expand_by: &Uint) {
+        let expand_by = usize::try_from(expand_by).unwrap();
+        self.resize(self.bytes.len() + expand_by);
+    }
+
+    fn resize(&mut self, size: usize) {
+        if size > self.bytes.capacity() {
+            self.bytes.reserve_exact(size.next_multiple_of(PAGE_SIZE) - self.bytes.len());
+        }
+        self.bytes.resize(size, 0);
+    }
+}
+
+impl Deref for Memory {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
 /// Writes to memory.
 ///
 /// Parameters
@@ -25,15 +117,17 @@ use crate::ethereum::{
 ///     Starting pointer to the memory.
 /// value :
 ///     Data to write to memory.
-pub fn memory_write(memory: &mut Vec<u8>, start_position: U256, value: Bytes) {
+pub fn memory_write(memory: &mut Memory, start_position: U256, value: Bytes) {
+    if value.is_empty() {
+        return;
+    }
     let start_position = usize::try_from(start_position).unwrap();
 
     if memory.len() < start_position + value.len() {
-        let missing = start_position + value.len() - memory.len();
-        memory.extend(std::iter::repeat(0).take(missing));
+        memory.resize(start_position + value.len());
     }
 
-    memory[start_position..(start_position + value.len())].copy_from_slice(&value);
+    memory.bytes[start_position..(start_position + value.len())].copy_from_slice(&value);
 }
 
 /// Read bytes from memory.
@@ -52,8 +146,11 @@ pub fn memory_write(memory: &mut Vec<u8>, start_position: U256, value: Bytes) {
 /// data_bytes :
 ///     Data read from memory.
 pub fn memory_read_bytes(memory: &[u8], start_position: U256, size: U256) -> &[u8] {
-    let start_position = usize::try_from(start_position).unwrap();
     let size = usize::try_from(size).unwrap();
+    if size == 0 {
+        return &[];
+    }
+    let start_position = usize::try_from(start_position).unwrap();
     &memory[start_position..(start_position + size)]
 }
 
@@ -73,13 +170,11 @@ pub fn memory_read_bytes(memory: &[u8], start_position: U256, size: U256) -> &[u
 /// data_bytes :
 ///     Data read from memory.
 pub fn buffer_read(buffer: Bytes, start_position: U256, size: U256) -> Bytes {
-    let start_position = usize::try_from(start_position).unwrap();
     let size = usize::try_from(size).unwrap();
+    let start = usize::try_from(start_position)
+        .unwrap_or(usize::MAX)
+        .min(buffer.len());
+    let end = start.saturating_add(size).min(buffer.len());
 
-    right_pad_zero_bytes(
-        buffer[start_position..(start_position + size)]
-            .to_vec()
-            .into_boxed_slice(),
-        size.into(),
-    )
+    right_pad_zero_bytes(buffer[start..end].to_vec().into_boxed_slice(), size.into())
 }
@@ -1,19 +1,35 @@
 use std::collections::HashSet;
+use std::sync::Arc;
 
 use super::{
     fork_types::{Address, Hash32, Log},
-    state::State,
+    state::{State, StateBackend},
 };
 use crate::ethereum::base_types::{Bytes, Uint, U256};
+use exceptions::EvmError;
+use inspector::Inspector;
+use memory::Memory;
+use runtime::JumpDestinations;
+use stack::Stack;
 
 pub mod instructions;
 pub mod exceptions;
 pub mod gas;
+pub mod gas_profiler;
+pub mod inspector;
+pub mod interpreter;
 pub mod memory;
+pub mod precompiled_contracts;
+pub mod runtime;
 pub mod stack;
+pub mod trace;
+pub mod call_tracer;
 
 /// Items external to the virtual machine itself, provided by the environment.
-pub struct Environment {
+///
+/// The state can be any `StateBackend`, and defaults to the in-memory `State`.
+/// The inspector defaults to `()`, which does nothing.
+pub struct Environment<S = State, I = ()> {
     pub caller: Address,
     pub block_hashes: Vec<Hash32>,
     pub origin: Address,
@@ -23,7 +39,9 @@ pub struct Environment {
     pub gas_price: U256,
     pub time: U256,
     pub difficulty: Uint,
-    pub state: State,
+    pub state: S,
+    /// Receives the hooks of the execution.
+    pub inspector: I,
 }
 
 /// Items that are used by contract creation or message call.
@@ -31,30 +49,65 @@ pub struct Message {
     pub caller: Address,
     pub target: Option<Address>,
     pub current_target: Address,
-    pub gas: U256,
+    pub gas: Uint,
     pub value: U256,
     pub data: Bytes,
     pub code_address: Option<Address>,
     pub code: Bytes,
+    /// The hash of `code`, when it is known without hashing the code, which
+    /// keys the cache of its jump destinations.
+    pub code_hash: Option<Hash32>,
     pub depth: Uint,
+    /// Whether the message was sent by `CALLCODE`, running the code at
+    /// `code_address` on the account of `current_target`.
+    pub is_callcode: bool,
 }
 
 /// The internal state of the virtual machine.
-pub struct Evm {
+///
+/// Child frames borrow the `Environment` of their parent for as long as they
+/// run.
+pub struct Evm<'a, S = State, I = ()> {
     pub pc: usize,
-    pub stack: Vec<U256>,
-    pub memory: Vec<u8>,
+    pub stack: Stack,
+    pub memory: Memory,
     pub code: Bytes,
-    pub gas_left: U256,
-    pub env: Environment,
-    pub valid_jump_destinations: HashSet<U256>,
+    pub gas_left: Uint,
+    pub env: &'a mut Environment<S, I>,
+    pub valid_jump_destinations: Arc<JumpDestinations>,
     pub logs: Vec<Log>,
     pub refund_counter: U256,
     pub running: bool,
     pub message: Message,
     pub output: Bytes,
     pub accounts_to_delete: HashSet<Address>,
-    pub has_erred: bool,
+    pub error: Option<EvmError>,
+}
+
+/// What is left of a child `Evm` once it has run, and no longer borrows the
+/// `Environment`.
+pub struct ChildEvm {
+    pub gas_left: Uint,
+    pub logs: Vec<Log>,
+    pub refund_counter: U256,
+    pub message: Message,
+    pub output: Bytes,
+    pub accounts_to_delete: HashSet<Address>,
+    pub error: Option<EvmError>,
+}
+
+impl<S, I> From<Evm<'_, S, I>> for ChildEvm {
+    fn from(evm: Evm<'_, S, I>) -> Self {
+        Self {
+            gas_left: evm.gas_left,
+            logs: evm.logs,
+            refund_counter: evm.refund_counter,
+            message: evm.message,
+            output: evm.output,
+            accounts_to_delete: evm.accounts_to_delete,
+            error: evm.error,
+        }
+    }
 }
 
 /// Incorporate the state of a successful `child_evm` into the parent `evm`.
@@ -66,7 +119,7 @@ pub struct Evm {
 /// child_evm :
 ///     The child evm to incorporate.
 ///
-pub fn incorporate_child_on_success(evm: &mut Evm, child_evm: &Evm) {
+pub fn incorporate_child_on_success<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>, child_evm: &ChildEvm) {
     evm.gas_left += &child_evm.gas_left;
     evm.logs.extend(child_evm.logs.clone());
     evm.refund_counter += &child_evm.refund_counter;
@@ -83,6 +136,6 @@ pub fn incorporate_child_on_success(evm: &mut Evm, child_evm: &Evm) {
 /// child_evm :
 ///     The child evm to incorporate.
 ///
-pub fn incorporate_child_on_error(evm: &mut Evm, child_evm: &Evm) {
+pub fn incorporate_child_on_error<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>, child_evm: &ChildEvm) {
     evm.gas_left += &child_evm.gas_left;
 }
@@ -1,33 +1,31 @@
-// /// 
-// /// Ethereum Virtual Machine (EVM) IDENTITY PRECOMPILED CONTRACT
-// /// ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
-// /// 
-// /// .. contents:: Table of Contents
-// ///     :backlinks: none
-// ///     :local:
-// /// 
-// /// Introduction
-// /// ------------
-// /// 
-// /// Implementation of the `IDENTITY` precompiled contract.
-// /// 
-// use ::ethereum::base_types::{Uint};
-// use ::ethereum::utils::numeric::{ceil32};
-// use super::super::super::vm::{Evm};
-// use super::super::super::vm::gas::{GAS_IDENTITY, GAS_IDENTITY_WORD, charge_gas};
-// /// 
-// ///     Writes the message data to output.
-// /// 
-// ///     Parameters
-// ///     ----------
-// ///     evm :
-// ///         The current EVM frame.
-// ///     
-// pub fn identity(evm: Evm) -> Result<(), Error> {
-//     data = evm.message.data;
-//     word_count = (ceil32(Uint(len(data)?)?)?).floordiv(32);
-//     charge_gas(evm, GAS_IDENTITY + GAS_IDENTITY_WORD * word_count)?;
-//     evm.output = data;
-// }
+//! Ethereum Virtual Machine (EVM) IDENTITY PRECOMPILED CONTRACT
+//! ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+//!
+//! .. contents:: Table of Contents
+//!     :backlinks: none
+//!     :local:
+//!
+//! Introduction
+//! ------------
+//!
+//! Implementation of the `IDENTITY` precompiled contract.
 
+use super::super::{exceptions::Result, gas, Evm};
+use crate::ethereum::frontier::vm::inspector::Inspector;
+use crate::ethereum::base_types::Uint;
+use crate::ethereum::frontier::state::StateBackend;
+use crate::ethereum::utils::numeric::ceil32;
 
+/// Writes the message data to output.
+///
+/// Parameters
+/// ----------
+/// evm :
+///     The current EVM frame.
+pub fn identity<S: StateBackend, I: Inspector>(evm: &mut Evm<S, I>) -> Result<()> {
+    let data = evm.message.data.clone();
+    let word_count = ceil32(Uint::from(data.len())) / Uint::from(32u8);
+    gas::charge_gas(evm, gas::GAS_IDENTITY() + gas::GAS_IDENTITY_WORD() * word_count)?;
+    evm.output = data;
+    Ok(())
+}
@@ -1,21 +1,32 @@
-// /// 
-// /// Precompiled Contract Addresses
-// /// ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
-// /// 
-// /// .. contents:: Table of Contents
-// ///     :backlinks: none
-// ///     :local:
-// /// 
-// /// Introduction
-// /// ------------
-// /// 
-// /// Mapping of precompiled contracts their implementations.
-// /// 
-// use ::typing::{Callable, Dict};
-// use super::super::super::fork_types::{Address};
-// use super::::{ECRECOVER_ADDRESS, IDENTITY_ADDRESS, RIPEMD160_ADDRESS, SHA256_ADDRESS};
-// use super::ecrecover::{ecrecover};
-// use super::identity::{identity};
-// use super::ripemd160::{ripemd160};
-// use super::sha256::{sha256};
-// // TypedAssignment unsupported
+//! Precompiled Contract Addresses
+//! ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+//!
+//! .. contents:: Table of Contents
+//!     :backlinks: none
+//!     :local:
+//!
+//! Introduction
+//! ------------
+//!
+//! Mapping of precompiled contracts their implementations.
+
+use super::super::instructions::Implementation;
+use super::{identity::identity, IDENTITY_ADDRESS};
+use crate::ethereum::frontier::vm::inspector::Inspector;
+use crate::ethereum::frontier::fork_types::Address;
+use crate::ethereum::frontier::state::StateBackend;
+
+/// The implementation of the precompiled contract at `address`, if there is
+/// one.
+///
+/// `ECRECOVER`, `SHA256` and `RIPEMD160` (see `ecrecover.rs`, `sha256.rs` and
+/// `ripemd160.rs`) are not ported yet, as they need cryptography this crate
+/// does not have.
+pub fn pre_compiled_contract<S: StateBackend, I: Inspector>(
+    address: &Address,
+) -> Option<Implementation<S, I>> {
+    match *address {
+        IDENTITY_ADDRESS => Some(identity),
+        _ => None,
+    }
+}
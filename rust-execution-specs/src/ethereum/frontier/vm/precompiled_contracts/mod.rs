@@ -1,20 +1,29 @@
-// /// 
-// /// Precompiled Contract Addresses
-// /// ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
-// /// 
-// /// .. contents:: Table of Contents
-// ///     :backlinks: none
-// ///     :local:
-// /// 
-// /// Introduction
-// /// ------------
-// /// 
-// /// Addresses of precompiled contracts and mappings to their
-// /// implementations.
-// /// 
-// use super::super::super::utils::hexadecimal::{hex_to_address};
-// __all__ = ("ECRECOVER_ADDRESS", "SHA256_ADDRESS", "RIPEMD160_ADDRESS", "IDENTITY_ADDRESS");
-// ECRECOVER_ADDRESS = hex_to_address("0x01")?;
-// SHA256_ADDRESS = hex_to_address("0x02")?;
-// RIPEMD160_ADDRESS = hex_to_address("0x03")?;
-// IDENTITY_ADDRESS = hex_to_address("0x04")?;
+//! Precompiled Contract Addresses
+//! ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+//!
+//! .. contents:: Table of Contents
+//!     :backlinks: none
+//!     :local:
+//!
+//! Introduction
+//! ------------
+//!
+//! Addresses of precompiled contracts and mappings to their
+//! implementations.
+
+use super::super::fork_types::Address;
+
+pub mod identity;
+pub mod mapping;
+
+/// The address of a precompiled contract numbered `number`.
+const fn precompile_address(number: u8) -> Address {
+    let mut address = [0; 20];
+    address[19] = number;
+    address
+}
+
+pub const ECRECOVER_ADDRESS: Address = precompile_address(0x01);
+pub const SHA256_ADDRESS: Address = precompile_address(0x02);
+pub const RIPEMD160_ADDRESS: Address = precompile_address(0x03);
+pub const IDENTITY_ADDRESS: Address = precompile_address(0x04);
@@ -1,55 +1,173 @@
-// /// 
-// /// Ethereum Virtual Machine (EVM) Runtime Operations
-// /// ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
-// /// 
-// /// .. contents:: Table of Contents
-// ///     :backlinks: none
-// ///     :local:
-// /// 
-// /// Introduction
-// /// ------------
-// /// 
-// /// Runtime related operations used while executing EVM code.
-// /// 
-// use ::typing::{Set};
-// use ::ethereum::base_types::{Uint};
-// use super::instructions::{Ops};
-// /// 
-// ///     Analyze the evm code to obtain the set of valid jump destinations.
-// /// 
-// ///     Valid jump destinations are defined as follows:
-// ///         * The jump destination is less than the length of the code.
-// ///         * The jump destination should have the `JUMPDEST` opcode (0x5B).
-// ///         * The jump destination shouldn't be part of the data corresponding to
-// ///           `PUSH-N` opcodes.
-// /// 
-// ///     Note - Jump destinations are 0-indexed.
-// /// 
-// ///     Parameters
-// ///     ----------
-// ///     code :
-// ///         The EVM code which is to be executed.
-// /// 
-// ///     Returns
-// ///     -------
-// ///     valid_jump_destinations: `Set[Uint]`
-// ///         The set of valid jump destinations in the code.
-// ///     
-// pub fn get_valid_jump_destinations(code: bytes) -> Result<Set[Uint], Error> {
-//     valid_jump_destinations = set()?;
-//     pc = Uint(0)?;
-//     while pc < len(code)? {
-//         // Try 
-//             current_opcode = Ops(code[pc])?;
-//         if current_opcode == Ops.JUMPDEST {
-//             valid_jump_destinations.add(pc)?;
-//         } else if Ops.PUSH1.value <= current_opcode.value <= Ops.PUSH32.value {
-//             push_data_size = current_opcode.value - Ops.PUSH1.value + 1;
-//             pc += push_data_size;
-//         }
-//         pc += 1;
-//     }
-//     return Ok(valid_jump_destinations);
-// }
+//! Ethereum Virtual Machine (EVM) Runtime Operations
+//! ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+//!
+//! .. contents:: Table of Contents
+//!     :backlinks: none
+//!     :local:
+//!
+//! Introduction
+//! ------------
+//!
+//! Runtime related operations used while executing EVM code.
 
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, OnceLock};
 
+use super::instructions::Ops;
+use crate::ethereum::base_types::U256;
+use crate::ethereum::frontier::fork_types::Hash32;
+
+/// Number of codes whose jump destinations are cached, before the least
+/// recently run is forgotten.
+pub const JUMP_DESTINATIONS_CACHE_SIZE: usize = 4096;
+
+/// The valid jump destinations of some code, as a bitmap with one bit per
+/// byte of code.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct JumpDestinations {
+    bitmap: Vec<u64>,
+}
+
+impl JumpDestinations {
+    /// Whether `destination` is a valid jump destination.
+    pub fn contains(&self, destination: &U256) -> bool {
+        match usize::try_from(*destination) {
+            Ok(pc) => self.bitmap.get(pc / 64).is_some_and(|word| word & (1 << (pc % 64)) != 0),
+            Err(_) => false,
+        }
+    }
+
+    /// The valid jump destinations, in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.bitmap.len() * 64).filter(|pc| self.bitmap[pc / 64] & (1 << (pc % 64)) != 0)
+    }
+
+    fn insert(&mut self, pc: usize) {
+        self.bitmap[pc / 64] |= 1 << (pc % 64);
+    }
+}
+
+/// Analyze the evm code to obtain the set of valid jump destinations.
+///
+/// Valid jump destinations are defined as follows:
+///     * The jump destination is less than the length of the code.
+///     * The jump destination should have the `JUMPDEST` opcode (0x5B).
+///     * The jump destination shouldn't be part of the data corresponding to
+///       `PUSH-N` opcodes.
+///
+/// Note - Jump destinations are 0-indexed.
+///
+/// Parameters
+/// ----------
+/// code :
+///     The EVM code which is to be executed.
+///
+/// Returns
+/// -------
+/// valid_jump_destinations: `JumpDestinations`
+///     The set of valid jump destinations in the code.
+pub fn get_valid_jump_destinations(code: &[u8]) -> JumpDestinations {
+    let mut valid_jump_destinations = JumpDestinations {
+        bitmap: vec![0; code.len().div_ceil(64)],
+    };
+    let mut pc = 0;
+    while pc < code.len() {
+        let current_opcode = code[pc];
+        if current_opcode == Ops::JUMPDEST as u8 {
+            valid_jump_destinations.insert(pc);
+        } else if (Ops::PUSH1 as u8..=Ops::PUSH32 as u8).contains(&current_opcode) {
+            let push_data_size = usize::from(current_opcode - Ops::PUSH1 as u8) + 1;
+            pc += push_data_size;
+        }
+        pc += 1;
+    }
+    valid_jump_destinations
+}
+
+/// The valid jump destinations of the codes most recently run, keyed by
+/// code hash, holding at most `capacity` codes.
+///
+/// When it is full, the code run least recently is forgotten.
+pub struct JumpDestinationsCache {
+    capacity: usize,
+    entries: HashMap<Hash32, (u64, Arc<JumpDestinations>)>,
+    recency: BTreeMap<u64, Hash32>,
+    clock: u64,
+}
+
+impl JumpDestinationsCache {
+    /// Create an empty cache holding at most `capacity` codes.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            clock: 0,
+        }
+    }
+
+    /// The number of codes held.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no code is held.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The valid jump destinations of `code`, whose hash is `code_hash`,
+    /// analyzing the code unless it is held.
+    pub fn get(&mut self, code_hash: &Hash32, code: &[u8]) -> Arc<JumpDestinations> {
+        self.clock += 1;
+        if let Some((last_used, valid_jump_destinations)) = self.entries.get_mut(code_hash) {
+            self.recency.remove(last_used);
+            self.recency.insert(self.clock, *code_hash);
+            *last_used = self.clock;
+            return valid_jump_destinations.clone();
+        }
+        if self.entries.len() >= self.capacity {
+            if let Some((_, least_recent)) = self.recency.pop_first() {
+                self.entries.remove(&least_recent);
+            }
+        }
+        let valid_jump_destinations = Arc::new(get_valid_jump_destinations(code));
+        self.entries.insert(*code_hash, (self.clock, valid_jump_destinations.clone()));
+        self.recency.insert(self.clock, *code_hash);
+        valid_jump_destinations
+    }
+}
+
+/// The valid jump destinations of `code`, analyzed once per distinct code
+/// and shared by every frame that runs it, in any transaction.
+///
+/// Codes are told apart by `code_hash`, so that the code is never hashed
+/// here. Code without a known hash, such as the initialization code of a
+/// contract creation, is analyzed every time.
+///
+/// Parameters
+/// ----------
+/// code :
+///     The EVM code which is to be executed.
+/// code_hash :
+///     The hash of `code`, if known.
+///
+/// Returns
+/// -------
+/// valid_jump_destinations: `Arc<JumpDestinations>`
+///     The set of valid jump destinations in the code.
+pub fn cached_jump_destinations(code: &[u8], code_hash: Option<&Hash32>) -> Arc<JumpDestinations> {
+    static CACHE: OnceLock<Mutex<JumpDestinationsCache>> = OnceLock::new();
+
+    if code.is_empty() {
+        return Arc::default();
+    }
+    match code_hash {
+        Some(code_hash) => CACHE
+            .get_or_init(|| Mutex::new(JumpDestinationsCache::new(JUMP_DESTINATIONS_CACHE_SIZE)))
+            .lock()
+            .unwrap()
+            .get(code_hash, code),
+        None => Arc::new(get_valid_jump_destinations(code)),
+    }
+}
@@ -10,9 +10,72 @@
 //!
 //! Implementation of the stack operators for the EVM.
 
+use std::ops::Deref;
+
 use super::exceptions::{EvmError, Result};
 use crate::ethereum::base_types::U256;
 
+/// Maximum number of items on the stack.
+pub const STACK_LIMIT: usize = 1024;
+
+/// The EVM stack of at most `STACK_LIMIT` items. Its allocation grows with
+/// the items pushed, so that the many frames which use a few items allocate
+/// a few items.
+///
+/// It dereferences to its items, bottom first.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Stack {
+    items: Vec<U256>,
+}
+
+impl Stack {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pushes `value` on top of the stack.
+    pub fn push(&mut self, value: U256) -> Result<()> {
+        if self.items.len() == STACK_LIMIT {
+            return Err(EvmError::StackOverflow);
+        }
+        self.items.push(value);
+        Ok(())
+    }
+
+    /// Pops the top item off the stack.
+    pub fn pop(&mut self) -> Result<U256> {
+        self.items.pop().ok_or(EvmError::StackUnderflow)
+    }
+
+    /// The `n`th item from the top of the stack, the top being `0`.
+    pub fn peek(&self, n: usize) -> Result<&U256> {
+        let index = self.items.len().checked_sub(n + 1).ok_or(EvmError::StackUnderflow)?;
+        Ok(&self.items[index])
+    }
+
+    /// Pushes a copy of the `n`th item from the top of the stack.
+    pub fn dup(&mut self, n: usize) -> Result<()> {
+        let value = *self.peek(n)?;
+        self.push(value)
+    }
+
+    /// Swaps the top of the stack with the `n`th item from the top.
+    pub fn swap(&mut self, n: usize) -> Result<()> {
+        let index = self.items.len().checked_sub(n + 1).ok_or(EvmError::StackUnderflow)?;
+        let top = self.items.len() - 1;
+        self.items.swap(index, top);
+        Ok(())
+    }
+}
+
+impl Deref for Stack {
+    type Target = [U256];
+
+    fn deref(&self) -> &[U256] {
+        &self.items
+    }
+}
+
 /// Pops the top item off of `stack`.
 ///
 /// Parameters
@@ -24,14 +87,19 @@ use crate::ethereum::base_types::U256;
 /// -------
 /// value : `U256`
 ///     The top element on the stack.
-pub fn pop(stack: &mut Vec<U256>) -> Result<U256> {
-    stack.pop().ok_or(EvmError::StackUnderflow)
+pub fn pop(stack: &mut Stack) -> Result<U256> {
+    stack.pop()
 }
 
-pub fn push(stack: &mut Vec<U256>, value: U256) -> Result<()> {
-    if stack.len() == 1024 {
-        return Err(EvmError::StackOverflow);
-    }
-    stack.push(value);
-    Ok(())
+/// Pushes an item onto `stack`.
+///
+/// Parameters
+/// ----------
+/// stack :
+///     EVM stack.
+///
+/// value :
+///     Item to be pushed onto `stack`.
+pub fn push(stack: &mut Stack, value: U256) -> Result<()> {
+    stack.push(value)
 }
@@ -0,0 +1,151 @@
+//! Ethereum Virtual Machine (EVM) Tracing
+//! ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+//!
+//! .. contents:: Table of Contents
+//!     :backlinks: none
+//!     :local:
+//!
+//! Introduction
+//! ------------
+//!
+//! An `Inspector` writing the JSON lines of `EIP-3155
+//! <https://eips.ethereum.org/EIPS/eip-3155>`_, for diffing executions
+//! against other clients such as geth and evmone.
+//!
+//! There is one line per executed opcode, written as soon as the opcode is
+//! done, followed by a summary line once the message call is over, however
+//! it ended:
+//!
+//! ```json
+//! {"depth":1,"gas":"0x2710","gasCost":"0x3","memSize":0,"op":96,"opName":"PUSH1","pc":0,"refund":0,"stack":[]}
+//! {"gasUsed":"0x9","output":"0x"}
+//! ```
+
+use std::{fmt::Write as _, io::Write};
+
+use num_traits::ToPrimitive;
+use serde_json::{json, Value};
+
+use super::{
+    exceptions::EvmError,
+    inspector::{Frame, Inspector},
+    instructions::Ops,
+};
+use crate::ethereum::base_types::{Uint, U256};
+
+///
+/// Writes an EIP-3155 trace of each message call to `output`.
+///
+pub struct Eip3155Tracer {
+    output: Box<dyn Write>,
+    /// The line of the opcode being executed, until it is done.
+    pending: Option<Value>,
+    gas_cost_traced: bool,
+}
+
+impl Eip3155Tracer {
+    /// Create a tracer writing JSON lines to `output`.
+    pub fn new(output: impl Write + 'static) -> Self {
+        Self {
+            output: Box::new(output),
+            pending: None,
+            gas_cost_traced: false,
+        }
+    }
+
+    fn write_line(&mut self, line: &Value) {
+        let _ = writeln!(self.output, "{line}");
+    }
+
+    /// Write the line of the opcode being executed, if it has not been
+    /// written yet.
+    fn flush_op(&mut self) {
+        if let Some(line) = self.pending.take() {
+            self.write_line(&line);
+        }
+    }
+
+    fn start_op(&mut self, frame: &Frame, op: u8) {
+        self.flush_op();
+        let name = Ops::from_byte(op).map_or_else(|| "INVALID".to_string(), |op| op.name());
+        let stack = frame.stack.iter().map(|item| format!("{item:#x}")).collect::<Vec<_>>();
+        self.pending = Some(json!({
+            "pc": frame.pc,
+            "op": op,
+            "gas": format!("{:#x}", frame.gas_left),
+            "gasCost": "0x0",
+            "memSize": frame.memory.len(),
+            "stack": stack,
+            "depth": frame.message.depth.to_u64().unwrap_or(u64::MAX).saturating_add(1),
+            "refund": frame.refund_counter.to_u64(),
+            "opName": name,
+        }));
+        self.gas_cost_traced = false;
+    }
+
+    fn gas_and_refund(&mut self, gas_cost: &Uint, refund_counter: &U256) {
+        if self.gas_cost_traced {
+            return;
+        }
+        if let Some(line) = self.pending.as_mut() {
+            line["gasCost"] = json!(format!("{gas_cost:#x}"));
+            line["refund"] = json!(refund_counter.to_u64());
+        }
+        self.gas_cost_traced = true;
+    }
+
+    fn exception(&mut self, error: &EvmError) {
+        if let Some(line) = self.pending.as_mut() {
+            line["error"] = json!(format!("{error:?}"));
+        }
+        self.flush_op();
+    }
+
+    fn transaction_end(&mut self, gas_used: &Uint, output: &[u8], error: Option<&EvmError>) {
+        self.flush_op();
+        let mut hex = String::from("0x");
+        for byte in output {
+            let _ = write!(hex, "{byte:02x}");
+        }
+        let mut summary = json!({
+            "output": hex,
+            "gasUsed": format!("{gas_used:#x}"),
+        });
+        if let Some(error) = error {
+            summary["error"] = json!(format!("{error:?}"));
+        }
+        self.write_line(&summary);
+        let _ = self.output.flush();
+    }
+}
+
+impl Inspector for Eip3155Tracer {
+    fn step(&mut self, frame: &Frame, op: u8) {
+        self.start_op(frame, op);
+    }
+
+    fn step_end(&mut self, _frame: &Frame) {
+        self.flush_op();
+    }
+
+    fn charge_gas(&mut self, frame: &Frame, amount: &Uint) {
+        self.gas_and_refund(amount, frame.refund_counter);
+    }
+
+    fn call(&mut self, _frame: &Frame) {
+        // The opcode starting the call comes before the lines of the call.
+        self.flush_op();
+    }
+
+    fn create(&mut self, _frame: &Frame) {
+        self.flush_op();
+    }
+
+    fn error(&mut self, _frame: &Frame, error: &EvmError) {
+        self.exception(error);
+    }
+
+    fn message_call_end(&mut self, gas_used: &Uint, output: &[u8], error: Option<&EvmError>) {
+        self.transaction_end(gas_used, output, error);
+    }
+}
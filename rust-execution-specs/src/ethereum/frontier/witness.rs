@@ -0,0 +1,278 @@
+//!
+//! # Execution Witnesses
+//!
+//! ## Introduction
+//!
+//! Stateless execution of blocks. Executing a block against a
+//! `WitnessRecorder` notes every account and storage slot it touches, and
+//! `build_witness` then collects the trie nodes and code needed to touch
+//! them again, by proving each of them over the state before the block.
+//!
+//! A `Witness` is turned back into a partial state with
+//! `Witness::into_node_db`. Executing the same block against it gives the
+//! same state root, while touching anything outside of the witness fails
+//! with `EthereumException::InvalidProof` instead of giving a wrong result.
+//!
+
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet, HashMap},
+};
+
+use crate::ethereum::{
+    base_types::{Bytes, Bytes32, U256, U64},
+    exceptions::EthereumException,
+};
+
+use super::{
+    fork::{state_transition, BlockChain},
+    fork_types::{keccak256, Account, Address, Block, Hash32, Root},
+    state::{State, StateBackend},
+    state_backends::{NodeDb, NodeDbState},
+    trie::{dummy_root, Trie},
+    trie_proof::prove_keys,
+};
+
+/// The storage slots touched in each touched account.
+pub type Touched = BTreeMap<Address, BTreeSet<Bytes32>>;
+
+///
+/// The trie nodes and code needed to execute a block, without the rest of
+/// the state.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Witness {
+    pub nodes: Vec<Bytes>,
+    pub codes: Vec<Bytes>,
+}
+
+impl Witness {
+    ///
+    /// Turn the witness into a node database.
+    ///
+    /// Parameters
+    /// ----------
+    /// root :
+    ///     The state root the witness was built against.
+    ///
+    /// Returns
+    /// -------
+    /// node_db : `NodeDb`
+    ///     The partial state, which fails cleanly on nodes it does not have.
+    ///
+    pub fn into_node_db(self, root: Root) -> NodeDb {
+        NodeDb {
+            nodes: self.nodes.into_iter().map(|node| (keccak256(&node), node)).collect(),
+            codes: self.codes.into_iter().map(|code| (keccak256(&code), code)).collect(),
+            root,
+        }
+    }
+}
+
+///
+/// A `StateBackend` that notes every account and storage slot read or
+/// written through it.
+///
+pub struct WitnessRecorder<S> {
+    pub state: S,
+    touched: RefCell<Touched>,
+}
+
+impl<S: StateBackend> WitnessRecorder<S> {
+    /// Start recording the accounts and storage slots touched in `state`.
+    pub fn new(state: S) -> Self {
+        Self {
+            state,
+            touched: RefCell::new(Touched::new()),
+        }
+    }
+
+    /// The accounts and storage slots touched so far.
+    pub fn touched(&self) -> Touched {
+        self.touched.borrow().clone()
+    }
+
+    /// Stop recording, and give back the state and what was touched.
+    pub fn into_parts(self) -> (S, Touched) {
+        (self.state, self.touched.into_inner())
+    }
+
+    fn touch_account(&self, address: &Address) {
+        self.touched.borrow_mut().entry(*address).or_default();
+    }
+
+    fn touch_storage(&self, address: &Address, key: &Bytes32) {
+        self.touched.borrow_mut().entry(*address).or_default().insert(*key);
+    }
+}
+
+impl<S: StateBackend> StateBackend for WitnessRecorder<S> {
+    fn get_account_optional(&self, address: &Address) -> Result<Option<Account>, EthereumException> {
+        self.touch_account(address);
+        self.state.get_account_optional(address)
+    }
+
+    fn set_account(&mut self, address: Address, account: Option<Account>) {
+        self.touch_account(&address);
+        self.state.set_account(address, account);
+    }
+
+    fn destroy_storage(&mut self, address: &Address) {
+        self.touch_account(address);
+        self.state.destroy_storage(address);
+    }
+
+    fn get_storage(&self, address: &Address, key: &Bytes32) -> Result<U256, EthereumException> {
+        self.touch_storage(address, key);
+        self.state.get_storage(address, key)
+    }
+
+    fn set_storage(&mut self, address: Address, key: &Bytes32, value: U256) {
+        self.touch_storage(&address, key);
+        self.state.set_storage(address, key, value);
+    }
+
+    fn get_code_hash(&self, address: &Address) -> Result<Hash32, EthereumException> {
+        self.touch_account(address);
+        self.state.get_code_hash(address)
+    }
+
+    fn begin_transaction(&mut self) {
+        self.state.begin_transaction();
+    }
+
+    fn commit_transaction(&mut self) {
+        self.state.commit_transaction();
+    }
+
+    fn rollback_transaction(&mut self) {
+        self.state.rollback_transaction();
+    }
+
+    fn storage_root(&self, address: &Address) -> Result<Root, EthereumException> {
+        self.state.storage_root(address)
+    }
+
+    fn state_root(&self) -> Result<Root, EthereumException> {
+        self.state.state_root()
+    }
+}
+
+///
+/// Collect the witness for touching `touched` in `state`.
+///
+/// Parameters
+/// ----------
+/// state :
+///     The state before the touches. No transaction may be ongoing.
+/// touched :
+///     The accounts and storage slots touched.
+///
+/// Returns
+/// -------
+/// witness : `Witness`
+///     The proofs of every touched account and storage slot, and the code of
+///     the touched accounts.
+///
+pub fn build_witness(state: &State, touched: &Touched) -> Result<Witness, EthereumException> {
+    let addresses = touched.keys().copied().collect::<Vec<_>>();
+    let mut nodes = prove_keys(state.main_trie(), &addresses, |address: &Address| {
+        state.storage_trie_root(address)
+    })?;
+
+    let empty = Trie::new(true, U256::default());
+    let mut codes = HashMap::new();
+    for (address, keys) in touched {
+        if let Some(Some(account)) = state.main_trie().data.get(address) {
+            if !account.code.is_empty() {
+                codes.insert(keccak256(&account.code), account.code.clone());
+            }
+        }
+        if !keys.is_empty() {
+            let storage_trie = state.storage_trie(address).unwrap_or(&empty);
+            let keys = keys.iter().copied().collect::<Vec<_>>();
+            nodes.extend(prove_keys(storage_trie, &keys, dummy_root)?);
+        }
+    }
+
+    nodes.sort();
+    nodes.dedup();
+    let mut codes = codes.into_values().collect::<Vec<_>>();
+    codes.sort();
+    Ok(Witness { nodes, codes })
+}
+
+///
+/// Apply a block to `chain`, recording the witness needed to apply it again
+/// statelessly.
+///
+/// Parameters
+/// ----------
+/// chain :
+///     History and current state.
+/// block :
+///     Block to apply to `chain`.
+///
+/// Returns
+/// -------
+/// witness : `Witness`
+///     The witness for `block` over the state before it.
+///
+/// The changes of the block are recorded with `State::begin_block`, so that
+/// the witness is built over the state before them without copying it, and
+/// so that a rejected block leaves the chain as it was.
+///
+pub fn state_transition_with_witness(
+    chain: &mut BlockChain,
+    block: Block,
+) -> Result<Witness, EthereumException> {
+    chain.state.begin_block();
+    let mut recording = BlockChain {
+        blocks: std::mem::take(&mut chain.blocks),
+        state: WitnessRecorder::new(std::mem::take(&mut chain.state)),
+        chain_id: chain.chain_id,
+    };
+    let result = state_transition(&mut recording, block);
+
+    let (state, touched) = recording.state.into_parts();
+    chain.blocks = recording.blocks;
+    chain.state = state;
+    if let Err(error) = result {
+        chain.state.rollback_block();
+        return Err(error);
+    }
+
+    chain.state.commit_block_after(|pre_state| build_witness(pre_state, &touched))
+}
+
+///
+/// Build a chain whose state is only the witness of the next block, so that
+/// `state_transition` executes the block statelessly.
+///
+/// Parameters
+/// ----------
+/// blocks :
+///     The recent blocks, the last of which is the parent of the block the
+///     witness is for.
+/// chain_id :
+///     The chain id.
+/// witness :
+///     The witness of the next block.
+///
+/// Returns
+/// -------
+/// chain : `BlockChain<NodeDbState>`
+///     A chain backed by the witness alone.
+///
+pub fn stateless_chain(
+    blocks: Vec<Block>,
+    chain_id: U64,
+    witness: Witness,
+) -> BlockChain<NodeDbState> {
+    let root = blocks.last().expect("parent block").header.state_root;
+    BlockChain {
+        blocks,
+        state: NodeDbState::new(witness.into_node_db(root)),
+        chain_id,
+    }
+}
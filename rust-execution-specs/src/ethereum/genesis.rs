@@ -6,14 +6,14 @@
 //! Functionalities and entities to obtain the genesis configurations for
 //! different chains.
 //!
-use std::{collections::HashMap, todo};
+use std::collections::HashMap;
 
 use num_bigint::BigUint;
 
 use crate::{
     ethereum::{
         frontier::{
-            fork_types::{Hash32, Header},
+            fork_types::{Block, Hash32, Header},
             state::{self, state_root, State},
             trie::{root, Trie, dummy_root},
         },
@@ -67,8 +67,27 @@ pub struct GenesisConfiguration {
 pub fn get_genesis_configuration(
     genesis_file: &str,
 ) -> Result<GenesisConfiguration, EthereumException> {
-    let path = format!("./assets/{genesis_file}");
-    let file = std::fs::read_to_string(&path).map_err(|_| EthereumException::FileNotFound(path))?;
+    load_genesis_configuration(&format!("./assets/{genesis_file}"))
+}
+
+///
+///     Obtain the genesis configuration from a json genesis file anywhere on
+///     disk, in the format of `get_genesis_configuration`.
+///
+///     Parameters
+///     ----------
+///     path :
+///         Path of the json genesis file.
+///
+///     Returns
+///     -------
+///     configuration : `GenesisConfiguration`
+///         The genesis configuration obtained from the json genesis file.
+///
+pub fn load_genesis_configuration(
+    path: &str,
+) -> Result<GenesisConfiguration, EthereumException> {
+    let file = std::fs::read_to_string(path).map_err(|_| EthereumException::FileNotFound(path.to_string()))?;
 
     let value: serde_json::Value = serde_json::from_str(&file)
         .map_err(|e| EthereumException::JsonDecodeError(e.to_string()))?;
@@ -138,19 +157,19 @@ pub fn get_genesis_configuration(
 ///
 pub fn add_genesis_block(
     _hardfork: HardFork,
-    chain: BlockChain,
+    chain: &mut BlockChain,
     genesis: GenesisConfiguration,
 ) -> Result<(), EthereumException> {
     let mut state = State::default();
     for (account, balance) in genesis.initial_balances {
-        state::create_ether(&mut state, account, balance);
+        state::create_ether(&mut state, account, balance)?;
     }
 
-    let _genesis_header = Header {
+    let genesis_header = Header {
         parent_hash: Hash32::default(),
         ommers_hash: rlp::rlp_hash(&()),
         coinbase: Address::default(),
-        state_root: state_root(&chain.state),
+        state_root: state_root(&state)?,
         transactions_root: root(&Trie::<Address, _>::new(false, ()), dummy_root),
         receipt_root: root(&Trie::<Address, _>::new(false, ()), dummy_root),
         bloom: [0; 256],
@@ -163,8 +182,37 @@ pub fn add_genesis_block(
         mix_digest: Hash32::default(),
         nonce: genesis.nonce,
     };
-    // genesis_block = hardfork.eth_types.Block(header = genesis_header, transactions = (), ommers = ())?;
-    // chain.blocks.append(genesis_block)?;
-    // chain.chain_id = genesis.chain_id;
-    todo!()
+    let genesis_block = Block {
+        header: genesis_header,
+        transactions: Vec::new(),
+        ommers: Vec::new(),
+    };
+    chain.state = state;
+    chain.blocks.push(genesis_block);
+    chain.chain_id = genesis.chain_id;
+
+    Ok(())
+}
+
+///
+///     Start a Frontier chain from a genesis configuration.
+///
+///     Parameters
+///     ----------
+///     genesis :
+///         The genesis configuration to use.
+///
+///     Returns
+///     -------
+///     chain : `BlockChain`
+///         A chain holding only the genesis block.
+///
+pub fn genesis_chain(genesis: GenesisConfiguration) -> Result<BlockChain, EthereumException> {
+    let mut chain = BlockChain {
+        blocks: Vec::new(),
+        state: State::default(),
+        chain_id: U64::from(0u64),
+    };
+    add_genesis_block(HardFork::Frontier, &mut chain, genesis)?;
+    Ok(chain)
 }
@@ -18,7 +18,10 @@
 //!
 
 pub mod base_types;
+pub mod fixed_uint;
+pub mod u256;
 pub mod exceptions;
+pub mod crypto;
 pub mod rlp;
 pub mod genesis;
 pub mod ethash;
@@ -5,7 +5,7 @@
 //! Defines the serialization and deserialization format used throughout Ethereum.
 //!
 
-use super::{base_types::{strip_leading_zeros, Bytes, Uint, U32, U64}, frontier::fork_types::{keccak256, Hash32}};
+use super::{base_types::{strip_leading_zeros, Bytes, Uint, U256, U32, U64}, exceptions::EthereumException, frontier::fork_types::{keccak256, Hash32}};
 
 /// Trait for converting objects to RLP-encoded byte arrays.
 pub trait RLP : std::fmt::Debug {
@@ -65,15 +65,21 @@ impl RLP for Uint {
     }
 }
 
+impl RLP for U256 {
+    fn encode(&self) -> Bytes {
+        encode_bytes(&self.to_be_bytes())
+    }
+}
+
 impl RLP for U64 {
     fn encode(&self) -> Bytes {
-        encode_bytes(strip_leading_zeros(&self.to_be_bytes()))
+        encode_bytes(&self.to_be_bytes())
     }
 }
 
 impl RLP for U32 {
     fn encode(&self) -> Bytes {
-        encode_bytes(strip_leading_zeros(&self.to_be_bytes()))
+        encode_bytes(&self.to_be_bytes())
     }
 }
 
@@ -137,6 +143,20 @@ impl RLP for () {
     }
 }
 
+// } else if isinstance(raw_data, Bytes0)? {
+//     return Ok(encode_bytes(b"")?);
+
+/// `None` is encoded as the empty byte string, like the missing `to` of a
+/// contract creation.
+impl<T: RLP> RLP for Option<T> {
+    fn encode(&self) -> Bytes {
+        match self {
+            Some(value) => value.encode(),
+            None => encode_bytes(&[]),
+        }
+    }
+}
+
 impl RLP for Box<dyn RLP> {
     fn encode(&self) -> Bytes {
         self.as_ref().encode()
@@ -262,4 +282,276 @@ pub fn encode_sequence(joined_encodings: &[u8]) -> Bytes {
 pub fn rlp_hash<R: ?Sized + RLP>(raw_data: &R) -> Hash32{
     let data = encode(raw_data);
     return keccak256(&data)
-}
\ No newline at end of file
+}
+
+///
+///     The result of decoding RLP: either a byte string or a (possibly nested)
+///     sequence of decoded items.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Simple {
+    Bytes(Bytes),
+    List(Vec<Simple>),
+}
+
+impl Simple {
+    /// The decoded byte string, or an error if this is a sequence.
+    pub fn as_bytes(&self) -> Result<&Bytes, EthereumException> {
+        match self {
+            Simple::Bytes(bytes) => Ok(bytes),
+            Simple::List(_) => Err(EthereumException::RLPDecodingError),
+        }
+    }
+
+    /// The decoded sequence, or an error if this is a byte string.
+    pub fn as_list(&self) -> Result<&[Simple], EthereumException> {
+        match self {
+            Simple::Bytes(_) => Err(EthereumException::RLPDecodingError),
+            Simple::List(items) => Ok(items),
+        }
+    }
+}
+
+impl RLP for Simple {
+    fn encode(&self) -> Bytes {
+        match self {
+            Simple::Bytes(bytes) => encode_bytes(bytes),
+            Simple::List(items) => items.encode(),
+        }
+    }
+}
+
+/// Decode the big endian length prefix of a long string or long sequence.
+fn decode_length(length_bytes: &[u8]) -> Result<usize, EthereumException> {
+    if length_bytes.len() > std::mem::size_of::<usize>() {
+        return Err(EthereumException::RLPDecodingError);
+    }
+    let mut length = 0usize;
+    for b in length_bytes {
+        length = (length << 8) | *b as usize;
+    }
+    Ok(length)
+}
+
+///
+///     Decodes an integer byte sequence into a sequence of bytes or a
+///     sequence of decoded items.
+///
+///     Parameters
+///     ----------
+///     encoded_data :
+///         A sequence of bytes, in RLP form.
+///
+///     Returns
+///     -------
+///     decoded_data : `Simple`
+///         Object decoded from `encoded_data`.
+///
+pub fn decode(encoded_data: &[u8]) -> Result<Simple, EthereumException> {
+    if encoded_data.is_empty() {
+        return Err(EthereumException::RLPDecodingError);
+    }
+
+    if encoded_data[0] <= 0xBF {
+        Ok(Simple::Bytes(decode_to_bytes(encoded_data)?))
+    } else {
+        Ok(Simple::List(decode_to_sequence(encoded_data)?))
+    }
+}
+
+///
+///     Decodes a rlp encoded byte stream assuming that the decoded data
+///     should be of type `bytes`.
+///
+///     Parameters
+///     ----------
+///     encoded_bytes :
+///         RLP encoded byte stream.
+///
+///     Returns
+///     -------
+///     decoded : `ethereum.base_types.Bytes`
+///         RLP decoded Bytes data
+///
+pub fn decode_to_bytes(encoded_bytes: &[u8]) -> Result<Bytes, EthereumException> {
+    if encoded_bytes.is_empty() || encoded_bytes[0] > 0xBF {
+        return Err(EthereumException::RLPDecodingError);
+    }
+    if encoded_bytes[0] < 0x80 {
+        if encoded_bytes.len() != 1 {
+            return Err(EthereumException::RLPDecodingError);
+        }
+        Ok(encoded_bytes.into())
+    } else if encoded_bytes[0] <= 0xB7 {
+        let len_raw_data = (encoded_bytes[0] - 0x80) as usize;
+        if 1 + len_raw_data != encoded_bytes.len() {
+            return Err(EthereumException::RLPDecodingError);
+        }
+        let raw_data = &encoded_bytes[1..1 + len_raw_data];
+        if len_raw_data == 1 && raw_data[0] < 0x80 {
+            return Err(EthereumException::RLPDecodingError);
+        }
+        Ok(raw_data.into())
+    } else {
+        // This is the index in the encoded data at which decoded data
+        // starts from.
+        let decoded_data_start_idx = 1 + (encoded_bytes[0] - 0xB7) as usize;
+        if decoded_data_start_idx > encoded_bytes.len() {
+            return Err(EthereumException::RLPDecodingError);
+        }
+        if encoded_bytes[1] == 0 {
+            return Err(EthereumException::RLPDecodingError);
+        }
+        let len_decoded_data = decode_length(&encoded_bytes[1..decoded_data_start_idx])?;
+        if len_decoded_data < 0x38 {
+            return Err(EthereumException::RLPDecodingError);
+        }
+        let decoded_data_end_idx = decoded_data_start_idx
+            .checked_add(len_decoded_data)
+            .ok_or(EthereumException::RLPDecodingError)?;
+        if decoded_data_end_idx != encoded_bytes.len() {
+            return Err(EthereumException::RLPDecodingError);
+        }
+        Ok(encoded_bytes[decoded_data_start_idx..decoded_data_end_idx].into())
+    }
+}
+
+///
+///     Decodes a rlp encoded byte stream assuming that the decoded data
+///     should be of type `Sequence` of objects.
+///
+///     Parameters
+///     ----------
+///     encoded_sequence :
+///         An RLP encoded Sequence.
+///
+///     Returns
+///     -------
+///     decoded : `Sequence[Simple]`
+///         Sequence of objects decoded from `encoded_sequence`.
+///
+pub fn decode_to_sequence(encoded_sequence: &[u8]) -> Result<Vec<Simple>, EthereumException> {
+    if encoded_sequence.is_empty() || encoded_sequence[0] < 0xC0 {
+        return Err(EthereumException::RLPDecodingError);
+    }
+    let joined_encodings = if encoded_sequence[0] <= 0xF7 {
+        let len_joined_encodings = (encoded_sequence[0] - 0xC0) as usize;
+        if 1 + len_joined_encodings != encoded_sequence.len() {
+            return Err(EthereumException::RLPDecodingError);
+        }
+        &encoded_sequence[1..1 + len_joined_encodings]
+    } else {
+        let joined_encodings_start_idx = 1 + (encoded_sequence[0] - 0xF7) as usize;
+        if joined_encodings_start_idx > encoded_sequence.len() {
+            return Err(EthereumException::RLPDecodingError);
+        }
+        if encoded_sequence[1] == 0 {
+            return Err(EthereumException::RLPDecodingError);
+        }
+        let len_joined_encodings = decode_length(&encoded_sequence[1..joined_encodings_start_idx])?;
+        if len_joined_encodings < 0x38 {
+            return Err(EthereumException::RLPDecodingError);
+        }
+        let joined_encodings_end_idx = joined_encodings_start_idx
+            .checked_add(len_joined_encodings)
+            .ok_or(EthereumException::RLPDecodingError)?;
+        if joined_encodings_end_idx != encoded_sequence.len() {
+            return Err(EthereumException::RLPDecodingError);
+        }
+        &encoded_sequence[joined_encodings_start_idx..joined_encodings_end_idx]
+    };
+
+    decode_joined_encodings(joined_encodings)
+}
+
+///
+///     Decodes `joined_encodings`, which is a concatenation of RLP encoded
+///     objects.
+///
+///     Parameters
+///     ----------
+///     joined_encodings :
+///         concatenation of RLP encoded objects
+///
+///     Returns
+///     -------
+///     decoded : `List[Simple]`
+///         A list of objects decoded from `joined_encodings`.
+///
+pub fn decode_joined_encodings(joined_encodings: &[u8]) -> Result<Vec<Simple>, EthereumException> {
+    let mut decoded_sequence = vec![];
+
+    let mut item_start_idx = 0;
+    while item_start_idx < joined_encodings.len() {
+        let encoded_item_length = decode_item_length(&joined_encodings[item_start_idx..])?;
+        let item_end_idx = item_start_idx
+            .checked_add(encoded_item_length)
+            .ok_or(EthereumException::RLPDecodingError)?;
+        if item_end_idx > joined_encodings.len() {
+            return Err(EthereumException::RLPDecodingError);
+        }
+        let encoded_item = &joined_encodings[item_start_idx..item_end_idx];
+        decoded_sequence.push(decode(encoded_item)?);
+        item_start_idx = item_end_idx;
+    }
+
+    Ok(decoded_sequence)
+}
+
+///
+///     Find the length of an rlp encoding of the first object in the
+///     `encoded_data`, including its length prefix.
+///
+///     Parameters
+///     ----------
+///     encoded_data :
+///         RLP encoded data, possibly followed by further encodings.
+///
+///     Returns
+///     -------
+///     length : `int`
+///         Length of the first encoding in `encoded_data`.
+///
+pub fn decode_item_length(encoded_data: &[u8]) -> Result<usize, EthereumException> {
+    if encoded_data.is_empty() {
+        return Err(EthereumException::RLPDecodingError);
+    }
+
+    let first_rlp_byte = encoded_data[0];
+
+    // This is the length of the big endian representation of the length of
+    // rlp encoded object byte stream.
+    let mut length_length = 0usize;
+    let decoded_data_length;
+
+    if first_rlp_byte < 0x80 {
+        // This is a single byte which is directly the decoded data.
+        return Ok(1);
+    } else if first_rlp_byte <= 0xB7 {
+        decoded_data_length = (first_rlp_byte - 0x80) as usize;
+    } else if first_rlp_byte <= 0xBF {
+        length_length = (first_rlp_byte - 0xB7) as usize;
+        if length_length >= encoded_data.len() {
+            return Err(EthereumException::RLPDecodingError);
+        }
+        if encoded_data[1] == 0 {
+            return Err(EthereumException::RLPDecodingError);
+        }
+        decoded_data_length = decode_length(&encoded_data[1..1 + length_length])?;
+    } else if first_rlp_byte <= 0xF7 {
+        decoded_data_length = (first_rlp_byte - 0xC0) as usize;
+    } else {
+        length_length = (first_rlp_byte - 0xF7) as usize;
+        if length_length >= encoded_data.len() {
+            return Err(EthereumException::RLPDecodingError);
+        }
+        if encoded_data[1] == 0 {
+            return Err(EthereumException::RLPDecodingError);
+        }
+        decoded_data_length = decode_length(&encoded_data[1..1 + length_length])?;
+    }
+
+    (1 + length_length)
+        .checked_add(decoded_data_length)
+        .ok_or(EthereumException::RLPDecodingError)
+}
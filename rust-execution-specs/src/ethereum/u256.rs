@@ -0,0 +1,556 @@
+//!
+//! # 256-bit Unsigned Integers
+//!
+//! ## Introduction
+//!
+//! `U256`, the unsigned integer of the stack of the virtual machine, and of
+//! balances and values.
+//!
+//! It is stored in four `u64` limbs, least significant first, so the value
+//! itself never allocates. Neither does most of the arithmetic, but `add_mod`
+//! and `mul_mod` need more than 256 bits for their intermediate results, and
+//! go through a heap allocated `Uint`, as do `Display` and `LowerHex`.
+//! Division which does not fit in `u128` is plain bit-by-bit long division.
+//!
+//! The arithmetic operators panic on overflow, as `U256` raises in the
+//! Python specification; the EVM instructions use the `wrapping_*` methods
+//! instead, and the `checked_*` methods return `None`.
+//!
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{
+    Add, AddAssign, BitAnd, BitOr, BitXor, Div, Mul, MulAssign, Not, Rem, Shl, Shr, Sub,
+    SubAssign,
+};
+
+use num_traits::ToPrimitive;
+
+use super::base_types::{Bytes32, Uint};
+use super::exceptions::EthereumException;
+
+///
+///     Unsigned positive integer, which can represent `0` to `2 ** 256 - 1`,
+///     inclusive.
+///
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct U256([u64; 4]);
+
+impl U256 {
+    pub const ZERO: U256 = U256([0; 4]);
+    pub const ONE: U256 = U256([1, 0, 0, 0]);
+    pub const MAX: U256 = U256([u64::MAX; 4]);
+
+    /// Creates an unsigned integer from its limbs, least significant first.
+    pub const fn from_limbs(limbs: [u64; 4]) -> U256 {
+        U256(limbs)
+    }
+
+    ///
+    ///     Converts a sequence of bytes into an unsigned integer, interpreting
+    ///     them as big endian.
+    ///
+    ///     Panics when there are more than 32 bytes.
+    ///
+    pub fn from_be_bytes(bytes: &[u8]) -> U256 {
+        U256::try_from_be_bytes(bytes).expect("more than 32 bytes in a U256")
+    }
+
+    ///
+    ///     Converts a sequence of bytes into an unsigned integer, interpreting
+    ///     them as big endian, if there are at most 32 of them.
+    ///
+    pub fn try_from_be_bytes(bytes: &[u8]) -> Option<U256> {
+        if bytes.len() > 32 {
+            return None;
+        }
+        let mut word = [0; 32];
+        word[32 - bytes.len()..].copy_from_slice(bytes);
+
+        let mut limbs = [0; 4];
+        for (index, chunk) in word.rchunks_exact(8).enumerate() {
+            limbs[index] = u64::from_be_bytes(chunk.try_into().unwrap());
+        }
+        Some(U256(limbs))
+    }
+
+    ///
+    ///     Converts this unsigned integer into its big endian representation,
+    ///     with exactly 32 bytes.
+    ///
+    pub fn to_be_bytes32(&self) -> Bytes32 {
+        let mut word = [0; 32];
+        for (index, chunk) in word.rchunks_exact_mut(8).enumerate() {
+            chunk.copy_from_slice(&self.0[index].to_be_bytes());
+        }
+        word
+    }
+
+    ///
+    ///     Converts this unsigned integer into its big endian representation,
+    ///     omitting leading zero bytes.
+    ///
+    pub fn to_be_bytes(&self) -> Vec<u8> {
+        let word = self.to_be_bytes32();
+        let leading_zeros = word.iter().position(|byte| *byte != 0).unwrap_or(32);
+        word[leading_zeros..].to_vec()
+    }
+
+    /// Parses `src`, in base 10 or 16, without any prefix.
+    pub fn from_str_radix(src: &str, radix: u32) -> Option<U256> {
+        U256::try_from(Uint::parse_bytes(src.as_bytes(), radix)?).ok()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        *self == U256::ZERO
+    }
+
+    /// Whether bit `bit` is set, counting from the least significant.
+    pub fn bit(&self, bit: usize) -> bool {
+        bit < 256 && self.0[bit / 64] & (1 << (bit % 64)) != 0
+    }
+
+    /// The number of bits needed to represent this integer.
+    pub fn bits(&self) -> usize {
+        for index in (0..4).rev() {
+            if self.0[index] != 0 {
+                return 64 * index + 64 - self.0[index].leading_zeros() as usize;
+            }
+        }
+        0
+    }
+
+    pub fn overflowing_add(self, rhs: U256) -> (U256, bool) {
+        let mut limbs = [0; 4];
+        let mut carry = false;
+        for (index, limb) in limbs.iter_mut().enumerate() {
+            let (sum, overflow) = self.0[index].overflowing_add(rhs.0[index]);
+            let (sum, carried) = sum.overflowing_add(u64::from(carry));
+            *limb = sum;
+            carry = overflow || carried;
+        }
+        (U256(limbs), carry)
+    }
+
+    pub fn overflowing_sub(self, rhs: U256) -> (U256, bool) {
+        let mut limbs = [0; 4];
+        let mut borrow = false;
+        for (index, limb) in limbs.iter_mut().enumerate() {
+            let (difference, underflow) = self.0[index].overflowing_sub(rhs.0[index]);
+            let (difference, borrowed) = difference.overflowing_sub(u64::from(borrow));
+            *limb = difference;
+            borrow = underflow || borrowed;
+        }
+        (U256(limbs), borrow)
+    }
+
+    pub fn overflowing_mul(self, rhs: U256) -> (U256, bool) {
+        let mut product = [0u64; 8];
+        for i in 0..4 {
+            let mut carry = 0u128;
+            for j in 0..4 {
+                let value = u128::from(self.0[i]) * u128::from(rhs.0[j])
+                    + u128::from(product[i + j])
+                    + carry;
+                product[i + j] = value as u64;
+                carry = value >> 64;
+            }
+            product[i + 4] = carry as u64;
+        }
+        let overflow = product[4..].iter().any(|limb| *limb != 0);
+        (U256(product[..4].try_into().unwrap()), overflow)
+    }
+
+    pub fn wrapping_add(self, rhs: U256) -> U256 {
+        self.overflowing_add(rhs).0
+    }
+
+    pub fn wrapping_sub(self, rhs: U256) -> U256 {
+        self.overflowing_sub(rhs).0
+    }
+
+    pub fn wrapping_mul(self, rhs: U256) -> U256 {
+        self.overflowing_mul(rhs).0
+    }
+
+    /// `self` raised to `exponent`, modulo `2 ** 256`.
+    pub fn wrapping_pow(self, exponent: U256) -> U256 {
+        let mut result = U256::ONE;
+        for bit in (0..exponent.bits()).rev() {
+            result = result.wrapping_mul(result);
+            if exponent.bit(bit) {
+                result = result.wrapping_mul(self);
+            }
+        }
+        result
+    }
+
+    pub fn checked_add(self, rhs: U256) -> Option<U256> {
+        match self.overflowing_add(rhs) {
+            (sum, false) => Some(sum),
+            (_, true) => None,
+        }
+    }
+
+    pub fn checked_sub(self, rhs: U256) -> Option<U256> {
+        match self.overflowing_sub(rhs) {
+            (difference, false) => Some(difference),
+            (_, true) => None,
+        }
+    }
+
+    pub fn checked_mul(self, rhs: U256) -> Option<U256> {
+        match self.overflowing_mul(rhs) {
+            (product, false) => Some(product),
+            (_, true) => None,
+        }
+    }
+
+    pub fn checked_div(self, rhs: U256) -> Option<U256> {
+        self.checked_div_rem(rhs).map(|(quotient, _)| quotient)
+    }
+
+    pub fn checked_rem(self, rhs: U256) -> Option<U256> {
+        self.checked_div_rem(rhs).map(|(_, remainder)| remainder)
+    }
+
+    /// The quotient and remainder of `self` by `rhs`, unless `rhs` is zero.
+    ///
+    /// Beyond `u128`, this takes one shift and subtraction per bit of
+    /// `self`.
+    pub fn checked_div_rem(self, rhs: U256) -> Option<(U256, U256)> {
+        if rhs.is_zero() {
+            return None;
+        }
+        if let (Some(dividend), Some(divisor)) = (self.to_u128(), rhs.to_u128()) {
+            return Some((U256::from(dividend / divisor), U256::from(dividend % divisor)));
+        }
+
+        let mut quotient = U256::ZERO;
+        let mut remainder = U256::ZERO;
+        for bit in (0..self.bits()).rev() {
+            let carry = remainder.bit(255);
+            remainder = remainder << 1;
+            if self.bit(bit) {
+                remainder.0[0] |= 1;
+            }
+            if carry || remainder >= rhs {
+                remainder = remainder.wrapping_sub(rhs);
+                quotient.0[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+        Some((quotient, remainder))
+    }
+
+    /// `(self + rhs) % modulus`, computed without overflow through `Uint`,
+    /// so it allocates.
+    pub fn add_mod(self, rhs: U256, modulus: U256) -> U256 {
+        if modulus.is_zero() {
+            return U256::ZERO;
+        }
+        U256::try_from((Uint::from(self) + Uint::from(rhs)) % Uint::from(modulus)).unwrap()
+    }
+
+    /// `(self * rhs) % modulus`, computed without overflow through `Uint`,
+    /// so it allocates.
+    pub fn mul_mod(self, rhs: U256, modulus: U256) -> U256 {
+        if modulus.is_zero() {
+            return U256::ZERO;
+        }
+        U256::try_from((Uint::from(self) * Uint::from(rhs)) % Uint::from(modulus)).unwrap()
+    }
+
+    /// Whether this integer is negative, read as two's complement.
+    pub fn is_negative(&self) -> bool {
+        self.bit(255)
+    }
+
+    /// The two's complement negation of this integer.
+    pub fn wrapping_neg(self) -> U256 {
+        (!self).wrapping_add(U256::ONE)
+    }
+
+    /// The absolute value of this integer, read as two's complement.
+    pub fn unsigned_abs(self) -> U256 {
+        if self.is_negative() {
+            self.wrapping_neg()
+        } else {
+            self
+        }
+    }
+
+    /// Compare two integers, read as two's complement.
+    pub fn signed_cmp(&self, other: &U256) -> Ordering {
+        match (self.is_negative(), other.is_negative()) {
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            _ => self.cmp(other),
+        }
+    }
+
+    ///
+    ///     Signed division of two integers read as two's complement,
+    ///     rounding towards zero. Dividing by zero gives zero, and
+    ///     `-2 ** 255 / -1` wraps to `-2 ** 255`.
+    ///
+    pub fn signed_div(self, rhs: U256) -> U256 {
+        let quotient = match self.unsigned_abs().checked_div(rhs.unsigned_abs()) {
+            Some(quotient) => quotient,
+            None => return U256::ZERO,
+        };
+        if self.is_negative() != rhs.is_negative() {
+            quotient.wrapping_neg()
+        } else {
+            quotient
+        }
+    }
+
+    ///
+    ///     Signed remainder of two integers read as two's complement, with
+    ///     the sign of `self`. The remainder of a division by zero is zero.
+    ///
+    pub fn signed_rem(self, rhs: U256) -> U256 {
+        let remainder = match self.unsigned_abs().checked_rem(rhs.unsigned_abs()) {
+            Some(remainder) => remainder,
+            None => return U256::ZERO,
+        };
+        if self.is_negative() {
+            remainder.wrapping_neg()
+        } else {
+            remainder
+        }
+    }
+}
+
+impl Ord for U256 {
+    fn cmp(&self, other: &U256) -> Ordering {
+        self.0.iter().rev().cmp(other.0.iter().rev())
+    }
+}
+
+impl PartialOrd for U256 {
+    fn partial_cmp(&self, other: &U256) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+macro_rules! from_primitive {
+    ($($t:ty),*) => {$(
+        impl From<$t> for U256 {
+            fn from(value: $t) -> U256 {
+                let value = value as u128;
+                U256([value as u64, (value >> 64) as u64, 0, 0])
+            }
+        }
+    )*};
+}
+
+from_primitive!(u8, u16, u32, u64, u128, usize);
+
+impl From<bool> for U256 {
+    fn from(value: bool) -> U256 {
+        U256::from(u8::from(value))
+    }
+}
+
+impl From<U256> for Uint {
+    fn from(value: U256) -> Uint {
+        Uint::from_bytes_be(&value.to_be_bytes32())
+    }
+}
+
+impl From<&U256> for Uint {
+    fn from(value: &U256) -> Uint {
+        Uint::from(*value)
+    }
+}
+
+impl TryFrom<Uint> for U256 {
+    type Error = EthereumException;
+
+    fn try_from(value: Uint) -> Result<U256, EthereumException> {
+        U256::try_from(&value)
+    }
+}
+
+impl TryFrom<&Uint> for U256 {
+    type Error = EthereumException;
+
+    fn try_from(value: &Uint) -> Result<U256, EthereumException> {
+        U256::try_from_be_bytes(&value.to_bytes_be()).ok_or(EthereumException::OverflowError)
+    }
+}
+
+impl ToPrimitive for U256 {
+    fn to_i64(&self) -> Option<i64> {
+        self.to_u64()?.to_i64()
+    }
+
+    fn to_u64(&self) -> Option<u64> {
+        match self.0 {
+            [value, 0, 0, 0] => Some(value),
+            _ => None,
+        }
+    }
+
+    fn to_u128(&self) -> Option<u128> {
+        match self.0 {
+            [low, high, 0, 0] => Some(u128::from(high) << 64 | u128::from(low)),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! try_into_primitive {
+    ($($t:ty => $method:ident),*) => {$(
+        impl TryFrom<U256> for $t {
+            type Error = EthereumException;
+
+            fn try_from(value: U256) -> Result<$t, EthereumException> {
+                value.$method().ok_or(EthereumException::OverflowError)
+            }
+        }
+    )*};
+}
+
+try_into_primitive!(u8 => to_u8, u32 => to_u32, u64 => to_u64, u128 => to_u128, usize => to_usize);
+
+macro_rules! arithmetic {
+    ($($trait:ident $method:ident $assign_trait:ident $assign_method:ident => $body:expr;)*) => {$(
+        impl $trait for U256 {
+            type Output = U256;
+
+            fn $method(self, rhs: U256) -> U256 {
+                $body(self, rhs)
+            }
+        }
+
+        impl $trait<&U256> for U256 {
+            type Output = U256;
+
+            fn $method(self, rhs: &U256) -> U256 {
+                $body(self, *rhs)
+            }
+        }
+
+        impl $trait<U256> for &U256 {
+            type Output = U256;
+
+            fn $method(self, rhs: U256) -> U256 {
+                $body(*self, rhs)
+            }
+        }
+
+        impl $trait<&U256> for &U256 {
+            type Output = U256;
+
+            fn $method(self, rhs: &U256) -> U256 {
+                $body(*self, *rhs)
+            }
+        }
+
+        impl $assign_trait for U256 {
+            fn $assign_method(&mut self, rhs: U256) {
+                *self = $body(*self, rhs);
+            }
+        }
+
+        impl $assign_trait<&U256> for U256 {
+            fn $assign_method(&mut self, rhs: &U256) {
+                *self = $body(*self, *rhs);
+            }
+        }
+    )*};
+}
+
+arithmetic! {
+    Add add AddAssign add_assign => |x: U256, y| x.checked_add(y).expect("U256 addition overflowed");
+    Sub sub SubAssign sub_assign => |x: U256, y| x.checked_sub(y).expect("U256 subtraction overflowed");
+    Mul mul MulAssign mul_assign => |x: U256, y| x.checked_mul(y).expect("U256 multiplication overflowed");
+}
+
+impl Div for U256 {
+    type Output = U256;
+
+    fn div(self, rhs: U256) -> U256 {
+        self.checked_div(rhs).expect("U256 division by zero")
+    }
+}
+
+impl Rem for U256 {
+    type Output = U256;
+
+    fn rem(self, rhs: U256) -> U256 {
+        self.checked_rem(rhs).expect("U256 division by zero")
+    }
+}
+
+macro_rules! bitwise {
+    ($($trait:ident $method:ident $op:tt;)*) => {$(
+        impl $trait for U256 {
+            type Output = U256;
+
+            fn $method(self, rhs: U256) -> U256 {
+                U256(std::array::from_fn(|index| self.0[index] $op rhs.0[index]))
+            }
+        }
+    )*};
+}
+
+bitwise! {
+    BitAnd bitand &;
+    BitOr bitor |;
+    BitXor bitxor ^;
+}
+
+impl Not for U256 {
+    type Output = U256;
+
+    fn not(self) -> U256 {
+        U256(self.0.map(|limb| !limb))
+    }
+}
+
+impl Shl<usize> for U256 {
+    type Output = U256;
+
+    fn shl(self, shift: usize) -> U256 {
+        let (limb_shift, bit_shift) = (shift / 64, shift % 64);
+        let limb = |index: usize| index.checked_sub(limb_shift).map_or(0, |index| self.0[index]);
+        U256(std::array::from_fn(|index| match bit_shift {
+            0 => limb(index),
+            _ => limb(index) << bit_shift | index.checked_sub(1).map_or(0, limb) >> (64 - bit_shift),
+        }))
+    }
+}
+
+impl Shr<usize> for U256 {
+    type Output = U256;
+
+    fn shr(self, shift: usize) -> U256 {
+        let (limb_shift, bit_shift) = (shift / 64, shift % 64);
+        let limb = |index: usize| self.0.get(index + limb_shift).copied().unwrap_or(0);
+        U256(std::array::from_fn(|index| match bit_shift {
+            0 => limb(index),
+            _ => limb(index) >> bit_shift | limb(index + 1) << (64 - bit_shift),
+        }))
+    }
+}
+
+impl fmt::Display for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&Uint::from(self), f)
+    }
+}
+
+impl fmt::Debug for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl fmt::LowerHex for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::LowerHex::fmt(&Uint::from(self), f)
+    }
+}
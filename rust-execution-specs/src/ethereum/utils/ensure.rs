@@ -6,25 +6,23 @@
 //! Functions that simplify checking assertions and raising exceptions.
 //! 
 
-// use ::typing::{Type, Union};
-// /// 
-// ///     Does nothing if `value` is truthy, otherwise raises the exception returned
-// ///     by `exception_class`.
-// /// 
-// ///     Parameters
-// ///     ----------
-// /// 
-// ///     value :
-// ///         Value that should be true.
-// /// 
-// ///     exception :
-// ///         Constructor for the exception to raise.
-// ///     
-// pub fn ensure(value: bool, exception: Union[Type[BaseException]][BaseException]) -> Result<(), Error> {
-//     if value {
-//         return;
-//     }
-//     return Err(Error::exception);
-// }
-
+use crate::ethereum::exceptions::EthereumException;
 
+/// 
+///     Does nothing if `value` is truthy, otherwise raises `exception`.
+/// 
+///     Parameters
+///     ----------
+/// 
+///     value :
+///         Value that should be true.
+/// 
+///     exception :
+///         The exception to raise.
+///     
+pub fn ensure(value: bool, exception: EthereumException) -> Result<(), EthereumException> {
+    if value {
+        return Ok(());
+    }
+    Err(exception)
+}
@@ -222,7 +222,7 @@ pub fn hex_to_uint(hex_string: &str) -> Result<Uint, EthereumException> {
 ///     
 pub fn hex_to_u64(hex_string: &str) -> Result<U64, EthereumException> {
     let bytes = hex_to_bytes8(hex_string)?;
-    Ok(U64::from_be_bytes(bytes))
+    Ok(U64::from_be_bytes(&bytes))
 }
 
 
@@ -240,8 +240,8 @@ pub fn hex_to_u64(hex_string: &str) -> Result<U64, EthereumException> {
 ///         The U256 integer obtained from the given hexadecimal string.
 ///     
 pub fn hex_to_u256(hex_string: &str) -> Result<U256, EthereumException> {
-    Ok(U256::from_str_radix(remove_hex_prefix(hex_string), 16)
-        .map_err(|_| EthereumException::BadHexString(hex_string.to_owned()))?)
+    U256::from_str_radix(remove_hex_prefix(hex_string), 16)
+        .ok_or_else(|| EthereumException::BadHexString(hex_string.to_owned()))
 }
 
 /// Convert a slice of bytes to hex.
@@ -7,5 +7,7 @@
 //! 
 
 pub mod byte;
+pub mod ensure;
 pub mod hexadecimal;
 pub mod numeric;
+pub mod safe_arithmetic;
@@ -0,0 +1,257 @@
+//!
+//! # EVM Assembly
+//!
+//! ## Introduction
+//!
+//! A disassembler turning Frontier bytecode into mnemonics, and an assembler
+//! turning mnemonics back into bytecode, for writing test contracts by hand:
+//!
+//! ```text
+//! ; count down from 3
+//!         PUSH1 3
+//! loop:   JUMPDEST
+//!         PUSH1 1
+//!         SWAP1
+//!         SUB
+//!         DUP1
+//!         PUSH @loop      ; PUSH2 of the pc of `loop`
+//!         JUMPI
+//!         STOP
+//!         .byte 0xfe      ; raw bytes
+//! ```
+//!
+//! A line holds any number of labels, followed by at most one instruction.
+//! Immediates are decimal or `0x` prefixed hexadecimal, and `@label` is the
+//! pc of a label. A bare `PUSH` takes the narrowest `PUSHn` that fits its
+//! immediate, or `PUSH2` for a label. Comments start with `;`.
+//!
+//! Disassembling and then assembling gives back the same bytecode: bytes that
+//! are not opcodes, and truncated `PUSHn` at the end of the code, are
+//! disassembled as `.byte`.
+//!
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::ethereum::{
+    base_types::{Bytes, U256},
+    frontier::vm::instructions::Ops,
+    utils::hexadecimal::hex,
+};
+
+/// An instruction of disassembled code.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Instruction {
+    /// An opcode, and the immediate of `PUSHn`.
+    Op(Ops, Vec<u8>),
+    /// Bytes that are not instructions.
+    Data(Vec<u8>),
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Instruction::Op(op, immediate) if immediate.is_empty() => write!(f, "{}", op.name()),
+            Instruction::Op(op, immediate) => write!(f, "{} {}", op.name(), hex(immediate)),
+            Instruction::Data(bytes) => write!(f, ".byte {}", hex(bytes)),
+        }
+    }
+}
+
+/// Disassemble `code` into instructions, each with its program counter.
+///
+/// Parameters
+/// ----------
+/// code :
+///     The bytecode to disassemble.
+///
+/// Returns
+/// -------
+/// instructions : `Vec<(usize, Instruction)>`
+///     The instructions of `code`, in order.
+pub fn disassemble(code: &[u8]) -> Vec<(usize, Instruction)> {
+    let mut instructions = Vec::new();
+    let mut pc = 0;
+    while pc < code.len() {
+        let instruction = match Ops::from_byte(code[pc]) {
+            Some(op) if pc + 1 + op.immediate_size() <= code.len() => {
+                Instruction::Op(op, code[pc + 1..pc + 1 + op.immediate_size()].to_vec())
+            }
+            Some(_) => Instruction::Data(code[pc..].to_vec()),
+            None => Instruction::Data(vec![code[pc]]),
+        };
+        let size = match &instruction {
+            Instruction::Op(_, immediate) => 1 + immediate.len(),
+            Instruction::Data(bytes) => bytes.len(),
+        };
+        instructions.push((pc, instruction));
+        pc += size;
+    }
+    instructions
+}
+
+/// A listing of `code`, with one instruction per line, prefixed by its pc.
+pub fn disassembly(code: &[u8]) -> String {
+    disassemble(code)
+        .iter()
+        .map(|(pc, instruction)| format!("{pc:#06x}  {instruction}\n"))
+        .collect()
+}
+
+/// Why a line of assembly could not be assembled.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssemblyError {
+    /// The line, counting from 1.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for AssemblyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+enum Immediate {
+    Value(Vec<u8>),
+    Label(String),
+}
+
+struct Item {
+    line: usize,
+    op: Option<Ops>,
+    size: usize,
+    immediate: Immediate,
+}
+
+fn parse_number(number: &str) -> Option<Vec<u8>> {
+    let value = match number.strip_prefix("0x") {
+        Some(hex) => U256::from_str_radix(hex, 16)?,
+        None => U256::from_str_radix(number, 10)?,
+    };
+    Some(value.to_be_bytes())
+}
+
+fn parse_bytes(bytes: &str) -> Option<Vec<u8>> {
+    let hex = bytes.strip_prefix("0x")?;
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok())
+        .collect()
+}
+
+fn is_label(label: &str) -> bool {
+    label.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn parse_item(line: usize, mnemonic: &str, operands: &[&str]) -> Result<Item, String> {
+    if mnemonic == ".byte" {
+        let mut bytes = Vec::new();
+        for operand in operands {
+            bytes.extend(parse_bytes(operand).ok_or(format!("bad bytes {operand}"))?);
+        }
+        return Ok(Item { line, op: None, size: bytes.len(), immediate: Immediate::Value(bytes) });
+    }
+
+    let immediate = match operands {
+        [] => None,
+        [operand] => match operand.strip_prefix('@') {
+            Some(label) if is_label(label) => Some(Immediate::Label(label.to_string())),
+            _ => Some(Immediate::Value(
+                parse_number(operand).ok_or(format!("bad immediate {operand}"))?,
+            )),
+        },
+        _ => return Err(format!("too many operands to {mnemonic}")),
+    };
+
+    let op = if mnemonic.eq_ignore_ascii_case("PUSH") {
+        let size = match &immediate {
+            Some(Immediate::Value(value)) => value.len().max(1),
+            Some(Immediate::Label(_)) => 2,
+            None => return Err("PUSH without an immediate".to_string()),
+        };
+        Ops::from_byte(0x5f + u8::try_from(size).map_err(|_| "immediate is too large")?)
+            .ok_or("immediate is too large")?
+    } else {
+        Ops::from_name(mnemonic).ok_or(format!("unknown mnemonic {mnemonic}"))?
+    };
+
+    let size = op.immediate_size();
+    let immediate = match immediate {
+        None if size == 0 => Immediate::Value(Vec::new()),
+        None => return Err(format!("{} without an immediate", op.name())),
+        Some(_) if size == 0 => return Err(format!("{} takes no immediate", op.name())),
+        Some(immediate) => immediate,
+    };
+    Ok(Item { line, op: Some(op), size: 1 + size, immediate })
+}
+
+/// Assemble `source` into bytecode.
+///
+/// Parameters
+/// ----------
+/// source :
+///     The assembly, as described in the module documentation.
+///
+/// Returns
+/// -------
+/// code : `ethereum.base_types.Bytes`
+///     The assembled bytecode.
+pub fn assemble(source: &str) -> Result<Bytes, AssemblyError> {
+    let mut items = Vec::new();
+    let mut labels = HashMap::new();
+    let mut pc = 0;
+
+    for (index, text) in source.lines().enumerate() {
+        let line = index + 1;
+        let error = |message: String| AssemblyError { line, message };
+        let text = text.split(';').next().unwrap_or_default();
+
+        let mut words = text.split_whitespace().collect::<Vec<_>>();
+        while let Some(label) = words.first().and_then(|word| word.strip_suffix(':')) {
+            if !is_label(label) {
+                return Err(error(format!("bad label {label}")));
+            }
+            if labels.insert(label.to_string(), pc).is_some() {
+                return Err(error(format!("duplicate label {label}")));
+            }
+            words.remove(0);
+        }
+
+        if let Some((mnemonic, operands)) = words.split_first() {
+            let item = parse_item(line, mnemonic, operands).map_err(error)?;
+            pc += item.size;
+            items.push(item);
+        }
+    }
+
+    let mut code = Vec::with_capacity(pc);
+    for item in items {
+        let error = |message: String| AssemblyError { line: item.line, message };
+        let value = match item.immediate {
+            Immediate::Value(value) => value,
+            Immediate::Label(label) => {
+                let pc = labels.get(&label).ok_or_else(|| error(format!("undefined label {label}")))?;
+                U256::from(*pc).to_be_bytes()
+            }
+        };
+
+        match item.op {
+            Some(op) => {
+                let size = item.size - 1;
+                if value.len() > size {
+                    return Err(error(format!("immediate does not fit {}", op.name())));
+                }
+                code.push(op as u8);
+                code.extend(std::iter::repeat_n(0, size - value.len()));
+                code.extend(value);
+            }
+            None => code.extend(value),
+        }
+    }
+    Ok(code.into_boxed_slice())
+}
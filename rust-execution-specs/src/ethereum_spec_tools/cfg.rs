@@ -0,0 +1,182 @@
+//!
+//! # Control Flow Graph
+//!
+//! ## Introduction
+//!
+//! Recover the basic blocks of Frontier bytecode and the edges between them,
+//! without executing it, and render them as a Graphviz dot graph:
+//!
+//! ```text
+//! dot -Tsvg cfg.dot > cfg.svg
+//! ```
+//!
+//! A basic block starts at the beginning of the code, at every `JUMPDEST`,
+//! and after every jump, halting opcode or invalid byte. The target of a
+//! jump is resolved when it is pushed by the instruction right before the
+//! jump, which is how compilers emit jumps to constant destinations. Other
+//! jumps, such as returns from internal functions, are unresolved.
+//!
+
+use std::fmt::Write;
+
+use crate::ethereum::{
+    base_types::U256,
+    frontier::vm::{instructions::Ops, runtime::get_valid_jump_destinations},
+};
+
+use super::assembly::{disassemble, Instruction};
+
+/// How control passes from one basic block to another.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EdgeKind {
+    /// `JUMP` to a constant destination.
+    Jump,
+    /// `JUMPI` to a constant destination, when the condition holds.
+    Branch,
+    /// Execution continues with the next instruction.
+    Fallthrough,
+}
+
+/// How control leaves a basic block through a jump which has no edge.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnresolvedJump {
+    /// The destination is not a constant.
+    Dynamic,
+    /// The destination is a constant, but not a valid jump destination, so
+    /// the jump always fails.
+    Invalid(U256),
+}
+
+/// A straight line sequence of instructions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BasicBlock {
+    /// Program counter of the first instruction.
+    pub start: usize,
+    /// The instructions of the block, each with its program counter.
+    pub instructions: Vec<(usize, Instruction)>,
+    /// Set when the block ends with a jump which could not be resolved.
+    pub unresolved_jump: Option<UnresolvedJump>,
+}
+
+impl BasicBlock {
+    /// The opcode of the last instruction, unless it is invalid.
+    pub fn last_op(&self) -> Option<Ops> {
+        match self.instructions.last() {
+            Some((_, Instruction::Op(op, _))) => Some(*op),
+            _ => None,
+        }
+    }
+}
+
+/// The basic blocks of some code, and the edges between them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ControlFlowGraph {
+    /// The basic blocks, in the order of the code.
+    pub blocks: Vec<BasicBlock>,
+    /// Edges between blocks, as the start of the source, the start of the
+    /// destination, and how control passes.
+    pub edges: Vec<(usize, usize, EdgeKind)>,
+}
+
+/// Split disassembled code into basic blocks.
+fn basic_blocks(instructions: Vec<(usize, Instruction)>) -> Vec<BasicBlock> {
+    let mut blocks: Vec<BasicBlock> = Vec::new();
+    let mut ended = true;
+    for (pc, instruction) in instructions {
+        let starts_block = matches!(instruction, Instruction::Op(Ops::JUMPDEST, _));
+        if ended || starts_block {
+            blocks.push(BasicBlock { start: pc, instructions: Vec::new(), unresolved_jump: None });
+        }
+        ended = match &instruction {
+            Instruction::Op(op, _) => op.is_terminating() || op.is_jump(),
+            Instruction::Data(_) => true,
+        };
+        blocks.last_mut().unwrap().instructions.push((pc, instruction));
+    }
+    blocks
+}
+
+/// The destination pushed right before the jump which ends `block`.
+fn constant_jump_target(block: &BasicBlock) -> Option<U256> {
+    match block.instructions.iter().rev().nth(1) {
+        Some((_, Instruction::Op(op, immediate))) if op.immediate_size() > 0 => {
+            Some(U256::from_be_bytes(immediate))
+        }
+        _ => None,
+    }
+}
+
+/// Extract the control flow graph of `code`.
+///
+/// Parameters
+/// ----------
+/// code :
+///     The bytecode to analyze.
+///
+/// Returns
+/// -------
+/// cfg : `ControlFlowGraph`
+///     The basic blocks of `code` and the edges between them.
+pub fn control_flow_graph(code: &[u8]) -> ControlFlowGraph {
+    let valid_jump_destinations = get_valid_jump_destinations(code);
+    let mut blocks = basic_blocks(disassemble(code));
+    let mut edges = Vec::new();
+
+    let starts: Vec<usize> = blocks.iter().map(|block| block.start).collect();
+    for (index, block) in blocks.iter_mut().enumerate() {
+        let last_op = block.last_op();
+        if let Some(op) = last_op.filter(Ops::is_jump) {
+            let kind = if op == Ops::JUMP { EdgeKind::Jump } else { EdgeKind::Branch };
+            match constant_jump_target(block) {
+                Some(target) if valid_jump_destinations.contains(&target) => {
+                    edges.push((block.start, usize::try_from(target).unwrap(), kind));
+                }
+                Some(target) => block.unresolved_jump = Some(UnresolvedJump::Invalid(target)),
+                None => block.unresolved_jump = Some(UnresolvedJump::Dynamic),
+            }
+        }
+
+        let falls_through = last_op.is_some_and(|op| !op.is_terminating());
+        if let (true, Some(next)) = (falls_through, starts.get(index + 1)) {
+            edges.push((block.start, *next, EdgeKind::Fallthrough));
+        }
+    }
+
+    ControlFlowGraph { blocks, edges }
+}
+
+impl ControlFlowGraph {
+    /// Render the graph in the Graphviz dot language, with a node listing
+    /// the instructions of each basic block.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        writeln!(dot, "digraph cfg {{").unwrap();
+        writeln!(dot, "    node [shape=box fontname=monospace];").unwrap();
+
+        for block in &self.blocks {
+            let label: String = block
+                .instructions
+                .iter()
+                .map(|(pc, instruction)| format!("{pc:#06x}  {instruction}\\l"))
+                .collect();
+            let style = match block.unresolved_jump {
+                None => "",
+                Some(UnresolvedJump::Dynamic) => " style=dashed",
+                Some(UnresolvedJump::Invalid(_)) => " color=red",
+            };
+            writeln!(dot, "    b{} [label=\"{label}\"{style}];", block.start).unwrap();
+        }
+
+        for (from, to, kind) in &self.edges {
+            let attributes = match kind {
+                EdgeKind::Jump => "",
+                EdgeKind::Branch => " [label=\"true\"]",
+                EdgeKind::Fallthrough => " [style=dashed]",
+            };
+            writeln!(dot, "    b{from} -> b{to}{attributes};").unwrap();
+        }
+
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
+}
@@ -0,0 +1,286 @@
+//!
+//! # Chain Files
+//!
+//! ## Introduction
+//!
+//! Read and write chains in the format of `geth export` and `geth import`,
+//! which is nothing more than the RLP encodings of blocks, one after the
+//! other, without any header or separator. geth writes such files starting
+//! from the genesis block, so blocks the chain already has are skipped on
+//! import.
+//!
+//! Files compressed by geth, ending in `.gz`, have to be decompressed first.
+//!
+
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::time::{Duration, Instant};
+
+use crate::ethereum::{
+    exceptions::EthereumException,
+    frontier::{
+        fork::{state_transition, BlockChain},
+        fork_types::Block,
+    },
+    rlp,
+};
+
+use super::chain_sync::{BlockDownloader, SyncError};
+
+/// Why reading or writing a chain file stopped.
+#[derive(Debug)]
+pub enum ChainFileError {
+    /// The file could not be read or written.
+    Io(io::Error),
+    /// The block at the byte offset could not be decoded, or its number
+    /// does not fit in 64 bits. Nothing after it can be read.
+    Malformed(u64, EthereumException),
+    /// The blocks to export could not be downloaded.
+    Sync(SyncError),
+}
+
+impl From<io::Error> for ChainFileError {
+    fn from(error: io::Error) -> Self {
+        ChainFileError::Io(error)
+    }
+}
+
+impl From<SyncError> for ChainFileError {
+    fn from(error: SyncError) -> Self {
+        ChainFileError::Sync(error)
+    }
+}
+
+impl fmt::Display for ChainFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChainFileError::Io(error) => write!(f, "io: {error}"),
+            ChainFileError::Malformed(offset, error) => write!(f, "malformed block at byte {offset}: {error:?}"),
+            ChainFileError::Sync(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+///
+/// Reads the blocks of a chain file one at a time, without holding more
+/// than one of them in memory.
+///
+pub struct BlockReader<R> {
+    reader: R,
+    offset: u64,
+    failed: bool,
+}
+
+impl<R: Read> BlockReader<R> {
+    pub fn new(reader: R) -> Self {
+        BlockReader { reader, offset: 0, failed: false }
+    }
+
+    /// The byte offset of the next block.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    fn read_block(&mut self) -> Result<Option<Block>, ChainFileError> {
+        let malformed = |offset, error| ChainFileError::Malformed(offset, error);
+        let truncated = |offset, error: io::Error| match error.kind() {
+            io::ErrorKind::UnexpectedEof => malformed(offset, EthereumException::RLPDecodingError),
+            _ => ChainFileError::Io(error),
+        };
+
+        let mut first = [0u8];
+        loop {
+            match self.reader.read(&mut first) {
+                Ok(0) => return Ok(None),
+                Ok(_) => break,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error.into()),
+            }
+        }
+
+        // Blocks are lists, so the prefix is the first byte and, for lists
+        // of 56 bytes or more, the bytes of their length.
+        let length_length = match first[0] {
+            0xC0..=0xF7 => 0,
+            0xF8..=0xFF => (first[0] - 0xF7) as usize,
+            _ => return Err(malformed(self.offset, EthereumException::RLPDecodingError)),
+        };
+        let mut encoded = vec![0u8; 1 + length_length];
+        encoded[0] = first[0];
+        self.reader.read_exact(&mut encoded[1..]).map_err(|error| truncated(self.offset, error))?;
+        let length = rlp::decode_item_length(&encoded).map_err(|error| malformed(self.offset, error))?;
+
+        // Read through `take` so that a corrupt length does not allocate
+        // more than the file holds.
+        let remaining = (length - encoded.len()) as u64;
+        let read = self.reader.by_ref().take(remaining).read_to_end(&mut encoded)?;
+        if (read as u64) < remaining {
+            return Err(malformed(self.offset, EthereumException::RLPDecodingError));
+        }
+
+        let block = rlp::decode(&encoded)
+            .and_then(|decoded| Block::decode(&decoded))
+            .map_err(|error| malformed(self.offset, error))?;
+        self.offset += length as u64;
+        Ok(Some(block))
+    }
+}
+
+impl<R: Read> Iterator for BlockReader<R> {
+    type Item = Result<Block, ChainFileError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let block = self.read_block().transpose();
+        self.failed = matches!(block, Some(Err(_)));
+        block
+    }
+}
+
+///
+/// Append `block` to a chain file.
+///
+pub fn write_block<W: Write>(writer: &mut W, block: &Block) -> io::Result<()> {
+    writer.write_all(&rlp::encode(block))
+}
+
+/// How to deal with blocks which can not be applied.
+#[derive(Clone, Debug, Default)]
+pub struct ImportOptions {
+    /// Stop at the first block `state_transition` rejects. Otherwise the
+    /// block is left out, and importing carries on with the next one, as
+    /// long as the blocks can still follow the head of the chain.
+    pub stop_on_failure: bool,
+}
+
+/// What happened to the blocks of an import.
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    /// Number of blocks applied.
+    pub imported: u64,
+    /// Number of blocks left out as the chain already had them.
+    pub skipped: u64,
+    /// Numbers of the blocks rejected, and why.
+    pub failed: Vec<(u64, EthereumException)>,
+}
+
+///
+/// Apply the blocks read by `blocks` to `chain` with `state_transition`.
+///
+/// `state_transition` can reject a block after applying part of it, so the
+/// changes of every block are recorded with `State::begin_block`, and rolled
+/// back if the block is rejected. The chain is thus always left at the last
+/// block applied.
+///
+/// When carrying on past failures, a block can only be applied if it
+/// follows the head of the chain, as another block with the number of the
+/// rejected one would. Importing stops at the first block which does not,
+/// since no later block of the file could be applied either.
+///
+/// Parameters
+/// ----------
+/// chain :
+///     The chain to extend, at least holding its genesis block.
+/// blocks :
+///     The blocks to apply, in order.
+/// options :
+///     How to deal with blocks which can not be applied.
+/// on_block :
+///     Called with every block which is not skipped, how long applying it
+///     took, and whether it was applied.
+///
+/// Returns
+/// -------
+/// summary : `ImportSummary`
+///     What happened to the blocks.
+///
+pub fn import_blocks<R: Read>(
+    chain: &mut BlockChain,
+    mut blocks: BlockReader<R>,
+    options: &ImportOptions,
+    mut on_block: impl FnMut(&Block, Duration, Result<(), &EthereumException>),
+) -> Result<ImportSummary, ChainFileError> {
+    let mut summary = ImportSummary::default();
+    loop {
+        let offset = blocks.offset();
+        let Some(block) = blocks.next().transpose()? else {
+            break;
+        };
+        let head = &chain.blocks.last().expect("the chain has no genesis block").header.number;
+        if block.header.number <= *head {
+            summary.skipped += 1;
+            continue;
+        }
+
+        // No chain gets that long, so such a block can only be corrupt.
+        let number = u64::try_from(&block.header.number)
+            .map_err(|_| ChainFileError::Malformed(offset, EthereumException::InvalidBlock))?;
+        if !summary.failed.is_empty() && block.header.number != head + 1u8 {
+            break;
+        }
+
+        chain.state.begin_block();
+        let started = Instant::now();
+        match state_transition(chain, block.clone()) {
+            Ok(()) => {
+                chain.state.commit_block();
+                on_block(&block, started.elapsed(), Ok(()));
+                summary.imported += 1;
+            }
+            Err(error) => {
+                chain.state.rollback_block();
+                on_block(&block, started.elapsed(), Err(&error));
+                summary.failed.push((number, error));
+                if options.stop_on_failure {
+                    break;
+                }
+            }
+        }
+    }
+    Ok(summary)
+}
+
+///
+/// Write the blocks of the endpoint at `rpc_url`, from `first` up to and
+/// including `last`, or up to its latest block, to a chain file.
+///
+/// Parameters
+/// ----------
+/// writer :
+///     Where to write the chain file.
+/// rpc_url :
+///     The endpoint to download blocks from.
+/// first :
+///     Number of the first block to write, `0` for a file geth can import.
+/// last :
+///     Number of the last block to write.
+/// batch_size :
+///     How many blocks to download at a time.
+/// on_block :
+///     Called with every block written.
+///
+/// Returns
+/// -------
+/// exported : `u64`
+///     The number of blocks written.
+///
+pub fn export_blocks<W: Write>(
+    writer: &mut W,
+    rpc_url: &str,
+    first: u64,
+    last: Option<u64>,
+    batch_size: u64,
+    mut on_block: impl FnMut(&Block),
+) -> Result<u64, ChainFileError> {
+    let mut downloader = BlockDownloader::new(rpc_url, first, last, batch_size)?;
+    let mut exported = 0;
+    while let Some(block) = downloader.next_block()? {
+        write_block(writer, &block)?;
+        on_block(&block);
+        exported += 1;
+    }
+    writer.flush()?;
+    Ok(exported)
+}
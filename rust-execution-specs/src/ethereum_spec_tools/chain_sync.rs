@@ -0,0 +1,447 @@
+//!
+//! # Block Sync
+//!
+//! ## Introduction
+//!
+//! Follow a chain served over JSON-RPC, such as by an archive node, by
+//! downloading its blocks and applying them to a `BlockChain` with
+//! `state_transition`.
+//!
+//! Blocks are fetched ahead of the one being applied, in batches of a
+//! JSON-RPC batch request for the blocks, and another for their ommers.
+//! Every block must hash to the hash the endpoint gives for it, which
+//! catches blocks the Frontier types can not represent.
+//!
+//! Progress is saved as a single JSON file holding the chain id, the last
+//! 255 blocks, RLP encoded, and a geth style dump of the state, which is all
+//! `state_transition` needs to carry on after a restart:
+//!
+//! ```json
+//! { "chainId": 1, "blocks": ["0x…"], "state": { "root": "0x…", "accounts": {} } }
+//! ```
+//!
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use crate::ethereum::{
+    base_types::{Uint, U64},
+    exceptions::EthereumException,
+    frontier::{
+        fork::{state_transition, BlockChain},
+        fork_types::{Block, Header, Transaction},
+        state::state_root,
+        state_dump::{dump_state, load_state},
+    },
+    genesis::{genesis_chain, load_genesis_configuration},
+    rlp,
+    utils::hexadecimal::{
+        hex, hex_to_bytes, hex_to_bytes20, hex_to_bytes256, hex_to_bytes8, hex_to_hash, hex_to_u256,
+        hex_to_uint,
+    },
+};
+
+use super::rpc::{ClientError, RpcClient};
+
+/// Why syncing stopped.
+#[derive(Debug)]
+pub enum SyncError {
+    /// The endpoint could not be queried.
+    Rpc(ClientError),
+    /// The endpoint sent a block which could not be converted.
+    Malformed(u64, EthereumException),
+    /// `state_transition` rejected a block. Its changes are rolled back, so
+    /// the chain is left at the block before it.
+    InvalidBlock(u64, EthereumException),
+    /// The progress file could not be read or written.
+    Progress(String),
+}
+
+impl From<ClientError> for SyncError {
+    fn from(error: ClientError) -> Self {
+        SyncError::Rpc(error)
+    }
+}
+
+impl fmt::Display for SyncError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyncError::Rpc(error) => write!(f, "rpc: {error}"),
+            SyncError::Malformed(number, error) => write!(f, "block {number} is malformed: {error:?}"),
+            SyncError::InvalidBlock(number, error) => write!(f, "block {number} is invalid: {error:?}"),
+            SyncError::Progress(message) => write!(f, "progress: {message}"),
+        }
+    }
+}
+
+fn field<'a>(json: &'a Value, name: &str) -> Result<&'a str, EthereumException> {
+    json.get(name)
+        .and_then(Value::as_str)
+        .ok_or_else(|| EthereumException::JsonDecodeError(format!("missing or malformed {name}")))
+}
+
+///
+/// Convert a header in the JSON format of `eth_getBlockByNumber`.
+///
+pub fn header_from_json(json: &Value) -> Result<Header, EthereumException> {
+    Ok(Header {
+        parent_hash: hex_to_hash(field(json, "parentHash")?)?,
+        ommers_hash: hex_to_hash(field(json, "sha3Uncles")?)?,
+        coinbase: hex_to_bytes20(field(json, "miner")?)?,
+        state_root: hex_to_hash(field(json, "stateRoot")?)?,
+        transactions_root: hex_to_hash(field(json, "transactionsRoot")?)?,
+        receipt_root: hex_to_hash(field(json, "receiptsRoot")?)?,
+        bloom: hex_to_bytes256(field(json, "logsBloom")?)?,
+        difficulty: hex_to_uint(field(json, "difficulty")?)?,
+        number: hex_to_uint(field(json, "number")?)?,
+        gas_limit: hex_to_uint(field(json, "gasLimit")?)?,
+        gas_used: hex_to_uint(field(json, "gasUsed")?)?,
+        timestamp: hex_to_u256(field(json, "timestamp")?)?,
+        extra_data: hex_to_bytes(field(json, "extraData")?)?,
+        mix_digest: hex_to_hash(field(json, "mixHash")?)?,
+        nonce: hex_to_bytes8(field(json, "nonce")?)?,
+    })
+}
+
+///
+/// Convert a transaction in the JSON format of `eth_getTransactionByHash`.
+/// Only untyped transactions can be represented.
+///
+pub fn transaction_from_json(json: &Value) -> Result<Transaction, EthereumException> {
+    if let Some(kind) = json.get("type").and_then(Value::as_str) {
+        if hex_to_uint(kind)? != Uint::from(0u8) {
+            return Err(EthereumException::JsonDecodeError(format!("unsupported transaction type {kind}")));
+        }
+    }
+    let to = match json.get("to") {
+        None | Some(Value::Null) => None,
+        Some(_) => Some(hex_to_bytes20(field(json, "to")?)?),
+    };
+    Ok(Transaction {
+        nonce: hex_to_u256(field(json, "nonce")?)?,
+        gas_price: hex_to_u256(field(json, "gasPrice")?)?,
+        gas: hex_to_u256(field(json, "gas")?)?,
+        to,
+        value: hex_to_u256(field(json, "value")?)?,
+        data: hex_to_bytes(field(json, "input")?)?,
+        v: hex_to_u256(field(json, "v")?)?,
+        r: hex_to_u256(field(json, "r")?)?,
+        s: hex_to_u256(field(json, "s")?)?,
+    })
+}
+
+///
+/// Convert a block in the JSON format of `eth_getBlockByNumber`, with full
+/// transactions, and its ommers, checking the hashes the JSON gives.
+///
+/// Parameters
+/// ----------
+/// json :
+///     The block.
+/// ommers :
+///     The headers of its ommers, in the order of its `uncles`.
+///
+/// Returns
+/// -------
+/// block : `Block`
+///     The converted block.
+///
+/// Raises
+/// ------
+/// InvalidBlock :
+///     If the block, one of its transactions or ommers does not hash to its
+///     hash, as when it has fields Frontier does not know about.
+///
+pub fn block_from_json(json: &Value, ommers: &[Value]) -> Result<Block, EthereumException> {
+    let header = header_from_json(json)?;
+    if rlp::rlp_hash(&header) != hex_to_hash(field(json, "hash")?)? {
+        return Err(EthereumException::InvalidBlock);
+    }
+
+    let transactions = json
+        .get("transactions")
+        .and_then(Value::as_array)
+        .ok_or_else(|| EthereumException::JsonDecodeError("missing or malformed transactions".to_string()))?
+        .iter()
+        .map(|json| {
+            let tx = transaction_from_json(json)?;
+            if rlp::rlp_hash(&tx) != hex_to_hash(field(json, "hash")?)? {
+                return Err(EthereumException::InvalidBlock);
+            }
+            Ok(tx)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let ommers = ommers
+        .iter()
+        .map(|json| {
+            let ommer = header_from_json(json)?;
+            if rlp::rlp_hash(&ommer) != hex_to_hash(field(json, "hash")?)? {
+                return Err(EthereumException::InvalidBlock);
+            }
+            Ok(ommer)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Block { header, transactions, ommers })
+}
+
+///
+/// Downloads consecutive blocks from a JSON-RPC endpoint, a batch at a
+/// time.
+///
+pub struct BlockDownloader {
+    client: RpcClient,
+    queue: VecDeque<Block>,
+    next: u64,
+    end: Option<u64>,
+    batch_size: u64,
+    caught_up: bool,
+}
+
+impl BlockDownloader {
+    ///
+    /// Download the blocks of the endpoint at `rpc_url`, from `start` up to
+    /// and including `end`, or up to its latest block.
+    ///
+    pub fn new(rpc_url: &str, start: u64, end: Option<u64>, batch_size: u64) -> Result<Self, SyncError> {
+        Ok(BlockDownloader {
+            client: RpcClient::new(rpc_url)?,
+            queue: VecDeque::with_capacity(batch_size as usize),
+            next: start,
+            end,
+            batch_size: batch_size.max(1),
+            caught_up: false,
+        })
+    }
+
+    fn download(&mut self) -> Result<(), SyncError> {
+        let mut last = self.next + self.batch_size - 1;
+        if let Some(end) = self.end {
+            last = last.min(end);
+        }
+        let calls = (self.next..=last)
+            .map(|number| ("eth_getBlockByNumber", json!([format!("{number:#x}"), true])))
+            .collect();
+        let mut blocks = self.client.batch(calls)?;
+
+        // A missing block is past the latest one.
+        if let Some(missing) = blocks.iter().position(Value::is_null) {
+            blocks.truncate(missing);
+            self.caught_up = true;
+        }
+
+        let ommer_calls = blocks
+            .iter()
+            .flat_map(|block| {
+                let count = block.get("uncles").and_then(Value::as_array).map_or(0, Vec::len);
+                (0..count).map(move |index| {
+                    ("eth_getUncleByBlockNumberAndIndex", json!([block["number"], format!("{index:#x}")]))
+                })
+            })
+            .collect();
+        let mut ommers = self.client.batch(ommer_calls)?.into_iter();
+
+        for json in &blocks {
+            let number = self.next;
+            let count = json.get("uncles").and_then(Value::as_array).map_or(0, Vec::len);
+            let ommers: Vec<Value> = ommers.by_ref().take(count).collect();
+            let block = block_from_json(json, &ommers).map_err(|error| SyncError::Malformed(number, error))?;
+            self.queue.push_back(block);
+            self.next += 1;
+        }
+        Ok(())
+    }
+
+    ///
+    /// The next block, or `None` once the latest block, or `end`, has been
+    /// returned.
+    ///
+    pub fn next_block(&mut self) -> Result<Option<Block>, SyncError> {
+        let past_end = self.end.is_some_and(|end| self.next > end);
+        if self.queue.is_empty() && !self.caught_up && !past_end {
+            self.download()?;
+        }
+        Ok(self.queue.pop_front())
+    }
+}
+
+///
+/// Save the progress of `chain` to `path`, replacing the file at once so
+/// that it is never left half written.
+///
+pub fn save_progress(chain: &BlockChain, path: &Path) -> Result<(), SyncError> {
+    let state = dump_state(&chain.state).map_err(|error| SyncError::Progress(format!("{error:?}")))?;
+    let blocks: Vec<String> = chain.blocks.iter().map(|block| hex(&rlp::encode(block))).collect();
+    let progress = json!({
+        "chainId": u64::from(chain.chain_id),
+        "blocks": blocks,
+        "state": state,
+    });
+
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".partial");
+    let partial = PathBuf::from(partial);
+    fs::write(&partial, progress.to_string()).map_err(|error| SyncError::Progress(error.to_string()))?;
+    fs::rename(&partial, path).map_err(|error| SyncError::Progress(error.to_string()))
+}
+
+///
+/// Load the progress saved to `path` by `save_progress`, if there is any.
+///
+pub fn load_progress(path: &Path) -> Result<Option<BlockChain>, SyncError> {
+    let malformed = |error: EthereumException| SyncError::Progress(format!("{}: {error:?}", path.display()));
+    let json = match fs::read_to_string(path) {
+        Ok(json) => json,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(SyncError::Progress(error.to_string())),
+    };
+    let progress: Value = serde_json::from_str(&json)
+        .map_err(|error| malformed(EthereumException::JsonDecodeError(error.to_string())))?;
+
+    let chain_id = progress["chainId"]
+        .as_u64()
+        .ok_or_else(|| malformed(EthereumException::JsonDecodeError("missing chainId".to_string())))?;
+    let blocks = progress["blocks"]
+        .as_array()
+        .ok_or_else(|| malformed(EthereumException::JsonDecodeError("missing blocks".to_string())))?
+        .iter()
+        .map(|block| {
+            let encoded = hex_to_bytes(block.as_str().unwrap_or_default())?;
+            Block::decode(&rlp::decode(&encoded)?)
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(malformed)?;
+    let state = load_state(&progress["state"]).map_err(malformed)?;
+
+    let head = blocks.last().ok_or_else(|| malformed(EthereumException::InvalidBlock))?;
+    if state_root(&state).map_err(malformed)? != head.header.state_root {
+        return Err(malformed(EthereumException::InvalidBlock));
+    }
+    Ok(Some(BlockChain { blocks, state, chain_id: U64::from(chain_id) }))
+}
+
+///
+/// Resume a chain from the progress saved to `progress`, or start it from
+/// the genesis file at `genesis` if there is no progress yet.
+///
+/// Parameters
+/// ----------
+/// progress :
+///     The progress file, if any.
+/// genesis :
+///     Path of the genesis file of the chain.
+///
+/// Returns
+/// -------
+/// chain : `BlockChain`
+///     The chain to carry on from.
+///
+pub fn load_progress_or_genesis(progress: Option<&Path>, genesis: &str) -> Result<BlockChain, SyncError> {
+    if let Some(chain) = progress.map(load_progress).transpose()?.flatten() {
+        return Ok(chain);
+    }
+    load_genesis_configuration(genesis)
+        .and_then(genesis_chain)
+        .map_err(|error| SyncError::Progress(format!("{genesis}: {error:?}")))
+}
+
+/// How far to sync, and how to save progress.
+#[derive(Clone, Debug)]
+pub struct SyncOptions {
+    /// Stop after this block, rather than at the latest one.
+    pub until: Option<u64>,
+    /// Where to save progress.
+    pub progress: Option<PathBuf>,
+    /// Save progress every this many blocks, and when syncing stops.
+    pub checkpoint_interval: u64,
+    /// How many blocks to download at a time.
+    pub batch_size: u64,
+}
+
+impl Default for SyncOptions {
+    fn default() -> Self {
+        SyncOptions { until: None, progress: None, checkpoint_interval: 1000, batch_size: 64 }
+    }
+}
+
+///
+/// Apply the blocks of the endpoint at `rpc_url` which follow the head of
+/// `chain`.
+///
+/// Parameters
+/// ----------
+/// chain :
+///     The chain to extend, at least holding its genesis block.
+/// rpc_url :
+///     The endpoint to download blocks from.
+/// options :
+///     How far to sync, and how to save progress.
+/// on_block :
+///     Called with every block applied, and how long applying it took.
+///
+/// Returns
+/// -------
+/// applied : `u64`
+///     The number of blocks applied.
+///
+/// Progress is saved when syncing stops, also when the endpoint fails,
+/// sends a malformed block, or sends a block `state_transition` rejects, as
+/// every block applied until then is valid.
+///
+pub fn sync(
+    chain: &mut BlockChain,
+    rpc_url: &str,
+    options: &SyncOptions,
+    mut on_block: impl FnMut(&Block, Duration),
+) -> Result<u64, SyncError> {
+    let head = u64::try_from(&chain.blocks.last().expect("the chain has no genesis block").header.number)
+        .expect("block numbers fit in 64 bits");
+    if options.until.is_some_and(|until| until <= head) {
+        return Ok(0);
+    }
+    let mut downloader = BlockDownloader::new(rpc_url, head + 1, options.until, options.batch_size)?;
+
+    let mut applied = 0;
+    let stopped = loop {
+        let block = match downloader.next_block() {
+            Ok(Some(block)) => block,
+            Ok(None) => break None,
+            Err(error) => break Some(error),
+        };
+        let number = head + applied + 1;
+        if block.header.number != Uint::from(number) {
+            break Some(SyncError::Malformed(number, EthereumException::InvalidBlock));
+        }
+
+        // `state_transition` can reject a block after applying part of it.
+        chain.state.begin_block();
+        let started = Instant::now();
+        if let Err(error) = state_transition(chain, block.clone()) {
+            chain.state.rollback_block();
+            break Some(SyncError::InvalidBlock(number, error));
+        }
+        chain.state.commit_block();
+        on_block(&block, started.elapsed());
+        applied += 1;
+
+        if let Some(path) = &options.progress {
+            if applied % options.checkpoint_interval.max(1) == 0 {
+                save_progress(chain, path)?;
+            }
+        }
+    };
+
+    if let Some(path) = &options.progress {
+        save_progress(chain, path)?;
+    }
+    match stopped {
+        Some(error) => Err(error),
+        None => Ok(applied),
+    }
+}
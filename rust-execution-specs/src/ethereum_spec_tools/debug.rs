@@ -0,0 +1,96 @@
+//!
+//! # EVM Debugger
+//!
+//! ## Introduction
+//!
+//! Step through a transaction, executed against a geth style state dump:
+//!
+//! ```text
+//! debug --state dump.json --transaction tx.json --break SSTORE
+//! ```
+//!
+
+use std::io::{stdin, stdout};
+
+use clap::Parser;
+use execution_specs_rs::ethereum::{
+    base_types::{Uint, U256},
+    frontier::{
+        state_dump::load_state,
+        vm::{interpreter::process_message_call, Environment},
+    },
+    utils::hexadecimal::hex,
+};
+use execution_specs_rs::ethereum_spec_tools::debugger::{transaction_message, Breakpoint, Debugger};
+use serde_json::Value;
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+pub struct Args {
+    /// State dump to execute the transaction against.
+    #[arg(short, long)]
+    state: String,
+
+    /// Transaction to execute, as the JSON arguments of `eth_call`.
+    #[arg(short, long)]
+    transaction: String,
+
+    /// Break at a program counter, or on an opcode.
+    #[arg(short, long = "break")]
+    breakpoints: Vec<String>,
+
+    /// Number of the block the transaction is executed in.
+    #[arg(long, default_value_t = 0)]
+    number: u64,
+
+    /// Timestamp of the block the transaction is executed in.
+    #[arg(long, default_value_t = 0)]
+    timestamp: u64,
+
+    /// Gas limit of the block the transaction is executed in.
+    #[arg(long, default_value_t = 30_000_000)]
+    gas_limit: u64,
+}
+
+fn read_json(path: &str) -> Value {
+    let json = std::fs::read_to_string(path).unwrap_or_else(|error| panic!("{path}: {error}"));
+    serde_json::from_str(&json).unwrap_or_else(|error| panic!("{path}: {error}"))
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let state = load_state(&read_json(&args.state)).expect("malformed state dump");
+    let transaction = read_json(&args.transaction);
+    let message = transaction_message(&transaction, &state).expect("malformed transaction");
+
+    let mut debugger = Debugger::new(stdin().lock(), stdout());
+    for breakpoint in &args.breakpoints {
+        debugger.add_breakpoint(
+            Breakpoint::parse(breakpoint)
+                .unwrap_or_else(|| panic!("not a pc or an opcode: {breakpoint}")),
+        );
+    }
+
+    let mut env = Environment {
+        caller: message.caller,
+        block_hashes: Vec::new(),
+        origin: message.caller,
+        coinbase: [0; 20],
+        number: Uint::from(args.number),
+        gas_limit: Uint::from(args.gas_limit),
+        gas_price: U256::from(0u8),
+        time: U256::from(args.timestamp),
+        difficulty: Uint::from(0u8),
+        state,
+        inspector: debugger,
+    };
+    let gas = message.gas.clone();
+    let output = process_message_call(message, &mut env).expect("state unavailable");
+
+    println!("output: {}", hex(&output.output));
+    println!("gas used: {}", gas - output.gas_left);
+    if let Some(error) = output.error {
+        println!("error: {error:?}");
+    }
+}
@@ -0,0 +1,393 @@
+//!
+//! # EVM Debugger
+//!
+//! ## Introduction
+//!
+//! An interactive step debugger over the Frontier interpreter. The `Debugger`
+//! is an `Inspector` that pauses before opcodes, and reads commands until
+//! execution should go on:
+//!
+//! ```text
+//! step | s              execute the next opcode
+//! next | n              execute the next opcode, stepping over calls
+//! continue | c          run until a breakpoint
+//! break | b <pc|op>     break at a program counter, or on an opcode
+//! delete <n>            delete the breakpoint numbered `n`
+//! breakpoints           list the breakpoints
+//! stack                 print the stack, top first
+//! memory                print the memory
+//! storage [slot]        print a slot, or the slots seen so far, of the
+//!                       current target
+//! gas                   print the gas left and the refund counter
+//! where                 print the current location
+//! quit | q              run to the end without stopping
+//! ```
+//!
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{BufRead, Write};
+
+use serde_json::Value;
+
+use crate::ethereum::{
+    base_types::{Bytes, Bytes32, Uint},
+    exceptions::EthereumException,
+    frontier::{
+        fork_types::Address,
+        state::{get_account, get_code_hash, StateBackend},
+        utils::address::compute_contract_address,
+        vm::{
+            exceptions::EvmError,
+            inspector::{Frame, Inspector},
+            instructions::Ops,
+            Message,
+        },
+    },
+    utils::hexadecimal::{hex, hex_to_bytes, hex_to_bytes20, hex_to_u256, hex_to_uint},
+};
+
+const HELP: &str = "\
+step | s              execute the next opcode
+next | n              execute the next opcode, stepping over calls
+continue | c          run until a breakpoint
+break | b <pc|op>     break at a program counter, or on an opcode
+delete <n>            delete the breakpoint numbered `n`
+breakpoints           list the breakpoints
+stack                 print the stack, top first
+memory                print the memory
+storage [slot]        print a slot, or the slots seen so far, of the current target
+gas                   print the gas left and the refund counter
+where                 print the current location
+quit | q              run to the end without stopping";
+
+/// Where execution pauses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Breakpoint {
+    /// Before the opcode at this program counter, in any frame.
+    Pc(usize),
+    /// Before any execution of this opcode.
+    Op(Ops),
+}
+
+impl Breakpoint {
+    /// Parse a program counter, in decimal or `0x` prefixed hexadecimal, or
+    /// the mnemonic of an opcode.
+    pub fn parse(breakpoint: &str) -> Option<Breakpoint> {
+        if let Some(pc) = breakpoint.strip_prefix("0x") {
+            usize::from_str_radix(pc, 16).ok().map(Breakpoint::Pc)
+        } else if let Ok(pc) = breakpoint.parse() {
+            Some(Breakpoint::Pc(pc))
+        } else {
+            Ops::from_name(breakpoint).map(Breakpoint::Op)
+        }
+    }
+
+    fn hit(&self, pc: usize, op: u8) -> bool {
+        match self {
+            Breakpoint::Pc(breakpoint) => *breakpoint == pc,
+            Breakpoint::Op(breakpoint) => *breakpoint as u8 == op,
+        }
+    }
+}
+
+enum Mode {
+    Step,
+    StepOver(Uint),
+    Continue,
+    Detached,
+}
+
+///
+/// Pauses execution on breakpoints, reading commands from `input` and
+/// writing to `output`.
+///
+/// The debugger pauses before the first opcode, and runs to the end once
+/// `input` is exhausted.
+///
+pub struct Debugger<R, W> {
+    input: R,
+    output: W,
+    breakpoints: Vec<Breakpoint>,
+    mode: Mode,
+    storage_keys: BTreeMap<Address, BTreeSet<Bytes32>>,
+}
+
+impl<R: BufRead, W: Write> Debugger<R, W> {
+    /// Create a debugger with no breakpoints.
+    pub fn new(input: R, output: W) -> Self {
+        Self {
+            input,
+            output,
+            breakpoints: Vec::new(),
+            mode: Mode::Step,
+            storage_keys: BTreeMap::new(),
+        }
+    }
+
+    /// Pause before the opcodes matching `breakpoint`.
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        self.breakpoints.push(breakpoint);
+    }
+
+    fn should_pause(&self, frame: &Frame, op: u8) -> bool {
+        match &self.mode {
+            Mode::Step => true,
+            Mode::StepOver(depth) => frame.message.depth <= *depth,
+            Mode::Continue => self.breakpoints.iter().any(|breakpoint| breakpoint.hit(frame.pc, op)),
+            Mode::Detached => false,
+        }
+    }
+
+    fn pause(&mut self, frame: &Frame, op: u8) {
+        self.print_location(frame, op);
+        loop {
+            let _ = write!(self.output, "(debug) ");
+            let _ = self.output.flush();
+
+            let mut line = String::new();
+            if !matches!(self.input.read_line(&mut line), Ok(read) if read > 0) {
+                self.mode = Mode::Detached;
+                return;
+            }
+
+            let mut words = line.split_whitespace();
+            match (words.next(), words.next()) {
+                (Some("step" | "s"), _) => {
+                    self.mode = Mode::Step;
+                    return;
+                }
+                (Some("next" | "n"), _) => {
+                    self.mode = Mode::StepOver(frame.message.depth.clone());
+                    return;
+                }
+                (Some("continue" | "c"), _) => {
+                    self.mode = Mode::Continue;
+                    return;
+                }
+                (Some("quit" | "q"), _) => {
+                    self.mode = Mode::Detached;
+                    return;
+                }
+                (Some("break" | "b"), Some(breakpoint)) => match Breakpoint::parse(breakpoint) {
+                    Some(breakpoint) => {
+                        self.breakpoints.push(breakpoint);
+                        let _ = writeln!(self.output, "breakpoint {}: {breakpoint:?}", self.breakpoints.len() - 1);
+                    }
+                    None => {
+                        let _ = writeln!(self.output, "not a pc or an opcode: {breakpoint}");
+                    }
+                },
+                (Some("delete"), Some(number)) => match number.parse::<usize>() {
+                    Ok(number) if number < self.breakpoints.len() => {
+                        self.breakpoints.remove(number);
+                    }
+                    _ => {
+                        let _ = writeln!(self.output, "no breakpoint {number}");
+                    }
+                },
+                (Some("breakpoints"), _) => {
+                    for (number, breakpoint) in self.breakpoints.iter().enumerate() {
+                        let _ = writeln!(self.output, "{number}: {breakpoint:?}");
+                    }
+                }
+                (Some("stack"), _) => {
+                    for (index, item) in frame.stack.iter().rev().enumerate() {
+                        let _ = writeln!(self.output, "{index:>4}: {item:#x}");
+                    }
+                }
+                (Some("memory"), _) => {
+                    for (offset, word) in frame.memory.chunks(32).enumerate() {
+                        let _ = writeln!(self.output, "{:#06x}: {}", offset * 32, &hex(word)[2..]);
+                    }
+                }
+                (Some("storage"), slot) => self.print_storage(frame, slot),
+                (Some("gas"), _) => {
+                    let _ = writeln!(
+                        self.output,
+                        "gas left: {}, refund: {}",
+                        frame.gas_left, frame.refund_counter
+                    );
+                }
+                (Some("where"), _) => self.print_location(frame, op),
+                (None, _) => {}
+                _ => {
+                    let _ = writeln!(self.output, "{HELP}");
+                }
+            }
+        }
+    }
+
+    fn print_location(&mut self, frame: &Frame, op: u8) {
+        let name = Ops::from_byte(op).map_or_else(|| "INVALID".to_string(), |op| op.name());
+        let _ = writeln!(
+            self.output,
+            "{} depth {} pc {:#06x} {} gas {}",
+            hex(&frame.message.current_target),
+            frame.message.depth,
+            frame.pc,
+            name,
+            frame.gas_left
+        );
+    }
+
+    fn print_storage(&mut self, frame: &Frame, slot: Option<&str>) {
+        let target = frame.message.current_target;
+        let keys = match slot.map(hex_to_u256) {
+            Some(Ok(slot)) => vec![slot.to_be_bytes32()],
+            Some(Err(_)) => {
+                let _ = writeln!(self.output, "not a slot: {}", slot.unwrap_or_default());
+                return;
+            }
+            None => self
+                .storage_keys
+                .get(&target)
+                .map(|keys| keys.iter().copied().collect())
+                .unwrap_or_default(),
+        };
+
+        for key in keys {
+            match frame.state.get_storage(&target, &key) {
+                Ok(value) => {
+                    let _ = writeln!(self.output, "{}: {value:#x}", hex(&key));
+                }
+                Err(error) => {
+                    let _ = writeln!(self.output, "{}: {error:?}", hex(&key));
+                }
+            }
+        }
+    }
+}
+
+impl<R: BufRead, W: Write> Inspector for Debugger<R, W> {
+    fn step(&mut self, frame: &Frame, op: u8) {
+        if op == Ops::SLOAD as u8 || op == Ops::SSTORE as u8 {
+            if let Some(key) = frame.stack.last() {
+                self.storage_keys
+                    .entry(frame.message.current_target)
+                    .or_default()
+                    .insert(key.to_be_bytes32());
+            }
+        }
+
+        if self.should_pause(frame, op) {
+            self.pause(frame, op);
+        }
+    }
+
+    fn call(&mut self, frame: &Frame) {
+        if !matches!(self.mode, Mode::Detached) {
+            let _ = writeln!(
+                self.output,
+                "-> call {} with {} gas",
+                hex(&frame.message.current_target),
+                frame.message.gas
+            );
+        }
+    }
+
+    fn create(&mut self, frame: &Frame) {
+        if !matches!(self.mode, Mode::Detached) {
+            let _ = writeln!(
+                self.output,
+                "-> create {} with {} gas",
+                hex(&frame.message.current_target),
+                frame.message.gas
+            );
+        }
+    }
+
+    fn call_end(&mut self, frame: &Frame, error: Option<&EvmError>) {
+        self.frame_end(frame, error);
+    }
+
+    fn create_end(&mut self, frame: &Frame, error: Option<&EvmError>) {
+        self.frame_end(frame, error);
+    }
+}
+
+impl<R: BufRead, W: Write> Debugger<R, W> {
+    fn frame_end(&mut self, frame: &Frame, error: Option<&EvmError>) {
+        if matches!(self.mode, Mode::Detached) {
+            return;
+        }
+        let _ = match error {
+            Some(error) => writeln!(
+                self.output,
+                "<- {} halted with {error:?}",
+                hex(&frame.message.current_target)
+            ),
+            None => writeln!(
+                self.output,
+                "<- {} returned {}, {} gas left",
+                hex(&frame.message.current_target),
+                hex(frame.output),
+                frame.gas_left
+            ),
+        };
+    }
+}
+
+///
+/// Build the message of a transaction given as JSON, with `from`, and
+/// optionally `to`, `gas`, `value` and `data` (or `input`).
+///
+/// The transaction is not validated, and no intrinsic gas is charged.
+///
+/// Parameters
+/// ----------
+/// transaction :
+///     The transaction, in the JSON format of `eth_call`.
+/// state :
+///     The state the transaction is executed in.
+///
+/// Returns
+/// -------
+/// message : `ethereum.frontier.vm.Message`
+///     The top level message of the transaction.
+///
+pub fn transaction_message<S: StateBackend>(
+    transaction: &Value,
+    state: &S,
+) -> Result<Message, EthereumException> {
+    let field = |name: &str| transaction.get(name).and_then(Value::as_str);
+    let caller = hex_to_bytes20(field("from").ok_or_else(|| {
+        EthereumException::JsonDecodeError("missing or malformed from".to_string())
+    })?)?;
+    let to = field("to").map(hex_to_bytes20).transpose()?;
+    let gas = field("gas").map(hex_to_uint).transpose()?.unwrap_or_else(|| Uint::from(30_000_000u32));
+    let value = field("value").map(hex_to_u256).transpose()?.unwrap_or_default();
+    let data = field("data")
+        .or_else(|| field("input"))
+        .map(hex_to_bytes)
+        .transpose()?
+        .unwrap_or_default();
+
+    Ok(match to {
+        Some(to) => Message {
+            caller,
+            target: Some(to),
+            current_target: to,
+            gas,
+            value,
+            data,
+            code_address: Some(to),
+            code: get_account(state, &to)?.code,
+            code_hash: Some(get_code_hash(state, &to)?),
+            depth: Uint::from(0u8),
+            is_callcode: false,
+        },
+        None => Message {
+            caller,
+            target: None,
+            current_target: compute_contract_address(&caller, &get_account(state, &caller)?.nonce),
+            gas,
+            value,
+            data: Bytes::default(),
+            code_address: None,
+            code: data,
+            code_hash: None,
+            depth: Uint::from(0u8),
+            is_callcode: false,
+        },
+    })
+}
@@ -0,0 +1,65 @@
+//!
+//! # Block Export
+//!
+//! ## Introduction
+//!
+//! Download a range of blocks from a JSON-RPC endpoint into a chain file in
+//! the format of `geth export`, for `import` to apply later without a
+//! network:
+//!
+//! ```text
+//! export --rpc-url http://127.0.0.1:8545 --last 99999 mainnet-0-99999.rlp
+//! ```
+//!
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+use std::process::exit;
+
+use clap::Parser;
+use execution_specs_rs::ethereum::base_types::Uint;
+use execution_specs_rs::ethereum_spec_tools::chain_file::export_blocks;
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+pub struct Args {
+    /// Chain file to write.
+    file: PathBuf,
+
+    /// JSON-RPC endpoint to download blocks from.
+    #[arg(short, long)]
+    rpc_url: String,
+
+    /// First block to write.
+    #[arg(long, default_value_t = 0)]
+    first: u64,
+
+    /// Last block to write, rather than the latest one.
+    #[arg(long)]
+    last: Option<u64>,
+
+    /// Number of blocks to download at a time.
+    #[arg(long, default_value_t = 64)]
+    batch_size: u64,
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let file = File::create(&args.file).unwrap_or_else(|error| panic!("{}: {error}", args.file.display()));
+    let mut writer = BufWriter::new(file);
+    let result = export_blocks(&mut writer, &args.rpc_url, args.first, args.last, args.batch_size, |block| {
+        if &block.header.number % 1000u32 == Uint::from(0u8) {
+            println!("block {}", block.header.number);
+        }
+    });
+
+    match result {
+        Ok(exported) => println!("exported {exported} blocks to {}", args.file.display()),
+        Err(error) => {
+            eprintln!("{error}");
+            exit(1);
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//!
+//! # Block Import
+//!
+//! ## Introduction
+//!
+//! Apply the blocks of chain files, as written by `geth export` or by
+//! `export`, to a Frontier chain, without a network:
+//!
+//! ```text
+//! import mainnet-0-99999.rlp
+//! import --progress mainnet.json --stop-on-failure mainnet-100000-199999.rlp
+//! ```
+//!
+//! The chain starts from the saved progress of `sync` or an earlier import,
+//! if there is any, and the progress is saved again at the end.
+//!
+
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+use std::process::exit;
+
+use clap::Parser;
+use execution_specs_rs::ethereum::{rlp, utils::hexadecimal::hex};
+use execution_specs_rs::ethereum_spec_tools::{
+    chain_file::{import_blocks, BlockReader, ImportOptions},
+    chain_sync::{load_progress_or_genesis, save_progress},
+};
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+pub struct Args {
+    /// Chain files to import, in order.
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+
+    /// Genesis file of the chain, used when there is no progress yet.
+    #[arg(short, long, default_value = "./assets/mainnet.json")]
+    genesis: String,
+
+    /// File to resume from, and save progress to.
+    #[arg(short, long)]
+    progress: Option<PathBuf>,
+
+    /// Stop at the first block which can not be applied.
+    #[arg(long)]
+    stop_on_failure: bool,
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let mut chain = load_progress_or_genesis(args.progress.as_deref(), &args.genesis)
+        .unwrap_or_else(|error| panic!("{error}"));
+    println!("starting after block {}", chain.blocks.last().unwrap().header.number);
+
+    let options = ImportOptions { stop_on_failure: args.stop_on_failure };
+    let mut failed = 0;
+    for path in &args.files {
+        let file = File::open(path).unwrap_or_else(|error| panic!("{}: {error}", path.display()));
+        let result = import_blocks(&mut chain, BlockReader::new(BufReader::new(file)), &options, |block, elapsed, result| {
+            let header = &block.header;
+            match result {
+                Ok(()) => println!(
+                    "block {} {} {} txs {:?}",
+                    header.number,
+                    hex(&rlp::rlp_hash(header)),
+                    block.transactions.len(),
+                    elapsed
+                ),
+                Err(error) => println!("block {} {} failed {:?}: {error:?}", header.number, hex(&rlp::rlp_hash(header)), elapsed),
+            }
+        });
+
+        match result {
+            Ok(summary) => {
+                println!(
+                    "{}: imported {} blocks, skipped {}, failed {}",
+                    path.display(),
+                    summary.imported,
+                    summary.skipped,
+                    summary.failed.len()
+                );
+                failed += summary.failed.len();
+            }
+            Err(error) => {
+                eprintln!("{}: {error}", path.display());
+                failed += 1;
+            }
+        }
+        if failed > 0 && args.stop_on_failure {
+            break;
+        }
+    }
+
+    if let Some(path) = &args.progress {
+        save_progress(&chain, path).unwrap_or_else(|error| panic!("{error}"));
+    }
+    println!("up to block {}", chain.blocks.last().unwrap().header.number);
+    if failed > 0 {
+        exit(1);
+    }
+}
@@ -4,4 +4,11 @@
 //! Library of utilities and tools necessary for rendering (or otherwise working
 //! with) the Ethereum specifications.
 //!
-pub mod forks;
\ No newline at end of file
+pub mod forks;
+pub mod debugger;
+pub mod assembly;
+pub mod cfg;
+pub mod rpc;
+pub mod node;
+pub mod chain_sync;
+pub mod chain_file;
\ No newline at end of file
@@ -0,0 +1,494 @@
+//!
+//! # Development Node
+//!
+//! ## Introduction
+//!
+//! A `Handler` answering the Ethereum JSON-RPC API from a Frontier
+//! `BlockChain`, so that off-the-shelf tooling can be pointed at the
+//! specification.
+//!
+//! Every transaction sent is mined at once, into a block of its own which
+//! pays the block reward to the coinbase of the node. Calls and gas
+//! estimates execute as if in that next block.
+//!
+//! Only the latest state is kept, so state queries accept no block but
+//! `latest`, or `pending`, which is the same since there is no transaction
+//! pool. As in Frontier, transactions are signed without a chain id.
+//!
+
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use num_traits::CheckedSub;
+use serde_json::{json, Value};
+
+use crate::ethereum::{
+    base_types::{Bytes, Uint, U256},
+    exceptions::EthereumException,
+    frontier::{
+        call::call,
+        fork::{
+            apply_body_with_receipts, calculate_block_difficulty, check_transaction,
+            get_last_256_block_hashes, BlockChain,
+        },
+        fork_types::{Address, Block, Hash32, Header, Receipt, Transaction},
+        gas_estimation::estimate_gas,
+        state::{get_account, get_storage, state_root, State},
+        utils::{address::compute_contract_address, message::prepare_message},
+        vm::Environment,
+    },
+    rlp,
+    utils::hexadecimal::{hex, hex_to_bytes, hex_to_bytes20, hex_to_hash, hex_to_u256, hex_to_uint},
+};
+
+use super::rpc::{Handler, RpcError, METHOD_NOT_FOUND};
+
+/// Where a transaction was included, and its outcome.
+struct IncludedTransaction {
+    block_number: Uint,
+    index: usize,
+    sender: Address,
+    gas_used: Uint,
+    receipt: Receipt,
+}
+
+///
+/// A block chain which mines the transactions sent to it, with the history
+/// needed to answer queries about them.
+///
+pub struct Node {
+    chain: BlockChain,
+    blocks: Vec<Block>,
+    transactions: HashMap<Hash32, IncludedTransaction>,
+    coinbase: Address,
+}
+
+fn quantity(value: impl std::fmt::LowerHex) -> Value {
+    json!(format!("{value:#x}"))
+}
+
+fn data(bytes: &[u8]) -> Value {
+    json!(hex(bytes))
+}
+
+fn invalid(error: EthereumException) -> RpcError {
+    RpcError::invalid_params(format!("{error:?}"))
+}
+
+fn param(params: &[Value], index: usize) -> Result<&str, RpcError> {
+    params
+        .get(index)
+        .and_then(Value::as_str)
+        .ok_or_else(|| RpcError::invalid_params(format!("missing or malformed parameter {index}")))
+}
+
+fn now() -> U256 {
+    let since_epoch = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    U256::from(since_epoch.as_secs())
+}
+
+///
+/// Read the sender and transaction of `eth_call` or `eth_estimateGas`, given
+/// as JSON with optional `from`, `to`, `gas`, `gasPrice`, `value` and `data`
+/// (or `input`). The sender defaults to the zero address.
+///
+fn call_transaction(transaction: Option<&Value>) -> Result<(Address, Transaction), RpcError> {
+    let transaction = transaction
+        .filter(|transaction| transaction.is_object())
+        .ok_or_else(|| RpcError::invalid_params("missing or malformed transaction"))?;
+    let field = |name: &str| transaction.get(name).and_then(Value::as_str);
+
+    let sender = field("from").map(hex_to_bytes20).transpose().map_err(invalid)?.unwrap_or_default();
+    let tx = Transaction {
+        nonce: U256::ZERO,
+        gas_price: field("gasPrice").map(hex_to_u256).transpose().map_err(invalid)?.unwrap_or_default(),
+        gas: field("gas").map(hex_to_u256).transpose().map_err(invalid)?.unwrap_or_default(),
+        to: field("to").map(hex_to_bytes20).transpose().map_err(invalid)?,
+        value: field("value").map(hex_to_u256).transpose().map_err(invalid)?.unwrap_or_default(),
+        data: field("data")
+            .or_else(|| field("input"))
+            .map(hex_to_bytes)
+            .transpose()
+            .map_err(invalid)?
+            .unwrap_or_default(),
+        v: U256::ZERO,
+        r: U256::ZERO,
+        s: U256::ZERO,
+    };
+    Ok((sender, tx))
+}
+
+impl Node {
+    ///
+    /// Serve `chain`, whose blocks are taken to be its whole history, paying
+    /// the rewards of the blocks mined to `coinbase`.
+    ///
+    pub fn new(chain: BlockChain, coinbase: Address) -> Self {
+        assert!(!chain.blocks.is_empty(), "the chain has no genesis block");
+        Node {
+            blocks: chain.blocks.clone(),
+            chain,
+            transactions: HashMap::new(),
+            coinbase,
+        }
+    }
+
+    /// The block chain, with the latest state.
+    pub fn chain(&self) -> &BlockChain {
+        &self.chain
+    }
+
+    /// The header of the latest block.
+    pub fn head(&self) -> &Header {
+        &self.blocks.last().unwrap().header
+    }
+
+    /// The environment of the next block, executing against `state`.
+    fn pending_environment<S>(
+        &self,
+        caller: Address,
+        gas_price: U256,
+        state: S,
+    ) -> Result<Environment<S>, EthereumException> {
+        let parent = self.head();
+        let number = &parent.number + 1u8;
+        let time = now().max(parent.timestamp + U256::ONE);
+        let difficulty = calculate_block_difficulty(&number, &time, &parent.timestamp, &parent.difficulty)?;
+        Ok(Environment {
+            caller,
+            block_hashes: get_last_256_block_hashes(&self.chain)?,
+            origin: caller,
+            coinbase: self.coinbase,
+            number,
+            gas_limit: parent.gas_limit.clone(),
+            gas_price,
+            time,
+            difficulty,
+            state,
+            inspector: (),
+        })
+    }
+
+    ///
+    /// Mine `tx` into a new block.
+    ///
+    /// Parameters
+    /// ----------
+    /// tx :
+    ///     The signed transaction.
+    ///
+    /// Returns
+    /// -------
+    /// hash : `Hash32`
+    ///     The hash of the transaction.
+    ///
+    /// Raises
+    /// ------
+    /// InvalidBlock :
+    ///     If the transaction can not be included, in which case the chain
+    ///     is left as it was.
+    ///
+    pub fn send_transaction(&mut self, tx: Transaction) -> Result<Hash32, EthereumException> {
+        let parent = self.head().clone();
+        let sender = check_transaction(&tx, &parent.gas_limit)?;
+        let Environment { block_hashes, number, gas_limit, time, difficulty, .. } =
+            self.pending_environment(sender, tx.gas_price, ())?;
+
+        // `apply_body` can fail part way through the block, so its changes
+        // are recorded to be rolled back if it does.
+        let transactions = vec![tx];
+        self.chain.state.begin_block();
+        let applied = apply_body_with_receipts(
+            &mut self.chain.state,
+            block_hashes,
+            &self.coinbase,
+            &number,
+            &gas_limit,
+            &time,
+            &difficulty,
+            &transactions,
+            &Vec::new(),
+        )
+        .and_then(|body| Ok((body, state_root(&self.chain.state)?)));
+        let ((gas_used, transactions_root, receipt_root, bloom, mut receipts), state_root) = match applied {
+            Ok(applied) => {
+                self.chain.state.commit_block();
+                applied
+            }
+            Err(error) => {
+                self.chain.state.rollback_block();
+                return Err(error);
+            }
+        };
+
+        let header = Header {
+            parent_hash: rlp::rlp_hash(&parent),
+            ommers_hash: rlp::rlp_hash(&Vec::<Header>::new()),
+            coinbase: self.coinbase,
+            state_root,
+            transactions_root,
+            receipt_root,
+            bloom,
+            difficulty,
+            number: number.clone(),
+            gas_limit,
+            gas_used: gas_used.clone(),
+            timestamp: time,
+            extra_data: Bytes::default(),
+            mix_digest: Hash32::default(),
+            nonce: [0; 8],
+        };
+        let receipt = receipts.remove(0);
+
+        let hash = rlp::rlp_hash(&transactions[0]);
+        let block = Block { header, transactions, ommers: Vec::new() };
+        self.chain.blocks.push(block.clone());
+        if self.chain.blocks.len() > 255 {
+            let excess = self.chain.blocks.len() - 255;
+            self.chain.blocks.drain(..excess);
+        }
+        self.blocks.push(block);
+        self.transactions.insert(
+            hash,
+            IncludedTransaction { block_number: number, index: 0, sender, gas_used, receipt },
+        );
+        Ok(hash)
+    }
+
+    fn block_by_number(&self, number: &Uint) -> Option<&Block> {
+        let first = &self.blocks[0].header.number;
+        let index = usize::try_from(number.checked_sub(first)?).ok()?;
+        self.blocks.get(index)
+    }
+
+    fn block_by_tag(&self, tag: &str) -> Result<Option<&Block>, RpcError> {
+        match tag {
+            "latest" | "pending" => Ok(self.blocks.last()),
+            "earliest" => Ok(self.blocks.first()),
+            number => Ok(self.block_by_number(&hex_to_uint(number).map_err(invalid)?)),
+        }
+    }
+
+    fn check_latest(&self, tag: Option<&Value>) -> Result<(), RpcError> {
+        let latest = match tag.and_then(Value::as_str) {
+            None | Some("latest") | Some("pending") => true,
+            Some(number) => hex_to_uint(number).ok().as_ref() == Some(&self.head().number),
+        };
+        if latest {
+            Ok(())
+        } else {
+            Err(RpcError::server("only the latest state is available"))
+        }
+    }
+
+    fn block_json(&self, block: &Block, full_transactions: bool) -> Value {
+        let header = &block.header;
+        let transactions: Vec<Value> = block
+            .transactions
+            .iter()
+            .map(|tx| {
+                let hash = rlp::rlp_hash(tx);
+                if full_transactions {
+                    self.transaction_json(&hash).unwrap_or(Value::Null)
+                } else {
+                    data(&hash)
+                }
+            })
+            .collect();
+        let uncles: Vec<Value> = block.ommers.iter().map(|ommer| data(&rlp::rlp_hash(ommer))).collect();
+        json!({
+            "number": quantity(&header.number),
+            "hash": data(&rlp::rlp_hash(header)),
+            "parentHash": data(&header.parent_hash),
+            "nonce": data(&header.nonce),
+            "mixHash": data(&header.mix_digest),
+            "sha3Uncles": data(&header.ommers_hash),
+            "logsBloom": data(&header.bloom),
+            "transactionsRoot": data(&header.transactions_root),
+            "stateRoot": data(&header.state_root),
+            "receiptsRoot": data(&header.receipt_root),
+            "miner": data(&header.coinbase),
+            "difficulty": quantity(&header.difficulty),
+            "extraData": data(&header.extra_data),
+            "size": quantity(rlp::encode(block).len()),
+            "gasLimit": quantity(&header.gas_limit),
+            "gasUsed": quantity(&header.gas_used),
+            "timestamp": quantity(header.timestamp),
+            "transactions": transactions,
+            "uncles": uncles,
+        })
+    }
+
+    fn transaction_json(&self, hash: &Hash32) -> Option<Value> {
+        let included = self.transactions.get(hash)?;
+        let block = self.block_by_number(&included.block_number)?;
+        let tx = &block.transactions[included.index];
+        Some(json!({
+            "hash": data(hash),
+            "nonce": quantity(tx.nonce),
+            "blockHash": data(&rlp::rlp_hash(&block.header)),
+            "blockNumber": quantity(&included.block_number),
+            "transactionIndex": quantity(included.index),
+            "from": data(&included.sender),
+            "to": tx.to.as_ref().map(|to| data(to)),
+            "value": quantity(tx.value),
+            "gasPrice": quantity(tx.gas_price),
+            "gas": quantity(tx.gas),
+            "input": data(&tx.data),
+            "v": quantity(tx.v),
+            "r": quantity(tx.r),
+            "s": quantity(tx.s),
+        }))
+    }
+
+    fn receipt_json(&self, hash: &Hash32) -> Option<Value> {
+        let included = self.transactions.get(hash)?;
+        let block = self.block_by_number(&included.block_number)?;
+        let tx = &block.transactions[included.index];
+        let block_hash = rlp::rlp_hash(&block.header);
+        let contract_address = match tx.to {
+            Some(_) => None,
+            None => Some(compute_contract_address(&included.sender, &Uint::from(tx.nonce))),
+        };
+        let receipt = &included.receipt;
+        let logs: Vec<Value> = receipt
+            .logs
+            .iter()
+            .enumerate()
+            .map(|(index, log)| {
+                let topics: Vec<Value> = log.topics.iter().map(|topic| data(topic)).collect();
+                json!({
+                    "address": data(&log.address),
+                    "topics": topics,
+                    "data": data(&log.data),
+                    "blockNumber": quantity(&included.block_number),
+                    "blockHash": data(&block_hash),
+                    "transactionHash": data(hash),
+                    "transactionIndex": quantity(included.index),
+                    "logIndex": quantity(index),
+                    "removed": false,
+                })
+            })
+            .collect();
+        Some(json!({
+            "transactionHash": data(hash),
+            "transactionIndex": quantity(included.index),
+            "blockHash": data(&block_hash),
+            "blockNumber": quantity(&included.block_number),
+            "from": data(&included.sender),
+            "to": tx.to.as_ref().map(|to| data(to)),
+            "contractAddress": contract_address.as_ref().map(|address| data(address)),
+            "cumulativeGasUsed": quantity(&receipt.cumulative_gas_used),
+            "gasUsed": quantity(&included.gas_used),
+            "logs": logs,
+            "logsBloom": data(&receipt.bloom),
+            "root": data(&receipt.post_state),
+        }))
+    }
+
+    fn eth_call(&mut self, params: &[Value]) -> Result<Value, RpcError> {
+        let (sender, tx) = call_transaction(params.first())?;
+        self.check_latest(params.get(1))?;
+
+        let mut env = self
+            .pending_environment(sender, tx.gas_price, State::default())
+            .map_err(invalid)?;
+        let gas = match params[0].get("gas") {
+            Some(_) => Uint::from(tx.gas),
+            None => env.gas_limit.clone(),
+        };
+        std::mem::swap(&mut env.state, &mut self.chain.state);
+        let output = prepare_message(sender, tx.to, tx.value, tx.data, gas, &env, None)
+            .and_then(|message| call(message, &mut env));
+        std::mem::swap(&mut env.state, &mut self.chain.state);
+
+        let output = output.map_err(|error| RpcError::server(format!("{error:?}")))?;
+        match output.error {
+            None => Ok(data(&output.output)),
+            Some(error) => Err(RpcError::server(format!("execution failed: {error:?}"))),
+        }
+    }
+
+    fn eth_estimate_gas(&mut self, params: &[Value]) -> Result<Value, RpcError> {
+        let (sender, tx) = call_transaction(params.first())?;
+        self.check_latest(params.get(1))?;
+
+        let mut env = self
+            .pending_environment(sender, tx.gas_price, State::default())
+            .map_err(invalid)?;
+        std::mem::swap(&mut env.state, &mut self.chain.state);
+        let gas = estimate_gas(&mut env, sender, &tx);
+        std::mem::swap(&mut env.state, &mut self.chain.state);
+
+        let gas = gas.map_err(|error| RpcError::server(format!("gas estimation failed: {error:?}")))?;
+        Ok(quantity(&gas))
+    }
+
+    fn eth_send_raw_transaction(&mut self, params: &[Value]) -> Result<Value, RpcError> {
+        let encoded = hex_to_bytes(param(params, 0)?).map_err(invalid)?;
+        let tx = rlp::decode(&encoded)
+            .and_then(|decoded| Transaction::decode(&decoded))
+            .map_err(invalid)?;
+        let hash = self
+            .send_transaction(tx)
+            .map_err(|error| RpcError::server(format!("invalid transaction: {error:?}")))?;
+        Ok(data(&hash))
+    }
+}
+
+impl Handler for Node {
+    fn handle(&mut self, method: &str, params: &[Value]) -> Result<Value, RpcError> {
+        let state = &self.chain.state;
+        match method {
+            "eth_chainId" => Ok(quantity(self.chain.chain_id)),
+            "net_version" => Ok(json!(self.chain.chain_id.to_string())),
+            "eth_gasPrice" => Ok(quantity(0u8)),
+            "eth_blockNumber" => Ok(quantity(&self.head().number)),
+            "eth_getBalance" => {
+                let address = hex_to_bytes20(param(params, 0)?).map_err(invalid)?;
+                self.check_latest(params.get(1))?;
+                Ok(quantity(get_account(state, &address).map_err(invalid)?.balance))
+            }
+            "eth_getTransactionCount" => {
+                let address = hex_to_bytes20(param(params, 0)?).map_err(invalid)?;
+                self.check_latest(params.get(1))?;
+                Ok(quantity(&get_account(state, &address).map_err(invalid)?.nonce))
+            }
+            "eth_getCode" => {
+                let address = hex_to_bytes20(param(params, 0)?).map_err(invalid)?;
+                self.check_latest(params.get(1))?;
+                Ok(data(&get_account(state, &address).map_err(invalid)?.code))
+            }
+            "eth_getStorageAt" => {
+                let address = hex_to_bytes20(param(params, 0)?).map_err(invalid)?;
+                let slot = hex_to_u256(param(params, 1)?).map_err(invalid)?;
+                self.check_latest(params.get(2))?;
+                let value = get_storage(state, &address, &slot.to_be_bytes32()).map_err(invalid)?;
+                Ok(data(&value.to_be_bytes32()))
+            }
+            "eth_call" => self.eth_call(params),
+            "eth_estimateGas" => self.eth_estimate_gas(params),
+            "eth_sendRawTransaction" => self.eth_send_raw_transaction(params),
+            "eth_getBlockByNumber" => {
+                let full_transactions = params.get(1).and_then(Value::as_bool).unwrap_or(false);
+                let block = self.block_by_tag(param(params, 0)?)?;
+                Ok(block.map_or(Value::Null, |block| self.block_json(block, full_transactions)))
+            }
+            "eth_getBlockByHash" => {
+                let hash = hex_to_hash(param(params, 0)?).map_err(invalid)?;
+                let full_transactions = params.get(1).and_then(Value::as_bool).unwrap_or(false);
+                let block = self.blocks.iter().find(|block| rlp::rlp_hash(&block.header) == hash);
+                Ok(block.map_or(Value::Null, |block| self.block_json(block, full_transactions)))
+            }
+            "eth_getTransactionByHash" => {
+                let hash = hex_to_hash(param(params, 0)?).map_err(invalid)?;
+                Ok(self.transaction_json(&hash).unwrap_or(Value::Null))
+            }
+            "eth_getTransactionReceipt" => {
+                let hash = hex_to_hash(param(params, 0)?).map_err(invalid)?;
+                Ok(self.receipt_json(&hash).unwrap_or(Value::Null))
+            }
+            _ => Err(RpcError::new(METHOD_NOT_FOUND, format!("the method {method} does not exist"))),
+        }
+    }
+}
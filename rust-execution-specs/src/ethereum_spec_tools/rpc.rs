@@ -0,0 +1,416 @@
+//!
+//! # JSON-RPC
+//!
+//! ## Introduction
+//!
+//! A minimal JSON-RPC 2.0 server over HTTP/1.1, enough for the usual
+//! Ethereum tooling to talk to a `Handler`.
+//!
+//! Requests are served one at a time, each on its own connection, so the
+//! handler needs no locking. Both single requests and batches are
+//! supported; notifications, which have no `id`, get no response. Bodies
+//! larger than `MAX_BODY_SIZE` are refused, and a client which stalls for
+//! `TIMEOUT` is hung up on, so that one client can not hold up the others.
+//!
+//! `RpcClient` is the other end, for plain `http://` endpoints.
+//!
+
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+/// Invalid JSON was received.
+pub const PARSE_ERROR: i64 = -32700;
+/// The JSON sent is not a valid request object.
+pub const INVALID_REQUEST: i64 = -32600;
+/// The method does not exist.
+pub const METHOD_NOT_FOUND: i64 = -32601;
+/// Invalid method parameters.
+pub const INVALID_PARAMS: i64 = -32602;
+/// The request was well formed, but could not be carried out, e.g. an
+/// invalid transaction.
+pub const SERVER_ERROR: i64 = -32000;
+
+/// The largest request body served, as in geth.
+pub const MAX_BODY_SIZE: usize = 5 * 1024 * 1024;
+/// How long connecting, reading or writing may stall, for the server and
+/// `RpcClient` alike.
+pub const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The error object of a failed request.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    /// An error with `code` and `message`.
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        RpcError { code, message: message.into() }
+    }
+
+    /// An `INVALID_PARAMS` error.
+    pub fn invalid_params(message: impl Into<String>) -> Self {
+        RpcError::new(INVALID_PARAMS, message)
+    }
+
+    /// A `SERVER_ERROR` error.
+    pub fn server(message: impl Into<String>) -> Self {
+        RpcError::new(SERVER_ERROR, message)
+    }
+}
+
+/// Carries out the methods of the API.
+pub trait Handler {
+    /// Carry out `method` with positional `params`.
+    fn handle(&mut self, method: &str, params: &[Value]) -> Result<Value, RpcError>;
+
+    /// Told by `serve` about a connection which could not be accepted, from
+    /// an unknown `peer`, or which failed, as when the client hangs up.
+    /// Nothing is done by default.
+    fn connection_error(&mut self, peer: Option<SocketAddr>, error: &io::Error) {
+        let _ = (peer, error);
+    }
+}
+
+fn response(id: Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": error.code, "message": error.message },
+        }),
+    }
+}
+
+fn handle_call<H: Handler>(handler: &mut H, request: &Value) -> Option<Value> {
+    let id = request.get("id").cloned();
+    let method = request.get("method").and_then(Value::as_str);
+    let result = match (method, request.get("params")) {
+        (None, _) => Err(RpcError::new(INVALID_REQUEST, "missing method")),
+        (Some(method), None) => handler.handle(method, &[]),
+        (Some(method), Some(Value::Array(params))) => handler.handle(method, params),
+        (Some(_), Some(_)) => Err(RpcError::new(INVALID_REQUEST, "params must be an array")),
+    };
+    id.map(|id| response(id, result))
+}
+
+///
+/// Handle the body of a JSON-RPC request, single or batched.
+///
+/// Parameters
+/// ----------
+/// handler :
+///     Carries out the methods called.
+/// body :
+///     The body of the HTTP request.
+///
+/// Returns
+/// -------
+/// response : `Option<String>`
+///     The body of the response, unless only notifications were sent.
+///
+pub fn handle_request<H: Handler>(handler: &mut H, body: &str) -> Option<String> {
+    let request: Value = match serde_json::from_str(body) {
+        Ok(request) => request,
+        Err(error) => {
+            let error = RpcError::new(PARSE_ERROR, error.to_string());
+            return Some(response(Value::Null, Err(error)).to_string());
+        }
+    };
+
+    match request {
+        Value::Array(calls) if calls.is_empty() => {
+            let error = RpcError::new(INVALID_REQUEST, "empty batch");
+            Some(response(Value::Null, Err(error)).to_string())
+        }
+        Value::Array(calls) => {
+            let responses: Vec<Value> = calls.iter().filter_map(|call| handle_call(handler, call)).collect();
+            (!responses.is_empty()).then(|| Value::Array(responses).to_string())
+        }
+        request => handle_call(handler, &request).map(|response| response.to_string()),
+    }
+}
+
+/// An HTTP request, as far as it was read.
+enum HttpRequest {
+    /// A `POST` with its body.
+    Post(String),
+    /// Any other method, whose body is left unread.
+    OtherMethod,
+    /// A `POST` whose body, left unread, is larger than `MAX_BODY_SIZE`.
+    TooLarge,
+}
+
+/// Read the head of an HTTP request, and the body of a `POST`.
+fn read_request(stream: &mut BufReader<&TcpStream>) -> io::Result<HttpRequest> {
+    let mut request_line = String::new();
+    stream.read_line(&mut request_line)?;
+    let is_post = request_line.starts_with("POST ");
+
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        if stream.read_line(&mut line)? == 0 || line.trim_end().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "bad content length"))?;
+            }
+        }
+    }
+
+    if !is_post {
+        return Ok(HttpRequest::OtherMethod);
+    }
+    if content_length > MAX_BODY_SIZE {
+        return Ok(HttpRequest::TooLarge);
+    }
+    let mut body = vec![0; content_length];
+    stream.read_exact(&mut body)?;
+    String::from_utf8(body)
+        .map(HttpRequest::Post)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "body is not utf-8"))
+}
+
+fn serve_connection<H: Handler>(handler: &mut H, stream: &TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let (status, body) = match read_request(&mut BufReader::new(stream))? {
+        HttpRequest::Post(body) => match handle_request(handler, &body) {
+            Some(response) => ("200 OK", response),
+            None => ("204 No Content", String::new()),
+        },
+        HttpRequest::OtherMethod => ("405 Method Not Allowed", String::new()),
+        HttpRequest::TooLarge => ("413 Payload Too Large", String::new()),
+    };
+
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\n\
+         Content-Type: application/json\r\n\
+         Content-Length: {}\r\n\
+         Access-Control-Allow-Origin: *\r\n\
+         Connection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()
+}
+
+///
+/// Serve JSON-RPC requests on `listener`, forever.
+///
+/// Connections which can not be accepted, or which fail, such as when a
+/// client hangs up, are passed to `Handler::connection_error`, and do not
+/// stop the server.
+///
+/// Parameters
+/// ----------
+/// listener :
+///     Socket to accept connections on.
+/// handler :
+///     Carries out the methods called.
+///
+pub fn serve<H: Handler>(listener: TcpListener, handler: &mut H) {
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if let Err(error) = serve_connection(handler, &stream) {
+                    handler.connection_error(stream.peer_addr().ok(), &error);
+                }
+            }
+            Err(error) => handler.connection_error(None, &error),
+        }
+    }
+}
+
+/// Why a call made by an `RpcClient` failed.
+#[derive(Debug)]
+pub enum ClientError {
+    /// The endpoint could not be reached, or hung up.
+    Io(io::Error),
+    /// The endpoint did not answer with a JSON-RPC response.
+    Http(String),
+    /// The endpoint answered with an error object.
+    Rpc(RpcError),
+}
+
+impl From<io::Error> for ClientError {
+    fn from(error: io::Error) -> Self {
+        ClientError::Io(error)
+    }
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Io(error) => write!(f, "{error}"),
+            ClientError::Http(message) => write!(f, "{message}"),
+            ClientError::Rpc(error) => write!(f, "error {}: {}", error.code, error.message),
+        }
+    }
+}
+
+///
+/// Calls the methods of a JSON-RPC endpoint over HTTP/1.1, one connection
+/// per request.
+///
+pub struct RpcClient {
+    host: String,
+    path: String,
+    next_id: u64,
+}
+
+/// Undo the chunked transfer encoding of a response body.
+fn dechunk(mut body: &[u8]) -> Result<Vec<u8>, ClientError> {
+    let malformed = || ClientError::Http("malformed chunked body".to_string());
+    let mut decoded = Vec::new();
+    loop {
+        let line_end = body.windows(2).position(|window| window == b"\r\n").ok_or_else(malformed)?;
+        let size = std::str::from_utf8(&body[..line_end]).map_err(|_| malformed())?;
+        let size = size.split(';').next().unwrap_or_default().trim();
+        let size = usize::from_str_radix(size, 16).map_err(|_| malformed())?;
+        if size == 0 {
+            return Ok(decoded);
+        }
+        let chunk = body.get(line_end + 2..line_end + 2 + size).ok_or_else(malformed)?;
+        decoded.extend_from_slice(chunk);
+        body = body.get(line_end + 4 + size..).ok_or_else(malformed)?;
+    }
+}
+
+impl RpcClient {
+    ///
+    /// A client of the endpoint at `url`, such as `http://127.0.0.1:8545`.
+    ///
+    pub fn new(url: &str) -> Result<Self, ClientError> {
+        let address = url
+            .strip_prefix("http://")
+            .ok_or_else(|| ClientError::Http(format!("only http:// endpoints are supported: {url}")))?;
+        let (host, path) = match address.find('/') {
+            Some(index) => (&address[..index], &address[index..]),
+            None => (address, "/"),
+        };
+        let host = if host.contains(':') { host.to_string() } else { format!("{host}:80") };
+        Ok(RpcClient { host, path: path.to_string(), next_id: 1 })
+    }
+
+    /// Connect to the endpoint, trying each of its addresses in turn.
+    fn connect(&self) -> io::Result<TcpStream> {
+        let mut last_error = None;
+        for address in self.host.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&address, TIMEOUT) {
+                Ok(stream) => {
+                    stream.set_read_timeout(Some(TIMEOUT))?;
+                    stream.set_write_timeout(Some(TIMEOUT))?;
+                    return Ok(stream);
+                }
+                Err(error) => last_error = Some(error),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no address for the endpoint")))
+    }
+
+    fn post(&self, body: &str) -> Result<Value, ClientError> {
+        let mut stream = self.connect()?;
+        write!(
+            stream,
+            "POST {} HTTP/1.1\r\n\
+             Host: {}\r\n\
+             Content-Type: application/json\r\n\
+             Content-Length: {}\r\n\
+             Connection: close\r\n\r\n{body}",
+            self.path,
+            self.host,
+            body.len()
+        )?;
+        stream.flush()?;
+
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response)?;
+        let head_end = response
+            .windows(4)
+            .position(|window| window == b"\r\n\r\n")
+            .ok_or_else(|| ClientError::Http("truncated response".to_string()))?;
+        let head = String::from_utf8_lossy(&response[..head_end]);
+        let mut lines = head.split("\r\n");
+        let status = lines.next().unwrap_or_default();
+        if status.split(' ').nth(1) != Some("200") {
+            return Err(ClientError::Http(format!("unexpected response: {status}")));
+        }
+        let chunked = lines.any(|line| {
+            line.split_once(':').is_some_and(|(name, value)| {
+                name.trim().eq_ignore_ascii_case("transfer-encoding") && value.trim().eq_ignore_ascii_case("chunked")
+            })
+        });
+
+        let body = &response[head_end + 4..];
+        let body = if chunked { dechunk(body)? } else { body.to_vec() };
+        serde_json::from_slice(&body).map_err(|error| ClientError::Http(format!("malformed response: {error}")))
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> Value {
+        let id = self.next_id;
+        self.next_id += 1;
+        json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+    }
+
+    fn result(response: &Value) -> Result<Value, ClientError> {
+        if let Some(error) = response.get("error") {
+            let code = error.get("code").and_then(Value::as_i64).unwrap_or_default();
+            let message = error.get("message").and_then(Value::as_str).unwrap_or_default();
+            return Err(ClientError::Rpc(RpcError::new(code, message)));
+        }
+        response
+            .get("result")
+            .cloned()
+            .ok_or_else(|| ClientError::Http("response has neither result nor error".to_string()))
+    }
+
+    ///
+    /// Call `method` with positional `params`.
+    ///
+    pub fn call(&mut self, method: &str, params: Value) -> Result<Value, ClientError> {
+        let request = self.request(method, params);
+        RpcClient::result(&self.post(&request.to_string())?)
+    }
+
+    ///
+    /// Make all of `calls` in a single batch, returning their results in
+    /// the same order.
+    ///
+    pub fn batch(&mut self, calls: Vec<(&str, Value)>) -> Result<Vec<Value>, ClientError> {
+        if calls.is_empty() {
+            return Ok(Vec::new());
+        }
+        let requests: Vec<Value> = calls.into_iter().map(|(method, params)| self.request(method, params)).collect();
+        let responses = match self.post(&Value::Array(requests.clone()).to_string())? {
+            Value::Array(responses) => responses,
+            response => return Err(RpcClient::result(&response).err().unwrap_or_else(|| {
+                ClientError::Http("batch response is not an array".to_string())
+            })),
+        };
+
+        // Responses to a batch may come in any order.
+        requests
+            .iter()
+            .map(|request| {
+                let response = responses
+                    .iter()
+                    .find(|response| response.get("id") == request.get("id"))
+                    .ok_or_else(|| ClientError::Http(format!("no response to request {}", request["id"])))?;
+                RpcClient::result(response)
+            })
+            .collect()
+    }
+}
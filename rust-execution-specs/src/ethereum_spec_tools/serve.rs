@@ -0,0 +1,116 @@
+//!
+//! # JSON-RPC Server
+//!
+//! ## Introduction
+//!
+//! Serve the Ethereum JSON-RPC API over HTTP from a Frontier chain, starting
+//! from a genesis file or a geth style state dump:
+//!
+//! ```text
+//! serve --genesis genesis.json --coinbase 0x05a56e2d52c817161883f50c441c3228cfe54d9f
+//! serve --state dump.json --chain-id 1337 --port 8545
+//! ```
+//!
+//! Transactions sent are mined at once, one per block.
+//!
+
+use std::io;
+use std::net::{SocketAddr, TcpListener};
+
+use clap::Parser;
+use serde_json::Value;
+use execution_specs_rs::ethereum::{
+    base_types::{Uint, U64},
+    frontier::{
+        fork::MINIMUM_DIFFICULTY,
+        state::state_root,
+        state_dump::load_state,
+    },
+    genesis::{genesis_chain, load_genesis_configuration, GenesisConfiguration},
+    utils::hexadecimal::hex_to_bytes20,
+};
+use execution_specs_rs::ethereum_spec_tools::{
+    node::Node,
+    rpc::{serve, Handler, RpcError},
+};
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+pub struct Args {
+    /// Genesis file to start the chain from.
+    #[arg(short, long, conflicts_with = "state", required_unless_present = "state")]
+    genesis: Option<String>,
+
+    /// State dump to start the chain from, instead of a genesis file.
+    #[arg(short, long)]
+    state: Option<String>,
+
+    /// Chain id of a chain started from a state dump.
+    #[arg(long, default_value_t = 1337)]
+    chain_id: u64,
+
+    /// Gas limit of the blocks of a chain started from a state dump.
+    #[arg(long, default_value_t = 8_000_000)]
+    gas_limit: u64,
+
+    /// Address which receives the rewards of the blocks mined.
+    #[arg(long, default_value = "0x0000000000000000000000000000000000000000")]
+    coinbase: String,
+
+    /// Address to listen on.
+    #[arg(long, default_value = "127.0.0.1")]
+    host: String,
+
+    /// Port to listen on.
+    #[arg(short, long, default_value_t = 8545)]
+    port: u16,
+}
+
+fn main() {
+    let args = Args::parse();
+    let coinbase = hex_to_bytes20(&args.coinbase).expect("malformed coinbase");
+
+    let chain = match (&args.genesis, &args.state) {
+        (Some(genesis), _) => {
+            let genesis = load_genesis_configuration(genesis).expect("malformed genesis file");
+            genesis_chain(genesis).expect("invalid genesis")
+        }
+        (None, Some(dump)) => {
+            let json = std::fs::read_to_string(dump).unwrap_or_else(|error| panic!("{dump}: {error}"));
+            let dump = serde_json::from_str(&json).unwrap_or_else(|error| panic!("{dump}: {error}"));
+            let genesis = GenesisConfiguration {
+                chain_id: U64::from(args.chain_id),
+                difficulty: Uint::from(MINIMUM_DIFFICULTY),
+                gas_limit: Uint::from(args.gas_limit),
+                ..GenesisConfiguration::default()
+            };
+            let mut chain = genesis_chain(genesis).expect("invalid genesis");
+            chain.state = load_state(&dump).expect("malformed state dump");
+            chain.blocks[0].header.state_root = state_root(&chain.state).expect("state unavailable");
+            chain
+        }
+        (None, None) => unreachable!("clap requires a genesis or a state"),
+    };
+
+    let address = format!("{}:{}", args.host, args.port);
+    let listener = TcpListener::bind(&address).unwrap_or_else(|error| panic!("{address}: {error}"));
+    println!("listening on http://{address}");
+
+    serve(listener, &mut Logged(Node::new(chain, coinbase)));
+}
+
+/// A `Node` which reports failed connections on stderr.
+struct Logged(Node);
+
+impl Handler for Logged {
+    fn handle(&mut self, method: &str, params: &[Value]) -> Result<Value, RpcError> {
+        self.0.handle(method, params)
+    }
+
+    fn connection_error(&mut self, peer: Option<SocketAddr>, error: &io::Error) {
+        match peer {
+            Some(peer) => eprintln!("connection from {peer}: {error}"),
+            None => eprintln!("connection: {error}"),
+        }
+    }
+}
@@ -1,31 +1,84 @@
+//!
+//! # Block Sync
+//!
+//! ## Introduction
+//!
+//! Sync a Frontier chain from a JSON-RPC endpoint, starting over from the
+//! saved progress when restarted:
+//!
+//! ```text
+//! sync --rpc-url http://127.0.0.1:8545 --progress mainnet.json --until 1149999
+//! ```
+//!
 
-// use clap::Parser;
-
-// #[derive(Parser, Debug)]
-// #[command(author, version, about, long_about = None)]
-// pub struct Args {
-//     #[arg(short, long)]
-//     rpc_url: String,
-// }
-
-// pub struct BlockDownloader {
-//     rpc_url: String,
-//     queue: VecDeque<u64>,
-// }
-
-// impl BlockDownloader {
-//     fn new(rpc_url: String) -> Self {
-//         let mut downlowder = Self { 
-//             rpc_url,
-//             queue: VecDeque::with_capacity(512)
-//         };
-//         downlowder
-//     }
-
-//     async fn download(&mut self) {
-//     }
-// }
+use std::path::PathBuf;
+use std::process::exit;
+
+use clap::Parser;
+use execution_specs_rs::ethereum::{base_types::Uint, rlp, utils::hexadecimal::hex};
+use execution_specs_rs::ethereum_spec_tools::chain_sync::{load_progress_or_genesis, sync, SyncOptions};
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+pub struct Args {
+    /// JSON-RPC endpoint to download blocks from.
+    #[arg(short, long)]
+    rpc_url: String,
+
+    /// Genesis file of the chain, used when there is no progress yet.
+    #[arg(short, long, default_value = "./assets/mainnet.json")]
+    genesis: String,
+
+    /// File to save progress to, and resume from.
+    #[arg(short, long)]
+    progress: Option<PathBuf>,
+
+    /// Stop after this block.
+    #[arg(long)]
+    until: Option<u64>,
+
+    /// Save progress every this many blocks.
+    #[arg(long, default_value_t = 1000)]
+    checkpoint_interval: u64,
+
+    /// Number of blocks to download at a time.
+    #[arg(long, default_value_t = 64)]
+    batch_size: u64,
+}
 
 fn main() {
-    // let args = Args::parse();
+    let args = Args::parse();
+
+    let mut chain = load_progress_or_genesis(args.progress.as_deref(), &args.genesis)
+        .unwrap_or_else(|error| panic!("{error}"));
+    println!("starting after block {}", chain.blocks.last().unwrap().header.number);
+
+    let options = SyncOptions {
+        until: args.until,
+        progress: args.progress,
+        checkpoint_interval: args.checkpoint_interval,
+        batch_size: args.batch_size,
+    };
+    let result = sync(&mut chain, &args.rpc_url, &options, |block, elapsed| {
+        let header = &block.header;
+        // Empty blocks are only reported every thousand blocks.
+        if block.transactions.is_empty() && &header.number % 1000u32 != Uint::from(0u8) {
+            return;
+        }
+        println!(
+            "block {} {} {} txs {:?}",
+            header.number,
+            hex(&rlp::rlp_hash(header)),
+            block.transactions.len(),
+            elapsed
+        );
+    });
+
+    match result {
+        Ok(applied) => println!("applied {applied} blocks, up to block {}", chain.blocks.last().unwrap().header.number),
+        Err(error) => {
+            eprintln!("{error}");
+            exit(1);
+        }
+    }
 }
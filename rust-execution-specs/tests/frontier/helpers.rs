@@ -0,0 +1,159 @@
+use std::fs;
+use std::net::{SocketAddr, TcpListener};
+use std::thread;
+
+use execution_specs_rs::ethereum::{
+    base_types::{Bytes, Bytes32, Uint, U256, U64},
+    frontier::{
+        fork::{recover_sender, BlockChain, MINIMUM_DIFFICULTY},
+        fork_types::{Account, Address, Block, Transaction},
+        state::{set_account, set_storage, State},
+        vm::{Environment, Message},
+    },
+    genesis::{genesis_chain, get_genesis_configuration, GenesisConfiguration},
+};
+use execution_specs_rs::ethereum_spec_tools::{
+    chain_sync::block_from_json,
+    rpc::{serve, Handler},
+};
+use hex_literal::hex;
+use serde_json::Value;
+
+/// A chain holding only the mainnet genesis block.
+pub fn mainnet_chain() -> BlockChain {
+    genesis_chain(get_genesis_configuration("mainnet.json").unwrap()).unwrap()
+}
+
+/// A mainnet block from `assets/blocks`, which has no ommers.
+pub fn mainnet_block(file: &str) -> Block {
+    let json: Value = serde_json::from_str(&fs::read_to_string(format!("./assets/blocks/{file}")).unwrap()).unwrap();
+    block_from_json(&json, &[]).unwrap()
+}
+
+pub const CALLER: Address = [0xaa; 20];
+pub const TARGET: Address = [0xcc; 20];
+
+/// The environment of block 1, for a transaction sent by `CALLER`.
+pub fn environment<S, I>(state: S, inspector: I) -> Environment<S, I> {
+    Environment {
+        caller: CALLER,
+        block_hashes: Vec::new(),
+        origin: CALLER,
+        coinbase: [0; 20],
+        number: Uint::from(1u8),
+        gas_limit: Uint::from(1_000_000u32),
+        gas_price: U256::from(1u8),
+        time: U256::from(0u8),
+        difficulty: Uint::from(0u8),
+        state,
+        inspector,
+    }
+}
+
+/// A call from `CALLER` to `TARGET`, running `code`.
+pub fn message(code: &[u8], gas: u32) -> Message {
+    Message {
+        caller: CALLER,
+        target: Some(TARGET),
+        current_target: TARGET,
+        gas: Uint::from(gas),
+        value: U256::from(0u8),
+        data: Bytes::default(),
+        code_address: Some(TARGET),
+        code: code.into(),
+        code_hash: None,
+        depth: Uint::from(0u8),
+        is_callcode: false,
+    }
+}
+
+/// The creation of a contract at `TARGET` by `CALLER`, running `code`.
+pub fn create_message(code: &[u8], gas: u32) -> Message {
+    Message {
+        target: None,
+        code_address: None,
+        ..message(code, gas)
+    }
+}
+
+/// The address whose last byte is `last`, and the rest zero.
+pub fn address(last: u8) -> Address {
+    let mut address = [0; 20];
+    address[19] = last;
+    address
+}
+
+/// Set the account at `address` to a contract holding `balance`.
+pub fn contract(state: &mut State, address: Address, balance: u64, code: &[u8]) {
+    set_account(state, address, Some(account(0, balance.into(), code)));
+}
+
+/// An account with `nonce`, holding `balance` and running `code`.
+pub fn account(nonce: u64, balance: u128, code: &[u8]) -> Account {
+    Account {
+        nonce: Uint::from(nonce),
+        balance: U256::from(balance),
+        code: Bytes::from(code),
+    }
+}
+
+/// A state holding `accounts`, in which each of `storage` is set to its
+/// value.
+pub fn state_with(accounts: &[(Address, Account)], storage: &[(Address, Bytes32, u32)]) -> State {
+    let mut state = State::default();
+    for (address, account) in accounts {
+        set_account(&mut state, *address, Some(account.clone()));
+    }
+    for (address, key, value) in storage {
+        set_storage(&mut state, *address, key, U256::from(*value));
+    }
+    state
+}
+
+/// A chain with id 1337 holding only a genesis block, in which each of
+/// `funded` has a balance of 1000000000.
+pub fn funded_chain(funded: &[Address]) -> BlockChain {
+    let mut genesis = GenesisConfiguration {
+        chain_id: U64::from(1337u64),
+        difficulty: Uint::from(MINIMUM_DIFFICULTY),
+        gas_limit: Uint::from(1_000_000u32),
+        ..GenesisConfiguration::default()
+    };
+    for address in funded {
+        genesis.initial_balances.insert(*address, U256::from(1_000_000_000u64));
+    }
+    genesis_chain(genesis).unwrap()
+}
+
+/// An unsigned transaction with a gas price of 1 and 100000 gas.
+pub fn transaction(to: Option<Address>, value: u64, data: &[u8]) -> Transaction {
+    Transaction {
+        nonce: U256::ZERO,
+        gas_price: U256::ONE,
+        gas: U256::from(100_000u32),
+        to,
+        value: U256::from(value),
+        data: Bytes::from(data),
+        v: U256::ZERO,
+        r: U256::ZERO,
+        s: U256::ZERO,
+    }
+}
+
+/// Sign `tx` with `r` set to the `x` coordinate of the base point, which
+/// makes a valid signature from some sender.
+pub fn sign(mut tx: Transaction) -> (Address, Transaction) {
+    tx.v = U256::from(27u8);
+    tx.r = U256::from_be_bytes(&hex!("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"));
+    tx.s = U256::ONE;
+    let sender = recover_sender(&tx).unwrap();
+    (sender, tx)
+}
+
+/// Serve `handler` over HTTP from another thread, on a free local port.
+pub fn spawn<H: Handler + Send + 'static>(mut handler: H) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    thread::spawn(move || serve(listener, &mut handler));
+    address
+}
@@ -1 +1,24 @@
+pub mod helpers;
+pub mod test_state;
+pub mod test_state_backends;
+pub mod test_state_dump;
+pub mod test_snapshot;
+pub mod test_state_overrides;
+pub mod test_prestate;
+pub mod test_witness;
 pub mod test_trie;
+pub mod test_trie_proof;
+pub mod test_trie_diff;
+pub mod test_trace;
+pub mod test_inspector;
+pub mod test_call_tracer;
+pub mod test_gas_profiler;
+pub mod test_runtime;
+pub mod test_stack_memory;
+pub mod test_gas_estimation;
+pub mod test_call;
+pub mod test_state_transition;
+pub mod test_chain_sync;
+
+pub mod test_node;
+pub mod test_chain_file;
\ No newline at end of file
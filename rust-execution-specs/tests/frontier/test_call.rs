@@ -0,0 +1,138 @@
+use execution_specs_rs::ethereum::{
+    base_types::{Bytes, Bytes32, Uint, U256},
+    exceptions::EthereumException,
+    frontier::{
+        call::call,
+        fork_types::{Account, Address, Root},
+        state::{get_account, get_storage, state_root, State, StateBackend},
+        utils::message::prepare_message,
+        vm::{exceptions::EvmError, Environment},
+    },
+};
+
+use super::helpers::{self, contract, CALLER, TARGET};
+
+fn environment(code: &[u8]) -> Environment {
+    let mut state = State::default();
+    contract(&mut state, CALLER, 100, &[]);
+    contract(&mut state, TARGET, 0, code);
+    Environment { gas_price: U256::ZERO, ..helpers::environment(state, ()) }
+}
+
+#[test]
+fn test_call_rolls_back() -> Result<(), EthereumException> {
+    // SSTORE(0, 1) MSTORE(0, 0x2a) LOG0(0, 0) RETURN(0, 32)
+    let mut env = environment(&[
+        0x60, 0x01, 0x60, 0x00, 0x55, 0x60, 0x2a, 0x60, 0x00, 0x52, 0x60, 0x00, 0x60, 0x00, 0xa0,
+        0x60, 0x20, 0x60, 0x00, 0xf3,
+    ]);
+    let message = prepare_message(
+        CALLER,
+        Some(TARGET),
+        U256::from(7u8),
+        Bytes::default(),
+        Uint::from(100_000u32),
+        &env,
+        None,
+    )?;
+    let output = call(message, &mut env)?;
+
+    assert!(output.error.is_none());
+    assert_eq!(U256::from_be_bytes(&output.output), U256::from(0x2au8));
+    assert_eq!(output.gas_used, Uint::from(20006u16 + 12 + 381 + 6));
+    assert_eq!(output.logs.len(), 1);
+    assert_eq!(output.logs[0].address, TARGET);
+
+    assert_eq!(get_storage(&env.state, &TARGET, &[0; 32])?, U256::ZERO);
+    assert_eq!(get_account(&env.state, &CALLER)?.balance, U256::from(100u8));
+    assert_eq!(get_account(&env.state, &TARGET)?.balance, U256::ZERO);
+    Ok(())
+}
+
+#[test]
+fn test_call_error() -> Result<(), EthereumException> {
+    let mut env = environment(&[0x60, 0x01, 0x60, 0x00, 0x55, 0xfe]);
+    let message =
+        prepare_message(CALLER, Some(TARGET), U256::ZERO, Bytes::default(), Uint::from(50_000u32), &env, None)?;
+    let output = call(message, &mut env)?;
+
+    assert!(matches!(output.error, Some(EvmError::InvalidOpcode)));
+    assert_eq!(output.gas_used, Uint::from(50_000u32));
+    assert!(output.output.is_empty());
+    assert_eq!(get_storage(&env.state, &TARGET, &[0; 32])?, U256::ZERO);
+    Ok(())
+}
+
+/// A state whose storage can not be read for `UNAVAILABLE`.
+struct FailingStorage(State);
+
+const UNAVAILABLE: Address = [0xdd; 20];
+
+impl StateBackend for FailingStorage {
+    fn get_account_optional(&self, address: &Address) -> Result<Option<Account>, EthereumException> {
+        self.0.get_account_optional(address)
+    }
+
+    fn set_account(&mut self, address: Address, account: Option<Account>) {
+        self.0.set_account(address, account)
+    }
+
+    fn destroy_storage(&mut self, address: &Address) {
+        self.0.destroy_storage(address)
+    }
+
+    fn get_storage(&self, address: &Address, key: &Bytes32) -> Result<U256, EthereumException> {
+        if *address == UNAVAILABLE {
+            return Err(EthereumException::StateUnavailable("storage".to_string()));
+        }
+        self.0.get_storage(address, key)
+    }
+
+    fn set_storage(&mut self, address: Address, key: &Bytes32, value: U256) {
+        self.0.set_storage(address, key, value)
+    }
+
+    fn begin_transaction(&mut self) {
+        self.0.begin_transaction()
+    }
+
+    fn commit_transaction(&mut self) {
+        self.0.commit_transaction()
+    }
+
+    fn rollback_transaction(&mut self) {
+        self.0.rollback_transaction()
+    }
+
+    fn storage_root(&self, address: &Address) -> Result<Root, EthereumException> {
+        self.0.storage_root(address)
+    }
+
+    fn state_root(&self) -> Result<Root, EthereumException> {
+        self.0.state_root()
+    }
+}
+
+#[test]
+fn test_call_state_unavailable() -> Result<(), EthereumException> {
+    let mut state = State::default();
+    contract(&mut state, CALLER, 100, &[]);
+    // SSTORE(0, 1) CALL(0xffff, UNAVAILABLE, 0, 0, 0, 0, 0)
+    let mut code = vec![0x60, 0x01, 0x60, 0x00, 0x55, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x73];
+    code.extend_from_slice(&UNAVAILABLE);
+    code.extend_from_slice(&[0x61, 0xff, 0xff, 0xf1]);
+    contract(&mut state, TARGET, 0, &code);
+    // SLOAD(0)
+    contract(&mut state, UNAVAILABLE, 0, &[0x60, 0x00, 0x54]);
+    let root = state_root(&state)?;
+
+    let mut env = Environment { gas_price: U256::ZERO, ..helpers::environment(FailingStorage(state), ()) };
+    let message =
+        prepare_message(CALLER, Some(TARGET), U256::ZERO, Bytes::default(), Uint::from(100_000u32), &env, None)?;
+    let result = call(message, &mut env);
+    assert!(matches!(result, Err(EthereumException::StateUnavailable(_))));
+
+    // Every frame the failure unwound through closed its transaction.
+    assert_eq!(state_root(&env.state.0)?, root);
+    Ok(())
+}
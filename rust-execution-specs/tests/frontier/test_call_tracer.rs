@@ -0,0 +1,109 @@
+use execution_specs_rs::ethereum::{
+    base_types::{Bytes, Uint},
+    frontier::{
+        state::State,
+        vm::{
+            call_tracer::CallTracer, exceptions::EvmError, interpreter::process_message_call, Message,
+        },
+    },
+};
+use serde_json::json;
+
+use super::helpers::{address, contract, create_message, environment, message, TARGET};
+
+#[test]
+fn test_call_tracer_nested_calls() -> Result<(), EvmError> {
+    let mut state = State::default();
+    // PUSH1 0xdd SELFDESTRUCT
+    contract(&mut state, address(0xbb), 0, &[0x60, 0xdd, 0xff]);
+    // INVALID
+    contract(&mut state, address(0xee), 0, &[0xfe]);
+    let mut env = environment(state, CallTracer::new());
+
+    // CALL(gas=1000, to=0xbb) POP CALL(gas=100, to=0xee) POP STOP
+    let code = [
+        0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0xbb, 0x61, 0x03, 0xe8,
+        0xf1, 0x50, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0xee, 0x60,
+        0x64, 0xf1, 0x50, 0x00,
+    ];
+    let message = Message {
+        data: Bytes::from(&[0x12, 0x34][..]),
+        ..message(&code, 10_000)
+    };
+    let result = process_message_call(message, &mut env)?;
+    let gas_used = Uint::from(10_000u32) - result.gas_left;
+
+    let cc = format!("0x{}", "cc".repeat(20));
+    let bb = format!("0x{}bb", "00".repeat(19));
+    let ee = format!("0x{}ee", "00".repeat(19));
+    assert_eq!(
+        env.inspector.traces(),
+        [json!({
+            "type": "CALL",
+            "from": format!("0x{}", "aa".repeat(20)),
+            "to": cc,
+            "value": "0x0",
+            "gas": "0x2710",
+            "gasUsed": format!("{gas_used:#x}"),
+            "input": "0x1234",
+            "calls": [
+                {
+                    "type": "CALL", "from": cc, "to": bb, "value": "0x0",
+                    "gas": "0x3e8", "gasUsed": "0x3", "input": "0x",
+                    "calls": [{
+                        "type": "SELFDESTRUCT", "from": bb,
+                        "to": format!("0x{}dd", "00".repeat(19)), "value": "0x0",
+                        "gas": "0x0", "gasUsed": "0x0", "input": "0x",
+                    }],
+                },
+                {
+                    "type": "CALL", "from": cc, "to": ee, "value": "0x0",
+                    "gas": "0x64", "gasUsed": "0x64", "input": "0x",
+                    "error": "invalid opcode",
+                },
+            ],
+        })]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_call_tracer_create() -> Result<(), EvmError> {
+    let mut env = environment(State::default(), CallTracer::new());
+
+    // PUSH1 0x2a PUSH1 0 MSTORE8 PUSH1 1 PUSH1 0 RETURN
+    let code = [0x60, 0x2a, 0x60, 0x00, 0x53, 0x60, 0x01, 0x60, 0x00, 0xf3];
+    let message = Message {
+        current_target: address(0x11),
+        ..create_message(&code, 1_000)
+    };
+    let result = process_message_call(message, &mut env)?;
+    assert!(result.error.is_none());
+
+    let trace = &env.inspector.take_traces()[0];
+    assert_eq!(trace["type"], json!("CREATE"));
+    assert_eq!(trace["to"], json!(format!("0x{}11", "00".repeat(19))));
+    assert_eq!(trace["input"], json!("0x602a60005360016000f3"));
+    assert_eq!(trace["output"], json!("0x2a"));
+    // 4 pushes, an MSTORE8 with memory expansion and the code deposit.
+    assert_eq!(trace["gasUsed"], json!(format!("{:#x}", 4 * 3 + 3 + 3 + 200)));
+    assert!(env.inspector.traces().is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_call_tracer_callcode_to_self() -> Result<(), EvmError> {
+    let mut env = environment(State::default(), CallTracer::new());
+
+    // CALLCODE(gas=100, to=TARGET) POP STOP
+    let mut code = vec![0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x73];
+    code.extend_from_slice(&TARGET);
+    code.extend_from_slice(&[0x60, 0x64, 0xf2, 0x50, 0x00]);
+    let result = process_message_call(message(&code, 10_000), &mut env)?;
+    assert!(result.error.is_none());
+
+    let calls = &env.inspector.traces()[0]["calls"];
+    assert_eq!(calls[0]["type"], json!("CALLCODE"));
+    assert_eq!(calls[0]["to"], json!(format!("0x{}", "cc".repeat(20))));
+    Ok(())
+}
@@ -0,0 +1,131 @@
+use serde_json::json;
+
+use execution_specs_rs::ethereum::{
+    base_types::Uint,
+    exceptions::EthereumException,
+    frontier::{fork_types::Block, state::state_root},
+    rlp,
+    utils::hexadecimal::hex,
+};
+use execution_specs_rs::ethereum_spec_tools::{
+    chain_file::{export_blocks, import_blocks, write_block, BlockReader, ChainFileError, ImportOptions},
+    node::Node,
+    rpc::Handler,
+};
+
+use super::helpers::{funded_chain, mainnet_block, mainnet_chain, sign, spawn, transaction};
+
+fn chain_file(blocks: &[&Block]) -> Vec<u8> {
+    let mut file = Vec::new();
+    for block in blocks {
+        write_block(&mut file, block).unwrap();
+    }
+    file
+}
+
+#[test]
+fn test_read_blocks() {
+    let blocks = [mainnet_block("block_1.json"), mainnet_block("block_1234567.json")];
+    let file = chain_file(&[&blocks[0], &blocks[1]]);
+
+    let read: Vec<Block> = BlockReader::new(file.as_slice()).collect::<Result<_, _>>().unwrap();
+    assert_eq!(read, blocks);
+
+    // A truncated block ends the file.
+    let mut reader = BlockReader::new(&file[..file.len() - 1]);
+    assert_eq!(reader.next().unwrap().unwrap(), blocks[0]);
+    let offset = reader.offset();
+    assert_eq!(offset, rlp::encode(&blocks[0]).len() as u64);
+    assert!(matches!(
+        reader.next(),
+        Some(Err(ChainFileError::Malformed(at, EthereumException::RLPDecodingError))) if at == offset
+    ));
+    assert!(reader.next().is_none());
+}
+
+#[test]
+fn test_import_skips_known_blocks() {
+    let mut chain = mainnet_chain();
+    let genesis = chain.blocks[0].clone();
+    let block = mainnet_block("block_1.json");
+    let file = chain_file(&[&genesis, &block]);
+
+    let mut imported = Vec::new();
+    let summary = import_blocks(&mut chain, BlockReader::new(file.as_slice()), &ImportOptions::default(), |block, _, result| {
+        assert!(result.is_ok());
+        imported.push(block.header.number.clone());
+    })
+    .unwrap();
+    assert_eq!((summary.imported, summary.skipped), (1, 1));
+    assert!(summary.failed.is_empty());
+    assert_eq!(imported, vec![Uint::from(1u8)]);
+    assert_eq!(state_root(&chain.state).unwrap(), block.header.state_root);
+}
+
+#[test]
+fn test_import_failures() {
+    let block = mainnet_block("block_1.json");
+    let mut invalid = block.clone();
+    invalid.header.state_root = [0; 32];
+    let file = chain_file(&[&invalid, &block]);
+
+    // The state is restored after the rejected block, so the valid one
+    // applies.
+    let mut chain = mainnet_chain();
+    let options = ImportOptions { stop_on_failure: false };
+    let summary = import_blocks(&mut chain, BlockReader::new(file.as_slice()), &options, |_, _, _| {}).unwrap();
+    assert_eq!(summary.imported, 1);
+    assert!(matches!(summary.failed.as_slice(), [(1, EthereumException::InvalidBlock)]));
+    assert_eq!(state_root(&chain.state).unwrap(), block.header.state_root);
+
+    // The rejected block is rolled back when stopping at it too.
+    let mut chain = mainnet_chain();
+    let genesis_root = state_root(&chain.state).unwrap();
+    let options = ImportOptions { stop_on_failure: true };
+    let summary = import_blocks(&mut chain, BlockReader::new(file.as_slice()), &options, |_, _, _| {}).unwrap();
+    assert_eq!(summary.imported, 0);
+    assert_eq!(summary.failed.len(), 1);
+    assert_eq!(chain.blocks.len(), 1);
+    assert_eq!(state_root(&chain.state).unwrap(), genesis_root);
+
+    // Once no block can follow the head, importing stops.
+    let mut later = invalid.clone();
+    later.header.number = Uint::from(2u8);
+    let file = chain_file(&[&invalid, &later, &block]);
+    let mut chain = mainnet_chain();
+    let options = ImportOptions { stop_on_failure: false };
+    let summary = import_blocks(&mut chain, BlockReader::new(file.as_slice()), &options, |_, _, _| {}).unwrap();
+    assert_eq!(summary.imported, 0);
+    assert!(matches!(summary.failed.as_slice(), [(1, EthereumException::InvalidBlock)]));
+    assert_eq!(chain.blocks.len(), 1);
+
+    // A block number beyond 64 bits ends the file.
+    let mut huge = block.clone();
+    huge.header.number = Uint::from(u64::MAX) + 1u8;
+    let file = chain_file(&[&block, &huge]);
+    let mut chain = mainnet_chain();
+    let result = import_blocks(&mut chain, BlockReader::new(file.as_slice()), &options, |_, _, _| {});
+    let offset = rlp::encode(&block).len() as u64;
+    assert!(matches!(
+        result,
+        Err(ChainFileError::Malformed(at, EthereumException::InvalidBlock)) if at == offset
+    ));
+    assert_eq!(chain.blocks.len(), 2);
+}
+
+#[test]
+fn test_export_and_import() {
+    let (sender, tx) = sign(transaction(Some([0xcc; 20]), 1000, &[]));
+    let mut node = Node::new(funded_chain(&[sender]), [0xbb; 20]);
+    node.handle("eth_sendRawTransaction", &[json!(hex(&rlp::encode(&tx)))]).unwrap();
+    let root = state_root(&node.chain().state).unwrap();
+    let url = format!("http://{}", spawn(node));
+
+    let mut file = Vec::new();
+    assert_eq!(export_blocks(&mut file, &url, 0, None, 64, |_| {}).unwrap(), 2);
+
+    let mut chain = funded_chain(&[sender]);
+    let summary = import_blocks(&mut chain, BlockReader::new(file.as_slice()), &ImportOptions::default(), |_, _, _| {}).unwrap();
+    assert_eq!((summary.imported, summary.skipped), (1, 1));
+    assert_eq!(state_root(&chain.state).unwrap(), root);
+}
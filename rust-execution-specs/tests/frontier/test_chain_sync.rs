@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+use std::fs;
+
+use serde_json::{json, Value};
+
+use execution_specs_rs::ethereum::{
+    base_types::{Uint, U256, U64},
+    exceptions::EthereumException,
+    frontier::{
+        fork_types::Address,
+        state::{get_account, state_root},
+    },
+    rlp,
+    utils::hexadecimal::{hex, hex_to_hash, hex_to_uint},
+};
+use execution_specs_rs::ethereum_spec_tools::{
+    chain_sync::{
+        block_from_json, header_from_json, load_progress, load_progress_or_genesis, sync, BlockDownloader,
+        SyncError, SyncOptions,
+    },
+    node::Node,
+    rpc::{Handler, RpcError, METHOD_NOT_FOUND},
+};
+
+use super::helpers::{funded_chain, mainnet_chain, sign, spawn, transaction};
+
+/// Replays the blocks in `assets/blocks`, and serves as ommers the blocks
+/// whose hashes are among the `uncles` of a block.
+///
+/// `block_with_ommer.json` is not a mainnet block, but block 1 with block 1
+/// as its ommer, and its hashes recomputed.
+struct Replay {
+    blocks: HashMap<Uint, Value>,
+}
+
+impl Replay {
+    fn new() -> Self {
+        let blocks = ["block_1.json", "block_1234567.json", "block_12964999.json", "block_with_ommer.json"]
+            .iter()
+            .map(|file| {
+                let json: Value = serde_json::from_str(&fs::read_to_string(format!("./assets/blocks/{file}")).unwrap()).unwrap();
+                (hex_to_uint(json["number"].as_str().unwrap()).unwrap(), json)
+            })
+            .collect();
+        Replay { blocks }
+    }
+}
+
+impl Handler for Replay {
+    fn handle(&mut self, method: &str, params: &[Value]) -> Result<Value, RpcError> {
+        match method {
+            "eth_getBlockByNumber" => {
+                let number = hex_to_uint(params[0].as_str().unwrap()).unwrap();
+                Ok(self.blocks.get(&number).cloned().unwrap_or(Value::Null))
+            }
+            "eth_getUncleByBlockNumberAndIndex" => {
+                let number = hex_to_uint(params[0].as_str().unwrap()).unwrap();
+                let index = usize::try_from(hex_to_uint(params[1].as_str().unwrap()).unwrap()).unwrap();
+                let hash = &self.blocks[&number]["uncles"][index];
+                Ok(self.blocks.values().find(|block| &block["hash"] == hash).cloned().unwrap_or(Value::Null))
+            }
+            _ => Err(RpcError::new(METHOD_NOT_FOUND, method)),
+        }
+    }
+}
+
+/// Serves the blocks of a `Replay`, but with a field of every ommer changed.
+struct TamperedOmmers(Replay);
+
+impl Handler for TamperedOmmers {
+    fn handle(&mut self, method: &str, params: &[Value]) -> Result<Value, RpcError> {
+        let mut result = self.0.handle(method, params)?;
+        if method == "eth_getUncleByBlockNumberAndIndex" {
+            result["gasUsed"] = json!("0x1");
+        }
+        Ok(result)
+    }
+}
+
+/// Serves the blocks of a `Replay` up to `last`, and fails for later ones.
+struct FailsAfter {
+    replay: Replay,
+    last: Uint,
+}
+
+impl Handler for FailsAfter {
+    fn handle(&mut self, method: &str, params: &[Value]) -> Result<Value, RpcError> {
+        let number = params.first().and_then(Value::as_str).and_then(|number| hex_to_uint(number).ok());
+        if method == "eth_getBlockByNumber" && number.is_some_and(|number| number > self.last) {
+            return Err(RpcError::server("unavailable"));
+        }
+        self.replay.handle(method, params)
+    }
+}
+
+/// Serves the blocks of a `Node`, but with the state root of those after
+/// `last` changed, and their hashes recomputed, so that they are only
+/// rejected once applied.
+struct InvalidAfter {
+    node: Node,
+    last: Uint,
+}
+
+impl Handler for InvalidAfter {
+    fn handle(&mut self, method: &str, params: &[Value]) -> Result<Value, RpcError> {
+        let mut result = self.node.handle(method, params)?;
+        let number = result["number"].as_str().and_then(|number| hex_to_uint(number).ok());
+        if method == "eth_getBlockByNumber" && number.is_some_and(|number| number > self.last) {
+            result["stateRoot"] = json!(hex(&[0; 32]));
+            result["hash"] = json!(hex(&rlp::rlp_hash(&header_from_json(&result).unwrap())));
+        }
+        Ok(result)
+    }
+}
+
+#[test]
+fn test_block_from_json() {
+    let replay = Replay::new();
+
+    let json = &replay.blocks[&Uint::from(1_234_567u32)];
+    let block = block_from_json(json, &[]).unwrap();
+    assert_eq!(rlp::rlp_hash(&block.header), hex_to_hash(json["hash"].as_str().unwrap()).unwrap());
+
+    // Its transactions include one with an access list, which came with Berlin.
+    let json = &replay.blocks[&Uint::from(12_964_999u32)];
+    assert!(matches!(block_from_json(json, &[]), Err(EthereumException::JsonDecodeError(_))));
+}
+
+#[test]
+fn test_download_ommers() {
+    let replay = Replay::new();
+    let block_1 = replay.blocks[&Uint::from(1u8)].clone();
+    let url = format!("http://{}", spawn(replay));
+
+    let mut downloader = BlockDownloader::new(&url, 3, Some(3), 1).unwrap();
+    let block = downloader.next_block().unwrap().unwrap();
+    assert_eq!(block.ommers, vec![header_from_json(&block_1).unwrap()]);
+    assert_eq!(rlp::rlp_hash(&block.ommers[0]), hex_to_hash(block_1["hash"].as_str().unwrap()).unwrap());
+    assert_eq!(rlp::rlp_hash(&block.ommers), block.header.ommers_hash);
+    assert!(downloader.next_block().unwrap().is_none());
+
+    // An ommer which does not hash to its hash is rejected.
+    let url = format!("http://{}", spawn(TamperedOmmers(Replay::new())));
+    let mut downloader = BlockDownloader::new(&url, 3, Some(3), 1).unwrap();
+    assert!(matches!(downloader.next_block(), Err(SyncError::Malformed(3, EthereumException::InvalidBlock))));
+}
+
+#[test]
+fn test_sync_saves_and_resumes_progress() {
+    let url = format!("http://{}", spawn(Replay::new()));
+    let progress = std::env::temp_dir().join(format!("test_chain_sync_{}.json", std::process::id()));
+    let options = SyncOptions { progress: Some(progress.clone()), ..SyncOptions::default() };
+
+    let mut chain = mainnet_chain();
+    let mut applied = Vec::new();
+    assert_eq!(sync(&mut chain, &url, &options, |block, _| applied.push(block.header.number.clone())).unwrap(), 1);
+    assert_eq!(applied, vec![Uint::from(1u8)]);
+
+    let mut resumed = load_progress_or_genesis(Some(&progress), "./assets/mainnet.json").unwrap();
+    fs::remove_file(&progress).unwrap();
+    assert_eq!(resumed.chain_id, U64::from(1u64));
+    assert_eq!(resumed.blocks.len(), 2);
+    assert_eq!(state_root(&resumed.state).unwrap(), state_root(&chain.state).unwrap());
+
+    // Block 2 is not served, so there is nothing more to sync.
+    let options = SyncOptions::default();
+    assert_eq!(sync(&mut resumed, &url, &options, |_, _| {}).unwrap(), 0);
+}
+
+#[test]
+fn test_load_progress_or_genesis() {
+    // Without progress, the chain starts from the genesis file.
+    let missing = std::env::temp_dir().join(format!("test_chain_sync_missing_{}.json", std::process::id()));
+    let genesis = mainnet_chain();
+    for progress in [None, Some(missing.as_path())] {
+        let chain = load_progress_or_genesis(progress, "./assets/mainnet.json").unwrap();
+        assert_eq!(chain.blocks, genesis.blocks);
+        assert_eq!(state_root(&chain.state).unwrap(), state_root(&genesis.state).unwrap());
+    }
+    assert!(matches!(load_progress_or_genesis(None, "./assets/missing.json"), Err(SyncError::Progress(_))));
+}
+
+#[test]
+fn test_sync_saves_progress_when_the_endpoint_fails() {
+    let url = format!("http://{}", spawn(FailsAfter { replay: Replay::new(), last: Uint::from(1u8) }));
+    let progress = std::env::temp_dir().join(format!("test_chain_sync_failing_{}.json", std::process::id()));
+    let options = SyncOptions { progress: Some(progress.clone()), batch_size: 1, ..SyncOptions::default() };
+
+    let mut chain = mainnet_chain();
+    assert!(matches!(sync(&mut chain, &url, &options, |_, _| {}), Err(SyncError::Rpc(_))));
+
+    let resumed = load_progress(&progress).unwrap().unwrap();
+    fs::remove_file(&progress).unwrap();
+    assert_eq!(resumed.blocks.len(), 2);
+    assert_eq!(state_root(&resumed.state).unwrap(), state_root(&chain.state).unwrap());
+}
+
+#[test]
+fn test_sync_transactions_from_node() {
+    let transactions = [
+        sign(transaction(Some([0xcc; 20]), 1000, &[])),
+        sign(transaction(Some([0xdd; 20]), 1000, &[])),
+    ];
+    let senders: Vec<Address> = transactions.iter().map(|(sender, _)| *sender).collect();
+
+    let mut node = Node::new(funded_chain(&senders), [0xbb; 20]);
+    for (_, tx) in &transactions {
+        node.handle("eth_sendRawTransaction", &[json!(hex(&rlp::encode(tx)))]).unwrap();
+    }
+    let root = state_root(&node.chain().state).unwrap();
+    let url = format!("http://{}", spawn(node));
+
+    let mut chain = funded_chain(&senders);
+    let options = SyncOptions { until: Some(5), batch_size: 2, ..SyncOptions::default() };
+    assert_eq!(sync(&mut chain, &url, &options, |_, _| {}).unwrap(), 2);
+    assert_eq!(state_root(&chain.state).unwrap(), root);
+    assert_eq!(get_account(&chain.state, &[0xcc; 20]).unwrap().balance, U256::from(1000u32));
+    assert_eq!(get_account(&chain.state, &[0xdd; 20]).unwrap().balance, U256::from(1000u32));
+}
+
+#[test]
+fn test_sync_saves_progress_when_a_block_is_rejected() {
+    let transactions = [
+        sign(transaction(Some([0xcc; 20]), 1000, &[])),
+        sign(transaction(Some([0xdd; 20]), 1000, &[])),
+    ];
+    let senders: Vec<Address> = transactions.iter().map(|(sender, _)| *sender).collect();
+
+    let mut node = Node::new(funded_chain(&senders), [0xbb; 20]);
+    node.handle("eth_sendRawTransaction", &[json!(hex(&rlp::encode(&transactions[0].1)))]).unwrap();
+    let root = state_root(&node.chain().state).unwrap();
+    node.handle("eth_sendRawTransaction", &[json!(hex(&rlp::encode(&transactions[1].1)))]).unwrap();
+    let url = format!("http://{}", spawn(InvalidAfter { node, last: Uint::from(1u8) }));
+    let progress = std::env::temp_dir().join(format!("test_chain_sync_rejected_{}.json", std::process::id()));
+    let options = SyncOptions { progress: Some(progress.clone()), ..SyncOptions::default() };
+
+    // The chain is left at block 1, without the transfer of block 2.
+    let mut chain = funded_chain(&senders);
+    assert!(matches!(
+        sync(&mut chain, &url, &options, |_, _| {}),
+        Err(SyncError::InvalidBlock(2, EthereumException::InvalidBlock))
+    ));
+    assert_eq!(chain.blocks.len(), 2);
+    assert_eq!(state_root(&chain.state).unwrap(), root);
+    assert_eq!(get_account(&chain.state, &[0xdd; 20]).unwrap().balance, U256::ZERO);
+
+    let resumed = load_progress(&progress).unwrap().unwrap();
+    fs::remove_file(&progress).unwrap();
+    assert_eq!(resumed.blocks.len(), 2);
+    assert_eq!(state_root(&resumed.state).unwrap(), root);
+}
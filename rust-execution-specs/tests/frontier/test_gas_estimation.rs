@@ -0,0 +1,80 @@
+use execution_specs_rs::ethereum::{
+    base_types::{Bytes, Uint, U256},
+    frontier::{
+        fork::calculate_intrinsic_cost,
+        fork_types::Transaction,
+        gas_estimation::{estimate_gas, EstimateGasError},
+        state::{get_account, state_root, State},
+        vm::{exceptions::EvmError, Environment},
+    },
+};
+
+use super::helpers::{self, contract, CALLER, TARGET};
+
+fn environment(code: &[u8], balance: u64) -> Environment {
+    let mut state = State::default();
+    contract(&mut state, CALLER, balance, &[]);
+    contract(&mut state, TARGET, 0, code);
+    Environment { gas_price: U256::ZERO, ..helpers::environment(state, ()) }
+}
+
+fn transaction(gas_price: u8, data: &[u8]) -> Transaction {
+    Transaction {
+        nonce: U256::ZERO,
+        gas_price: U256::from(gas_price),
+        gas: U256::ZERO,
+        to: Some(TARGET),
+        value: U256::from(7u8),
+        data: Bytes::from(data),
+        v: U256::ZERO,
+        r: U256::ZERO,
+        s: U256::ZERO,
+    }
+}
+
+#[test]
+fn test_intrinsic_cost() {
+    assert_eq!(calculate_intrinsic_cost(&transaction(0, &[])), Uint::from(21000u16));
+    assert_eq!(calculate_intrinsic_cost(&transaction(0, &[0, 1, 0])), Uint::from(21000u16 + 4 + 68 + 4));
+}
+
+#[test]
+fn test_estimate_gas() {
+    // Nothing but the intrinsic cost when there is no code.
+    let mut env = environment(&[], 1_000_000);
+    assert_eq!(estimate_gas(&mut env, CALLER, &transaction(1, &[])).unwrap(), Uint::from(21000u16));
+
+    // PUSH1 1 PUSH1 0 SSTORE
+    let mut env = environment(&[0x60, 0x01, 0x60, 0x00, 0x55], 1_000_000);
+    let root = state_root(&env.state).unwrap();
+    let tx = transaction(1, &[0xff]);
+    assert_eq!(estimate_gas(&mut env, CALLER, &tx).unwrap(), Uint::from(21000u32 + 68 + 20006));
+
+    // The state is left untouched, with every probe rolled back.
+    assert_eq!(state_root(&env.state).unwrap(), root);
+    assert_eq!(get_account(&env.state, &CALLER).unwrap().balance, U256::from(1_000_000u32));
+    assert_eq!(get_account(&env.state, &CALLER).unwrap().nonce, Uint::from(0u8));
+}
+
+#[test]
+fn test_estimate_gas_failures() {
+    let mut env = environment(&[0xfe], 1_000_000);
+    assert!(matches!(
+        estimate_gas(&mut env, CALLER, &transaction(1, &[])),
+        Err(EstimateGasError::Failed(EvmError::InvalidOpcode))
+    ));
+
+    // The sender can only pay for 20000 gas.
+    let mut env = environment(&[], 20007);
+    assert!(matches!(
+        estimate_gas(&mut env, CALLER, &transaction(1, &[])),
+        Err(EstimateGasError::InsufficientFunds)
+    ));
+
+    let mut env = environment(&[], 1_000_000);
+    env.gas_limit = Uint::from(21067u16);
+    assert!(matches!(
+        estimate_gas(&mut env, CALLER, &transaction(0, &[1])),
+        Err(EstimateGasError::IntrinsicGasTooHigh(cost)) if cost == Uint::from(21068u16)
+    ));
+}
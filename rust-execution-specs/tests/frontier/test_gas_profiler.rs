@@ -0,0 +1,86 @@
+use execution_specs_rs::ethereum::{
+    base_types::Uint,
+    frontier::{
+        state::State,
+        vm::{
+            exceptions::EvmError,
+            gas_profiler::{GasProfiler, OpcodeGas},
+            interpreter::process_message_call,
+        },
+    },
+};
+
+use super::helpers::{create_message, environment, message, TARGET};
+
+fn opcode(count: u64, gas: u32, memory_gas: u32) -> OpcodeGas {
+    OpcodeGas {
+        count,
+        gas: Uint::from(gas),
+        memory_gas: Uint::from(memory_gas),
+    }
+}
+
+#[test]
+fn test_gas_profile() -> Result<(), EvmError> {
+    let mut env = environment(State::default(), GasProfiler::new());
+
+    // MSTORE(0, 1) CALL(gas=100, to=IDENTITY, in=[0, 32), out=[32, 64)) POP STOP
+    let code = [
+        0x60, 0x01, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x20, 0x60, 0x20, 0x60, 0x00, 0x60, 0x00,
+        0x60, 0x04, 0x60, 0x64, 0xf1, 0x50, 0x00,
+    ];
+    let result = process_message_call(message(&code, 30_000), &mut env)?;
+    assert!(result.error.is_none());
+    assert_eq!(Uint::from(30_000u32) - result.gas_left, Uint::from(25_096u32));
+
+    let profiler = &env.inspector;
+    let by_opcode = profiler.by_opcode();
+    assert_eq!(
+        by_opcode,
+        [
+            // The new account, the call itself and memory expansion, but not
+            // the 100 gas forwarded to the precompile.
+            ("CALL", &opcode(1, 25_043, 3)),
+            ("PUSH1", &opcode(9, 27, 0)),
+            ("PRECOMPILE", &opcode(1, 18, 0)),
+            ("MSTORE", &opcode(1, 6, 3)),
+            ("POP", &opcode(1, 2, 0)),
+            ("STOP", &opcode(1, 0, 0)),
+        ]
+    );
+
+    let mut identity = [0; 20];
+    identity[19] = 4;
+    assert_eq!(
+        profiler.by_contract(),
+        [(&TARGET, &Uint::from(25_078u32)), (&identity, &Uint::from(18u8))]
+    );
+    assert_eq!(profiler.by_pc()[0], (&TARGET, 19, &Uint::from(25_043u32)));
+
+    let target = format!("0x{}", "cc".repeat(20));
+    let folded = profiler.folded_stacks();
+    assert!(folded.contains(&format!("{target};CALL 25043\n")));
+    assert!(folded.contains(&format!("{target};0x{}04;PRECOMPILE 18\n", "00".repeat(19))));
+    assert!(!folded.contains("STOP"));
+    assert!(profiler.report().starts_with("opcode"));
+    Ok(())
+}
+
+#[test]
+fn test_gas_profile_exceptional_halt_and_create() -> Result<(), EvmError> {
+    let mut env = environment(State::default(), GasProfiler::new());
+
+    // PUSH1 1 INVALID
+    process_message_call(message(&[0x60, 0x01, 0xfe], 100), &mut env)?;
+    // PUSH1 0x2a PUSH1 0 MSTORE8 PUSH1 1 PUSH1 0 RETURN
+    let code = [0x60, 0x2a, 0x60, 0x00, 0x53, 0x60, 0x01, 0x60, 0x00, 0xf3];
+    process_message_call(create_message(&code, 1_000), &mut env)?;
+
+    let by_opcode = env.inspector.by_opcode();
+    assert_eq!(by_opcode[0], ("CODE_DEPOSIT", &opcode(1, 200, 0)));
+    // All the gas left is lost to the invalid opcode.
+    assert_eq!(by_opcode[1], ("INVALID", &opcode(1, 97, 0)));
+    assert_eq!(by_opcode[2], ("PUSH1", &opcode(5, 15, 0)));
+    assert_eq!(by_opcode[3], ("MSTORE8", &opcode(1, 6, 3)));
+    Ok(())
+}
@@ -0,0 +1,169 @@
+use execution_specs_rs::ethereum::{
+    base_types::{Bytes32, Uint, U256},
+    exceptions::EthereumException,
+    frontier::{
+        fork_types::{Account, Address, Log},
+        state::{get_account, State},
+        state_backends::{OverlayState, StateSource},
+        vm::{
+            exceptions::EvmError,
+            inspector::{Frame, Inspector},
+            interpreter::process_message_call,
+        },
+    },
+};
+
+use super::helpers::{address, contract, environment, message, TARGET};
+
+#[derive(Default)]
+struct Recorder {
+    events: Vec<String>,
+    steps: usize,
+    step_ends: usize,
+    max_stack: usize,
+}
+
+impl Inspector for Recorder {
+    fn step(&mut self, frame: &Frame, _op: u8) {
+        self.steps += 1;
+        self.max_stack = self.max_stack.max(frame.stack.len());
+    }
+
+    fn step_end(&mut self, _frame: &Frame) {
+        self.step_ends += 1;
+    }
+
+    fn call(&mut self, frame: &Frame) {
+        self.events.push(format!("call {} {}", frame.message.depth, frame.gas_left));
+    }
+
+    fn call_end(&mut self, frame: &Frame, error: Option<&EvmError>) {
+        self.events.push(format!("call_end {} {:?}", frame.message.depth, error));
+    }
+
+    fn log(&mut self, frame: &Frame, log: &Log) {
+        self.events.push(format!("log {} {:?}", frame.pc, log.data));
+    }
+
+    fn selfdestruct(&mut self, _frame: &Frame, beneficiary: &Address, value: &U256) {
+        self.events.push(format!("selfdestruct {:x} {}", beneficiary[19], value));
+    }
+
+    fn error(&mut self, frame: &Frame, error: &EvmError) {
+        self.events.push(format!("error {} {:?}", frame.pc, error));
+    }
+}
+
+#[test]
+fn test_inspector_nested_frames() -> Result<(), EvmError> {
+    let mut state = State::default();
+    let callee = address(0xbb);
+    // PUSH1 0 PUSH1 0 LOG0 INVALID
+    contract(&mut state, callee, 0, &[0x60, 0x00, 0x60, 0x00, 0xa0, 0xfe]);
+
+    let mut recorder = Recorder::default();
+    let mut env = environment(state, &mut recorder);
+
+    // CALL(gas=1000, to=0xbb, value=0, no input, no output) STOP
+    let code = [
+        0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0xbb, 0x61, 0x03, 0xe8,
+        0xf1, 0x00,
+    ];
+    let result = process_message_call(message(&code, 10_000), &mut env)?;
+    assert!(result.error.is_none());
+    // The log of the failed frame is discarded, but was still inspected.
+    assert!(result.logs.is_empty());
+
+    assert_eq!(
+        recorder.events,
+        [
+            "call 0 10000",
+            "call 1 1000",
+            "log 4 []",
+            "error 5 InvalidOpcode",
+            "call_end 1 Some(InvalidOpcode)",
+            "call_end 0 None",
+        ]
+    );
+    // The `INVALID` opcode is stepped into, but never completed.
+    assert_eq!(recorder.steps, 8 + 4 + 1);
+    assert_eq!(recorder.step_ends, recorder.steps - 1);
+    assert_eq!(recorder.max_stack, 7);
+    Ok(())
+}
+
+#[test]
+fn test_inspector_selfdestruct() -> Result<(), EvmError> {
+    let mut state = State::default();
+    contract(&mut state, TARGET, 5, &[]);
+
+    let mut recorder = Recorder::default();
+    let mut env = environment(state, &mut recorder);
+
+    // PUSH1 0xbb SELFDESTRUCT
+    let result = process_message_call(message(&[0x60, 0xbb, 0xff], 10_000), &mut env)?;
+    assert_eq!(result.accounts_to_delete.len(), 1);
+
+    let mut beneficiary = [0; 20];
+    beneficiary[19] = 0xbb;
+    assert_eq!(get_account(&env.state, &beneficiary)?.balance, U256::from(5u8));
+    assert_eq!(
+        recorder.events,
+        ["call 0 10000", "selfdestruct bb 5", "call_end 0 None"]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_no_inspector() -> Result<(), EvmError> {
+    let mut env = environment(State::default(), ());
+
+    // PUSH1 1 PUSH1 2 ADD
+    let result = process_message_call(message(&[0x60, 0x01, 0x60, 0x02, 0x01], 100), &mut env)?;
+    assert!(result.error.is_none());
+    assert_eq!(result.gas_left, Uint::from(91u8));
+    Ok(())
+}
+
+/// A state whose storage can not be read.
+struct NoStorage(State);
+
+impl StateSource for NoStorage {
+    fn account(&self, address: &Address) -> Result<Option<Account>, EthereumException> {
+        self.0.account(address)
+    }
+
+    fn storage(&self, _address: &Address, _key: &Bytes32) -> Result<U256, EthereumException> {
+        Err(EthereumException::StateUnavailable("storage".to_string()))
+    }
+}
+
+#[test]
+fn test_inspector_state_error_exits_frames() {
+    let mut state = State::default();
+    let callee = address(0xbb);
+    // PUSH1 0 SLOAD
+    contract(&mut state, callee, 0, &[0x60, 0x00, 0x54]);
+
+    let mut recorder = Recorder::default();
+    let mut env = environment(OverlayState::new(NoStorage(state)), &mut recorder);
+
+    // CALL(gas=1000, to=0xbb, value=0, no input, no output) STOP
+    let code = [
+        0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0xbb, 0x61, 0x03, 0xe8,
+        0xf1, 0x00,
+    ];
+    let result = process_message_call(message(&code, 10_000), &mut env);
+    assert!(matches!(result, Err(EvmError::State(_))));
+
+    let error = r#"Some(State(StateUnavailable("storage")))"#;
+    assert_eq!(
+        recorder.events,
+        [
+            "call 0 10000".to_string(),
+            "call 1 1000".to_string(),
+            format!("call_end 1 {error}"),
+            format!("call_end 0 {error}"),
+        ]
+    );
+}
@@ -0,0 +1,175 @@
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::sync::mpsc::{channel, Sender};
+use std::time::Duration;
+
+use hex_literal::hex;
+use serde_json::{json, Value};
+
+use execution_specs_rs::ethereum::{
+    base_types::U256,
+    frontier::{fork::state_transition, fork_types::Transaction, state::state_root},
+    rlp,
+    utils::hexadecimal::hex,
+};
+use execution_specs_rs::ethereum_spec_tools::{
+    node::Node,
+    rpc::{handle_request, Handler, RpcError, MAX_BODY_SIZE, METHOD_NOT_FOUND, PARSE_ERROR},
+};
+
+use super::helpers::{funded_chain, sign, spawn, transaction, TARGET};
+
+/// Runtime code which stores 1 in slot 0 and returns 42.
+const RUNTIME: [u8; 15] = hex!("6001600055602a60005260206000f3");
+
+fn send(node: &mut Node, tx: &Transaction) -> Result<Value, i64> {
+    node.handle("eth_sendRawTransaction", &[json!(hex(&rlp::encode(tx)))]).map_err(|error| error.code)
+}
+
+#[test]
+fn test_send_raw_transaction_mines_a_valid_block() {
+    let (sender, tx) = sign(transaction(Some(TARGET), 1000, &[]));
+    let mut node = Node::new(funded_chain(&[sender]), [0xbb; 20]);
+
+    let hash = send(&mut node, &tx).unwrap();
+
+    assert_eq!(node.handle("eth_blockNumber", &[]).unwrap(), json!("0x1"));
+    assert_eq!(node.handle("eth_getBalance", &[json!(hex(&TARGET)), json!("latest")]).unwrap(), json!("0x3e8"));
+    assert_eq!(node.handle("eth_getTransactionCount", &[json!(hex(&sender))]).unwrap(), json!("0x1"));
+
+    let receipt = node.handle("eth_getTransactionReceipt", std::slice::from_ref(&hash)).unwrap();
+    assert_eq!(receipt["gasUsed"], json!("0x5208"));
+    assert_eq!(receipt["from"], json!(hex(&sender)));
+    let block = node.handle("eth_getBlockByNumber", &[json!("latest"), json!(false)]).unwrap();
+    assert_eq!(block["transactions"], json!([hash]));
+    assert_eq!(block["hash"], receipt["blockHash"]);
+
+    // The block is valid for the specification.
+    let mut chain = funded_chain(&[sender]);
+    state_transition(&mut chain, node.chain().blocks[1].clone()).unwrap();
+
+    // Sending it again fails on its nonce, and leaves the chain as it was.
+    assert!(send(&mut node, &tx).is_err());
+    assert_eq!(node.handle("eth_blockNumber", &[]).unwrap(), json!("0x1"));
+    assert_eq!(state_root(&node.chain().state).unwrap(), state_root(&chain.state).unwrap());
+}
+
+#[test]
+fn test_deploy_then_call_and_estimate_gas() {
+    let mut init_code = hex!("600f600c600039600f6000f3").to_vec();
+    init_code.extend(RUNTIME);
+    let (sender, tx) = sign(transaction(None, 0, &init_code));
+    let mut node = Node::new(funded_chain(&[sender]), [0xbb; 20]);
+
+    let hash = send(&mut node, &tx).unwrap();
+    let contract = node.handle("eth_getTransactionReceipt", &[hash]).unwrap()["contractAddress"].clone();
+    assert_eq!(node.handle("eth_getCode", &[contract.clone(), json!("latest")]).unwrap(), json!(hex(&RUNTIME)));
+
+    let call = json!({ "to": contract });
+    let output = node.handle("eth_call", &[call.clone(), json!("latest")]).unwrap();
+    assert_eq!(output, json!(hex(&U256::from(42u8).to_be_bytes32())));
+    let slot = node.handle("eth_getStorageAt", &[contract, json!("0x0"), json!("latest")]).unwrap();
+    assert_eq!(slot, json!(hex(&[0; 32])));
+
+    let gas = node.handle("eth_estimateGas", &[call]).unwrap();
+    let gas = u64::from_str_radix(gas.as_str().unwrap().trim_start_matches("0x"), 16).unwrap();
+    assert!(gas > 21_000 + 20_000);
+}
+
+#[test]
+fn test_errors_and_batches() {
+    let (sender, tx) = sign(transaction(Some(TARGET), 1000, &[]));
+    let mut node = Node::new(funded_chain(&[sender]), [0xbb; 20]);
+
+    let mut unsigned = tx.clone();
+    unsigned.v = U256::from(29u8);
+    assert!(send(&mut node, &unsigned).is_err());
+    assert_eq!(node.handle("eth_blockNumber", &[]).unwrap(), json!("0x0"));
+    assert_eq!(node.handle("eth_getTransactionReceipt", &[json!(hex(&[0; 32]))]).unwrap(), Value::Null);
+
+    let response = handle_request(&mut node, "{").unwrap();
+    let response: Value = serde_json::from_str(&response).unwrap();
+    assert_eq!(response["error"]["code"], json!(PARSE_ERROR));
+
+    let batch = r#"[
+        {"jsonrpc": "2.0", "id": 1, "method": "eth_chainId"},
+        {"jsonrpc": "2.0", "method": "eth_blockNumber"},
+        {"jsonrpc": "2.0", "id": 2, "method": "eth_mine", "params": []}
+    ]"#;
+    let response: Value = serde_json::from_str(&handle_request(&mut node, batch).unwrap()).unwrap();
+    assert_eq!(response[0], json!({ "jsonrpc": "2.0", "id": 1, "result": "0x539" }));
+    assert_eq!(response[1]["error"]["code"], json!(METHOD_NOT_FOUND));
+    assert_eq!(response.as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn test_serve_over_http() {
+    let (sender, _) = sign(transaction(Some(TARGET), 1000, &[]));
+    let address = spawn(Node::new(funded_chain(&[sender]), [0xbb; 20]));
+
+    let body = json!({ "jsonrpc": "2.0", "id": 7, "method": "eth_getBalance", "params": [hex(&sender), "latest"] }).to_string();
+    let mut stream = TcpStream::connect(address).unwrap();
+    write!(stream, "POST / HTTP/1.1\r\nHost: {address}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}", body.len()).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    assert!(head.starts_with("HTTP/1.1 200 OK"));
+    let body: Value = serde_json::from_str(body).unwrap();
+    assert_eq!(body, json!({ "jsonrpc": "2.0", "id": 7, "result": "0x3b9aca00" }));
+}
+
+#[test]
+fn test_serve_leaves_refused_bodies_unread() {
+    let (sender, _) = sign(transaction(Some(TARGET), 1000, &[]));
+    let address = spawn(Node::new(funded_chain(&[sender]), [0xbb; 20]));
+
+    // Neither body is ever sent, so reading it would hang the server.
+    for (method, content_length, status) in [
+        ("POST", MAX_BODY_SIZE + 1, "413 Payload Too Large"),
+        ("GET", 100, "405 Method Not Allowed"),
+    ] {
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(stream, "{method} / HTTP/1.1\r\nHost: {address}\r\nContent-Length: {content_length}\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with(&format!("HTTP/1.1 {status}")), "{response}");
+    }
+}
+
+/// A `Node` which passes the errors of its connections on.
+struct ReportsErrors {
+    node: Node,
+    errors: Sender<io::ErrorKind>,
+}
+
+impl Handler for ReportsErrors {
+    fn handle(&mut self, method: &str, params: &[Value]) -> Result<Value, RpcError> {
+        self.node.handle(method, params)
+    }
+
+    fn connection_error(&mut self, _: Option<SocketAddr>, error: &io::Error) {
+        self.errors.send(error.kind()).unwrap();
+    }
+}
+
+#[test]
+fn test_serve_reports_failed_connections() {
+    let (sender, _) = sign(transaction(Some(TARGET), 1000, &[]));
+    let (errors, received) = channel();
+    let address = spawn(ReportsErrors { node: Node::new(funded_chain(&[sender]), [0xbb; 20]), errors });
+
+    // The client hangs up before sending the body it announced.
+    let mut stream = TcpStream::connect(address).unwrap();
+    write!(stream, "POST / HTTP/1.1\r\nHost: {address}\r\nContent-Length: 10\r\n\r\n").unwrap();
+    drop(stream);
+    assert_eq!(received.recv_timeout(Duration::from_secs(10)).unwrap(), io::ErrorKind::UnexpectedEof);
+
+    // The server carries on.
+    let body = json!({ "jsonrpc": "2.0", "id": 1, "method": "eth_blockNumber" }).to_string();
+    let mut stream = TcpStream::connect(address).unwrap();
+    write!(stream, "POST / HTTP/1.1\r\nHost: {address}\r\nContent-Length: {}\r\n\r\n{body}", body.len()).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.ends_with(r#""result":"0x0"}"#), "{response}");
+}
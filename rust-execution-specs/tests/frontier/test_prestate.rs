@@ -0,0 +1,71 @@
+use execution_specs_rs::ethereum::{
+    base_types::{Bytes, U256},
+    exceptions::EthereumException,
+    frontier::{
+        prestate::PrestateRecorder,
+        state::{
+            begin_transaction, destroy_account, increment_nonce, move_ether, rollback_transaction,
+            set_code, set_storage, State,
+        },
+    },
+};
+use serde_json::json;
+
+use super::helpers::{account, state_with};
+
+fn sample_state() -> State {
+    state_with(
+        &[
+            ([1; 20], account(1, 1000, b"")),
+            ([2; 20], account(1, 0, b"")),
+            ([3; 20], account(1, 5, b"")),
+        ],
+        &[([2; 20], [1; 32], 1), ([2; 20], [2; 32], 2)],
+    )
+}
+
+#[test]
+fn test_prestate_diff() -> Result<(), EthereumException> {
+    let mut state = PrestateRecorder::new(sample_state());
+
+    move_ether(&mut state, [1; 20], [4; 20], U256::from(0x10u8))?;
+    increment_nonce(&mut state, [1; 20])?;
+    set_storage(&mut state, [2; 20], &[1; 32], U256::from(0u8));
+    set_storage(&mut state, [2; 20], &[3; 32], U256::from(3u8));
+    set_code(&mut state, [2; 20], Bytes::from(&[0x00][..]))?;
+    destroy_account(&mut state, &[3; 20]);
+
+    // Changes that are rolled back do not show up.
+    begin_transaction(&mut state);
+    set_storage(&mut state, [2; 20], &[2; 32], U256::from(9u8));
+    move_ether(&mut state, [1; 20], [5; 20], U256::from(1u8))?;
+    rollback_transaction(&mut state);
+
+    let diff = state.take_diff()?;
+    let one = format!("0x{}", "01".repeat(20));
+    let two = format!("0x{}", "02".repeat(20));
+    let three = format!("0x{}", "03".repeat(20));
+    let four = format!("0x{}", "04".repeat(20));
+    let slot = |byte: &str| format!("0x{}", byte.repeat(32));
+    let word = |value: u8| format!("0x{:064x}", value);
+
+    assert_eq!(
+        diff.to_json(),
+        json!({
+            "pre": {
+                one.clone(): { "balance": "0x3e8", "nonce": 1 },
+                two.clone(): { "balance": "0x0", "nonce": 1, "storage": { slot("01"): word(1) } },
+                three: { "balance": "0x5", "nonce": 1 },
+            },
+            "post": {
+                one: { "balance": "0x3d8", "nonce": 2 },
+                two: { "code": "0x00", "storage": { slot("03"): word(3) } },
+                four: { "balance": "0x10" },
+            },
+        })
+    );
+
+    // Recording starts afresh for the next transaction.
+    assert_eq!(state.take_diff()?.to_json(), json!({ "pre": {}, "post": {} }));
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use std::sync::Arc;
+
+use execution_specs_rs::ethereum::{
+    base_types::U256,
+    frontier::{
+        fork_types::keccak256,
+        vm::runtime::{cached_jump_destinations, get_valid_jump_destinations, JumpDestinationsCache},
+    },
+};
+
+#[test]
+fn test_jump_destinations_skip_push_data() {
+    // JUMPDEST, PUSH2 0x5b5b, JUMPDEST, PUSH32 of 0x5b, then a JUMPDEST at 70.
+    let mut code = vec![0x5b, 0x61, 0x5b, 0x5b, 0x5b, 0x7f];
+    code.extend([0x5b; 32]);
+    code.extend([0x00; 32]);
+    code.push(0x5b);
+
+    let valid_jump_destinations = get_valid_jump_destinations(&code);
+    assert_eq!(valid_jump_destinations.iter().collect::<Vec<_>>(), [0, 4, 70]);
+    assert!(valid_jump_destinations.contains(&U256::from(70u8)));
+    assert!(!valid_jump_destinations.contains(&U256::from(2u8)));
+    assert!(!valid_jump_destinations.contains(&U256::from(71u8)));
+    assert!(!valid_jump_destinations.contains(&U256::MAX));
+
+    // A truncated PUSH at the end of the code.
+    assert_eq!(get_valid_jump_destinations(&[0x5b, 0x62, 0x5b]).iter().count(), 1);
+}
+
+#[test]
+fn test_jump_destinations_are_cached_by_code_hash() {
+    let code = [0x60, 0x04, 0x56, 0x00, 0x5b, 0x00, 0x01];
+    let code_hash = keccak256(&code);
+    let first = cached_jump_destinations(&code, Some(&code_hash));
+    let second = cached_jump_destinations(&code, Some(&code_hash));
+    assert!(Arc::ptr_eq(&first, &second));
+    assert_eq!(*first, get_valid_jump_destinations(&code));
+
+    let other_hash = keccak256(&code[..6]);
+    let other = cached_jump_destinations(&code[..6], Some(&other_hash));
+    assert!(!Arc::ptr_eq(&first, &other));
+    // Without a hash the code is analyzed again.
+    assert!(!Arc::ptr_eq(&first, &cached_jump_destinations(&code, None)));
+    assert!(cached_jump_destinations(&[], None).iter().next().is_none());
+}
+
+#[test]
+fn test_jump_destinations_cache_forgets_least_recently_run() {
+    let codes: Vec<[u8; 1]> = (0..3).map(|byte| [byte]).collect();
+    let hashes: Vec<_> = codes.iter().map(|code| keccak256(code)).collect();
+    let mut cache = JumpDestinationsCache::new(2);
+
+    let first = cache.get(&hashes[0], &codes[0]);
+    let second = cache.get(&hashes[1], &codes[1]);
+    // Running the first code again makes the second the least recent.
+    assert!(Arc::ptr_eq(&first, &cache.get(&hashes[0], &codes[0])));
+    let third = cache.get(&hashes[2], &codes[2]);
+    assert_eq!(cache.len(), 2);
+
+    assert!(Arc::ptr_eq(&third, &cache.get(&hashes[2], &codes[2])));
+    assert!(Arc::ptr_eq(&first, &cache.get(&hashes[0], &codes[0])));
+    assert!(!Arc::ptr_eq(&second, &cache.get(&hashes[1], &codes[1])));
+    assert_eq!(cache.len(), 2);
+}
@@ -0,0 +1,132 @@
+use execution_specs_rs::ethereum::{
+    base_types::U256,
+    exceptions::EthereumException,
+    frontier::{
+        snapshot::{Snapshot, SnapshotState},
+        state::{
+            destroy_account, get_account, get_account_optional, get_storage, move_ether,
+            set_account, set_storage, state_root, State, StateBackend,
+        },
+        state_backends::{NodeDb, NodeDbState},
+    },
+};
+
+use super::helpers::account;
+
+fn genesis<S: StateBackend>(state: &mut S) {
+    for i in 1..10u8 {
+        set_account(state, [i; 20], Some(account(0, 1000, b"")));
+        set_storage(state, [i; 20], &[i; 32], U256::from(i as u32));
+    }
+}
+
+fn block_1<S: StateBackend>(state: &mut S) -> Result<(), EthereumException> {
+    move_ether(state, [1; 20], [2; 20], U256::from(100u8))?;
+    set_storage(state, [3; 20], &[3; 32], U256::from(33u8));
+    destroy_account(state, &[4; 20]);
+    Ok(())
+}
+
+fn block_2<S: StateBackend>(state: &mut S) -> Result<(), EthereumException> {
+    move_ether(state, [2; 20], [10; 20], U256::from(50u8))?;
+    set_storage(state, [3; 20], &[4; 32], U256::from(44u8));
+    set_account(state, [4; 20], Some(account(0, 1, b"")));
+    Ok(())
+}
+
+fn block_2_fork<S: StateBackend>(state: &mut S) -> Result<(), EthereumException> {
+    move_ether(state, [5; 20], [1; 20], U256::from(7u8))?;
+    set_storage(state, [3; 20], &[3; 32], U256::from(0u8));
+    Ok(())
+}
+
+fn snapshot() -> Result<SnapshotState, EthereumException> {
+    let mut state = NodeDbState::new(NodeDb::default());
+    genesis(&mut state);
+    state.commit()?;
+    Ok(SnapshotState::new(Snapshot::new(state.source)))
+}
+
+#[test]
+fn test_snapshot_layers() -> Result<(), EthereumException> {
+    let mut expected = State::default();
+    genesis(&mut expected);
+
+    let mut state = snapshot()?;
+    let genesis_root = state.source.disk_root();
+    assert_eq!(genesis_root, state_root(&expected)?);
+
+    block_1(&mut expected)?;
+    block_1(&mut state)?;
+    let root_1 = state.commit()?;
+    assert_eq!(root_1, state_root(&expected)?);
+
+    block_2(&mut expected)?;
+    block_2(&mut state)?;
+    let root_2 = state.commit()?;
+    assert_eq!(root_2, state_root(&expected)?);
+    assert_eq!(state.source.layers().len(), 2);
+
+    for i in 1..11u8 {
+        assert_eq!(get_account_optional(&state, &[i; 20])?, get_account_optional(&expected, &[i; 20])?);
+        for j in 1..11u8 {
+            assert_eq!(get_storage(&state, &[i; 20], &[j; 32])?, get_storage(&expected, &[i; 20], &[j; 32])?);
+        }
+    }
+
+    state.source.flatten(1);
+    assert_eq!(state.source.disk_root(), root_1);
+    assert_eq!(state.source.layers().len(), 1);
+    assert_eq!(get_account(&state, &[1; 20])?.balance, U256::from(900u32));
+    assert_eq!(get_account(&state, &[4; 20])?.balance, U256::from(1u8));
+    assert_eq!(get_storage(&state, &[4; 20], &[4; 32])?, U256::from(0u8));
+    assert_eq!(get_storage(&state, &[3; 20], &[3; 32])?, U256::from(33u8));
+    Ok(())
+}
+
+#[test]
+fn test_snapshot_reorg() -> Result<(), EthereumException> {
+    let mut expected = State::default();
+    genesis(&mut expected);
+    block_1(&mut expected)?;
+
+    let mut state = snapshot()?;
+    block_1(&mut state)?;
+    let root_1 = state.commit()?;
+    block_2(&mut state)?;
+    state.commit()?;
+
+    // The chain reorgs onto a different second block.
+    state.source.revert(&root_1)?;
+    assert_eq!(state.source.layers().len(), 1);
+    assert_eq!(get_account_optional(&state, &[10; 20])?, None);
+    assert_eq!(get_storage(&state, &[3; 20], &[4; 32])?, U256::from(0u8));
+
+    block_2_fork(&mut expected)?;
+    block_2_fork(&mut state)?;
+    assert_eq!(state.commit()?, state_root(&expected)?);
+    assert_eq!(get_account(&state, &[1; 20])?.balance, U256::from(907u32));
+
+    state.source.flatten(0);
+    assert!(matches!(
+        state.source.revert(&root_1),
+        Err(EthereumException::StateUnavailable(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_snapshot_disagreeing_with_trie() -> Result<(), EthereumException> {
+    let mut state = snapshot()?;
+    let genesis_root = state.source.disk_root();
+    block_1(&mut state)?;
+    state.commit()?;
+
+    // Move the tries back behind the snapshot's back, so that the diff
+    // layer of the first block no longer matches them.
+    state.source.trie.root = genesis_root;
+    block_2(&mut state)?;
+    assert!(matches!(state.commit(), Err(EthereumException::InvalidSnapshot(_))));
+    assert_eq!(state.source.layers().len(), 1);
+    Ok(())
+}
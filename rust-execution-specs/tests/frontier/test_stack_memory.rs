@@ -0,0 +1,65 @@
+use execution_specs_rs::ethereum::{
+    base_types::{Uint, U256},
+    frontier::vm::{
+        exceptions::EvmError,
+        gas::calculate_gas_extend_memory,
+        memory::{memory_read_bytes, memory_write, Memory},
+        stack::{Stack, STACK_LIMIT},
+    },
+};
+
+#[test]
+fn test_stack_operations() {
+    let mut stack = Stack::new();
+    assert!(matches!(stack.pop(), Err(EvmError::StackUnderflow)));
+    assert!(matches!(stack.dup(0), Err(EvmError::StackUnderflow)));
+
+    for value in 1u8..=3 {
+        stack.push(U256::from(value)).unwrap();
+    }
+    assert_eq!(*stack.peek(0).unwrap(), U256::from(3u8));
+    assert_eq!(*stack.peek(2).unwrap(), U256::from(1u8));
+    assert!(matches!(stack.peek(3), Err(EvmError::StackUnderflow)));
+
+    stack.dup(2).unwrap();
+    assert_eq!(&stack[..], [1u8, 2, 3, 1].map(U256::from));
+    stack.swap(2).unwrap();
+    assert_eq!(&stack[..], [1u8, 1, 3, 2].map(U256::from));
+    assert!(matches!(stack.swap(4), Err(EvmError::StackUnderflow)));
+    assert_eq!(stack.pop().unwrap(), U256::from(2u8));
+    assert_eq!(stack.len(), 3);
+}
+
+#[test]
+fn test_stack_overflow() {
+    let mut stack = Stack::new();
+    for _ in 0..STACK_LIMIT {
+        stack.push(U256::ONE).unwrap();
+    }
+    assert!(matches!(stack.push(U256::ONE), Err(EvmError::StackOverflow)));
+    assert!(matches!(stack.dup(0), Err(EvmError::StackOverflow)));
+    assert_eq!(stack.len(), STACK_LIMIT);
+}
+
+#[test]
+fn test_memory_expansion() {
+    let mut memory = Memory::new();
+    let extend_memory = calculate_gas_extend_memory(&memory, vec![(U256::from(30u8), U256::from(4u8))]);
+    assert_eq!(extend_memory.expand_by, Uint::from(64u8));
+    assert_eq!(extend_memory.cost, Uint::from(6u8));
+    memory.expand(&extend_memory.expand_by);
+    assert_eq!(memory.len(), 64);
+
+    memory_write(&mut memory, U256::from(30u8), Box::new([1, 2, 3, 4]));
+    assert_eq!(memory_read_bytes(&memory, U256::from(29u8), U256::from(6u8)), [0, 1, 2, 3, 4, 0]);
+
+    // Accesses within the memory, or of no bytes at all, are free.
+    let extend_memory = memory.extend_cost(&[(U256::from(32u8), U256::from(32u8)), (U256::MAX, U256::ZERO)]);
+    assert_eq!(extend_memory.expand_by, Uint::from(0u8));
+    assert_eq!(extend_memory.cost, Uint::from(0u8));
+
+    // Only the expansion beyond the current size is charged.
+    let extend_memory = calculate_gas_extend_memory(&memory, vec![(U256::from(1000u16), U256::from(1u8))]);
+    assert_eq!(extend_memory.expand_by, Uint::from(1024u16 - 64));
+    assert_eq!(extend_memory.cost, Uint::from(32u8 * 3 + 2 - 6));
+}
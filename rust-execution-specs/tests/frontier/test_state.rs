@@ -0,0 +1,135 @@
+use execution_specs_rs::ethereum::{
+    base_types::{Bytes, U256},
+    exceptions::EthereumException,
+    frontier::{
+        fork_types::keccak256,
+        state::{
+            begin_transaction, commit_transaction, destroy_account, get_account_optional,
+            get_code_hash, get_storage, rollback_transaction, set_account, set_code, set_storage,
+            state_root, State,
+        },
+    },
+};
+
+use super::helpers::{account, state_with};
+
+fn sample_state() -> State {
+    state_with(
+        &[([1; 20], account(0, 1000, b"")), ([2; 20], account(0, 1000, b""))],
+        &[([2; 20], [1; 32], 1)],
+    )
+}
+
+#[test]
+fn test_rollback_transaction() -> Result<(), EthereumException> {
+    let mut state = sample_state();
+    let before = state_root(&state)?;
+
+    begin_transaction(&mut state);
+    set_account(&mut state, [1; 20], Some(account(1, 1000, b"")));
+    set_account(&mut state, [3; 20], Some(account(0, 1000, b"")));
+    set_storage(&mut state, [2; 20], &[1; 32], U256::from(2u8));
+    set_storage(&mut state, [2; 20], &[2; 32], U256::from(3u8));
+    set_storage(&mut state, [3; 20], &[1; 32], U256::from(4u8));
+    destroy_account(&mut state, &[2; 20]);
+    rollback_transaction(&mut state);
+
+    assert_eq!(get_account_optional(&state, &[1; 20])?, Some(account(0, 1000, b"")));
+    assert_eq!(get_account_optional(&state, &[3; 20])?, None);
+    assert_eq!(get_storage(&state, &[2; 20], &[1; 32])?, U256::from(1u8));
+    assert_eq!(get_storage(&state, &[2; 20], &[2; 32])?, U256::from(0u8));
+    assert_eq!(state_root(&state)?, before);
+    Ok(())
+}
+
+#[test]
+fn test_nested_transactions() -> Result<(), EthereumException> {
+    let mut state = sample_state();
+
+    begin_transaction(&mut state);
+    set_account(&mut state, [1; 20], Some(account(1, 1000, b"")));
+
+    begin_transaction(&mut state);
+    set_account(&mut state, [1; 20], Some(account(2, 1000, b"")));
+    set_storage(&mut state, [2; 20], &[1; 32], U256::from(2u8));
+    rollback_transaction(&mut state);
+
+    begin_transaction(&mut state);
+    set_storage(&mut state, [2; 20], &[2; 32], U256::from(3u8));
+    commit_transaction(&mut state);
+
+    assert_eq!(get_account_optional(&state, &[1; 20])?, Some(account(1, 1000, b"")));
+    assert_eq!(get_storage(&state, &[2; 20], &[1; 32])?, U256::from(1u8));
+    assert_eq!(get_storage(&state, &[2; 20], &[2; 32])?, U256::from(3u8));
+
+    rollback_transaction(&mut state);
+    assert_eq!(get_account_optional(&state, &[1; 20])?, Some(account(0, 1000, b"")));
+    assert_eq!(get_storage(&state, &[2; 20], &[2; 32])?, U256::from(0u8));
+    assert_eq!(state_root(&state)?, state_root(&sample_state())?);
+    Ok(())
+}
+
+#[test]
+fn test_commit_transaction() -> Result<(), EthereumException> {
+    let mut state = sample_state();
+
+    begin_transaction(&mut state);
+    destroy_account(&mut state, &[2; 20]);
+    commit_transaction(&mut state);
+
+    assert_eq!(get_account_optional(&state, &[2; 20])?, None);
+    assert_eq!(get_storage(&state, &[2; 20], &[1; 32])?, U256::from(0u8));
+
+    let mut expected = State::default();
+    set_account(&mut expected, [1; 20], Some(account(0, 1000, b"")));
+    assert_eq!(state_root(&state)?, state_root(&expected)?);
+    Ok(())
+}
+
+#[test]
+fn test_rollback_block() -> Result<(), EthereumException> {
+    let mut state = sample_state();
+    let before = state_root(&state)?;
+
+    // Committed transactions, the state root, and a transaction left open
+    // by a failure are all undone.
+    state.begin_block();
+    begin_transaction(&mut state);
+    set_account(&mut state, [1; 20], Some(account(1, 1000, b"")));
+    commit_transaction(&mut state);
+    set_storage(&mut state, [2; 20], &[1; 32], U256::from(2u8));
+    assert_ne!(state_root(&state)?, before);
+    begin_transaction(&mut state);
+    destroy_account(&mut state, &[2; 20]);
+    state.rollback_block();
+
+    assert_eq!(get_account_optional(&state, &[1; 20])?, Some(account(0, 1000, b"")));
+    assert_eq!(get_storage(&state, &[2; 20], &[1; 32])?, U256::from(1u8));
+    assert_eq!(state_root(&state)?, before);
+
+    state.begin_block();
+    set_account(&mut state, [1; 20], Some(account(1, 1000, b"")));
+    state.commit_block();
+    assert_eq!(get_account_optional(&state, &[1; 20])?, Some(account(1, 1000, b"")));
+    Ok(())
+}
+
+#[test]
+fn test_code_hash_follows_code() -> Result<(), EthereumException> {
+    let mut state = sample_state();
+    assert_eq!(get_code_hash(&state, &[1; 20])?, keccak256(&[]));
+    assert_eq!(get_code_hash(&state, &[9; 20])?, keccak256(&[]));
+
+    set_code(&mut state, [1; 20], Bytes::from(&[0x00][..]))?;
+    assert_eq!(get_code_hash(&state, &[1; 20])?, keccak256(&[0x00]));
+
+    begin_transaction(&mut state);
+    set_code(&mut state, [1; 20], Bytes::from(&[0x01][..]))?;
+    assert_eq!(get_code_hash(&state, &[1; 20])?, keccak256(&[0x01]));
+    rollback_transaction(&mut state);
+    assert_eq!(get_code_hash(&state, &[1; 20])?, keccak256(&[0x00]));
+
+    destroy_account(&mut state, &[1; 20]);
+    assert_eq!(get_code_hash(&state, &[1; 20])?, keccak256(&[]));
+    Ok(())
+}
@@ -0,0 +1,139 @@
+use std::cell::Cell;
+
+use execution_specs_rs::ethereum::{
+    base_types::{Bytes32, U256},
+    exceptions::EthereumException,
+    frontier::{
+        fork_types::{Account, Address},
+        state::{
+            begin_transaction, destroy_account, get_account, get_account_optional, get_storage,
+            move_ether, rollback_transaction, set_account, set_storage, state_root, State,
+            StateBackend,
+        },
+        state_backends::{CachedSource, CachedState, NodeDb, NodeDbState, StateSource},
+    },
+};
+
+use super::helpers::account;
+
+/// Applies the same changes to any backend.
+fn populate<S: StateBackend>(state: &mut S) {
+    set_account(state, [1; 20], Some(account(1, 1000, b"")));
+    set_account(state, [2; 20], Some(account(1, 0, &[0x60, 0x00, 0x54])));
+    set_account(state, [3; 20], Some(account(1, 5, b"")));
+    for i in 0..20u8 {
+        set_storage(state, [2; 20], &[i; 32], U256::from(i as u32 + 1));
+    }
+    set_storage(state, [3; 20], &[1; 32], U256::from(7u8));
+}
+
+/// Applies further changes to any backend.
+fn modify<S: StateBackend>(state: &mut S) -> Result<(), EthereumException> {
+    move_ether(state, [1; 20], [4; 20], U256::from(10u8))?;
+    set_storage(state, [2; 20], &[3; 32], U256::from(0u8));
+    set_storage(state, [2; 20], &[100; 32], U256::from(100u8));
+    destroy_account(state, &[3; 20]);
+
+    begin_transaction(state);
+    set_storage(state, [2; 20], &[4; 32], U256::from(9u8));
+    destroy_account(state, &[1; 20]);
+    rollback_transaction(state);
+    Ok(())
+}
+
+#[test]
+fn test_node_db_state() -> Result<(), EthereumException> {
+    let mut expected = State::default();
+    populate(&mut expected);
+
+    let mut state = NodeDbState::new(NodeDb::default());
+    populate(&mut state);
+    assert_eq!(state_root(&state)?, state_root(&expected)?);
+    let root = state.commit()?;
+    assert_eq!(root, state_root(&expected)?);
+
+    // Reads are now served from the stored nodes.
+    let mut state = NodeDbState::new(state.source);
+    assert_eq!(get_account(&state, &[2; 20])?, account(1, 0, &[0x60, 0x00, 0x54]));
+    assert_eq!(get_storage(&state, &[2; 20], &[5; 32])?, U256::from(6u8));
+    assert_eq!(get_storage(&state, &[2; 20], &[200; 32])?, U256::from(0u8));
+    assert_eq!(get_account_optional(&state, &[9; 20])?, None);
+
+    modify(&mut expected)?;
+    modify(&mut state)?;
+    assert_eq!(state_root(&state)?, state_root(&expected)?);
+    assert_eq!(get_storage(&state, &[3; 20], &[1; 32])?, U256::from(0u8));
+    assert_eq!(get_storage(&state, &[2; 20], &[4; 32])?, U256::from(5u8));
+
+    let root = state.commit()?;
+    assert_eq!(root, state_root(&expected)?);
+    assert!(state.changes().accounts.is_empty());
+    assert_eq!(get_account(&state, &[4; 20])?.balance, U256::from(10u8));
+    Ok(())
+}
+
+#[test]
+fn test_node_db_missing_node() {
+    let mut state = NodeDbState::new(NodeDb::default());
+    populate(&mut state);
+    state.commit().unwrap();
+
+    let mut db = state.source;
+    db.nodes.clear();
+    let state = NodeDbState::new(db);
+    assert!(matches!(
+        get_account(&state, &[1; 20]),
+        Err(EthereumException::InvalidProof(_))
+    ));
+}
+
+/// Counts requests made to a `State` standing in for a remote node.
+struct CountingSource {
+    state: State,
+    requests: Cell<usize>,
+}
+
+impl StateSource for CountingSource {
+    fn account(&self, address: &Address) -> Result<Option<Account>, EthereumException> {
+        self.requests.set(self.requests.get() + 1);
+        self.state.account(address)
+    }
+
+    fn storage(&self, address: &Address, key: &Bytes32) -> Result<U256, EthereumException> {
+        self.requests.set(self.requests.get() + 1);
+        self.state.storage(address, key)
+    }
+}
+
+#[test]
+fn test_cached_state() -> Result<(), EthereumException> {
+    let mut remote = State::default();
+    populate(&mut remote);
+    let source = CountingSource {
+        state: remote,
+        requests: Cell::new(0),
+    };
+
+    let mut state = CachedState::new(CachedSource::new(source));
+    for _ in 0..3 {
+        assert_eq!(get_account(&state, &[1; 20])?.balance, U256::from(1000u32));
+        assert_eq!(get_storage(&state, &[2; 20], &[1; 32])?, U256::from(2u8));
+        assert_eq!(get_account_optional(&state, &[9; 20])?, None);
+    }
+    assert_eq!(state.source.inner.requests.get(), 3);
+
+    modify(&mut state)?;
+    assert_eq!(get_account(&state, &[4; 20])?.balance, U256::from(10u8));
+    assert_eq!(get_account(&state, &[1; 20])?.balance, U256::from(990u32));
+    assert_eq!(get_storage(&state, &[2; 20], &[4; 32])?, U256::from(5u8));
+    assert_eq!(
+        get_account(&state.source.inner.state, &[1; 20])?.balance,
+        U256::from(1000u32)
+    );
+
+    assert!(matches!(
+        state_root(&state),
+        Err(EthereumException::StateUnavailable(_))
+    ));
+    Ok(())
+}
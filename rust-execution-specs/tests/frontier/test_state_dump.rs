@@ -0,0 +1,116 @@
+use execution_specs_rs::ethereum::{
+    base_types::{Bytes, U256, Uint},
+    exceptions::EthereumException,
+    frontier::{
+        state::{get_account, get_storage, state_root, State},
+        state_dump::{dump_state, load_state},
+    },
+    utils::hexadecimal::{hex, hex_to_bytes20},
+};
+use serde_json::json;
+
+use super::helpers::{account, state_with};
+
+fn sample_state() -> State {
+    state_with(
+        &[
+            ([1; 20], account(0, 10u128.pow(20), b"")),
+            ([2; 20], account(1, 0, &[0x60, 0x01, 0x60, 0x00, 0x55])),
+        ],
+        &[([2; 20], [0; 32], 1), ([2; 20], [7; 32], 0x1234)],
+    )
+}
+
+#[test]
+fn test_dump_state() -> Result<(), EthereumException> {
+    let state = sample_state();
+    let dump = dump_state(&state)?;
+
+    assert_eq!(dump["root"], json!(hex(&state_root(&state)?)));
+    let plain = &dump["accounts"][hex(&[1; 20])];
+    assert_eq!(plain["balance"], json!("100000000000000000000"));
+    assert_eq!(plain["nonce"], json!(0));
+    assert_eq!(
+        plain["root"],
+        json!("0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421")
+    );
+    assert_eq!(
+        plain["codeHash"],
+        json!("0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470")
+    );
+    assert!(plain.get("code").is_none());
+    assert!(plain.get("storage").is_none());
+
+    let contract = &dump["accounts"][hex(&[2; 20])];
+    assert_eq!(contract["code"], json!("0x6001600055"));
+    assert_eq!(contract["storage"][hex(&[0; 32])], json!("01"));
+    assert_eq!(contract["storage"][hex(&[7; 32])], json!("1234"));
+    Ok(())
+}
+
+#[test]
+fn test_dump_and_load_round_trip() -> Result<(), EthereumException> {
+    let state = sample_state();
+    let dump = dump_state(&state)?;
+    let text = serde_json::to_string_pretty(&dump).unwrap();
+
+    let loaded = load_state(&serde_json::from_str(&text).unwrap())?;
+    assert_eq!(state_root(&loaded)?, state_root(&state)?);
+    assert_eq!(dump_state(&loaded)?, dump);
+    Ok(())
+}
+
+#[test]
+fn test_load_geth_dump() -> Result<(), EthereumException> {
+    let dump = json!({
+        "root": "0x0000000000000000000000000000000000000000000000000000000000000000",
+        "accounts": {
+            "0x000000000000000000000000000000000000dead": {
+                "balance": "0x10",
+                "nonce": 3,
+                "root": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+                "codeHash": "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+                "key": "0x0000000000000000000000000000000000000000000000000000000000000000"
+            },
+            "pre(0x00)": {
+                "balance": "12",
+                "nonce": 0,
+                "root": "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421",
+                "codeHash": "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+                "code": "0x00",
+                "storage": {
+                    "0x0000000000000000000000000000000000000000000000000000000000000001": "ff",
+                    "0x0000000000000000000000000000000000000000000000000000000000000002": "0x0100"
+                },
+                "address": "0x00000000000000000000000000000000000000aa"
+            }
+        }
+    });
+
+    let state = load_state(&dump)?;
+    let dead = hex_to_bytes20("0x000000000000000000000000000000000000dead")?;
+    let aa = hex_to_bytes20("0x00000000000000000000000000000000000000aa")?;
+
+    let account = get_account(&state, &dead)?;
+    assert_eq!(account.balance, U256::from(16u8));
+    assert_eq!(account.nonce, Uint::from(3u8));
+
+    let account = get_account(&state, &aa)?;
+    assert_eq!(account.balance, U256::from(12u8));
+    assert_eq!(account.code, Bytes::from(&[0u8][..]));
+    let mut slot = [0; 32];
+    slot[31] = 1;
+    assert_eq!(get_storage(&state, &aa, &slot)?, U256::from(0xffu8));
+    slot[31] = 2;
+    assert_eq!(get_storage(&state, &aa, &slot)?, U256::from(0x100u32));
+    Ok(())
+}
+
+#[test]
+fn test_load_malformed_dump() {
+    assert!(matches!(
+        load_state(&json!({ "root": "0x" })),
+        Err(EthereumException::JsonDecodeError(_))
+    ));
+    assert!(load_state(&json!({ "accounts": { "0x01": { "balance": 1 } } })).is_err());
+}
@@ -0,0 +1,80 @@
+use execution_specs_rs::ethereum::{
+    base_types::{Bytes, U256, Uint},
+    exceptions::EthereumException,
+    frontier::{
+        state::{get_account, get_storage, state_root, State},
+        state_overrides::{override_state, parse_state_overrides},
+    },
+};
+use serde_json::json;
+
+use super::helpers::{account, state_with};
+
+fn sample_state() -> State {
+    state_with(&[([1; 20], account(1, 1000, b""))], &[([1; 20], [1; 32], 1), ([1; 20], [2; 32], 2)])
+}
+
+#[test]
+fn test_state_overrides() -> Result<(), EthereumException> {
+    let state = sample_state();
+    let root = state_root(&state)?;
+    let one = format!("0x{}", "01".repeat(20));
+    let two = format!("0x{}", "02".repeat(20));
+    let slot = |byte: &str| format!("0x{}", byte.repeat(32));
+
+    let overrides = parse_state_overrides(&json!({
+        one: {
+            "balance": "0xff",
+            "stateDiff": { slot("02"): "0x22", slot("03"): "0x33" },
+        },
+        two: {
+            "nonce": "0x5",
+            "code": "0x6000",
+            "state": { slot("01"): "0x11" },
+        },
+    }))?;
+    let overridden = override_state(&state, &overrides)?;
+
+    let account = get_account(&overridden, &[1; 20])?;
+    assert_eq!(account.balance, U256::from(0xffu8));
+    assert_eq!(account.nonce, Uint::from(1u8));
+    assert_eq!(get_storage(&overridden, &[1; 20], &[1; 32])?, U256::from(1u8));
+    assert_eq!(get_storage(&overridden, &[1; 20], &[2; 32])?, U256::from(0x22u8));
+    assert_eq!(get_storage(&overridden, &[1; 20], &[3; 32])?, U256::from(0x33u8));
+
+    let account = get_account(&overridden, &[2; 20])?;
+    assert_eq!(account.nonce, Uint::from(5u8));
+    assert_eq!(account.code, Bytes::from(&[0x60, 0x00][..]));
+    assert_eq!(get_storage(&overridden, &[2; 20], &[1; 32])?, U256::from(0x11u8));
+
+    // The canonical state is left alone.
+    assert_eq!(get_account(&state, &[1; 20])?.balance, U256::from(1000u32));
+    assert_eq!(get_storage(&state, &[1; 20], &[2; 32])?, U256::from(2u8));
+    assert_eq!(state_root(&state)?, root);
+    Ok(())
+}
+
+#[test]
+fn test_state_override_replaces_storage() -> Result<(), EthereumException> {
+    let state = sample_state();
+    let overrides = parse_state_overrides(&json!({
+        format!("0x{}", "01".repeat(20)): {
+            "state": { format!("0x{}", "03".repeat(32)): "0x3" },
+        },
+    }))?;
+    let overridden = override_state(&state, &overrides)?;
+
+    assert_eq!(get_storage(&overridden, &[1; 20], &[1; 32])?, U256::from(0u8));
+    assert_eq!(get_storage(&overridden, &[1; 20], &[2; 32])?, U256::from(0u8));
+    assert_eq!(get_storage(&overridden, &[1; 20], &[3; 32])?, U256::from(3u8));
+    assert_eq!(get_account(&overridden, &[1; 20])?.balance, U256::from(1000u32));
+    Ok(())
+}
+
+#[test]
+fn test_malformed_state_overrides() {
+    let address = format!("0x{}", "01".repeat(20));
+    assert!(parse_state_overrides(&json!({ address.clone(): { "state": {}, "stateDiff": { "0x01": "0x1" } } })).is_err());
+    assert!(parse_state_overrides(&json!({ address.clone(): { "balanse": "0x1" } })).is_err());
+    assert!(parse_state_overrides(&json!({ address: { "balance": 1 } })).is_err());
+}
@@ -0,0 +1,45 @@
+use execution_specs_rs::ethereum::{
+    exceptions::EthereumException,
+    frontier::{
+        fork::{state_transition, BLOCK_REWARD},
+        state::{get_account, state_root},
+    },
+};
+
+use super::helpers::{mainnet_block, mainnet_chain};
+
+#[test]
+fn test_mainnet_block_1() {
+    let mut chain = mainnet_chain();
+    let block = mainnet_block("block_1.json");
+    let coinbase = block.header.coinbase;
+
+    state_transition(&mut chain, block).unwrap();
+
+    assert_eq!(chain.blocks.len(), 2);
+    assert_eq!(state_root(&chain.state).unwrap(), chain.blocks[1].header.state_root);
+    assert_eq!(get_account(&chain.state, &coinbase).unwrap().balance, BLOCK_REWARD());
+}
+
+#[test]
+fn test_block_with_wrong_state_root_is_invalid() {
+    let mut chain = mainnet_chain();
+    let mut block = mainnet_block("block_1.json");
+    block.header.state_root = [0; 32];
+
+    let result = state_transition(&mut chain, block);
+
+    assert!(matches!(result, Err(EthereumException::InvalidBlock)));
+    assert_eq!(chain.blocks.len(), 1);
+}
+
+#[test]
+fn test_block_with_wrong_difficulty_is_invalid() {
+    let mut chain = mainnet_chain();
+    let mut block = mainnet_block("block_1.json");
+    block.header.difficulty += 1u8;
+
+    let result = state_transition(&mut chain, block);
+
+    assert!(matches!(result, Err(EthereumException::InvalidBlock)));
+}
@@ -0,0 +1,170 @@
+use std::{cell::RefCell, io::Write, rc::Rc};
+
+use execution_specs_rs::ethereum::{
+    base_types::Uint,
+    frontier::{
+        state::State,
+        state_backends::{NodeDb, NodeDbState},
+        vm::{
+            exceptions::EvmError,
+            interpreter::{execute_code, process_message_call},
+            trace::Eip3155Tracer,
+        },
+    },
+};
+use serde_json::{json, Value};
+
+use super::helpers::{address, contract, create_message, environment, message, TARGET};
+
+#[derive(Clone, Default)]
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn lines(output: &SharedBuffer) -> Vec<Value> {
+    String::from_utf8(output.0.borrow().clone())
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_trace_return() -> Result<(), EvmError> {
+    let output = SharedBuffer::default();
+    let mut env = environment(State::default(), Eip3155Tracer::new(output.clone()));
+
+    // PUSH1 1 PUSH1 2 ADD PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+    let code = [0x60, 0x01, 0x60, 0x02, 0x01, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3];
+    let result = process_message_call(message(&code, 10_000), &mut env)?;
+    assert!(result.error.is_none());
+    assert_eq!(result.gas_left, Uint::from(10_000u32 - 24));
+
+    let lines = lines(&output);
+    assert_eq!(lines.len(), 9);
+    assert_eq!(
+        lines[0],
+        json!({
+            "pc": 0, "op": 0x60, "gas": "0x2710", "gasCost": "0x3", "memSize": 0,
+            "stack": [], "depth": 1, "refund": 0, "opName": "PUSH1",
+        })
+    );
+    assert_eq!(lines[4]["opName"], json!("MSTORE"));
+    assert_eq!(lines[4]["gasCost"], json!("0x6"));
+    assert_eq!(lines[4]["stack"], json!(["0x3", "0x0"]));
+    assert_eq!(lines[7]["opName"], json!("RETURN"));
+    assert_eq!(lines[7]["memSize"], json!(32));
+    assert_eq!(lines[7]["gas"], json!("0x26f8"));
+
+    let mut returned = format!("0x{}", "00".repeat(31));
+    returned.push_str("03");
+    assert_eq!(lines[8], json!({ "output": returned, "gasUsed": "0x18" }));
+    Ok(())
+}
+
+#[test]
+fn test_trace_exceptional_halt() -> Result<(), EvmError> {
+    let output = SharedBuffer::default();
+    let mut env = environment(State::default(), Eip3155Tracer::new(output.clone()));
+
+    // PUSH1 1 PUSH1 0 SUB INVALID
+    let code = [0x60, 0x01, 0x60, 0x00, 0x03, 0xfe];
+    let result = process_message_call(message(&code, 100), &mut env)?;
+    assert!(matches!(result.error, Some(EvmError::InvalidOpcode)));
+    assert_eq!(result.gas_left, Uint::from(0u8));
+
+    let lines = lines(&output);
+    assert_eq!(lines.len(), 5);
+    assert_eq!(lines[3]["opName"], json!("INVALID"));
+    assert_eq!(lines[3]["stack"], json!([format!("0x{}", "f".repeat(64))]));
+    assert_eq!(lines[3]["error"], json!("InvalidOpcode"));
+    assert_eq!(
+        lines[4],
+        json!({ "output": "0x", "gasUsed": "0x64", "error": "InvalidOpcode" })
+    );
+    Ok(())
+}
+
+#[test]
+fn test_trace_nested_call() -> Result<(), EvmError> {
+    let output = SharedBuffer::default();
+    let mut state = State::default();
+    // PUSH1 7
+    contract(&mut state, address(0xbb), 0, &[0x60, 0x07]);
+    let mut env = environment(state, Eip3155Tracer::new(output.clone()));
+
+    // CALL(gas=100, to=0xbb, value=0, no input, no output) STOP
+    let code = [
+        0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0xbb, 0x60, 0x64, 0xf1,
+        0x00,
+    ];
+    process_message_call(message(&code, 10_000), &mut env)?;
+
+    let lines = lines(&output);
+    let depths = lines.iter().map(|line| line["depth"].clone()).collect::<Vec<_>>();
+    assert_eq!(depths[7..], [json!(1), json!(2), json!(2), json!(1), Value::Null]);
+
+    let call = &lines[7];
+    assert_eq!(call["opName"], json!("CALL"));
+    assert_eq!(call["gasCost"], json!("0x8c"));
+    assert_eq!(lines[8]["gas"], json!("0x64"));
+    assert_eq!(lines[9]["opName"], json!("STOP"));
+    assert_eq!(lines[9]["pc"], json!(2));
+    assert_eq!(lines[10]["stack"], json!(["0x1"]));
+    Ok(())
+}
+
+#[test]
+fn test_trace_streams_lines() -> Result<(), EvmError> {
+    let output = SharedBuffer::default();
+    let mut env = environment(State::default(), Eip3155Tracer::new(output.clone()));
+
+    // PUSH1 1 PUSH1 2, traced step by step.
+    let code = [0x60, 0x01, 0x60, 0x02];
+    let mut message = message(&code, 100);
+    message.depth = Uint::from(1u8);
+    let evm = execute_code(message, &mut env)?;
+    assert!(evm.error.is_none());
+
+    // Both lines are written before the summary, which only comes with the
+    // end of the whole message call.
+    assert_eq!(lines(&output).len(), 3);
+    assert!(lines(&output).iter().all(|line| line.get("gasUsed").is_none()));
+    Ok(())
+}
+
+#[test]
+fn test_trace_summary_on_collision() -> Result<(), EvmError> {
+    let output = SharedBuffer::default();
+    let mut state = State::default();
+    contract(&mut state, TARGET, 0, &[0x00]);
+    let mut env = environment(state, Eip3155Tracer::new(output.clone()));
+
+    let result = process_message_call(create_message(&[0x00], 100), &mut env)?;
+    assert!(matches!(result.error, Some(EvmError::Halt)));
+    assert_eq!(lines(&output), [json!({ "output": "0x", "gasUsed": "0x64", "error": "Halt" })]);
+    Ok(())
+}
+
+#[test]
+fn test_trace_summary_on_state_error() {
+    let output = SharedBuffer::default();
+    // A state whose root is not in the node database can not be read.
+    let state = NodeDbState::new(NodeDb { root: [1; 32], ..NodeDb::default() });
+    let mut env = environment(state, Eip3155Tracer::new(output.clone()));
+
+    let result = process_message_call(message(&[0x00], 100), &mut env);
+    assert!(matches!(result, Err(EvmError::State(_))));
+    let lines = lines(&output);
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0]["gasUsed"], json!("0x64"));
+    assert!(lines[0]["error"].as_str().unwrap().starts_with("State("));
+}
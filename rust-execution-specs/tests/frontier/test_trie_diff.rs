@@ -0,0 +1,174 @@
+use execution_specs_rs::ethereum::{
+    base_types::{Bytes, U256},
+    exceptions::EthereumException,
+    frontier::{
+        fork_types::keccak256,
+        state::{begin_transaction, commit_transaction, diff_state},
+        trie::{dummy_root, trie_set, Trie},
+        trie_diff::{diff_roots, diff_tries, store_nodes, Change, NodeStore},
+    },
+};
+
+use super::helpers::{account, state_with};
+
+fn bytes(data: &[u8]) -> Bytes {
+    Bytes::from(data)
+}
+
+fn sample_trie(secured: bool) -> Trie<Bytes, Bytes> {
+    let mut trie = Trie::new(secured, Bytes::default());
+    for i in 0..64u8 {
+        trie_set(&mut trie, bytes(&keccak256(&[i])), bytes(&[i; 40]));
+    }
+    trie
+}
+
+#[test]
+fn test_diff_identical_tries() -> Result<(), EthereumException> {
+    let trie = sample_trie(true);
+    assert!(diff_tries(&trie, &trie.clone(), dummy_root, dummy_root)?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_diff_tries() -> Result<(), EthereumException> {
+    for secured in [false, true] {
+        let old = sample_trie(secured);
+        let mut new = old.clone();
+        let changed = bytes(&keccak256(&[3]));
+        let removed = bytes(&keccak256(&[7]));
+        let added = bytes(b"a short key");
+        trie_set(&mut new, changed.clone(), bytes(b"changed"));
+        trie_set(&mut new, removed.clone(), Bytes::default());
+        trie_set(&mut new, added.clone(), bytes(b"added"));
+
+        let mut changes = diff_tries(&old, &new, dummy_root, dummy_root)?;
+        changes.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut expected = vec![
+            (
+                changed,
+                Change::Changed {
+                    old: bytes(&[3; 40]),
+                    new: bytes(b"changed"),
+                },
+            ),
+            (removed, Change::Removed(bytes(&[7; 40]))),
+            (added, Change::Added(bytes(b"added"))),
+        ];
+        expected.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(changes, expected);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_diff_against_empty_trie() -> Result<(), EthereumException> {
+    let empty = Trie::new(false, Bytes::default());
+    let mut trie = empty.clone();
+    trie_set(&mut trie, bytes(b"do"), bytes(b"verb"));
+    trie_set(&mut trie, bytes(b"dog"), bytes(b"puppy"));
+
+    let changes = diff_tries(&empty, &trie, dummy_root, dummy_root)?;
+    assert_eq!(
+        changes,
+        vec![
+            (bytes(b"do"), Change::Added(bytes(b"verb"))),
+            (bytes(b"dog"), Change::Added(bytes(b"puppy"))),
+        ]
+    );
+    let changes = diff_tries(&trie, &empty, dummy_root, dummy_root)?;
+    assert_eq!(changes.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_diff_roots_skips_identical_subtrees() -> Result<(), EthereumException> {
+    let old = sample_trie(false);
+    let mut new = old.clone();
+    trie_set(&mut new, bytes(&keccak256(&[9])), bytes(b"changed"));
+
+    let mut old_nodes = NodeStore::new();
+    let mut new_nodes = NodeStore::new();
+    let old_root = store_nodes(&old, dummy_root, &mut old_nodes)?;
+    let new_root = store_nodes(&new, dummy_root, &mut new_nodes)?;
+
+    // Only the nodes on the path to the changed key are needed.
+    let store = old_nodes
+        .iter()
+        .filter(|(hash, _)| !new_nodes.contains_key(*hash))
+        .chain(new_nodes.iter().filter(|(hash, _)| !old_nodes.contains_key(*hash)))
+        .map(|(hash, node)| (*hash, node.clone()))
+        .collect::<NodeStore>();
+    assert!(store.len() < old_nodes.len());
+
+    let changes = diff_roots(&store, &old_root, &new_root)?;
+    assert_eq!(
+        changes,
+        vec![(
+            bytes(&keccak256(&[9])),
+            Change::Changed {
+                old: bytes(&[9; 40]),
+                new: bytes(b"changed"),
+            },
+        )]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_diff_state() -> Result<(), EthereumException> {
+    let key = [1; 32];
+    let old = state_with(
+        &[
+            ([1; 20], account(0, 1000, b"")),
+            ([2; 20], account(0, 1000, b"")),
+            ([3; 20], account(0, 1000, b"")),
+        ],
+        &[([2; 20], key, 1)],
+    );
+    let mut new = state_with(
+        &[
+            ([1; 20], account(1, 1000, b"")),
+            ([2; 20], account(0, 1000, b"")),
+            ([4; 20], account(0, 1000, b"")),
+        ],
+        &[([2; 20], key, 2)],
+    );
+
+    let mut diff = diff_state(&old, &new)?;
+    diff.accounts.sort_by_key(|(address, _)| *address);
+    assert_eq!(
+        diff.accounts,
+        vec![
+            (
+                [1; 20],
+                Change::Changed {
+                    old: account(0, 1000, b""),
+                    new: account(1, 1000, b""),
+                },
+            ),
+            ([3; 20], Change::Removed(account(0, 1000, b""))),
+            ([4; 20], Change::Added(account(0, 1000, b""))),
+        ]
+    );
+    assert_eq!(
+        diff.storage,
+        vec![(
+            [2; 20],
+            vec![(
+                key,
+                Change::Changed {
+                    old: U256::from(1u8),
+                    new: U256::from(2u8),
+                },
+            )],
+        )]
+    );
+
+    // Storage roots are only computed between transactions.
+    begin_transaction(&mut new);
+    assert!(matches!(diff_state(&old, &new), Err(EthereumException::TransactionOngoing)));
+    commit_transaction(&mut new);
+    assert!(diff_state(&old, &new).is_ok());
+    Ok(())
+}
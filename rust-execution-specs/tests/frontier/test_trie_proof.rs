@@ -0,0 +1,167 @@
+use execution_specs_rs::ethereum::{
+    base_types::Bytes,
+    exceptions::EthereumException,
+    frontier::{
+        fork_types::keccak256,
+        trie::{dummy_root, root, trie_set, Trie},
+        trie_proof::{prove_range, verify_range},
+    },
+    utils::hexadecimal::{hex, hex_to_bytes},
+};
+
+fn bytes(data: &[u8]) -> Bytes {
+    Bytes::from(data)
+}
+
+fn sample_trie(secured: bool) -> Trie<Bytes, Bytes> {
+    let mut trie = Trie::new(secured, Bytes::default());
+    for i in 0..64u8 {
+        let key = keccak256(&[i]);
+        let value = [i; 40].repeat(1 + i as usize % 3);
+        trie_set(&mut trie, bytes(&key), bytes(&value));
+    }
+    trie
+}
+
+#[test]
+fn test_root_of_known_trie() {
+    let mut trie = Trie::new(false, Bytes::default());
+    for (key, value) in [
+        ("do", "verb"),
+        ("dog", "puppy"),
+        ("doge", "coin"),
+        ("horse", "stallion"),
+    ] {
+        trie_set(&mut trie, bytes(key.as_bytes()), bytes(value.as_bytes()));
+    }
+    assert_eq!(
+        hex(&root(&trie, dummy_root)),
+        "0x5991bb8c6514148a29db676a14ac506cd2cd5775ace63c30a4fe457715e9ac84"
+    );
+}
+
+#[test]
+fn test_prove_and_verify_range() -> Result<(), EthereumException> {
+    let trie = sample_trie(false);
+    let trie_root = root(&trie, dummy_root);
+
+    let mut sorted = trie.data.keys().cloned().collect::<Vec<_>>();
+    sorted.sort();
+
+    for (start, end) in [(0, 63), (0, 10), (5, 20), (40, 63), (17, 17)] {
+        let proof = prove_range(&trie, &sorted[start], &sorted[end], dummy_root)?;
+        assert_eq!(proof.keys, sorted[start..=end].to_vec());
+        let has_more = verify_range(
+            &trie_root,
+            &sorted[start],
+            &sorted[end],
+            &proof.keys,
+            &proof.values,
+            &proof.proof,
+        )?;
+        assert_eq!(has_more, end != 63);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_prove_range_with_inlined_nodes() -> Result<(), EthereumException> {
+    let mut trie = Trie::new(false, Bytes::default());
+    for i in 0..200u8 {
+        trie_set(&mut trie, bytes(&[i / 7, i]), bytes(&[i]));
+    }
+    let trie_root = root(&trie, dummy_root);
+
+    for (first, last) in [([0, 0], [3, 40]), ([2, 15], [2, 19]), ([9, 0], [9, 255]), ([20, 0], [40, 0])] {
+        let proof = prove_range(&trie, &first, &last, dummy_root)?;
+        let has_more = verify_range(&trie_root, &first, &last, &proof.keys, &proof.values, &proof.proof)?;
+        assert_eq!(has_more, last < [28, 199]);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_verify_range_between_absent_keys() -> Result<(), EthereumException> {
+    let trie = sample_trie(false);
+    let trie_root = root(&trie, dummy_root);
+
+    let first = hex_to_bytes("0x4000000000000000000000000000000000000000000000000000000000000000")?;
+    let last = hex_to_bytes("0x8fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff")?;
+    let proof = prove_range(&trie, &first, &last, dummy_root)?;
+    assert!(!proof.keys.is_empty());
+    assert!(verify_range(&trie_root, &first, &last, &proof.keys, &proof.values, &proof.proof)?);
+
+    // An empty range still needs the proof of both boundaries.
+    let first = hex_to_bytes("0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff00")?;
+    let last = hex_to_bytes("0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff")?;
+    let proof = prove_range(&trie, &first, &last, dummy_root)?;
+    assert!(proof.keys.is_empty());
+    assert!(!verify_range(&trie_root, &first, &last, &[], &[], &proof.proof)?);
+    Ok(())
+}
+
+#[test]
+fn test_verify_whole_trie_without_proof() -> Result<(), EthereumException> {
+    let trie = sample_trie(false);
+    let trie_root = root(&trie, dummy_root);
+
+    let first = bytes(&[0; 32]);
+    let last = bytes(&[0xff; 32]);
+    let proof = prove_range(&trie, &first, &last, dummy_root)?;
+    assert_eq!(proof.keys.len(), 64);
+    assert!(!verify_range(&trie_root, &first, &last, &proof.keys, &proof.values, &[])?);
+    assert!(verify_range(&trie_root, &first, &last, &proof.keys[1..], &proof.values[1..], &[]).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_verify_range_rejects_tampering() -> Result<(), EthereumException> {
+    let trie = sample_trie(true);
+    let trie_root = root(&trie, dummy_root);
+
+    let mut sorted = trie
+        .data
+        .keys()
+        .map(|key| bytes(&keccak256(key)))
+        .collect::<Vec<_>>();
+    sorted.sort();
+    let (first, last) = (&sorted[10], &sorted[30]);
+    let proof = prove_range(&trie, first, last, dummy_root)?;
+    assert_eq!(proof.keys.len(), 21);
+    verify_range(&trie_root, first, last, &proof.keys, &proof.values, &proof.proof)?;
+
+    // A key left out of the middle of the range.
+    let mut keys = proof.keys.clone();
+    let mut values = proof.values.clone();
+    keys.remove(7);
+    values.remove(7);
+    assert!(verify_range(&trie_root, first, last, &keys, &values, &proof.proof).is_err());
+
+    // A value changed.
+    let mut values = proof.values.clone();
+    values[3] = bytes(b"forged");
+    assert!(verify_range(&trie_root, first, last, &proof.keys, &values, &proof.proof).is_err());
+
+    // A boundary key dropped.
+    assert!(verify_range(
+        &trie_root,
+        first,
+        last,
+        &proof.keys[..20],
+        &proof.values[..20],
+        &proof.proof
+    )
+    .is_err());
+
+    // Missing proof nodes.
+    assert!(verify_range(
+        &trie_root,
+        first,
+        last,
+        &proof.keys,
+        &proof.values,
+        &proof.proof[1..]
+    )
+    .is_err());
+    Ok(())
+}
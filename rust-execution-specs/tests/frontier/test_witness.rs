@@ -0,0 +1,149 @@
+use execution_specs_rs::ethereum::{
+    base_types::{U256, U64},
+    exceptions::EthereumException,
+    frontier::{
+        state::{
+            destroy_account, get_account_optional, get_storage, move_ether, set_storage, state_root,
+            State, StateBackend,
+        },
+        state_backends::NodeDbState,
+        fork::state_transition,
+        witness::{build_witness, state_transition_with_witness, stateless_chain, Witness, WitnessRecorder},
+    },
+};
+
+use super::helpers::{account, mainnet_block, mainnet_chain, state_with};
+
+fn sample_state(count: u8) -> State {
+    let accounts: Vec<_> = (0..count)
+        .map(|i| ([i; 20], account(1, 100 + i as u128, &[0x60, 100 + i])))
+        .collect();
+    let storage: Vec<_> = (0..count)
+        .flat_map(|i| (0..i % 4).map(move |j| ([i; 20], [j; 32], j as u32 + 1)))
+        .collect();
+    state_with(&accounts, &storage)
+}
+
+/// The changes made by a "block".
+fn execute<S: StateBackend>(state: &mut S) -> Result<(), EthereumException> {
+    move_ether(state, [1; 20], [2; 20], U256::from(10u8))?;
+    move_ether(state, [3; 20], [200; 20], U256::from(5u8))?;
+    set_storage(state, [3; 20], &[9; 32], U256::from(9u8));
+    set_storage(state, [7; 20], &[1; 32], U256::from(0u8));
+    get_storage(state, &[10; 20], &[0; 32])?;
+    destroy_account(state, &[5; 20]);
+    Ok(())
+}
+
+#[test]
+fn test_stateless_execution() -> Result<(), EthereumException> {
+    let pre_state = sample_state(40);
+    let pre_root = state_root(&pre_state)?;
+
+    let mut recorder = WitnessRecorder::new(sample_state(40));
+    execute(&mut recorder)?;
+    let (post_state, touched) = recorder.into_parts();
+    assert_eq!(touched.len(), 7);
+
+    let witness = build_witness(&pre_state, &touched)?;
+    let mut stateless = NodeDbState::new(witness.into_node_db(pre_root));
+    execute(&mut stateless)?;
+    assert_eq!(state_root(&stateless)?, state_root(&post_state)?);
+
+    // Whatever else can be read from the witness is read correctly, the rest
+    // fails for lack of nodes or code.
+    let mut missing = 0;
+    for i in 0..40 {
+        match get_account_optional(&stateless, &[i; 20]) {
+            Ok(found) => assert_eq!(found, get_account_optional(&post_state, &[i; 20])?),
+            Err(EthereumException::InvalidProof(_) | EthereumException::StateUnavailable(_)) => {
+                missing += 1
+            }
+            Err(error) => return Err(error),
+        }
+    }
+    assert!(missing > 0);
+    Ok(())
+}
+
+#[test]
+fn test_stateless_deletion_folds_branch() -> Result<(), EthereumException> {
+    let pre_state = sample_state(2);
+    let mut recorder = WitnessRecorder::new(sample_state(2));
+    destroy_account(&mut recorder, &[0; 20]);
+    let (post_state, touched) = recorder.into_parts();
+
+    let witness = build_witness(&pre_state, &touched)?;
+    let mut stateless = NodeDbState::new(witness.into_node_db(state_root(&pre_state)?));
+    destroy_account(&mut stateless, &[0; 20]);
+    assert_eq!(state_root(&stateless)?, state_root(&post_state)?);
+    Ok(())
+}
+
+#[test]
+fn test_stateless_missing_node() -> Result<(), EthereumException> {
+    let pre_state = sample_state(40);
+    let mut recorder = WitnessRecorder::new(sample_state(40));
+    execute(&mut recorder)?;
+    let (_, touched) = recorder.into_parts();
+
+    let witness = build_witness(&pre_state, &touched)?;
+    let mut stateless = NodeDbState::new(witness.into_node_db(state_root(&pre_state)?));
+    execute(&mut stateless)?;
+    set_storage(&mut stateless, [30; 20], &[0; 32], U256::from(1u8));
+    assert!(matches!(
+        state_root(&stateless),
+        Err(EthereumException::InvalidProof(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_stateless_block() -> Result<(), EthereumException> {
+    let mut chain = mainnet_chain();
+    let parents = chain.blocks.clone();
+    let block = mainnet_block("block_1.json");
+
+    let witness = state_transition_with_witness(&mut chain, block.clone())?;
+    assert_eq!(state_root(&chain.state)?, block.header.state_root);
+
+    let mut stateless = stateless_chain(parents.clone(), U64::from(1u64), witness);
+    state_transition(&mut stateless, block.clone())?;
+    assert_eq!(stateless.state.state_root()?, block.header.state_root);
+    assert_eq!(stateless.blocks.len(), 2);
+
+    // Without the witness the coinbase can not be read.
+    let mut stateless = stateless_chain(parents, U64::from(1u64), Witness::default());
+    assert!(matches!(state_transition(&mut stateless, block.clone()), Err(EthereumException::InvalidProof(_))));
+
+    // A rejected block leaves the chain as it was.
+    let mut chain = mainnet_chain();
+    let root = state_root(&chain.state)?;
+    let mut invalid = block;
+    invalid.header.state_root = [0; 32];
+    assert!(matches!(state_transition_with_witness(&mut chain, invalid), Err(EthereumException::InvalidBlock)));
+    assert_eq!(state_root(&chain.state)?, root);
+    assert_eq!(chain.blocks.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_witness_over_the_state_before_the_block() -> Result<(), EthereumException> {
+    let pre_state = sample_state(40);
+    let mut state = pre_state.clone();
+    state.begin_block();
+    let mut recorder = WitnessRecorder::new(state);
+    execute(&mut recorder)?;
+    // The first storage slot of an account creates its storage trie.
+    set_storage(&mut recorder, [0; 20], &[1; 32], U256::from(1u8));
+    let (mut state, touched) = recorder.into_parts();
+    let post_root = state_root(&state)?;
+
+    let witness = state.commit_block_after(|before| {
+        assert_eq!(state_root(before).unwrap(), state_root(&pre_state).unwrap());
+        build_witness(before, &touched)
+    })?;
+    assert_eq!(witness, build_witness(&pre_state, &touched)?);
+    assert_eq!(state_root(&state)?, post_root);
+    Ok(())
+}
@@ -1,2 +1,8 @@
 pub mod frontier;
-pub mod test_genesis;
\ No newline at end of file
+pub mod test_genesis;
+pub mod test_debugger;
+pub mod test_assembly;
+pub mod test_u256;
+pub mod test_fixed_uint;
+pub mod test_cfg;
+pub mod test_elliptic_curve;
\ No newline at end of file
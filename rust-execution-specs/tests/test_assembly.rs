@@ -0,0 +1,70 @@
+use execution_specs_rs::ethereum::frontier::vm::instructions::Ops;
+use execution_specs_rs::ethereum_spec_tools::assembly::{
+    assemble, disassemble, disassembly, AssemblyError, Instruction,
+};
+
+#[test]
+fn test_assemble_labels() {
+    let source = "
+        ; count down from 3
+                PUSH1 3
+        loop:   JUMPDEST
+                PUSH 1
+                SWAP1
+                sub
+                DUP1
+                PUSH @loop      ; PUSH2 of the pc of `loop`
+                PUSH4 @end
+                POP
+                JUMPI
+        end:    STOP
+                .byte 0xfe 0xef00
+    ";
+    let code = assemble(source).unwrap();
+    assert_eq!(
+        &code[..],
+        [
+            0x60, 0x03, 0x5b, 0x60, 0x01, 0x90, 0x03, 0x80, 0x61, 0x00, 0x02, 0x63, 0x00, 0x00,
+            0x00, 0x12, 0x50, 0x57, 0x00, 0xfe, 0xef, 0x00,
+        ]
+    );
+}
+
+#[test]
+fn test_disassemble() {
+    // PUSH2 0x0102, KECCAK256, an invalid byte, and a truncated PUSH3.
+    let code = [0x61, 0x01, 0x02, 0x20, 0xfe, 0x62, 0xaa];
+    assert_eq!(
+        disassemble(&code),
+        [
+            (0, Instruction::Op(Ops::PUSH2, vec![0x01, 0x02])),
+            (3, Instruction::Op(Ops::KECCAK, Vec::new())),
+            (4, Instruction::Data(vec![0xfe])),
+            (5, Instruction::Data(vec![0x62, 0xaa])),
+        ]
+    );
+    assert_eq!(
+        disassembly(&code),
+        "0x0000  PUSH2 0x0102\n0x0003  KECCAK256\n0x0004  .byte 0xfe\n0x0005  .byte 0x62aa\n"
+    );
+
+    let source = disassemble(&code)
+        .iter()
+        .map(|(_, instruction)| instruction.to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    assert_eq!(&assemble(&source).unwrap()[..], code);
+}
+
+#[test]
+fn test_assembly_errors() {
+    let error = |line: usize, message: &str| {
+        Err(AssemblyError { line, message: message.to_string() })
+    };
+    assert_eq!(assemble("ADD\nFROB"), error(2, "unknown mnemonic FROB"));
+    assert_eq!(assemble("PUSH1 256"), error(1, "immediate does not fit PUSH1"));
+    assert_eq!(assemble("JUMP @nowhere"), error(1, "JUMP takes no immediate"));
+    assert_eq!(assemble("PUSH @nowhere"), error(1, "undefined label nowhere"));
+    assert_eq!(assemble("a: STOP\na: STOP"), error(2, "duplicate label a"));
+    assert_eq!(assemble("PUSH3"), error(1, "PUSH3 without an immediate"));
+}
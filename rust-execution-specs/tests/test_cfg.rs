@@ -0,0 +1,75 @@
+use execution_specs_rs::ethereum::base_types::U256;
+use execution_specs_rs::ethereum_spec_tools::assembly::assemble;
+use execution_specs_rs::ethereum_spec_tools::cfg::{control_flow_graph, EdgeKind, UnresolvedJump};
+
+#[test]
+fn test_cfg_blocks_and_edges() {
+    let code = assemble(
+        "
+                PUSH1 3
+        loop:   JUMPDEST
+                PUSH1 1
+                SWAP1
+                SUB
+                DUP1
+                PUSH @loop
+                JUMPI
+                PUSH @end
+                JUMP
+                .byte 0xef
+        end:    JUMPDEST
+                STOP
+        ",
+    )
+    .unwrap();
+    let cfg = control_flow_graph(&code);
+
+    let starts: Vec<usize> = cfg.blocks.iter().map(|block| block.start).collect();
+    assert_eq!(starts, [0, 2, 12, 16, 17]);
+    assert_eq!(
+        cfg.edges,
+        [
+            (0, 2, EdgeKind::Fallthrough),
+            (2, 2, EdgeKind::Branch),
+            (2, 12, EdgeKind::Fallthrough),
+            (12, 17, EdgeKind::Jump),
+        ]
+    );
+    assert!(cfg.blocks.iter().all(|block| block.unresolved_jump.is_none()));
+}
+
+#[test]
+fn test_cfg_unresolved_jumps() {
+    let code = assemble(
+        "
+                CALLVALUE
+                JUMP
+                PUSH1 0x42
+                JUMPI
+                PUSH1 1
+                RETURN
+        ",
+    )
+    .unwrap();
+    let cfg = control_flow_graph(&code);
+
+    assert_eq!(cfg.blocks.len(), 3);
+    assert_eq!(cfg.blocks[0].unresolved_jump, Some(UnresolvedJump::Dynamic));
+    assert_eq!(cfg.blocks[1].unresolved_jump, Some(UnresolvedJump::Invalid(U256::from(0x42u8))));
+    assert_eq!(cfg.edges, [(2, 5, EdgeKind::Fallthrough)]);
+}
+
+#[test]
+fn test_cfg_dot() {
+    let code = assemble("PUSH @end\nJUMP\nend: JUMPDEST\nSTOP").unwrap();
+    assert_eq!(
+        control_flow_graph(&code).to_dot(),
+        "digraph cfg {
+    node [shape=box fontname=monospace];
+    b0 [label=\"0x0000  PUSH2 0x0004\\l0x0003  JUMP\\l\"];
+    b4 [label=\"0x0004  JUMPDEST\\l0x0005  STOP\\l\"];
+    b0 -> b4;
+}
+"
+    );
+}
@@ -0,0 +1,98 @@
+use execution_specs_rs::ethereum::{
+    base_types::{Bytes, Uint, U256},
+    frontier::{
+        fork_types::Account,
+        state::{set_account, State},
+        utils::address::compute_contract_address,
+        vm::{instructions::Ops, interpreter::process_message_call, Environment},
+    },
+};
+use execution_specs_rs::ethereum_spec_tools::debugger::{transaction_message, Breakpoint, Debugger};
+use serde_json::json;
+
+fn state(code: &[u8]) -> State {
+    let mut state = State::default();
+    set_account(
+        &mut state,
+        [0xcc; 20],
+        Some(Account {
+            nonce: Uint::from(0u8),
+            balance: U256::from(0u8),
+            code: Bytes::from(code),
+        }),
+    );
+    state
+}
+
+fn debug(state: State, commands: &str) -> String {
+    let transaction = json!({ "from": format!("0x{}", "aa".repeat(20)), "to": format!("0x{}", "cc".repeat(20)), "gas": "0x186a0" });
+    let message = transaction_message(&transaction, &state).unwrap();
+
+    let mut output = Vec::new();
+    let mut env = Environment {
+        caller: message.caller,
+        block_hashes: Vec::new(),
+        origin: message.caller,
+        coinbase: [0; 20],
+        number: Uint::from(0u8),
+        gas_limit: Uint::from(30_000_000u32),
+        gas_price: U256::from(0u8),
+        time: U256::from(0u8),
+        difficulty: Uint::from(0u8),
+        state,
+        inspector: Debugger::new(commands.as_bytes(), &mut output),
+    };
+    let result = process_message_call(message, &mut env).unwrap();
+    assert!(result.error.is_none());
+    drop(env);
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn test_debugger_breakpoints_and_storage() {
+    // SSTORE(1, 0x2a) MSTORE(0, SLOAD(1)) STOP
+    let code = [0x60, 0x2a, 0x60, 0x01, 0x55, 0x60, 0x01, 0x54, 0x60, 0x00, 0x52, 0x00];
+    let output = debug(state(&code), "b SSTORE\nc\nstack\nstorage\nn\nstorage\nb 0xa\nc\nmemory\ngas\nc\n");
+
+    let slot = format!("0x{}01", "00".repeat(31));
+    let target = format!("0x{}", "cc".repeat(20));
+    let expected = [
+        format!("-> call {target} with 100000 gas"),
+        format!("{target} depth 0 pc 0x0000 PUSH1 gas 100000"),
+        "(debug) breakpoint 0: Op(SSTORE)".to_string(),
+        format!("(debug) {target} depth 0 pc 0x0004 SSTORE gas 99994"),
+        "(debug)    0: 0x1".to_string(),
+        "   1: 0x2a".to_string(),
+        format!("(debug) {slot}: 0x0"),
+        format!("(debug) {target} depth 0 pc 0x0005 PUSH1 gas 79994"),
+        format!("(debug) {slot}: 0x2a"),
+        "(debug) breakpoint 1: Pc(10)".to_string(),
+        format!("(debug) {target} depth 0 pc 0x000a MSTORE gas 79938"),
+        "(debug) (debug) gas left: 79938, refund: 0".to_string(),
+        format!("(debug) <- {target} returned 0x, 79932 gas left"),
+    ];
+    assert_eq!(output.lines().collect::<Vec<_>>(), expected);
+}
+
+#[test]
+fn test_debugger_detaches_at_end_of_input() {
+    let output = debug(state(&[0x60, 0x01, 0x60, 0x02, 0x01]), "s\n");
+    assert_eq!(output.matches("(debug) ").count(), 2);
+    assert!(!output.contains("<-"));
+}
+
+#[test]
+fn test_transaction_message() {
+    let caller = [0xaa; 20];
+    let transaction = json!({ "from": format!("0x{}", "aa".repeat(20)), "data": "0x6001", "value": "0x10" });
+    let message = transaction_message(&transaction, &State::default()).unwrap();
+    assert!(message.target.is_none());
+    assert_eq!(message.current_target, compute_contract_address(&caller, &Uint::from(0u8)));
+    assert_eq!(&message.code[..], [0x60, 0x01]);
+    assert_eq!(message.value, U256::from(16u8));
+
+    assert!(transaction_message(&json!({ "to": "0x00" }), &State::default()).is_err());
+    assert_eq!(Breakpoint::parse("keccak256"), Some(Breakpoint::Op(Ops::KECCAK)));
+    assert_eq!(Breakpoint::parse("12"), Some(Breakpoint::Pc(12)));
+    assert_eq!(Breakpoint::parse("JUMPY"), None);
+}
@@ -0,0 +1,114 @@
+use hex_literal::hex;
+
+use execution_specs_rs::ethereum::{
+    base_types::{Uint, U256},
+    crypto::elliptic_curve::{secp256k1_recover, SECP256K1N},
+    exceptions::EthereumException,
+    frontier::fork_types::keccak256,
+};
+
+const MSG_HASH: [u8; 32] = hex!("daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53");
+
+fn signature() -> (U256, U256) {
+    let r = U256::from_str_radix("18515461264373351373200002665853028612451056578545711640558177340181847433846", 10).unwrap();
+    let s = U256::from_str_radix("46948507304638947509940763649030358759909902576025900602547168820602576006531", 10).unwrap();
+    (r, s)
+}
+
+#[test]
+fn test_secp256k1_recover() {
+    let (r, s) = signature();
+
+    let public_key = secp256k1_recover(r, s, U256::ZERO, MSG_HASH).unwrap();
+
+    assert_eq!(public_key.len(), 64);
+    assert_eq!(keccak256(&public_key)[12..], hex!("9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f"));
+}
+
+/// A signature of `msg_hash` by the private key of `public_key`.
+struct KnownSignature {
+    r: [u8; 32],
+    s: [u8; 32],
+    v: u8,
+    msg_hash: [u8; 32],
+    public_key: [u8; 64],
+}
+
+/// Signatures made with the private keys `1`, `2` and `0xc0ffee`.
+const SIGNATURES: [KnownSignature; 3] = [
+    KnownSignature {
+        r: hex!("d47644539acec3da5e3ecf5fe8863c628a9c97e8b71e9ea9167a6f4f83c03c32"),
+        s: hex!("a18c6295174ca5f312f492d0cacda7fa6be50ca193bc034073d551e8a493cb4e"),
+        v: 0,
+        msg_hash: hex!("1111111111111111111111111111111111111111111111111111111111111111"),
+        public_key: hex!("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8"),
+    },
+    KnownSignature {
+        r: hex!("12faae608bd6562562b8f85564664cd1fdcd667f6b24b2b221ef86b9231f4d74"),
+        s: hex!("ecde89a35a1b49bb90df84bc0cd30a657d6072b3632a6eacff12221e2c5ea135"),
+        v: 1,
+        msg_hash: hex!("2222222222222222222222222222222222222222222222222222222222222222"),
+        public_key: hex!("c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee51ae168fea63dc339a3c58419466ceaeef7f632653266d0e1236431a950cfe52a"),
+    },
+    KnownSignature {
+        r: hex!("cca6649424131300f1ff26543e27b7f1e20f7268d707e11210ea53a00171d198"),
+        s: hex!("39047f82eef955e659f96c032e80b43ad2be64cf30302113ff12c9bcca139440"),
+        v: 0,
+        msg_hash: hex!("00000000000000000000000000000000000000000000000000000000deadbeef"),
+        public_key: hex!("2a5bbcb0eede528e6abe5f2ec50ad7887eb5677af383a460b05ee23bf892dfe552c93747550eda8404c8b473786c00dfd8fd1ef4bc033f359ccf5b77bd656d21"),
+    },
+];
+
+#[test]
+fn test_secp256k1_recover_known_keys() {
+    for signature in &SIGNATURES {
+        let (r, s) = (U256::from_be_bytes(&signature.r), U256::from_be_bytes(&signature.s));
+        let public_key = secp256k1_recover(r, s, U256::from(signature.v), signature.msg_hash).unwrap();
+        assert_eq!(*public_key, signature.public_key);
+    }
+
+    // The well known addresses of the private keys `1` and `2`.
+    assert_eq!(keccak256(&SIGNATURES[0].public_key)[12..], hex!("7e5f4552091a69125d5dfcb7b8c2659029395bdf"));
+    assert_eq!(keccak256(&SIGNATURES[1].public_key)[12..], hex!("2b5ad5c4795c026514f8317c7a215e218dccd6cf"));
+}
+
+#[test]
+fn test_secp256k1_recover_other_parity_gives_other_key() {
+    let (r, s) = signature();
+
+    let public_key = secp256k1_recover(r, s, U256::ONE, MSG_HASH).unwrap();
+
+    assert_ne!(keccak256(&public_key)[12..], hex!("9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f"));
+}
+
+#[test]
+fn test_secp256k1_recover_rejects_out_of_range_values() {
+    let (r, s) = signature();
+    let n = U256::try_from(SECP256K1N()).unwrap();
+
+    assert!(matches!(secp256k1_recover(r, s, U256::from(2u8), MSG_HASH), Err(EthereumException::InvalidBlock)));
+    assert!(matches!(secp256k1_recover(U256::ZERO, s, U256::ZERO, MSG_HASH), Err(EthereumException::InvalidBlock)));
+    assert!(matches!(secp256k1_recover(r, n, U256::ZERO, MSG_HASH), Err(EthereumException::InvalidBlock)));
+    assert!(Uint::from(s) < SECP256K1N());
+}
+
+#[test]
+fn test_secp256k1_recover_rejects_invalid_signatures() {
+    let (r, s) = signature();
+    let n = U256::try_from(SECP256K1N()).unwrap();
+    let recover = |r, s, v| secp256k1_recover(r, s, v, MSG_HASH);
+
+    for (r, s, v) in [
+        (r, U256::ZERO, U256::ZERO),
+        (n, s, U256::ZERO),
+        (U256::MAX, s, U256::ZERO),
+        (r, U256::MAX, U256::ZERO),
+        (r, s, U256::from(27u8)),
+        (r, s, U256::MAX),
+        // 5 ** 3 + 7 is not a square modulo p, so no point has `x` 5.
+        (U256::from(5u8), s, U256::ZERO),
+        (U256::from(5u8), s, U256::ONE),
+    ] {
+        assert!(matches!(recover(r, s, v), Err(EthereumException::InvalidBlock)));
+    }
+}
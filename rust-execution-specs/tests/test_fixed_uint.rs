@@ -0,0 +1,70 @@
+use execution_specs_rs::ethereum::base_types::{
+    Uint, U255_CEIL_VALUE, U255_MAX_VALUE, U256, U256_CEIL_VALUE, U32, U32_CEIL_VALUE, U64,
+};
+use execution_specs_rs::ethereum::exceptions::EthereumException;
+use execution_specs_rs::ethereum::rlp;
+use execution_specs_rs::ethereum::utils::safe_arithmetic::{u256_safe_add, u256_safe_multiply};
+
+#[test]
+fn test_fixed_uint_ranges() {
+    assert_eq!(U32::MAX.checked_add(U32::ONE), None);
+    assert_eq!(U32::MAX.wrapping_add(U32::ONE), U32::ZERO);
+    assert_eq!(U32::ZERO.wrapping_sub(U32::ONE), U32::MAX);
+    assert_eq!(U64::from(3u8).wrapping_pow(41), U64::from(3u64.wrapping_pow(41)));
+    assert_eq!(U64::from(7u8) / U64::from(2u8), U64::from(3u8));
+    assert_eq!(U64::ONE.checked_div(U64::ZERO), None);
+
+    assert!(matches!(U32::try_from(U32_CEIL_VALUE), Err(EthereumException::OverflowError)));
+    assert!(matches!(U32::try_from(U64::MAX), Err(EthereumException::OverflowError)));
+    assert!(matches!(U64::try_from(Uint::from(u128::MAX)), Err(EthereumException::OverflowError)));
+    assert!(matches!(U64::try_from(U256::ONE << 64), Err(EthereumException::OverflowError)));
+    assert!(matches!(U256::try_from(U256_CEIL_VALUE()), Err(EthereumException::OverflowError)));
+
+    assert_eq!(U64::from(U32::MAX), U64::from(u32::MAX));
+    assert_eq!(U32::try_from(U64::from(5u8)).unwrap(), U32::from(5u8));
+    assert_eq!(Uint::from(U64::MAX), Uint::from(u64::MAX));
+    assert_eq!(U256::from(U32::MAX), U256::from(u32::MAX));
+
+    assert_eq!(U255_MAX_VALUE.wrapping_add(U256::ONE), U255_CEIL_VALUE);
+    assert!(U255_CEIL_VALUE.is_negative());
+    assert!(!U255_MAX_VALUE.is_negative());
+}
+
+#[test]
+#[should_panic(expected = "U64 addition overflowed")]
+fn test_fixed_uint_overflow_panics() {
+    let _ = U64::MAX + U64::ONE;
+}
+
+#[test]
+fn test_fixed_uint_bytes() {
+    assert_eq!(U32::from(0x0102u16).to_be_bytes4(), [0, 0, 1, 2]);
+    assert_eq!(U64::from(0x0102u16).to_be_bytes(), [1, 2]);
+    assert!(U64::ZERO.to_be_bytes().is_empty());
+    assert_eq!(U64::from_be_bytes(&[1, 2]), U64::from(0x0102u16));
+    assert_eq!(U32::try_from_be_bytes(&[1; 5]), None);
+    assert_eq!(&rlp::encode(&U64::from(0x0400u16))[..], [0x82, 0x04, 0x00]);
+    assert_eq!(&rlp::encode(&U32::ZERO)[..], [0x80]);
+    assert_eq!(format!("{:#x}", U64::from(255u8)), "0xff");
+}
+
+#[test]
+fn test_u256_safe_arithmetic() {
+    let half = Uint::from(U255_CEIL_VALUE);
+    assert_eq!(
+        u256_safe_add(&[half.clone() - 1u8, half.clone()], None).unwrap(),
+        U256::MAX
+    );
+    assert!(matches!(
+        u256_safe_add(&[half.clone(), half.clone()], None),
+        Err(EthereumException::OverflowError)
+    ));
+    assert!(matches!(
+        u256_safe_multiply(&[half, Uint::from(2u8)], Some(EthereumException::InvalidBlock)),
+        Err(EthereumException::InvalidBlock)
+    ));
+    assert_eq!(
+        u256_safe_multiply(&[Uint::from(6u8), Uint::from(7u8)], None).unwrap(),
+        U256::from(42u8)
+    );
+}
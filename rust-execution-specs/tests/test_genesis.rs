@@ -3,11 +3,13 @@ use std::{assert_eq};
 use hex_literal::hex;
 use num_bigint::BigUint;
 use num_traits::cast::{FromPrimitive};
-use execution_specs_rs::ethereum::base_types::{U256, Bytes};
+use execution_specs_rs::ethereum::base_types::{U256, U64, Bytes};
 use execution_specs_rs::ethereum::exceptions::EthereumException;
-use execution_specs_rs::ethereum::genesis::{get_genesis_configuration, GenesisConfiguration};
+use execution_specs_rs::ethereum::frontier::{fork::BlockChain, state::State};
+use execution_specs_rs::ethereum::genesis::{add_genesis_block, get_genesis_configuration, GenesisConfiguration};
 use execution_specs_rs::ethereum::rlp;
 use execution_specs_rs::ethereum::utils::hexadecimal::hex_to_bytes;
+use execution_specs_rs::ethereum_spec_tools::forks::HardFork;
 
 fn mainnet_genesis_configuration() -> GenesisConfiguration {
     get_genesis_configuration("mainnet.json").unwrap()
@@ -28,7 +30,7 @@ pub fn test_mainnet_alloc_rlp_encoding() {
 
     let mut alocs: Vec<_> = Vec::with_capacity(mainnet_genesis_configuration.initial_balances.len());
     for (address, balance) in mainnet_genesis_configuration.initial_balances {
-        alocs.push([U256::from_bytes_be(address.as_slice()), balance]);
+        alocs.push([U256::from_be_bytes(address.as_slice()), balance]);
     }
     alocs.sort_by(|a, b| a[0].cmp(&b[0]));
 
@@ -64,6 +66,23 @@ pub fn test_mainnet_genesis_config() {
     );
     assert_eq!(
         mainnet_genesis_configuration.timestamp,
-        BigUint::from_u64(0).unwrap()
+        U256::from(0u64)
+    );
+}
+
+#[test]
+pub fn test_mainnet_genesis_block_hash() {
+    let mut chain = BlockChain {
+        blocks: Vec::new(),
+        state: State::default(),
+        chain_id: U64::from(0u64),
+    };
+    add_genesis_block(HardFork::Frontier, &mut chain, mainnet_genesis_configuration()).unwrap();
+
+    assert_eq!(chain.blocks.len(), 1);
+    assert_eq!(chain.chain_id, U64::from(1u64));
+    assert_eq!(
+        rlp::rlp_hash(&chain.blocks[0].header),
+        hex!("d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3")
     );
 }
@@ -1,6 +1,6 @@
 use execution_specs_rs::ethereum::{
     base_types::{Uint, U256},
-    rlp::{encode, encode_bytes, encode_iter, encode_sequence},
+    rlp::{decode, decode_to_bytes, decode_to_sequence, encode, encode_bytes, encode_iter, encode_sequence, Simple},
 };
 
 macro_rules! bytes {
@@ -193,3 +193,67 @@ fn test_rlp_encode_successfully() {
         // ),
     }
 }
+
+#[test]
+fn test_rlp_decode_successfully() {
+    assert_eq!(decode(b"\x80").unwrap(), Simple::Bytes(Box::new([])));
+    assert_eq!(decode(b"h").unwrap(), Simple::Bytes(Box::new(*b"h")));
+    assert_eq!(decode(b"\xc0").unwrap(), Simple::List(vec![]));
+    assert_eq!(
+        decode(&bytes![0xb7, b'\x83' * 55]).unwrap(),
+        Simple::Bytes(bytes![b'\x83' * 55].into_boxed_slice())
+    );
+
+    let raw_data = (
+        b"hello",
+        Uint::from(255u8),
+        (b"how", (b"are", b"you", (b"doing",))),
+    );
+    let encoded = encode(&raw_data);
+    let decoded = decode(&encoded).unwrap();
+    assert_eq!(decoded.as_list().unwrap().len(), 3);
+    assert_eq!(*encode(&decoded), *encoded);
+}
+
+#[test]
+fn test_rlp_decode_failure() {
+    for encoded in [
+        &b""[..],
+        b"\x81\x05",
+        b"\x83ab",
+        b"\xb8\x05hello",
+        b"\xb9\x00\x40",
+        b"\xc3\x80",
+        b"\xc2\x83ab",
+        b"\xf8\x02\x80\x80",
+        // Bytes after the first item.
+        b"\x05\x06",
+        b"\x82abc",
+        b"\xc0\x01",
+        b"\xc1\x80\x80",
+    ] {
+        assert!(decode(encoded).is_err(), "{encoded:?}");
+    }
+    let mut long_string = bytes![0xb8, 0x38];
+    long_string.extend([b'a'; 0x39]);
+    assert!(decode(&long_string).is_err());
+    let mut long_list = bytes![0xf8, 0x38];
+    long_list.extend([0x80; 0x39]);
+    assert!(decode(&long_list).is_err());
+}
+
+#[test]
+fn test_rlp_decode_to_bytes_failure() {
+    for encoded in [&b""[..], b"\xc0", b"\xf8\x38", b"\x05\x06", b"\x81"] {
+        assert!(decode_to_bytes(encoded).is_err(), "{encoded:?}");
+    }
+    assert_eq!(*decode_to_bytes(b"\x82ab").unwrap(), *b"ab");
+}
+
+#[test]
+fn test_rlp_decode_to_sequence_failure() {
+    for encoded in [&b""[..], b"\x80", b"\x05", b"\x83abc", b"\xb8\x38"] {
+        assert!(decode_to_sequence(encoded).is_err(), "{encoded:?}");
+    }
+    assert_eq!(decode_to_sequence(b"\xc2\x80\x05").unwrap().len(), 2);
+}
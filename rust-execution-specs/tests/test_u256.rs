@@ -0,0 +1,99 @@
+use std::cmp::Ordering;
+
+use execution_specs_rs::ethereum::base_types::{Uint, U256};
+use execution_specs_rs::ethereum::rlp;
+
+fn values() -> Vec<U256> {
+    let mut values = vec![
+        U256::ZERO,
+        U256::ONE,
+        U256::from(0xffu8),
+        U256::from(u64::MAX),
+        U256::from(u128::MAX),
+        U256::MAX,
+        U256::ONE << 255,
+        (U256::ONE << 255).wrapping_sub(U256::ONE),
+    ];
+    values.push(U256::from_str_radix("123456789abcdef0fedcba9876543210deadbeef", 16).unwrap());
+    values.push(U256::MAX >> 3);
+    values
+}
+
+fn modulus() -> Uint {
+    Uint::from(1u8) << 256
+}
+
+#[test]
+fn test_u256_matches_biguint() {
+    for x in values() {
+        for y in values() {
+            let (a, b) = (Uint::from(x), Uint::from(y));
+            assert_eq!(Uint::from(x.wrapping_add(y)), (&a + &b) % modulus());
+            assert_eq!(Uint::from(x.wrapping_sub(y)), (&a + modulus() - &b) % modulus());
+            assert_eq!(Uint::from(x.wrapping_mul(y)), (&a * &b) % modulus());
+            assert_eq!(x.checked_add(y).is_none(), &a + &b >= modulus());
+            assert_eq!(x.cmp(&y), a.cmp(&b));
+            if !y.is_zero() {
+                assert_eq!(Uint::from(x / y), &a / &b);
+                assert_eq!(Uint::from(x % y), &a % &b);
+            }
+            assert_eq!(Uint::from(x & y), &a & &b);
+            assert_eq!(Uint::from(x ^ y), &a ^ &b);
+        }
+        assert_eq!(U256::try_from(Uint::from(x)).unwrap(), x);
+        assert_eq!(U256::from_be_bytes(&x.to_be_bytes()), x);
+        assert_eq!(x.to_be_bytes32().to_vec(), {
+            let bytes = Uint::from(x).to_bytes_be();
+            let mut word = vec![0; 32 - bytes.len()];
+            word.extend(bytes);
+            word
+        });
+        for shift in [0, 1, 63, 64, 65, 128, 200, 255, 256] {
+            assert_eq!(Uint::from(x << shift), (Uint::from(x) << shift) % modulus());
+            assert_eq!(Uint::from(x >> shift), Uint::from(x) >> shift);
+        }
+    }
+    assert!(U256::try_from(modulus()).is_err());
+    assert_eq!(U256::MAX.wrapping_add(U256::ONE), U256::ZERO);
+    assert_eq!(U256::from(3u8).wrapping_pow(U256::from(300u16)), {
+        U256::try_from(Uint::from(3u8).modpow(&Uint::from(300u16), &modulus())).unwrap()
+    });
+}
+
+#[test]
+fn test_u256_signed() {
+    let minus = |value: u8| U256::from(value).wrapping_neg();
+    let min = U256::ONE << 255;
+
+    assert_eq!(minus(7).signed_div(U256::from(2u8)), minus(3));
+    assert_eq!(U256::from(7u8).signed_div(minus(2)), minus(3));
+    assert_eq!(minus(7).signed_div(minus(2)), U256::from(3u8));
+    assert_eq!(min.signed_div(U256::MAX), min);
+    assert_eq!(U256::from(7u8).signed_div(U256::ZERO), U256::ZERO);
+
+    assert_eq!(minus(7).signed_rem(U256::from(2u8)), minus(1));
+    assert_eq!(U256::from(7u8).signed_rem(minus(2)), U256::ONE);
+    assert_eq!(min.signed_rem(U256::ZERO), U256::ZERO);
+
+    assert_eq!(minus(1).signed_cmp(&U256::ONE), Ordering::Less);
+    assert_eq!(min.signed_cmp(&minus(1)), Ordering::Less);
+    assert_eq!(U256::ONE.signed_cmp(&U256::ZERO), Ordering::Greater);
+}
+
+#[test]
+fn test_u256_conversions() {
+    assert_eq!(U256::from_str_radix("1000", 10), Some(U256::from(1000u16)));
+    assert_eq!(U256::from_str_radix("zz", 16), None);
+    assert_eq!(U256::try_from_be_bytes(&[1; 33]), None);
+    assert_eq!(usize::try_from(U256::from(42u8)).unwrap(), 42);
+    assert!(u64::try_from(U256::ONE << 64).is_err());
+
+    assert_eq!(format!("{}", U256::from(1000u16)), "1000");
+    assert_eq!(format!("{:#x}", U256::from(255u8)), "0xff");
+    assert!(U256::ZERO.to_be_bytes().is_empty());
+    assert_eq!(U256::MAX.bits(), 256);
+
+    assert_eq!(&rlp::encode(&U256::ZERO)[..], [0x80]);
+    assert_eq!(&rlp::encode(&U256::from(0x0400u16))[..], [0x82, 0x04, 0x00]);
+    assert_eq!(rlp::encode(&U256::MAX), rlp::encode(&Uint::from(U256::MAX)));
+}